@@ -21,9 +21,19 @@ defmodule ExJsonschema do
   - `compile/1,2` - Compile JSON Schema for validation
   - `validate/2,3` - Validate JSON against compiled schema
   - `valid?/2,3` - Quick boolean validation check
+  - `validate_at/3` - Validate against a subschema, or only part of a document
+  - `patch/3,4` - Apply a JSON Patch or Merge Patch and validate the result
+  - `suggest_fixes/2` - JSON Patch operations that repair validation errors
+  - `best_error/2` - The validation error most likely to explain a failure
   - `meta_validate/1` - Validate schema document itself
   - `format_errors/3` - Format validation errors for display
   - `analyze_errors/1,2` - Analyze error patterns and severity
+  - `dereference/1,2` - Inline `$ref`s into a self-contained schema
+  - `migrate/1,2` - Upgrade legacy schemas to a newer draft
+  - `infer/1,2` - Generate a schema from example documents
+  - `to_avro/1,2`, `from_avro/1` - Convert to and from Apache Avro schemas
+  - `serialize/1`, `deserialize/1` - Ship compiled schemas across nodes and restarts
+  - `info/1` - Draft, `$id`, content hash and options of a compiled schema
 
   ## Output Formats
 
@@ -44,10 +54,15 @@ defmodule ExJsonschema do
 
   ## Draft Support & Meta-Validation
 
-  Supports JSON Schema drafts 4, 6, 7, 2019-09, and 2020-12:
+  Supports JSON Schema drafts 4, 6, 7, 2019-09, and 2020-12, and the
+  OpenAPI 3.0 and 3.1 Schema Object dialects:
 
       ExJsonschema.compile_draft7(schema)        # Draft-specific compilation
       ExJsonschema.compile_auto_draft(schema)    # Auto-detect from $schema
+      ExJsonschema.compile(schema, draft: :openapi30)  # OpenAPI 3.0 `nullable` etc.
+
+  Whole OpenAPI documents validate requests and responses by operation with
+  `ExJsonschema.OpenAPI`.
 
       # Validate schema documents against meta-schemas
       ExJsonschema.meta_valid?(schema)           # Quick check
@@ -83,28 +98,37 @@ defmodule ExJsonschema do
   require Logger
 
   alias ExJsonschema.{
+    Avro,
     CompilationError,
+    CSV,
+    Dereferencer,
     DraftDetector,
     ErrorAnalyzer,
     ErrorFormatter,
+    Inferrer,
     MetaValidator,
+    Migrator,
     Native,
     Options,
+    Patch,
+    Schema,
+    Serializer,
+    Telemetry,
     ValidationError
   }
 
   @typedoc """
   A compiled JSON Schema validator optimized for repeated use.
 
-  This is an opaque reference to a compiled schema stored in the Rust NIF.
-  Compile once with `compile/1` or `compile/2`, then use multiple times
+  An `ExJsonschema.Schema` struct wrapping the compiled schema stored in the
+  Rust NIF. Use `info/1` to see how it was built. Compile once with `compile/1` or `compile/2`, then use multiple times
   with `validate/2`, `validate/3`, or `valid?/2`, `valid?/3`.
 
   ## Performance Note
   Compiled schemas are significantly faster than one-shot validation
   when validating multiple instances against the same schema.
   """
-  @type compiled_schema :: reference()
+  @type compiled_schema :: Schema.t()
 
   @typedoc """
   A JSON document represented as a string.
@@ -151,7 +175,7 @@ defmodule ExJsonschema do
 
       iex> schema = ~s({"type": "string"})
       iex> {:ok, compiled} = ExJsonschema.compile(schema)
-      iex> is_reference(compiled)
+      iex> match?(%ExJsonschema.Schema{}, compiled)
       true
 
       iex> invalid_schema = ~s({"type": "invalid_type"})
@@ -171,6 +195,8 @@ defmodule ExJsonschema do
   Accepts either an `ExJsonschema.Options` struct or keyword list of options.
   When using `:auto` draft detection, the `$schema` property in the schema
   will be examined to determine the appropriate JSON Schema draft version.
+  With `input_format: :yaml` or `input_format: :toml` the schema is YAML or
+  TOML text rather than JSON (see "Input Formats" in `ExJsonschema.Options`).
 
   ## Examples
 
@@ -178,19 +204,19 @@ defmodule ExJsonschema do
       iex> opts = ExJsonschema.Options.new(draft: :draft7, validate_formats: true)
       iex> schema = ~s({"type": "string"})
       iex> {:ok, compiled} = ExJsonschema.compile(schema, opts)
-      iex> is_reference(compiled)
+      iex> match?(%ExJsonschema.Schema{}, compiled)
       true
 
       # With keyword list
       iex> schema = ~s({"type": "string"})
       iex> {:ok, compiled} = ExJsonschema.compile(schema, draft: :auto)
-      iex> is_reference(compiled)
+      iex> match?(%ExJsonschema.Schema{}, compiled)
       true
 
       # Automatic draft detection
       iex> schema_with_draft = ~s({"$schema": "http://json-schema.org/draft-07/schema#", "type": "string"})
       iex> {:ok, compiled} = ExJsonschema.compile(schema_with_draft, draft: :auto)
-      iex> is_reference(compiled)
+      iex> match?(%ExJsonschema.Schema{}, compiled)
       true
 
   """
@@ -251,7 +277,7 @@ defmodule ExJsonschema do
 
       iex> schema = ~s({"type": "string"})
       iex> compiled = ExJsonschema.compile!(schema)
-      iex> is_reference(compiled)
+      iex> match?(%ExJsonschema.Schema{}, compiled)
       true
 
   """
@@ -283,7 +309,7 @@ defmodule ExJsonschema do
   """
   @spec validate(compiled_schema(), json_string()) :: validation_result()
   def validate(compiled_schema, instance_json)
-      when is_reference(compiled_schema) and is_binary(instance_json) do
+      when is_struct(compiled_schema, Schema) and is_binary(instance_json) do
     validate(compiled_schema, instance_json, [])
   end
 
@@ -296,9 +322,29 @@ defmodule ExJsonschema do
   - `:detailed` - Returns `:ok` or `{:error, [ValidationError.t()]}` (default)
   - `:verbose` - Returns detailed errors with additional context, values, and suggestions
 
+  In both `:detailed` and `:verbose` output, a failed `oneOf` or `anyOf` lists
+  the errors of each subschema under `:branches` (see `ExJsonschema.ValidationError`).
+
   ## Validation Options
 
   - `output: :basic | :detailed | :verbose` - Controls error output format (default: `:detailed`)
+  - `ranked: boolean()` - Orders errors from most to least relevant, as judged by
+    `best_error/2`: deeper instance locations first, then specific keywords before
+    `anyOf` and `oneOf`. Equally relevant errors keep their order. Formatting
+    ranked errors with `ErrorFormatter` leads with the most useful one.
+    (default: `false`)
+  - `direction: :request | :response` - Rejects values whose schema marks them
+    `readOnly` (in requests) or `writeOnly` (in responses), reported under those
+    keywords. See "Request and Response Direction" in `ExJsonschema.Options`.
+    (default: `nil`, neither)
+  - `input_format: :json | :yaml | :toml | :msgpack | :cbor` - The format of
+    the instance. Errors in YAML and TOML instances carry their line and
+    column. MessagePack and CBOR instances are binaries. With `:detailed` and
+    `:verbose` output, a binary instance that does not decode to JSON values
+    returns `{:error, CompilationError.t()}`, and a text instance that does
+    not parse returns `{:error, [:validation_error]}`. With `:basic` output,
+    which carries no details, both return `{:error, :validation_failed}`.
+    See "Input Formats" in `ExJsonschema.Options`. (default: `:json`)
 
   **Note**: Validation behavior options like `validate_formats`, `stop_on_first_error`, etc.
   must be set during schema compilation, not at validation time. Use `ExJsonschema.compile/2`
@@ -327,11 +373,11 @@ defmodule ExJsonschema do
 
   """
   @spec validate(compiled_schema(), json_string(), keyword() | Options.t()) ::
-          validation_result() | basic_validation_result()
+          validation_result() | basic_validation_result() | {:error, CompilationError.t()}
 
   # Accept Options struct
   def validate(compiled_schema, instance_json, %Options{} = options)
-      when is_reference(compiled_schema) and is_binary(instance_json) do
+      when is_struct(compiled_schema, Schema) and is_binary(instance_json) do
     Logger.debug("Starting validation", %{
       instance_size: byte_size(instance_json),
       output_format: options.output_format,
@@ -371,7 +417,7 @@ defmodule ExJsonschema do
 
   # Accept keyword list
   def validate(compiled_schema, instance_json, opts)
-      when is_reference(compiled_schema) and is_binary(instance_json) and is_list(opts) do
+      when is_struct(compiled_schema, Schema) and is_binary(instance_json) and is_list(opts) do
     Logger.debug("Converting validation options", %{
       instance_size: byte_size(instance_json),
       options: opts
@@ -381,6 +427,39 @@ defmodule ExJsonschema do
     validate_with_options(compiled_schema, instance_json, validated_options)
   end
 
+  @doc """
+  Validates an Elixir term, such as decoded params, against a compiled schema.
+
+  The term is read as JSON natively, the way `Jason` would encode it, so it
+  is not encoded to JSON text first: `nil`, `true` and `false` are null and
+  booleans, other atoms are strings, and map keys may be strings, atoms or
+  integers. Terms JSON has no equivalent for, such as tuples, structs and
+  non-UTF-8 binaries, return `{:error, CompilationError.t()}` of type
+  `:parse_error`, whose details say where the term is.
+
+  Takes the options of `validate/3` except `:input_format`.
+
+  ## Examples
+
+      iex> {:ok, compiled} = ExJsonschema.compile(~s({"required": ["name"]}))
+      iex> ExJsonschema.validate_term(compiled, %{"name" => "Ada"})
+      :ok
+      iex> ExJsonschema.validate_term(compiled, %{age: 36}, output: :basic)
+      {:error, :validation_failed}
+
+  """
+  @spec validate_term(compiled_schema(), term(), keyword()) ::
+          validation_result() | basic_validation_result() | {:error, CompilationError.t()}
+  def validate_term(compiled_schema, term, opts \\ [])
+      when is_struct(compiled_schema, Schema) and is_list(opts) do
+    if Keyword.has_key?(opts, :input_format) do
+      raise ArgumentError, "validate_term/3 does not take an :input_format option"
+    end
+
+    validated_options = validate_and_normalize_options(opts)
+    validate_with_options(compiled_schema, term, %{validated_options | input_format: :term})
+  end
+
   @doc """
   Validates JSON against a compiled schema, raising an exception on validation failure.
 
@@ -417,8 +496,8 @@ defmodule ExJsonschema do
   """
   @spec valid?(compiled_schema(), json_string()) :: boolean()
   def valid?(compiled_schema, instance_json)
-      when is_reference(compiled_schema) and is_binary(instance_json) do
-    Native.valid?(compiled_schema, instance_json)
+      when is_struct(compiled_schema, Schema) and is_binary(instance_json) do
+    valid_with_options(compiled_schema, instance_json, %Options{})
   end
 
   @doc """
@@ -430,7 +509,8 @@ defmodule ExJsonschema do
   ## Validation Options
 
   - `validate_formats: boolean()` - Enable format validation (default: `false`)
-  - `ignore_unknown_formats: boolean()` - Ignore unknown format assertions (default: `true`)
+  - `ignore_unknown_formats: boolean()` - Ignore unknown format assertions (default: `true`);
+    compile with `unknown_formats: :error` instead
   - `stop_on_first_error: boolean()` - Stop validation on first error (default: `false`)
   - `collect_annotations: boolean()` - Collect annotations during validation (default: `true`)
 
@@ -448,17 +528,241 @@ defmodule ExJsonschema do
 
   # Accept Options struct
   def valid?(compiled_schema, instance_json, %Options{} = options)
-      when is_reference(compiled_schema) and is_binary(instance_json) do
+      when is_struct(compiled_schema, Schema) and is_binary(instance_json) do
     valid_with_options(compiled_schema, instance_json, options)
   end
 
   # Accept keyword list
   def valid?(compiled_schema, instance_json, opts)
-      when is_reference(compiled_schema) and is_binary(instance_json) and is_list(opts) do
+      when is_struct(compiled_schema, Schema) and is_binary(instance_json) and is_list(opts) do
     validated_options = validate_and_normalize_options(opts)
     valid_with_options(compiled_schema, instance_json, validated_options)
   end
 
+  @doc """
+  Validates JSON against part of a compiled schema, or validates part of a document.
+
+  Pointers are looked up in a registry built once per compiled schema, and
+  the root itself is checked by the compiled validator. Any other subschema
+  is compiled into a validator of its own the first time it is used, with
+  the root's options and over a fresh registry of the whole document, so
+  references inside it resolve against the root. That validator is then
+  kept with the compiled schema for later calls.
+
+  ## Options
+
+  - `schema_pointer: String.t()` - Validate against the subschema at this
+    location instead of the root, e.g. `"#/$defs/Address"`. Accepts JSON
+    pointer fragments (`"#/..."` or `"/..."`), anchors (`"#name"`) and URI
+    references
+  - `instance_pointer: String.t()` - Validate only the value at this JSON
+    pointer, e.g. `"/billing"`. Without a `:schema_pointer`, the subschema
+    that applies to that value is found by following `properties`, `items`
+    and `$ref`. Where combinators such as `allOf` or `oneOf` decide what
+    applies, the whole document is validated and only errors at or below
+    the pointer are reported
+  - `output: :basic | :detailed` - Error output format (default: `:detailed`)
+
+  Error instance paths are reported relative to the whole document. Raises
+  `ArgumentError` if a pointer does not resolve.
+
+  ## Examples
+
+      iex> schema = ~s({
+      ...>   "properties": {"billing": {"$ref": "#/$defs/Address"}},
+      ...>   "$defs": {"Address": {"type": "object", "required": ["zip"]}}
+      ...> })
+      iex> {:ok, compiled} = ExJsonschema.compile(schema)
+      iex> ExJsonschema.validate_at(compiled, ~s({"zip": "12345"}), schema_pointer: "#/$defs/Address")
+      :ok
+      iex> {:error, [error]} = ExJsonschema.validate_at(compiled, ~s({"billing": {}}), instance_pointer: "/billing")
+      iex> error.instance_path
+      "/billing"
+
+  """
+  @spec validate_at(compiled_schema(), json_string(), keyword()) ::
+          validation_result() | basic_validation_result() | {:error, CompilationError.t()}
+  def validate_at(compiled_schema, instance_json, opts)
+      when is_struct(compiled_schema, Schema) and is_binary(instance_json) and is_list(opts) do
+    schema_pointer = Keyword.get(opts, :schema_pointer)
+    instance_pointer = Keyword.get(opts, :instance_pointer)
+    output_format = Keyword.get(opts, :output, :detailed)
+
+    for {name, pointer} <- [schema_pointer: schema_pointer, instance_pointer: instance_pointer],
+        not (is_nil(pointer) or is_binary(pointer)) do
+      raise ArgumentError, "Invalid #{name}: #{inspect(pointer)}. Must be a string"
+    end
+
+    unless output_format in [:basic, :detailed] do
+      raise ArgumentError,
+            "Invalid output format: #{inspect(output_format)}. Must be one of: :basic, :detailed"
+    end
+
+    metadata =
+      compiled_schema.ref
+      |> Telemetry.schema_metadata()
+      |> Map.merge(%{
+        output_format: output_format,
+        instance_size: byte_size(instance_json),
+        schema_pointer: schema_pointer,
+        instance_pointer: instance_pointer
+      })
+
+    Telemetry.span(:validate, metadata, fn ->
+      {result, measurements} =
+        Native.validate_at(
+          compiled_schema.ref,
+          instance_json,
+          schema_pointer,
+          instance_pointer,
+          output_format == :detailed
+        )
+
+      {validate_at_result(result), measurements, %{}}
+    end)
+  end
+
+  @doc """
+  Applies a JSON Patch (RFC 6902) or JSON Merge Patch (RFC 7396) to a document
+  and validates the result.
+
+  Returns `{:ok, patched_json}`, or validation errors paired with the patch
+  operation (or merge patch member) responsible for them. See
+  `ExJsonschema.Patch` for the options and how errors are attributed.
+
+  ## Examples
+
+      iex> {:ok, compiled} = ExJsonschema.compile(~s({"properties": {"age": {"minimum": 0}}}))
+      iex> ExJsonschema.patch(compiled, ~s({"age": 1}), ~s([{"op": "replace", "path": "/age", "value": 2}]))
+      {:ok, ~s({"age":2})}
+      iex> {:error, [{"/age", error}]} =
+      ...>   ExJsonschema.patch(compiled, ~s({"age": 1}), ~s({"age": -1}), format: :merge_patch)
+      iex> error.instance_path
+      "/age"
+
+  """
+  @spec patch(compiled_schema(), json_string(), json_string(), keyword()) :: Patch.result()
+  defdelegate patch(compiled_schema, document_json, patch_json, opts \\ []), to: Patch
+
+  @doc """
+  Validates each row of a CSV file against the schema of one row.
+
+  `csv` is a binary or an enumerable of binaries, such as a file stream.
+  The header names the columns, fields are converted to the types the
+  schema declares for their properties, and errors give the line and column
+  they are at. See `ExJsonschema.CSV` for the options and how rows are read.
+
+  ## Examples
+
+      iex> compiled = ExJsonschema.compile!(~s({"properties": {"age": {"type": "integer", "minimum": 0}}}))
+      iex> ExJsonschema.validate_csv(compiled, "name,age\\nAda,36\\n")
+      :ok
+      iex> {:error, [%{line: 3, column: "age", error: error}]} =
+      ...>   ExJsonschema.validate_csv(compiled, ["name,age\\nAda,36\\nBob,", "-1\\n"])
+      iex> error.keyword
+      "minimum"
+
+  """
+  @spec validate_csv(compiled_schema(), binary() | Enumerable.t(), keyword()) :: CSV.result()
+  defdelegate validate_csv(compiled_schema, csv, opts \\ []), to: CSV, as: :validate
+
+  @doc """
+  Suggests RFC 6902 JSON Patch operations that repair validation errors.
+
+  Unlike the free-text `suggestions` of verbose errors, the operations can
+  be applied directly, for example with `patch/4`. Only repairs that follow
+  from the schema are suggested:
+
+  - A missing required property is added with its `default`
+  - A number outside `minimum` or `maximum` is clamped to the limit
+  - A string longer than `maxLength` is truncated
+  - Properties rejected by `additionalProperties` or `unevaluatedProperties`
+    are removed
+  - A value not in `enum` is replaced with the closest option (by edit
+    distance for strings, by difference for numbers); a `const` value is
+    replaced with the constant
+
+  Other errors are left alone, so the repaired instance may still be invalid.
+  So are values a schema compiled with `:redact` masks, since a truncated
+  string or a clamped number would give them away. Each location is changed
+  at most once, and removals come last.
+
+  ## Examples
+
+      iex> schema = ~s({
+      ...>   "type": "object",
+      ...>   "required": ["role"],
+      ...>   "additionalProperties": false,
+      ...>   "properties": {
+      ...>     "role": {"enum": ["admin", "viewer"], "default": "viewer"},
+      ...>     "age": {"type": "integer", "maximum": 150}
+      ...>   }
+      ...> })
+      iex> {:ok, compiled} = ExJsonschema.compile(schema)
+      iex> instance = ~s({"age": 200, "nickname": "Al"})
+      iex> {:ok, operations} = ExJsonschema.suggest_fixes(compiled, instance)
+      iex> Enum.sort_by(operations, & &1["path"])
+      [
+        %{"op" => "replace", "path" => "/age", "value" => 150},
+        %{"op" => "remove", "path" => "/nickname"},
+        %{"op" => "add", "path" => "/role", "value" => "viewer"}
+      ]
+      iex> ExJsonschema.suggest_fixes(compiled, ~s({"role": "admn"}))
+      {:ok, [%{"op" => "replace", "path" => "/role", "value" => "admin"}]}
+
+  """
+  @spec suggest_fixes(compiled_schema(), json_string()) ::
+          {:ok, [map()]} | {:error, CompilationError.t()}
+  def suggest_fixes(compiled_schema, instance_json)
+      when is_struct(compiled_schema, Schema) and is_binary(instance_json) do
+    case Native.suggest_fixes(compiled_schema.ref, instance_json) do
+      {:ok, operations} -> {:ok, operations}
+      {:error, error_map} -> {:error, CompilationError.from_map(error_map)}
+    end
+  end
+
+  @doc """
+  Returns the validation error most likely to explain why `instance_json` is
+  invalid, or `nil` when it is valid.
+
+  Schemas built from `oneOf` and `anyOf` report many errors, most of them
+  about subschemas the instance was never meant to match. Like Python
+  jsonschema's `best_match`, the error is picked by relevance:
+
+  - Errors at deeper instance locations win over shallower ones
+  - Errors from specific keywords win over `anyOf` and `oneOf` errors
+  - A winning `anyOf` or `oneOf` error is replaced by the best error of the
+    subschema the instance came closest to matching: the one with the fewest
+    errors, preferring subschemas whose `type` the instance has
+
+  Equally relevant errors are decided by validation order. Raises
+  `ArgumentError` when `instance_json` is not valid JSON.
+
+  ## Examples
+
+      iex> schema = ~s({
+      ...>   "properties": {
+      ...>     "id": {"oneOf": [{"type": "string"}, {"type": "integer", "minimum": 1}]}
+      ...>   }
+      ...> })
+      iex> {:ok, compiled} = ExJsonschema.compile(schema)
+      iex> error = ExJsonschema.best_error(compiled, ~s({"id": 0}))
+      iex> error.schema_path
+      "/properties/id/oneOf/1/minimum"
+      iex> ExJsonschema.best_error(compiled, ~s({"id": 1}))
+      nil
+
+  """
+  @spec best_error(compiled_schema(), json_string()) :: ValidationError.t() | nil
+  def best_error(compiled_schema, instance_json)
+      when is_struct(compiled_schema, Schema) and is_binary(instance_json) do
+    case Native.best_error(compiled_schema.ref, instance_json) do
+      nil -> nil
+      {:error, %{"details" => details}} -> raise ArgumentError, "Invalid JSON: #{details}"
+      error_map -> ValidationError.from_detailed_map(error_map)
+    end
+  end
+
   @doc """
   One-shot validation: compiles schema and validates instance in a single call.
 
@@ -492,12 +796,12 @@ defmodule ExJsonschema do
 
       iex> schema = ~s({"type": "string"})
       iex> {:ok, compiled} = ExJsonschema.compile_draft4(schema)
-      iex> is_reference(compiled)
+      iex> match?(%ExJsonschema.Schema{}, compiled)
       true
 
       # With additional options
       iex> {:ok, compiled} = ExJsonschema.compile_draft4(schema, validate_formats: true)
-      iex> is_reference(compiled)
+      iex> match?(%ExJsonschema.Schema{}, compiled)
       true
 
   """
@@ -515,7 +819,7 @@ defmodule ExJsonschema do
 
       iex> schema = ~s({"type": "string"})
       iex> {:ok, compiled} = ExJsonschema.compile_draft6(schema)
-      iex> is_reference(compiled)
+      iex> match?(%ExJsonschema.Schema{}, compiled)
       true
 
   """
@@ -535,7 +839,7 @@ defmodule ExJsonschema do
 
       iex> schema = ~s({"type": "string"})
       iex> {:ok, compiled} = ExJsonschema.compile_draft7(schema)
-      iex> is_reference(compiled)
+      iex> match?(%ExJsonschema.Schema{}, compiled)
       true
 
   """
@@ -553,7 +857,7 @@ defmodule ExJsonschema do
 
       iex> schema = ~s({"type": "string"})
       iex> {:ok, compiled} = ExJsonschema.compile_draft201909(schema)
-      iex> is_reference(compiled)
+      iex> match?(%ExJsonschema.Schema{}, compiled)
       true
 
   """
@@ -573,7 +877,7 @@ defmodule ExJsonschema do
 
       iex> schema = ~s({"type": "string"})
       iex> {:ok, compiled} = ExJsonschema.compile_draft202012(schema)
-      iex> is_reference(compiled)
+      iex> match?(%ExJsonschema.Schema{}, compiled)
       true
 
   """
@@ -642,13 +946,13 @@ defmodule ExJsonschema do
       # Schema with explicit $schema
       iex> schema = ~s({"$schema": "http://json-schema.org/draft-07/schema#", "type": "string"})
       iex> {:ok, compiled} = ExJsonschema.compile_auto_draft(schema)
-      iex> is_reference(compiled)
+      iex> match?(%ExJsonschema.Schema{}, compiled)
       true
 
       # Schema without $schema (defaults to latest)
       iex> schema = ~s({"type": "number"})
       iex> {:ok, compiled} = ExJsonschema.compile_auto_draft(schema)
-      iex> is_reference(compiled)
+      iex> match?(%ExJsonschema.Schema{}, compiled)
       true
 
   """
@@ -744,6 +1048,168 @@ defmodule ExJsonschema do
     ErrorAnalyzer.summarize(errors)
   end
 
+  @doc """
+  Returns a copy of a schema with every `$ref` replaced by its target.
+
+  Accepts a compiled schema, a JSON string or a map. References are resolved
+  with the same registry the validator uses, so the output matches what
+  validation sees. See `ExJsonschema.Dereferencer` for details.
+
+  ## Options
+
+  - `cycles: :error | :keep` - Report cyclic references as errors (default)
+    or leave them as `$ref`s
+  - Any `compile/2` option when given an uncompiled schema
+
+  ## Examples
+
+      iex> schema = ~s({"items": {"$ref": "#/$defs/name"}, "$defs": {"name": {"type": "string"}}})
+      iex> ExJsonschema.dereference(schema)
+      {:ok, %{"items" => %{"type" => "string"}}}
+
+  """
+  @spec dereference(compiled_schema() | json_string() | map(), keyword()) ::
+          {:ok, map() | boolean()} | {:error, CompilationError.t()}
+  defdelegate dereference(schema, opts \\ []), to: Dereferencer
+
+  @doc """
+  Migrates a schema written for an older draft to 2019-09 or 2020-12.
+
+  Returns the rewritten schema together with a list of constructs that could
+  not be converted automatically. See `ExJsonschema.Migrator` for the list of
+  rewrites.
+
+  ## Options
+
+  - `:to` - `:draft201909` or `:draft202012` (default: `:draft202012`)
+  - `:from` - Source draft (default: `:auto`)
+
+  ## Examples
+
+      iex> legacy = ~s({"id": "http://example.com/s", "dependencies": {"card": ["billing"]}})
+      iex> {:ok, schema, []} = ExJsonschema.migrate(legacy, to: :draft202012)
+      iex> {schema["$id"], schema["dependentRequired"]}
+      {"http://example.com/s", %{"card" => ["billing"]}}
+
+  """
+  @spec migrate(json_string() | map(), keyword()) ::
+          {:ok, map() | boolean(), [Migrator.issue()]} | {:error, CompilationError.t()}
+  defdelegate migrate(schema, opts \\ []), to: Migrator
+
+  @doc """
+  Infers a schema from example documents.
+
+  Samples are decoded terms, or JSON text with `json: true`. The result
+  accepts every sample and records the types, required fields,
+  low-cardinality enums, string formats and numeric ranges they share. See
+  `ExJsonschema.Inferrer`.
+
+  ## Options
+
+  - `:draft` - Any of `ExJsonschema.DraftDetector.supported_drafts/0` (default: `:draft202012`)
+  - `:enum_threshold` - Largest number of distinct strings reported as an enum (default: `5`)
+  - `:json` - Whether the samples are JSON text to decode (default: `false`)
+
+  ## Examples
+
+      iex> {:ok, schema} = ExJsonschema.infer([%{"tags" => ["a"]}, %{"tags" => []}], draft: :draft7)
+      iex> {schema["$schema"], schema["properties"]["tags"]}
+      {"http://json-schema.org/draft-07/schema#", %{"type" => "array", "items" => %{"type" => "string"}}}
+
+  """
+  @spec infer([term()], keyword()) :: {:ok, map()} | {:error, CompilationError.t()}
+  defdelegate infer(samples, opts \\ []), to: Inferrer
+
+  @doc """
+  Converts a JSON Schema into an Apache Avro schema.
+
+  Returns the Avro schema together with everything it could not express,
+  such as `pattern` or `maxLength`, each at its path in the JSON Schema.
+  See `ExJsonschema.Avro` for how types map.
+
+  ## Options
+
+  - `:name` - Name of the root record (default: the schema's `title`, or `"Root"`)
+  - `:namespace` - Namespace of the named types (default: none)
+
+  ## Examples
+
+      iex> schema = ~s({"type": "object", "required": ["id"], "properties": {"id": {"type": "integer"}}})
+      iex> {:ok, avro, []} = ExJsonschema.to_avro(schema, name: "Order", namespace: "com.shop")
+      iex> {avro["name"], avro["namespace"], avro["fields"]}
+      {"Order", "com.shop", [%{"name" => "id", "type" => "long"}]}
+
+  """
+  @spec to_avro(json_string() | map(), keyword()) ::
+          {:ok, Avro.avro_schema(), [Avro.issue()]} | {:error, CompilationError.t()}
+  defdelegate to_avro(schema, opts \\ []), to: Avro
+
+  @doc """
+  Converts an Apache Avro schema, as JSON text or decoded, into a 2020-12
+  JSON Schema.
+
+  Returns the schema together with the Avro attributes it could not carry,
+  such as aliases or `decimal` precision. See `ExJsonschema.Avro`.
+
+  ## Examples
+
+      iex> avro = ~s({"type": "record", "name": "Order", "fields": [{"name": "id", "type": "long"}]})
+      iex> {:ok, schema, []} = ExJsonschema.from_avro(avro)
+      iex> {schema["title"], schema["required"], schema["properties"]["id"]}
+      {"Order", ["id"], %{"type" => "integer"}}
+
+  """
+  @spec from_avro(json_string() | map() | list()) ::
+          {:ok, map(), [Avro.issue()]} | {:error, CompilationError.t()}
+  defdelegate from_avro(avro), to: Avro
+
+  @doc """
+  Serializes a compiled schema into a binary.
+
+  The binary holds the canonical schema and the options it was compiled
+  with, so it can be stored in `:persistent_term`, ETS or on disk, or sent to
+  another node. Restore it with `deserialize/1`.
+
+  ## Examples
+
+      iex> compiled = ExJsonschema.compile!(~s({"type": "string"}))
+      iex> binary = ExJsonschema.serialize(compiled)
+      iex> {:ok, restored} = ExJsonschema.deserialize(binary)
+      iex> ExJsonschema.valid?(restored, ~s("hello"))
+      true
+
+  """
+  @spec serialize(compiled_schema()) :: binary()
+  defdelegate serialize(compiled), to: Serializer
+
+  @doc """
+  Rebuilds a compiled schema from the output of `serialize/1`.
+
+  The validator is recompiled with exactly the recorded options. Binaries that
+  were not produced by `serialize/1`, or by an incompatible version, return
+  `{:error, %CompilationError{type: :deserialization_error}}`.
+  """
+  @spec deserialize(binary()) :: {:ok, compiled_schema()} | {:error, CompilationError.t()}
+  defdelegate deserialize(binary), to: Serializer
+
+  @doc """
+  Returns details about how a compiled schema was built.
+
+  The result holds the effective draft, the schema's `$id`, a hash of the
+  schema content, a fingerprint of the compilation options and the compile
+  time. See `t:ExJsonschema.Schema.info/0`.
+
+  ## Examples
+
+      iex> {:ok, compiled} = ExJsonschema.compile(~s({"$id": "urn:example:age", "type": "integer"}))
+      iex> %{id: id, draft: draft} = ExJsonschema.info(compiled)
+      iex> {id, draft}
+      {"urn:example:age", :draft202012}
+
+  """
+  @spec info(compiled_schema()) :: Schema.info()
+  defdelegate info(compiled), to: Schema
+
   # Meta-validation functions
 
   @doc """
@@ -831,8 +1297,8 @@ defmodule ExJsonschema do
   # Private helper functions for validation options
 
   defp validate_and_normalize_options(opts) do
-    # Only accept output format at validation time
-    valid_options = [:output]
+    # Only accept output control at validation time
+    valid_options = [:output, :ranked, :direction, :input_format]
 
     deprecated_options = [
       :validate_formats,
@@ -847,15 +1313,13 @@ defmodule ExJsonschema do
     unless Enum.empty?(invalid_opts) do
       raise ArgumentError,
             "Invalid validation option(s): #{inspect(invalid_opts)}. " <>
-              "Valid option: :output"
+              "Valid options: :output, :ranked, :direction, :input_format"
     end
 
     # Warn about deprecated options
     for opt <- deprecated_options do
       if Keyword.has_key?(opts, opt) do
-        Logger.warning(
-          "Option #{inspect(opt)} ignored at validation time. Set during compilation with ExJsonschema.compile/2 instead."
-        )
+        Logger.warning(deprecated_option_warning(opt))
       end
     end
 
@@ -869,31 +1333,81 @@ defmodule ExJsonschema do
       end
     end
 
-    # Create minimal Options struct with only output format
-    %Options{output_format: Keyword.get(opts, :output, :detailed)}
+    ranked = Keyword.get(opts, :ranked, false)
+
+    unless is_boolean(ranked) do
+      raise ArgumentError, "Invalid ranked option: #{inspect(ranked)}. Must be a boolean"
+    end
+
+    direction = Keyword.get(opts, :direction)
+
+    unless direction in [nil, :request, :response] do
+      raise ArgumentError,
+            "Invalid direction option: #{inspect(direction)}. Must be :request or :response"
+    end
+
+    input_format = Keyword.get(opts, :input_format, :json)
+
+    unless input_format in [:json, :yaml, :toml, :msgpack, :cbor] do
+      raise ArgumentError,
+            "Invalid input_format option: #{inspect(input_format)}. " <>
+              "Must be one of: :json, :yaml, :toml, :msgpack, :cbor"
+    end
+
+    # Create minimal Options struct with only output control
+    %Options{
+      output_format: Keyword.get(opts, :output, :detailed),
+      ranked: ranked,
+      direction: direction,
+      input_format: input_format
+    }
+  end
+
+  defp deprecated_option_warning(:ignore_unknown_formats) do
+    "Option :ignore_unknown_formats ignored at validation time. Compile with " <>
+      "unknown_formats: :ignore or unknown_formats: :error instead."
+  end
+
+  defp deprecated_option_warning(opt) do
+    "Option #{inspect(opt)} ignored at validation time. Set during compilation with ExJsonschema.compile/2 instead."
   end
 
   defp validate_with_options(
-         compiled_schema,
+         %Schema{ref: compiled_schema},
          instance_json,
          %Options{output_format: output_format} = options
        ) do
-    case output_format do
-      :basic ->
-        validate_basic_with_options(compiled_schema, instance_json, options)
+    validate_fun =
+      case output_format do
+        :basic ->
+          &validate_basic_with_options/3
 
-      :detailed ->
-        validate_detailed_with_options(compiled_schema, instance_json, options)
+        :detailed ->
+          &validate_detailed_with_options/3
 
-      :verbose ->
-        validate_verbose_with_options(compiled_schema, instance_json, options)
+        :verbose ->
+          &validate_verbose_with_options/3
 
-      _ ->
-        raise ArgumentError,
-              "Invalid output format: #{inspect(output_format)}. Must be one of: :basic, :detailed, :verbose"
-    end
+        _ ->
+          raise ArgumentError,
+                "Invalid output format: #{inspect(output_format)}. Must be one of: :basic, :detailed, :verbose"
+      end
+
+    metadata =
+      compiled_schema
+      |> Telemetry.schema_metadata()
+      |> Map.merge(%{output_format: output_format, instance_size: instance_size(instance_json)})
+
+    Telemetry.span(:validate, metadata, fn ->
+      {result, measurements} = validate_fun.(compiled_schema, instance_json, options)
+      {result, measurements, %{}}
+    end)
   end
 
+  # Terms from `validate_term/3` have no size in bytes
+  defp instance_size(instance) when is_binary(instance), do: byte_size(instance)
+  defp instance_size(_term), do: nil
+
   defp valid_with_options(compiled_schema, instance_json, %Options{} = options) do
     # For now, use basic validation with options
     # In the future, this could be optimized to use a dedicated native function
@@ -905,68 +1419,135 @@ defmodule ExJsonschema do
 
   # Private validation functions for different output formats
 
-  defp validate_basic(compiled_schema, instance_json) do
-    if Native.valid?(compiled_schema, instance_json) do
-      :ok
-    else
-      {:error, :validation_failed}
+  # Each returns `{result, measurements}`, the measurements coming from the NIF
+
+  defp validate_basic(compiled_schema, instance_json, direction, input_format) do
+    case Native.valid(compiled_schema, instance_json, direction, input_format) do
+      {true, measurements} -> {:ok, measurements}
+      {false, measurements} -> {{:error, :validation_failed}, measurements}
     end
   end
 
-  defp validate_detailed(compiled_schema, instance_json) do
-    case Native.validate_detailed(compiled_schema, instance_json) do
+  defp validate_detailed(compiled_schema, instance_json, ranked, direction, input_format) do
+    {result, measurements} =
+      Native.validate_detailed(compiled_schema, instance_json, ranked, direction, input_format)
+
+    case result do
       :ok ->
-        :ok
+        {:ok, measurements}
 
       {:error, error_list} when is_list(error_list) ->
         errors = Enum.map(error_list, &ValidationError.from_detailed_map/1)
-        {:error, errors}
+        {{:error, errors}, measurements}
+
+      {:error, %{"type" => _} = error_map} ->
+        {{:error, CompilationError.from_map(error_map)}, measurements}
 
       {:error, _reason} ->
-        {:error, [:validation_error]}
+        {{:error, [:validation_error]}, measurements}
     end
   end
 
-  defp validate_verbose(compiled_schema, instance_json, _opts) do
-    case Native.validate_verbose(compiled_schema, instance_json) do
+  defp validate_verbose(compiled_schema, instance_json, ranked, direction, input_format) do
+    {result, measurements} =
+      Native.validate_verbose(compiled_schema, instance_json, ranked, direction, input_format)
+
+    case result do
       :ok ->
-        :ok
+        {:ok, measurements}
 
       {:error, error_list} when is_list(error_list) ->
         errors = Enum.map(error_list, &ValidationError.from_map/1)
-        {:error, errors}
+        {{:error, errors}, measurements}
+
+      {:error, %{"type" => _} = error_map} ->
+        {{:error, CompilationError.from_map(error_map)}, measurements}
 
       {:error, _reason} ->
-        {:error, [:validation_error]}
+        {{:error, [:validation_error]}, measurements}
     end
   end
 
+  defp validate_at_result(:ok), do: :ok
+  defp validate_at_result(:error), do: {:error, :validation_failed}
+
+  defp validate_at_result({:error, error_list}) when is_list(error_list) do
+    {:error, Enum.map(error_list, &ValidationError.from_detailed_map/1)}
+  end
+
+  defp validate_at_result({:error, %{"type" => "pointer_error", "details" => details}}) do
+    raise ArgumentError, details
+  end
+
+  defp validate_at_result({:error, %{"type" => "compilation_error"} = error_map}) do
+    {:error, CompilationError.from_map(error_map)}
+  end
+
+  defp validate_at_result({:error, _reason}), do: {:error, [:validation_error]}
+
   # Validation functions with output format selection
   # Validation behavior options (validate_formats, stop_on_first_error, etc.) are baked
   # into the compiled schema during compilation, not passed at validation time.
 
-  defp validate_basic_with_options(compiled_schema, instance_json, %Options{} = _options) do
+  defp validate_basic_with_options(compiled_schema, instance_json, %Options{} = options) do
     # Output format is handled at calling level - validation behavior is baked into compiled schema
-    validate_basic(compiled_schema, instance_json)
+    validate_basic(compiled_schema, instance_json, options.direction, options.input_format)
   end
 
-  defp validate_detailed_with_options(compiled_schema, instance_json, %Options{} = _options) do
+  defp validate_detailed_with_options(compiled_schema, instance_json, %Options{} = options) do
     # Output format is handled at calling level - validation behavior is baked into compiled schema
-    validate_detailed(compiled_schema, instance_json)
+    validate_detailed(
+      compiled_schema,
+      instance_json,
+      options.ranked,
+      options.direction,
+      options.input_format
+    )
   end
 
-  defp validate_verbose_with_options(compiled_schema, instance_json, %Options{} = _options) do
+  defp validate_verbose_with_options(compiled_schema, instance_json, %Options{} = options) do
     # Output format is handled at calling level - validation behavior is baked into compiled schema
-    validate_verbose(compiled_schema, instance_json, [])
+    validate_verbose(
+      compiled_schema,
+      instance_json,
+      options.ranked,
+      options.direction,
+      options.input_format
+    )
   end
 
   # Private functions
 
-  defp compile_with_options(schema_json, %Options{draft: :auto} = options) do
+  defp compile_with_options(schema_json, %Options{} = options) do
+    metadata = %{
+      draft: options.draft,
+      schema_id: nil,
+      output_format: options.output_format,
+      schema_size: byte_size(schema_json)
+    }
+
+    Telemetry.span(:compile, metadata, fn ->
+      {result, measurements, stop_metadata} = compile_resolving_draft(schema_json, options)
+
+      case result do
+        {:ok, %Schema{ref: compiled}} ->
+          schema_metadata = Telemetry.schema_metadata(compiled)
+          {result, measurements, Map.merge(stop_metadata, schema_metadata)}
+
+        {:error, _} ->
+          {result, measurements, stop_metadata}
+      end
+    end)
+  end
+
+  # The compile functions below return `{result, measurements, metadata}` for
+  # the compile span
+
+  defp compile_resolving_draft(schema_json, %Options{draft: :auto} = options) do
     Logger.debug("Auto-detecting JSON Schema draft")
 
     # Auto-detect draft from schema and update options
-    case DraftDetector.detect_draft(schema_json) do
+    case DraftDetector.detect_draft(schema_json, options.input_format) do
       {:ok, detected_draft} ->
         Logger.debug("Draft auto-detection successful", %{detected_draft: detected_draft})
         updated_options = %{options | draft: detected_draft}
@@ -974,63 +1555,68 @@ defmodule ExJsonschema do
 
       {:error, reason} ->
         Logger.warning("Draft auto-detection failed", %{reason: reason})
-        {:error, CompilationError.from_detection_error(reason)}
+        {{:error, CompilationError.from_detection_error(reason)}, %{}, %{cache: :none}}
     end
   end
 
-  defp compile_with_options(schema_json, %Options{} = options) do
+  defp compile_resolving_draft(schema_json, %Options{} = options) do
     compile_with_native_options(schema_json, options)
   end
 
   defp compile_with_native_options(schema_json, %Options{draft: draft} = options) do
     Logger.debug("Compiling with native options", %{draft: draft})
 
-    # Check cache first using schema ID
-    case extract_schema_id(schema_json) do
-      {:ok, schema_id} ->
+    # Check cache first using the content and options fingerprint
+    case ExJsonschema.Cache.key(schema_json, options) do
+      {:ok, cache_key} ->
         cache_module = get_cache_module()
 
-        case cache_module.get(schema_id) do
+        case cache_module.get(cache_key) do
           {:ok, cached_compiled} ->
             Logger.debug("Cache hit for schema compilation", %{
-              schema_id: schema_id,
+              cache_key: cache_key,
               cache: cache_module
             })
 
-            {:ok, cached_compiled}
+            {{:ok, cached_compiled}, %{}, %{cache: :hit}}
 
           {:error, :not_found} ->
             Logger.debug("Cache miss, proceeding with compilation", %{
-              schema_id: schema_id,
+              cache_key: cache_key,
               cache: cache_module
             })
 
-            compile_and_cache(schema_json, schema_id, options)
+            compile_and_cache(schema_json, cache_key, options)
 
           {:error, :no_test_cache_configured} ->
             Logger.debug("No test cache configured, proceeding without caching")
-            compile_without_cache(schema_json, options)
+            compile_uncached(schema_json, options)
         end
 
-      {:error, :no_id} ->
-        Logger.debug("No schema ID found, proceeding without caching")
-        compile_without_cache(schema_json, options)
+      {:error, _} ->
+        # Malformed JSON - let compilation report the error
+        compile_uncached(schema_json, options)
     end
   end
 
-  defp compile_and_cache(schema_json, schema_id, options) do
+  defp compile_and_cache(schema_json, cache_key, options) do
     case compile_without_cache(schema_json, options) do
-      {:ok, compiled} ->
+      {{:ok, compiled}, measurements} ->
         # Store in cache for future use
         cache_module = get_cache_module()
-        cache_module.put(schema_id, compiled)
-        {:ok, compiled}
+        cache_module.put(cache_key, compiled)
+        {{:ok, compiled}, measurements, %{cache: :miss}}
 
-      error ->
-        error
+      {error, measurements} ->
+        {error, measurements, %{cache: :miss}}
     end
   end
 
+  defp compile_uncached(schema_json, options) do
+    {result, measurements} = compile_without_cache(schema_json, options)
+    {result, measurements, %{cache: :none}}
+  end
+
   defp compile_without_cache(schema_json, %Options{} = options) do
     # Single transformation point: Options -> Native ValidationOptions
     native_options = ExJsonschema.Native.ValidationOptions.from_options(options)
@@ -1040,28 +1626,28 @@ defmodule ExJsonschema do
         Logger.debug("Using jsonschema::options() builder compilation")
 
         case Native.compile_schema_with_options(schema_json, native_options) do
-          {:ok, compiled} ->
+          {{:ok, compiled}, measurements} ->
             Logger.debug("Native compilation with options successful")
-            {:ok, compiled}
+            {{:ok, Schema.wrap(compiled)}, measurements}
 
-          {:error, error_map} ->
+          {{:error, error_map}, measurements} ->
             Logger.error("Native compilation failed", %{error_map: error_map})
-            {:error, CompilationError.from_map(error_map)}
+            {{:error, CompilationError.from_map(error_map)}, measurements}
         end
 
       {:error, reason} ->
         Logger.warning("Compilation options validation failed", %{reason: reason})
-        {:error, CompilationError.from_validation_error(reason)}
+        {{:error, CompilationError.from_validation_error(reason)}, %{}}
     end
   end
 
   # Validates that compilation options are consistent with the schema
-  defp validate_compilation_options(schema_json, %Options{draft: draft} = _options) do
+  defp validate_compilation_options(schema_json, %Options{draft: draft} = options) do
     # When draft is not :auto, validate it matches schema if schema has $schema
-    case DraftDetector.detect_draft(schema_json) do
+    case DraftDetector.detect_draft(schema_json, options.input_format) do
       {:ok, detected_draft} ->
         if draft != :auto and draft != detected_draft do
-          case DraftDetector.schema_has_draft?(schema_json) do
+          case DraftDetector.schema_has_draft?(schema_json, options.input_format) do
             true ->
               {:error, "Schema specifies #{detected_draft} but options specify #{draft}"}
 
@@ -1078,31 +1664,7 @@ defmodule ExJsonschema do
     end
   end
 
-  # Extract schema ID for caching purposes
-  # Looks for $id first (preferred), then falls back to $schema
   defp get_cache_module do
     Application.get_env(:ex_jsonschema, :cache, ExJsonschema.Cache.Noop)
   end
-
-  defp extract_schema_id(schema_json) when is_binary(schema_json) do
-    case Jason.decode(schema_json) do
-      {:ok, schema_map} when is_map(schema_map) ->
-        schema_id = Map.get(schema_map, "$id") || Map.get(schema_map, "$schema")
-
-        case schema_id do
-          id when is_binary(id) and byte_size(id) > 0 -> {:ok, id}
-          _ -> {:error, :no_id}
-        end
-
-      {:ok, _} ->
-        # Boolean schemas or other non-map schemas don't have IDs
-        {:error, :no_id}
-
-      {:error, _} ->
-        # Malformed JSON - no ID can be extracted
-        {:error, :no_id}
-    end
-  rescue
-    _ -> {:error, :no_id}
-  end
 end
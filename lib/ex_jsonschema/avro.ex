@@ -0,0 +1,180 @@
+defmodule ExJsonschema.Avro do
+  @moduledoc """
+  Converts between JSON Schema and Apache Avro schemas, so one can be kept
+  as the source of truth for entities that are both sent over HTTP and
+  written to Kafka.
+
+  Both directions describe the same data as plain JSON: an Avro `bytes`
+  value is a base64 string, a `date` a `"2024-05-01"` string, and a union
+  value is the value itself rather than Avro's `{"type": value}` wrapper.
+  The conversion runs in Rust.
+
+  ## JSON Schema to Avro
+
+  - Objects with `properties` become records. Required properties become
+    fields as they are; optional ones become unions with `null`, defaulting
+    to `null`, unless they have a default of their own
+  - Objects with only `additionalProperties` become maps
+  - `enum`s and `const`s of strings become Avro enums, when every value is
+    a valid Avro name
+  - `oneOf`, `anyOf` and lists of types become unions
+  - `integer` becomes `int` when `minimum` and `maximum` fit in 32 bits (or
+    with format `int32`), and `long` otherwise; `number` becomes `double`
+  - Formats become logical types: `date` → `date`, `date-time` →
+    `timestamp-millis`, `time` → `time-millis`, `uuid` → `uuid`. Strings
+    with `contentEncoding: "base64"` become `bytes`
+  - References within the schema are followed. Records and enums are
+    defined where first used and named after that, so recursive records
+    work
+
+  Records and enums are named after their `title`, the `$defs` entry or
+  property they come from, in PascalCase. Property names that are not
+  valid Avro names are rewritten, with `-` and other characters becoming
+  `_`.
+
+  ## Avro to JSON Schema
+
+  - Records become objects with `"additionalProperties": false`, their
+    fields without a default being required
+  - Enums become string `enum`s, arrays arrays and maps objects with
+    `additionalProperties`
+  - A union of `null` and one other type becomes that type made nullable;
+    other unions become `anyOf`
+  - `int` becomes an integer with 32-bit bounds, `long` an integer, `float`
+    and `double` numbers, `bytes` base64 strings and `fixed` base64 strings
+    of the length its size encodes to
+  - Logical types become the formats above
+  - Named types are defined under `$defs` by full name, such as
+    `"#/$defs/com.shop.Item"`, and the root record at the root
+
+  The result declares draft 2020-12.
+
+  ## Issues
+
+  Anything that cannot be carried over exactly is reported rather than
+  silently lost: each issue gives a JSON pointer to the keyword or
+  attribute in the schema that was converted, what it is, and what became
+  of it. Constraints such as `pattern`, `minimum` or `maxLength` have no
+  Avro equivalent; Avro's field `order`, `aliases`, enum defaults and
+  `decimal` precision have no JSON Schema one.
+
+      {:ok, avro, issues} =
+        ExJsonschema.Avro.to_avro(~s({
+          "title": "Order",
+          "type": "object",
+          "required": ["id"],
+          "properties": {
+            "id": {"type": "string", "format": "uuid"},
+            "note": {"type": "string", "maxLength": 200}
+          }
+        }), namespace: "com.shop")
+
+      avro["fields"]
+      #=> [%{"name" => "id", "type" => %{"type" => "string", "logicalType" => "uuid"}},
+      #=>  %{"name" => "note", "type" => ["null", "string"], "default" => nil}]
+
+      issues
+      #=> [%{path: "/properties/note/maxLength", keyword: "maxLength",
+      #=>    message: "maxLength has no Avro equivalent and is dropped"}]
+
+  Schemas that cannot be converted at all, such as ones with references
+  to other documents or Avro schemas using names before defining them,
+  return a `ExJsonschema.CompilationError` of type `:conversion_error`.
+  """
+
+  alias ExJsonschema.{CompilationError, Native}
+
+  @typedoc """
+  Something a conversion could not carry over exactly.
+
+  `path` is a JSON Pointer into the schema that was converted.
+  """
+  @type issue :: %{path: String.t(), keyword: String.t(), message: String.t()}
+
+  @typedoc "An Avro schema: a record or other object, a union, or a type name"
+  @type avro_schema :: map() | list() | String.t()
+
+  @doc """
+  Converts a JSON Schema, given as JSON text or a decoded map, into an Avro
+  schema.
+
+  ## Options
+
+  - `:name` - Name of the root record or enum (default: the schema's
+    `title`, or `"Root"`)
+  - `:namespace` - Namespace of the named types, such as `"com.shop"`
+    (default: none)
+
+  ## Examples
+
+      iex> schema = ~s({"type": "array", "items": {"type": "string"}})
+      iex> {:ok, avro, []} = ExJsonschema.Avro.to_avro(schema)
+      iex> avro
+      %{"type" => "array", "items" => "string"}
+
+  """
+  @spec to_avro(String.t() | map(), keyword()) ::
+          {:ok, avro_schema(), [issue()]} | {:error, CompilationError.t()}
+  def to_avro(schema, opts \\ [])
+
+  def to_avro(schema, opts) when is_map(schema) do
+    to_avro(Jason.encode!(schema), opts)
+  end
+
+  def to_avro(schema_json, opts) when is_binary(schema_json) and is_list(opts) do
+    name = Keyword.get(opts, :name)
+    namespace = Keyword.get(opts, :namespace)
+
+    unless is_nil(name) or avro_name?(name) do
+      raise ArgumentError,
+            "Invalid name option: #{inspect(name)}. Must be letters, digits and underscores"
+    end
+
+    unless is_nil(namespace) or
+             (is_binary(namespace) and Enum.all?(String.split(namespace, "."), &avro_name?/1)) do
+      raise ArgumentError,
+            "Invalid namespace option: #{inspect(namespace)}. Must be names separated by dots"
+    end
+
+    Native.schema_to_avro(schema_json, name, namespace) |> conversion_result()
+  end
+
+  @doc """
+  Converts an Avro schema, given as JSON text or decoded, into a JSON
+  Schema.
+
+  JSON text of a bare type name is a JSON string, such as `~s("long")`.
+
+  ## Examples
+
+      iex> avro = ~s({"type": "map", "values": ["null", "long"]})
+      iex> {:ok, schema, []} = ExJsonschema.Avro.from_avro(avro)
+      iex> schema["additionalProperties"]
+      %{"type" => ["integer", "null"]}
+
+  """
+  @spec from_avro(String.t() | map() | list()) ::
+          {:ok, map(), [issue()]} | {:error, CompilationError.t()}
+  def from_avro(avro) when is_map(avro) or is_list(avro) do
+    from_avro(Jason.encode!(avro))
+  end
+
+  def from_avro(avro_json) when is_binary(avro_json) do
+    Native.avro_to_schema(avro_json) |> conversion_result()
+  end
+
+  defp conversion_result({:ok, converted, issues}) do
+    {:ok, converted, Enum.map(issues, &to_issue/1)}
+  end
+
+  defp conversion_result({:error, error_map}) do
+    {:error, CompilationError.from_map(error_map)}
+  end
+
+  defp to_issue(%{"path" => path, "keyword" => keyword, "message" => message}) do
+    %{path: path, keyword: keyword, message: message}
+  end
+
+  defp avro_name?(name) when is_binary(name), do: name =~ ~r/^[A-Za-z_][A-Za-z0-9_]*$/
+  defp avro_name?(_name), do: false
+end
@@ -2,9 +2,19 @@ defmodule ExJsonschema.Cache do
   @moduledoc """
   Behaviour for caching compiled JSON schemas.
 
-  This behaviour defines the interface for caching compiled schemas by their ID.
+  This behaviour defines the interface for caching compiled schemas.
   Implementations can be stateless modules, GenServers, or any other approach.
 
+  ## Cache Keys
+
+  Keys are computed by `key/2`: a SHA-256 digest of the canonicalized schema
+  (sorted keys, no whitespace) combined with the options that affect
+  compilation (`draft`, `validate_formats`, `regex_engine`). Two schemas only
+  share an entry when they are the same document compiled the same way,
+  whatever their `$id` or `$schema`. A schema read from YAML or TOML (see
+  `:input_format`) does not share an entry with its JSON equivalent, since
+  it keeps source positions for its errors.
+
   ## Configuration
 
   Configure which cache module to use:
@@ -15,6 +25,12 @@ defmodule ExJsonschema.Cache do
 
   By default, uses `ExJsonschema.Cache.Noop` which disables caching.
 
+  ## Built-in Caches
+
+  - `ExJsonschema.Cache.ETS` - Supervised ETS table with optional TTL and LRU eviction
+  - `ExJsonschema.Cache.PersistentTerm` - Read-optimized storage for schemas
+    compiled once at boot
+
   ## Testing
 
   Use `ExJsonschema.Cache.Test` for isolated test caches:
@@ -63,4 +79,48 @@ defmodule ExJsonschema.Cache do
   Returns `:ok`.
   """
   @callback clear() :: :ok
+
+  alias ExJsonschema.{CompilationError, DraftDetector, Native, Options}
+
+  @doc """
+  Computes the cache key `ExJsonschema.compile/2` uses for a schema.
+
+  Accepts the same options as `ExJsonschema.compile/2`. With `draft: :auto`
+  the draft is detected first, exactly as compilation does.
+
+  ## Examples
+
+      iex> {:ok, a} = ExJsonschema.Cache.key(~s({"type": "string", "minLength": 1}))
+      iex> {:ok, b} = ExJsonschema.Cache.key(~s({ "minLength": 1.0, "type": "string" }))
+      iex> a == b
+      true
+
+      iex> {:ok, a} = ExJsonschema.Cache.key(~s({"type": "string"}))
+      iex> {:ok, b} = ExJsonschema.Cache.key(~s({"type": "string"}), validate_formats: true)
+      iex> a == b
+      false
+
+  """
+  @spec key(String.t(), Options.t() | keyword()) :: {:ok, binary()} | {:error, CompilationError.t()}
+  def key(schema_json, options \\ [])
+
+  def key(schema_json, options) when is_binary(schema_json) and is_list(options) do
+    key(schema_json, Options.new(options))
+  end
+
+  def key(schema_json, %Options{draft: :auto} = options) when is_binary(schema_json) do
+    case DraftDetector.detect_draft(schema_json, options.input_format) do
+      {:ok, draft} -> key(schema_json, %{options | draft: draft})
+      {:error, reason} -> {:error, CompilationError.from_detection_error(reason)}
+    end
+  end
+
+  def key(schema_json, %Options{} = options) when is_binary(schema_json) do
+    native_options = ExJsonschema.Native.ValidationOptions.from_options(options)
+
+    case Native.schema_cache_key(schema_json, native_options) do
+      {:ok, key} -> {:ok, key}
+      {:error, error_map} -> {:error, CompilationError.from_map(error_map)}
+    end
+  end
 end
@@ -0,0 +1,207 @@
+defmodule ExJsonschema.Cache.ETS do
+  @moduledoc """
+  An ETS-backed cache with optional TTL expiry and LRU eviction.
+
+  Reads and writes go straight to a public ETS table, so lookups never wait
+  on a process. The owning GenServer only sweeps expired entries and evicts
+  the least recently used ones when the table grows past `:max_entries`.
+
+  ## Usage
+
+  Add the cache to your supervision tree and configure it:
+
+      # lib/my_app/application.ex
+      children = [
+        {ExJsonschema.Cache.ETS, ttl: :timer.hours(1), max_entries: 1_000}
+      ]
+
+      # config/config.exs
+      config :ex_jsonschema, cache: ExJsonschema.Cache.ETS
+
+  ## Options
+
+  - `:ttl` - Milliseconds an entry stays valid after it is stored, or
+    `:infinity` (default: `:infinity`)
+  - `:max_entries` - Number of entries kept before the least recently used
+    ones are evicted, or `:infinity` (default: `:infinity`)
+  - `:sweep_interval` - Milliseconds between sweeps of expired entries
+    (default: `60_000`)
+
+  When the cache is not running, `get/1` misses and `put/2` is a no-op, so
+  compilation keeps working without it.
+  """
+
+  use GenServer
+
+  @behaviour ExJsonschema.Cache
+
+  @table __MODULE__
+  @config_key {__MODULE__, :config}
+
+  @doc """
+  Starts the cache and creates its table.
+  """
+  @spec start_link(keyword()) :: GenServer.on_start()
+  def start_link(opts \\ []) do
+    GenServer.start_link(__MODULE__, opts, name: __MODULE__)
+  end
+
+  @doc """
+  Returns the number of cached entries.
+  """
+  @spec size() :: non_neg_integer()
+  def size do
+    case :ets.info(@table, :size) do
+      :undefined -> 0
+      size -> size
+    end
+  end
+
+  @impl ExJsonschema.Cache
+  def get(key) do
+    now = now()
+
+    case lookup(key) do
+      [{^key, value, expires_at, _last_used}] when expires_at > now ->
+        # Recency only feeds LRU eviction, so a lost race here is harmless
+        :ets.update_element(@table, key, {4, now})
+        {:ok, value}
+
+      [{^key, _value, _expires_at, _last_used}] ->
+        :ets.delete(@table, key)
+        {:error, :not_found}
+
+      [] ->
+        {:error, :not_found}
+    end
+  end
+
+  @impl ExJsonschema.Cache
+  def put(key, value) do
+    %{ttl: ttl, max_entries: max_entries} = config()
+    now = now()
+    expires_at = if ttl == :infinity, do: :infinity, else: now + ttl
+
+    if table_exists?() do
+      :ets.insert(@table, {key, value, expires_at, now})
+
+      if max_entries != :infinity and size() > max_entries do
+        GenServer.cast(__MODULE__, :evict)
+      end
+    end
+
+    :ok
+  end
+
+  @impl ExJsonschema.Cache
+  def delete(key) do
+    if table_exists?(), do: :ets.delete(@table, key)
+    :ok
+  end
+
+  @impl ExJsonschema.Cache
+  def clear do
+    if table_exists?(), do: :ets.delete_all_objects(@table)
+    :ok
+  end
+
+  # GenServer callbacks
+
+  @impl GenServer
+  def init(opts) do
+    config = %{
+      ttl: validate_limit!(opts, :ttl),
+      max_entries: validate_limit!(opts, :max_entries),
+      sweep_interval: Keyword.get(opts, :sweep_interval, 60_000)
+    }
+
+    # Ensure terminate/2 runs on shutdown so the config is cleaned up
+    Process.flag(:trap_exit, true)
+    :persistent_term.put(@config_key, config)
+
+    :ets.new(@table, [
+      :named_table,
+      :set,
+      :public,
+      read_concurrency: true,
+      write_concurrency: true
+    ])
+
+    schedule_sweep(config)
+    {:ok, config}
+  end
+
+  @impl GenServer
+  def handle_cast(:evict, %{max_entries: max_entries} = config) do
+    evict(max_entries)
+    {:noreply, config}
+  end
+
+  @impl GenServer
+  def handle_info(:sweep, config) do
+    sweep_expired()
+    schedule_sweep(config)
+    {:noreply, config}
+  end
+
+  @impl GenServer
+  def terminate(_reason, _config) do
+    :persistent_term.erase(@config_key)
+    :ok
+  end
+
+  # Private functions
+
+  defp lookup(key) do
+    :ets.lookup(@table, key)
+  rescue
+    ArgumentError -> []
+  end
+
+  defp table_exists? do
+    :ets.whereis(@table) != :undefined
+  end
+
+  defp config do
+    :persistent_term.get(@config_key, %{ttl: :infinity, max_entries: :infinity})
+  end
+
+  defp evict(max_entries) do
+    excess = size() - max_entries
+
+    if excess > 0 do
+      @table
+      |> :ets.select([{{:"$1", :_, :_, :"$2"}, [], [{{:"$2", :"$1"}}]}])
+      |> Enum.sort()
+      |> Enum.take(excess)
+      |> Enum.each(fn {_last_used, key} -> :ets.delete(@table, key) end)
+    end
+  end
+
+  # Atoms compare greater than integers, so `:infinity` never expires
+  defp sweep_expired do
+    :ets.select_delete(@table, [{{:_, :_, :"$1", :_}, [{:"=<", :"$1", now()}], [true]}])
+  end
+
+  defp schedule_sweep(%{ttl: :infinity}), do: :ok
+
+  defp schedule_sweep(%{sweep_interval: interval}) do
+    Process.send_after(self(), :sweep, interval)
+  end
+
+  defp validate_limit!(opts, name) do
+    case Keyword.get(opts, name, :infinity) do
+      :infinity ->
+        :infinity
+
+      limit when is_integer(limit) and limit > 0 ->
+        limit
+
+      other ->
+        raise ArgumentError,
+              "Invalid #{name}: #{inspect(other)}. Must be a positive integer or :infinity"
+    end
+  end
+
+  defp now, do: System.monotonic_time(:millisecond)
+end
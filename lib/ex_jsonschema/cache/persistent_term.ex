@@ -0,0 +1,54 @@
+defmodule ExJsonschema.Cache.PersistentTerm do
+  @moduledoc """
+  A cache backed by `:persistent_term`.
+
+  Lookups are constant time and copy nothing, which makes this the fastest
+  option for a fixed set of schemas compiled at boot and reused for the
+  lifetime of the node.
+
+  Storing or removing a term triggers a global garbage collection pass, so
+  this cache is a poor fit for schemas that change at runtime. Use
+  `ExJsonschema.Cache.ETS` for those.
+
+  ## Usage
+
+      config :ex_jsonschema, cache: ExJsonschema.Cache.PersistentTerm
+
+  No process needs to be started.
+  """
+
+  @behaviour ExJsonschema.Cache
+
+  @impl ExJsonschema.Cache
+  def get(key) do
+    case :persistent_term.get({__MODULE__, key}, nil) do
+      nil -> {:error, :not_found}
+      value -> {:ok, value}
+    end
+  end
+
+  @impl ExJsonschema.Cache
+  def put(key, value) do
+    # Skip identical writes, which would still trigger a global GC
+    unless :persistent_term.get({__MODULE__, key}, nil) === value do
+      :persistent_term.put({__MODULE__, key}, value)
+    end
+
+    :ok
+  end
+
+  @impl ExJsonschema.Cache
+  def delete(key) do
+    :persistent_term.erase({__MODULE__, key})
+    :ok
+  end
+
+  @impl ExJsonschema.Cache
+  def clear do
+    for {{__MODULE__, key}, _value} <- :persistent_term.get() do
+      :persistent_term.erase({__MODULE__, key})
+    end
+
+    :ok
+  end
+end
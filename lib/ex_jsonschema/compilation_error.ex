@@ -9,11 +9,17 @@ defmodule ExJsonschema.CompilationError do
   @type t :: %__MODULE__{
           type:
             :json_parse_error
+            | :parse_error
             | :schema_validation_error
             | :compilation_error
             | :options_error
             | :detection_error
-            | :validation_error,
+            | :validation_error
+            | :dereference_error
+            | :migration_error
+            | :inference_error
+            | :conversion_error
+            | :deserialization_error,
           message: String.t(),
           details: String.t() | nil
         }
@@ -28,11 +34,17 @@ defmodule ExJsonschema.CompilationError do
     type =
       case type_str do
         "json_parse_error" -> :json_parse_error
+        "parse_error" -> :parse_error
         "schema_validation_error" -> :schema_validation_error
         "compilation_error" -> :compilation_error
         "options_error" -> :options_error
         "detection_error" -> :detection_error
         "validation_error" -> :validation_error
+        "dereference_error" -> :dereference_error
+        "migration_error" -> :migration_error
+        "inference_error" -> :inference_error
+        "conversion_error" -> :conversion_error
+        "deserialization_error" -> :deserialization_error
         _ -> :compilation_error
       end
 
@@ -0,0 +1,149 @@
+defmodule ExJsonschema.CSV do
+  @moduledoc """
+  Validates CSV files row by row against the schema of one row.
+
+  The first record names the columns. Every record after it becomes an
+  object, each field under its column's name, and is validated against the
+  schema on its own:
+
+      compiled = ExJsonschema.compile!(~s({
+        "type": "object",
+        "required": ["sku", "quantity"],
+        "properties": {
+          "sku": {"type": "string", "pattern": "^[A-Z]+-[0-9]+$"},
+          "quantity": {"type": "integer", "minimum": 1}
+        }
+      }))
+
+      ExJsonschema.CSV.validate(compiled, File.stream!("upload.csv", 64 * 1024))
+      #=> {:error, [%{line: 3, column: "quantity", error: %ExJsonschema.ValidationError{}}]}
+
+  ## Columns and Types
+
+  Fields are text, so each is converted to the type the schema declares for
+  its column's property before validation: `"integer"` and `"number"`
+  fields to numbers, and `"boolean"` fields reading `true` or `false` to
+  booleans. Properties are looked up in the schema's `properties`, then
+  its matching `patternProperties`, following `$ref`s as validation does;
+  columns without one take the type of `additionalProperties`. Fields that
+  do not convert stay strings, for the schema to reject.
+
+  Empty fields are left out of the row, so a column the schema requires
+  fails `required` when its field is empty. A record with more fields than
+  there are columns gets an error of keyword `"columns"` at no column.
+
+  Records follow RFC 4180: fields in double quotes may hold separators,
+  line breaks and `""` for a quote. Records end with `\\n` or `\\r\\n`,
+  blank lines are skipped and a leading byte order mark is ignored. A file
+  that cannot be read this way, or whose header names a column twice or
+  leaves one unnamed, returns a `ExJsonschema.CompilationError` of type
+  `:parse_error` whose details give the line.
+
+  ## Streams
+
+  Besides a binary, the CSV may be any enumerable of binaries, such as a
+  `File.stream!/2` or the chunks of an upload. Chunks are read as they come
+  and may split records anywhere, so only the record in progress is kept
+  in memory, not the file.
+
+  ## Errors
+
+  Each error says where it is in the file:
+
+  - `:line` - The line its record starts on, counted from 1
+  - `:column` - The column it is about: the property its instance path
+    starts with, or the one `required` found missing. `nil` for errors about
+    the row as a whole
+  - `:error` - The `ExJsonschema.ValidationError`, its paths relative to
+    the row
+  """
+
+  alias ExJsonschema.{CompilationError, Native, Schema, ValidationError}
+
+  @type row_error :: %{
+          line: pos_integer(),
+          column: String.t() | nil,
+          error: ValidationError.t()
+        }
+
+  @type result :: :ok | {:error, [row_error()]} | {:error, CompilationError.t()}
+
+  @doc """
+  Validates each row of `csv`, a binary or an enumerable of binaries,
+  against `compiled`.
+
+  ## Options
+
+  - `:separator` - The byte fields are separated by (default: `","`)
+  - `:headers` - The column names, for files without a header record; the
+    first record is then a row (default: `nil`, read from the file)
+  - `:ranked` - Lists each row's errors from most to least relevant, as with
+    `ExJsonschema.validate/3` (default: `false`)
+
+  Returns `:ok` when every row is valid, or the errors of all rows in file
+  order.
+  """
+  @spec validate(Schema.t(), binary() | Enumerable.t(), keyword()) :: result()
+  def validate(compiled_schema, csv, opts \\ [])
+      when is_struct(compiled_schema, Schema) and is_list(opts) do
+    state = initial_state(opts)
+    chunks = if is_binary(csv), do: [csv], else: csv
+
+    with {:ok, state, rest, errors} <-
+           Enum.reduce_while(chunks, {:ok, state, "", []}, &read_chunk(compiled_schema, &1, &2)),
+         {:ok, _state, _rest, errors} <- read(compiled_schema, rest, state, true, errors) do
+      case errors |> Enum.reverse() |> Enum.concat() do
+        [] -> :ok
+        errors -> {:error, errors}
+      end
+    end
+  end
+
+  defp initial_state(opts) do
+    separator = Keyword.get(opts, :separator, ",")
+    headers = Keyword.get(opts, :headers)
+    ranked = Keyword.get(opts, :ranked, false)
+
+    unless match?(<<_>>, separator) do
+      raise ArgumentError,
+            "Invalid separator option: #{inspect(separator)}. Must be a single-byte string"
+    end
+
+    unless is_nil(headers) or (is_list(headers) and Enum.all?(headers, &is_binary/1)) do
+      raise ArgumentError,
+            "Invalid headers option: #{inspect(headers)}. Must be a list of strings"
+    end
+
+    unless is_boolean(ranked) do
+      raise ArgumentError, "Invalid ranked option: #{inspect(ranked)}. Must be a boolean"
+    end
+
+    <<separator>> = separator
+    %{headers: headers, line: 1, separator: separator, ranked: ranked}
+  end
+
+  defp read_chunk(compiled_schema, chunk, {:ok, state, rest, errors}) do
+    case read(compiled_schema, rest <> chunk, state, false, errors) do
+      {:ok, _state, _rest, _errors} = read -> {:cont, read}
+      {:error, _error} = error -> {:halt, error}
+    end
+  end
+
+  # Reads the complete records of `buffer`, returning the bytes of the
+  # record it ends in the middle of, unless it is the last
+  defp read(%Schema{ref: ref}, buffer, state, last, errors) do
+    case Native.validate_csv(ref, buffer, state, last) do
+      {:ok, headers, line, consumed, row_errors} ->
+        rest = binary_part(buffer, consumed, byte_size(buffer) - consumed)
+        row_errors = Enum.map(row_errors, &row_error/1)
+        {:ok, %{state | headers: headers, line: line}, rest, [row_errors | errors]}
+
+      {:error, error_map} ->
+        {:error, CompilationError.from_map(error_map)}
+    end
+  end
+
+  defp row_error(%{"line" => line, "column" => column, "error" => error_map}) do
+    %{line: line, column: column, error: ValidationError.from_detailed_map(error_map)}
+  end
+end
@@ -0,0 +1,89 @@
+defmodule ExJsonschema.Dereferencer do
+  @moduledoc """
+  Produces `$ref`-free copies of JSON Schema documents.
+
+  Some downstream tools (form generators, OpenAPI viewers) cannot follow
+  `$ref`. This module inlines every local and registered reference so the
+  resulting schema can be consumed without a resolver.
+
+  Resolution happens in Rust using the same registry the validator builds
+  for a compiled schema, so the inlined subschemas are exactly the ones
+  validation would use.
+
+  ## Cyclic References
+
+  Recursive schemas (a tree node referencing itself, for example) cannot be
+  fully inlined. The `:cycles` option controls what happens:
+
+  - `:error` - Return `{:error, %CompilationError{type: :dereference_error}}` (default)
+  - `:keep` - Leave the cyclic `$ref` in place and keep the definitions it points to
+
+  ## Examples
+
+      schema = ~s({
+        "type": "object",
+        "properties": {"home": {"$ref": "#/$defs/address"}},
+        "$defs": {"address": {"type": "object", "properties": {"city": {"type": "string"}}}}
+      })
+
+      {:ok, flat} = ExJsonschema.Dereferencer.dereference(schema)
+      flat["properties"]["home"]
+      #=> %{"type" => "object", "properties" => %{"city" => %{"type" => "string"}}}
+  """
+
+  alias ExJsonschema.{CompilationError, Native, Schema}
+
+  @type cycle_policy :: :error | :keep
+
+  @cycle_policies [:error, :keep]
+
+  @doc """
+  Inlines all references in a schema.
+
+  Accepts a compiled schema, a JSON string or a decoded map. Schemas that are
+  not yet compiled are compiled first, and any options besides `:cycles` are
+  passed on to `ExJsonschema.compile/2`.
+
+  ## Options
+
+  - `cycles: :error | :keep` - Policy for cyclic references (default: `:error`)
+
+  ## Examples
+
+      iex> schema = ~s({"properties": {"id": {"$ref": "#/$defs/id"}}, "$defs": {"id": {"type": "integer"}}})
+      iex> ExJsonschema.Dereferencer.dereference(schema)
+      {:ok, %{"properties" => %{"id" => %{"type" => "integer"}}}}
+
+      iex> schema = ~s({"properties": {"child": {"$ref": "#"}}})
+      iex> {:error, %ExJsonschema.CompilationError{type: :dereference_error}} =
+      ...>   ExJsonschema.Dereferencer.dereference(schema)
+
+  """
+  @spec dereference(Schema.t() | String.t() | map(), keyword()) ::
+          {:ok, map() | boolean()} | {:error, CompilationError.t()}
+  def dereference(schema, opts \\ []) when is_list(opts) do
+    {cycles, compile_opts} = Keyword.pop(opts, :cycles, :error)
+
+    unless cycles in @cycle_policies do
+      raise ArgumentError,
+            "Invalid cycles policy: #{inspect(cycles)}. Must be one of: :error, :keep"
+    end
+
+    with {:ok, compiled} <- ensure_compiled(schema, compile_opts) do
+      case Native.dereference_schema(compiled.ref, cycles) do
+        {:ok, dereferenced} -> {:ok, dereferenced}
+        {:error, error_map} -> {:error, CompilationError.from_map(error_map)}
+      end
+    end
+  end
+
+  defp ensure_compiled(%Schema{} = compiled, _opts), do: {:ok, compiled}
+
+  defp ensure_compiled(schema, opts) when is_map(schema) do
+    ensure_compiled(Jason.encode!(schema), opts)
+  end
+
+  defp ensure_compiled(schema_json, opts) when is_binary(schema_json) do
+    ExJsonschema.compile(schema_json, opts)
+  end
+end
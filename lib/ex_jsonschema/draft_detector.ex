@@ -37,7 +37,7 @@ defmodule ExJsonschema.DraftDetector do
   require Logger
   alias ExJsonschema.Native
 
-  @type draft :: :draft4 | :draft6 | :draft7 | :draft201909 | :draft202012
+  @type draft :: :draft4 | :draft6 | :draft7 | :draft201909 | :draft202012 | :openapi31
   @type schema :: map() | String.t()
   @type detection_result :: {:ok, draft()} | {:error, String.t()}
 
@@ -81,7 +81,11 @@ defmodule ExJsonschema.DraftDetector do
     "https://json-schema.org/draft/2020-12/meta/meta-data" => :draft202012,
     "https://json-schema.org/draft/2020-12/meta/format-annotation" => :draft202012,
     "https://json-schema.org/draft/2020-12/meta/format-assertion" => :draft202012,
-    "https://json-schema.org/draft/2020-12/meta/content" => :draft202012
+    "https://json-schema.org/draft/2020-12/meta/content" => :draft202012,
+
+    # OpenAPI 3.1 (3.0 Schema Objects declare no $schema)
+    "https://spec.openapis.org/oas/3.1/dialect/base" => :openapi31,
+    "https://spec.openapis.org/oas/3.1/meta/base" => :openapi31
   }
 
   # Canonical URLs for each draft
@@ -159,6 +163,29 @@ defmodule ExJsonschema.DraftDetector do
     {:error, "Invalid schema input: expected map or JSON string, got #{inspect(schema)}"}
   end
 
+  @doc """
+  Detects the draft of a schema given as text in `input_format`: `:json`,
+  `:yaml` or `:toml` (see "Input Formats" in `ExJsonschema.Options`).
+
+  A document that does not parse is an error saying where it went wrong.
+
+  ## Examples
+
+      iex> DraftDetector.detect_draft("$schema: http://json-schema.org/draft-07/schema#", :yaml)
+      {:ok, :draft7}
+
+  """
+  @spec detect_draft(String.t(), ExJsonschema.Options.input_format()) :: detection_result()
+  def detect_draft(schema, :json) when is_binary(schema), do: detect_draft(schema)
+
+  def detect_draft(schema, input_format) when is_binary(schema) do
+    case Native.input_to_json(schema, input_format) do
+      {:ok, schema_json} -> detect_draft(schema_json)
+      {:error, %{"message" => message, "details" => details}} ->
+        {:error, "#{message}: #{details}"}
+    end
+  end
+
   @doc """
   Detects draft version from a schema URL.
 
@@ -259,6 +286,20 @@ defmodule ExJsonschema.DraftDetector do
 
   def schema_has_draft?(_), do: false
 
+  @doc """
+  Checks if a schema given as text in `input_format` contains a $schema
+  property. A document that does not parse has none.
+  """
+  @spec schema_has_draft?(String.t(), ExJsonschema.Options.input_format()) :: boolean()
+  def schema_has_draft?(schema, :json) when is_binary(schema), do: schema_has_draft?(schema)
+
+  def schema_has_draft?(schema, input_format) when is_binary(schema) do
+    case Native.input_to_json(schema, input_format) do
+      {:ok, schema_json} -> schema_has_draft?(schema_json)
+      {:error, _} -> false
+    end
+  end
+
   @doc """
   Returns the canonical URL for a draft version.
 
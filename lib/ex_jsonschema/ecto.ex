@@ -0,0 +1,82 @@
+if Code.ensure_loaded?(Ecto.Changeset) do
+  defmodule ExJsonschema.Ecto do
+    @moduledoc """
+    Validates JSON fields of Ecto changesets against compiled schemas.
+
+    Embedded JSON, such as a `:map` field, is validated as a whole, and each
+    validation error becomes a changeset error on the field. The error's
+    place inside the JSON is kept in its metadata rather than in the
+    message, so forms and APIs can show it next to the right input:
+
+        schema = ExJsonschema.compile!(File.read!("priv/schemas/order.json"))
+
+        changeset
+        |> cast(params, [:order])
+        |> ExJsonschema.Ecto.validate_json(:order, schema)
+
+        changeset.errors
+        #=> [order: {"\\"x\\" is not of type \\"integer\\"",
+        #=>   [validation: :json_schema, path: ["items", 3, "sku"],
+        #=>    keyword: "type", params: %{"expected" => "integer"}}]]
+
+    The metadata holds:
+
+    - `:validation` - Always `:json_schema`
+    - `:path` - Where the error is in the JSON, as the error's
+      `:instance_segments`: array indices as integers and object keys as
+      strings. `[]` is the field's value itself
+    - `:keyword` - The schema keyword that failed, such as `"required"`
+    - `:params` - What the keyword checked against, such as
+      `%{"limit" => 10}` for `maximum` (see "Structured Details" in
+      `ExJsonschema.ValidationError`)
+
+    Requires the optional `:ecto` dependency.
+    """
+
+    alias Ecto.Changeset
+    alias ExJsonschema.{Schema, ValidationError}
+
+    @doc """
+    Validates the change to `field` against `compiled`, adding an error to
+    the field for each validation error.
+
+    Like Ecto's own validations, only a change is validated, and a change to
+    `nil` is not. The value is encoded as JSON first, so maps with atom keys
+    are validated as their JSON equivalents.
+
+    ## Options
+
+    - `:message` - A function from the `ExJsonschema.ValidationError` to the
+      error message (default: the error's message)
+    - `:ranked` - Adds errors from most to least relevant, as with
+      `ExJsonschema.validate/3` (default: `false`)
+    - `:direction` - `:request` or `:response`, as with
+      `ExJsonschema.validate/3` (default: `nil`)
+    """
+    @spec validate_json(Changeset.t(), atom(), Schema.t(), keyword()) :: Changeset.t()
+    def validate_json(%Changeset{} = changeset, field, %Schema{} = compiled, opts \\ [])
+        when is_atom(field) do
+      {message, validate_opts} = Keyword.pop(opts, :message, & &1.message)
+      validate_opts = Keyword.put(validate_opts, :output, :detailed)
+
+      Changeset.validate_change(changeset, field, {:json_schema, compiled}, fn field, value ->
+        case ExJsonschema.validate(compiled, Jason.encode!(value), validate_opts) do
+          :ok ->
+            []
+
+          {:error, errors} when is_list(errors) ->
+            Enum.map(errors, &{field, {message.(&1), metadata(&1)}})
+        end
+      end)
+    end
+
+    defp metadata(%ValidationError{} = error) do
+      [
+        validation: :json_schema,
+        path: error.instance_segments || [],
+        keyword: error.keyword,
+        params: error.params || %{}
+      ]
+    end
+  end
+end
@@ -26,6 +26,12 @@ defmodule ExJsonschema.ErrorFormatter do
       # Format for LLM consumption
       ErrorFormatter.format(errors, :llm)
 
+  Errors are shown in the order given. Validate with `ranked: true` to have
+  the `:human` and `:llm` outputs lead with the most relevant error:
+
+      {:error, errors} = ExJsonschema.validate(compiled, json, ranked: true)
+      ErrorFormatter.format(errors, :llm, max_errors: 3)
+
   ## Formatting Options
 
   Each format supports specific options:
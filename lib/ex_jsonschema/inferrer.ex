@@ -0,0 +1,124 @@
+defmodule ExJsonschema.Inferrer do
+  @moduledoc """
+  Infers a JSON Schema from example documents.
+
+  Useful when onboarding a data feed that comes with samples but no schema.
+  Every sample is observed in Rust and the observations are merged into a
+  single schema that accepts all of them:
+
+  - `type` - every JSON type seen at a location (`integer` when all numbers
+    are whole, `number` otherwise)
+  - `properties` / `required` - fields present in every sample are required
+  - `items` - merged from every element of every array
+  - `enum` - low-cardinality strings whose values repeat across samples
+  - `format` - `date-time`, `email` or `uuid` when every string matches
+  - `minimum` / `maximum` - the observed numeric range
+
+  The inferred schema is a starting point: review it before relying on it,
+  since a handful of samples rarely shows every valid shape.
+
+  ## Examples
+
+      samples = [
+        %{"id" => "550e8400-e29b-41d4-a716-446655440000", "status" => "active", "score" => 3},
+        %{"id" => "6ba7b810-9dad-11d1-80b4-00c04fd430c8", "status" => "active", "score" => 7}
+      ]
+
+      {:ok, schema} = ExJsonschema.Inferrer.infer(samples)
+      schema["properties"]["status"]
+      #=> %{"type" => "string", "enum" => ["active"]}
+  """
+
+  alias ExJsonschema.{CompilationError, DraftDetector, Native}
+
+  @doc """
+  Infers a schema that accepts every sample.
+
+  Samples are decoded terms (maps, lists, strings, numbers...), so a string
+  sample is a JSON string. Pass `json: true` to give them as JSON text.
+
+  ## Options
+
+  - `:draft` - Draft of the generated schema, one of
+    `ExJsonschema.DraftDetector.supported_drafts/0` (default: `:draft202012`)
+  - `:enum_threshold` - Largest number of distinct string values reported as
+    an `enum` (default: `5`, `0` disables enums)
+  - `:json` - Whether every sample is JSON text to decode (default: `false`).
+    A sample that does not decode returns a `:json_parse_error`
+
+  ## Examples
+
+      iex> {:ok, schema} = ExJsonschema.Inferrer.infer([%{"n" => 1}, %{"n" => 4, "note" => "hi"}])
+      iex> {schema["required"], schema["properties"]["n"]}
+      {["n"], %{"type" => "integer", "minimum" => 1, "maximum" => 4}}
+
+  """
+  @spec infer([term()], keyword()) :: {:ok, map()} | {:error, CompilationError.t()}
+  def infer(samples, opts \\ []) when is_list(samples) and is_list(opts) do
+    draft = Keyword.get(opts, :draft, :draft202012)
+    enum_threshold = Keyword.get(opts, :enum_threshold, 5)
+    json = Keyword.get(opts, :json, false)
+
+    unless DraftDetector.supports_draft?(draft) do
+      raise ArgumentError,
+            "Invalid draft: #{inspect(draft)}. Must be one of: " <>
+              Enum.map_join(DraftDetector.supported_drafts(), ", ", &inspect/1)
+    end
+
+    unless is_integer(enum_threshold) and enum_threshold >= 0 do
+      raise ArgumentError,
+            "Invalid enum_threshold: #{inspect(enum_threshold)}. Must be a non-negative integer"
+    end
+
+    unless is_boolean(json) do
+      raise ArgumentError, "Invalid json: #{inspect(json)}. Must be a boolean"
+    end
+
+    if samples == [] do
+      raise ArgumentError, "At least one sample is required to infer a schema"
+    end
+
+    with {:ok, samples} <- decode_samples(samples, json),
+         {:ok, schema} <- Native.infer_schema(Jason.encode!(samples), draft, enum_threshold) do
+      {:ok, schema}
+    else
+      {:error, %CompilationError{} = error} -> {:error, error}
+      {:error, error_map} -> {:error, CompilationError.from_map(error_map)}
+    end
+  end
+
+  defp decode_samples(samples, false), do: {:ok, samples}
+
+  defp decode_samples(samples, true) do
+    samples
+    |> Enum.with_index()
+    |> Enum.reduce_while({:ok, []}, fn {sample, index}, {:ok, decoded} ->
+      case decode_sample(sample) do
+        {:ok, term} ->
+          {:cont, {:ok, [term | decoded]}}
+
+        {:error, reason} ->
+          {:halt,
+           {:error,
+            %CompilationError{
+              type: :json_parse_error,
+              message: "Invalid JSON sample",
+              details: "Sample #{index}: #{reason}"
+            }}}
+      end
+    end)
+    |> case do
+      {:ok, decoded} -> {:ok, Enum.reverse(decoded)}
+      error -> error
+    end
+  end
+
+  defp decode_sample(sample) when is_binary(sample) do
+    case Jason.decode(sample) do
+      {:ok, term} -> {:ok, term}
+      {:error, error} -> {:error, Exception.message(error)}
+    end
+  end
+
+  defp decode_sample(sample), do: {:error, "expected JSON text, got: #{inspect(sample)}"}
+end
@@ -40,7 +40,7 @@ defmodule ExJsonschema.MetaValidator do
   errors, making them compatible with all error formatting and analysis tools.
   """
 
-  alias ExJsonschema.{Native, ValidationError}
+  alias ExJsonschema.{Native, Telemetry, ValidationError}
 
   # URLs that DON'T cause panics in the Rust crate (whitelist approach)
   # Based on testing, these are the only known working $schema URLs
@@ -83,7 +83,7 @@ defmodule ExJsonschema.MetaValidator do
     # Preprocess to avoid Rust crate panics on problematic $schema URLs
     safe_schema = preprocess_schema_for_rust(schema_json)
 
-    case Native.meta_is_valid(safe_schema) do
+    case meta_span(:basic, &Native.meta_is_valid/1, safe_schema) do
       {:ok, result} ->
         result
 
@@ -124,7 +124,7 @@ defmodule ExJsonschema.MetaValidator do
     # Preprocess to avoid Rust crate panics on problematic $schema URLs
     safe_schema = preprocess_schema_for_rust(schema_json)
 
-    case Native.meta_validate(safe_schema) do
+    case meta_span(:simple, &Native.meta_validate/1, safe_schema) do
       :ok ->
         :ok
 
@@ -178,7 +178,7 @@ defmodule ExJsonschema.MetaValidator do
     # Preprocess to avoid Rust crate panics on problematic $schema URLs
     safe_schema = preprocess_schema_for_rust(schema_json)
 
-    case Native.meta_validate_detailed(safe_schema) do
+    case meta_span(:detailed, &Native.meta_validate_detailed/1, safe_schema) do
       :ok ->
         :ok
 
@@ -243,6 +243,16 @@ defmodule ExJsonschema.MetaValidator do
     end
   end
 
+  # Runs a meta-validation NIF in a telemetry span, returning its result
+  defp meta_span(output_format, nif, schema_json) do
+    metadata = %{output_format: output_format, schema_size: byte_size(schema_json)}
+
+    Telemetry.span(:meta_validate, metadata, fn ->
+      {result, measurements} = nif.(schema_json)
+      {result, measurements, %{}}
+    end)
+  end
+
   # Safely preprocess schema JSON to avoid known Rust crate panics
   # TODO: Remove this workaround once upstream jsonschema crate is fixed
   defp preprocess_schema_for_rust(schema_json) when is_binary(schema_json) do
@@ -0,0 +1,95 @@
+defmodule ExJsonschema.Migrator do
+  @moduledoc """
+  Upgrades JSON Schema documents from older drafts to 2019-09 or 2020-12.
+
+  `ExJsonschema.DraftDetector` can tell which draft a schema is written for;
+  this module rewrites it for a newer one. The rewrite runs in Rust and
+  covers the mechanical changes between drafts:
+
+  - `definitions` → `$defs` (including `$ref` pointers into them)
+  - `id` → `$id`, with plain-name fragments moved to `$anchor`
+  - boolean `exclusiveMinimum`/`exclusiveMaximum` → numeric bounds
+  - array-form `items` → `prefixItems` and `additionalItems` → `items` (2020-12)
+  - `dependencies` → `dependentRequired` / `dependentSchemas`
+
+  Anything that cannot be converted automatically is returned as an issue
+  alongside the migrated schema rather than silently dropped.
+
+  ## Examples
+
+      legacy = ~s({
+        "$schema": "http://json-schema.org/draft-04/schema#",
+        "properties": {"age": {"type": "number", "minimum": 0, "exclusiveMinimum": true}}
+      })
+
+      {:ok, schema, []} = ExJsonschema.Migrator.migrate(legacy, to: :draft202012)
+      schema["properties"]["age"]
+      #=> %{"type" => "number", "exclusiveMinimum" => 0}
+  """
+
+  alias ExJsonschema.{CompilationError, Native}
+
+  @typedoc """
+  A construct that could not be converted automatically.
+
+  `path` is a JSON Pointer into the original schema.
+  """
+  @type issue :: %{path: String.t(), keyword: String.t(), message: String.t()}
+
+  @source_drafts [:auto, :draft4, :draft6, :draft7, :draft201909, :draft202012]
+  @target_drafts [:draft201909, :draft202012]
+
+  @doc """
+  Migrates a schema to a newer draft.
+
+  Returns `{:ok, migrated_schema, issues}` where `issues` lists everything
+  that needs manual attention.
+
+  ## Options
+
+  - `:to` - Target draft, `:draft201909` or `:draft202012` (default: `:draft202012`)
+  - `:from` - Source draft (default: `:auto`, which reads `$schema` and
+    treats schemas without one as draft-04)
+
+  ## Examples
+
+      iex> schema = ~s({"definitions": {"id": {"type": "integer"}}, "items": {"$ref": "#/definitions/id"}})
+      iex> {:ok, migrated, []} = ExJsonschema.Migrator.migrate(schema, to: :draft202012)
+      iex> migrated["items"]
+      %{"$ref" => "#/$defs/id"}
+
+  """
+  @spec migrate(String.t() | map(), keyword()) ::
+          {:ok, map() | boolean(), [issue()]} | {:error, CompilationError.t()}
+  def migrate(schema, opts \\ [])
+
+  def migrate(schema, opts) when is_map(schema) do
+    migrate(Jason.encode!(schema), opts)
+  end
+
+  def migrate(schema_json, opts) when is_binary(schema_json) and is_list(opts) do
+    to = Keyword.get(opts, :to, :draft202012)
+    from = Keyword.get(opts, :from, :auto)
+
+    unless to in @target_drafts do
+      raise ArgumentError,
+            "Invalid migration target: #{inspect(to)}. Must be one of: :draft201909, :draft202012"
+    end
+
+    unless from in @source_drafts do
+      raise ArgumentError, "Invalid source draft: #{inspect(from)}"
+    end
+
+    case Native.migrate_schema(schema_json, from, to) do
+      {:ok, migrated, issues} ->
+        {:ok, migrated, Enum.map(issues, &to_issue/1)}
+
+      {:error, error_map} ->
+        {:error, CompilationError.from_map(error_map)}
+    end
+  end
+
+  defp to_issue(%{"path" => path, "keyword" => keyword, "message" => message}) do
+    %{path: path, keyword: keyword, message: message}
+  end
+end
@@ -18,7 +18,15 @@ defmodule ExJsonschema.Native do
     ]
 
   @type compiled_schema :: reference()
-  @type draft :: :auto | :draft4 | :draft6 | :draft7 | :draft201909 | :draft202012
+  @type draft ::
+          :auto
+          | :draft4
+          | :draft6
+          | :draft7
+          | :draft201909
+          | :draft202012
+          | :openapi30
+          | :openapi31
   @type regex_engine :: :fancy_regex | :regex
 
   @type compilation_options :: %{
@@ -38,13 +46,32 @@ defmodule ExJsonschema.Native do
 
   # Validation
   def validate(_compiled_schema, _instance_json), do: :erlang.nif_error(:nif_not_loaded)
-  def validate_detailed(_compiled_schema, _instance_json), do: :erlang.nif_error(:nif_not_loaded)
-  def validate_verbose(_compiled_schema, _instance_json), do: :erlang.nif_error(:nif_not_loaded)
-  def valid(_compiled_schema, _instance_json), do: :erlang.nif_error(:nif_not_loaded)
+  def validate_detailed(_compiled_schema, _instance, _ranked, _direction, _input_format),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  def validate_verbose(_compiled_schema, _instance, _ranked, _direction, _input_format),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  def valid(_compiled_schema, _instance, _direction, _input_format),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  def validate_at(_compiled_schema, _instance_json, _schema_ptr, _instance_ptr, _detailed),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  def patch_and_validate(_compiled_schema, _document_json, _patch_json, _merge, _incremental),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  def suggest_fixes(_compiled_schema, _instance_json), do: :erlang.nif_error(:nif_not_loaded)
+  def best_error(_compiled_schema, _instance_json), do: :erlang.nif_error(:nif_not_loaded)
 
   # Backward compatibility
-  def valid?(compiled_schema, instance_json), do: valid(compiled_schema, instance_json)
-  def is_valid(compiled_schema, instance_json), do: valid(compiled_schema, instance_json)
+  def valid?(compiled_schema, instance_json),
+    do: elem(valid(compiled_schema, instance_json, nil, :json), 0)
+  def is_valid(compiled_schema, instance_json), do: valid?(compiled_schema, instance_json)
+
+  # Schema metadata
+  def schema_metadata(_compiled_schema), do: :erlang.nif_error(:nif_not_loaded)
+  def schema_info(_compiled_schema), do: :erlang.nif_error(:nif_not_loaded)
 
   # Draft detection
   def detect_draft_from_schema(_schema_json), do: :erlang.nif_error(:nif_not_loaded)
@@ -53,4 +80,38 @@ defmodule ExJsonschema.Native do
   def meta_is_valid(_schema_json), do: :erlang.nif_error(:nif_not_loaded)
   def meta_validate(_schema_json), do: :erlang.nif_error(:nif_not_loaded)
   def meta_validate_detailed(_schema_json), do: :erlang.nif_error(:nif_not_loaded)
+
+  # Schema transformation
+  def dereference_schema(_compiled_schema, _cycles), do: :erlang.nif_error(:nif_not_loaded)
+  def migrate_schema(_schema_json, _from, _to), do: :erlang.nif_error(:nif_not_loaded)
+
+  def infer_schema(_samples_json, _draft, _enum_threshold),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  def schema_to_avro(_schema_json, _name, _namespace), do: :erlang.nif_error(:nif_not_loaded)
+  def avro_to_schema(_avro_json), do: :erlang.nif_error(:nif_not_loaded)
+
+  # Serialization
+  def serialize_schema(_compiled_schema), do: :erlang.nif_error(:nif_not_loaded)
+  def deserialize_schema(_serialized), do: :erlang.nif_error(:nif_not_loaded)
+
+  # Caching
+  def schema_cache_key(_schema_json, _options), do: :erlang.nif_error(:nif_not_loaded)
+
+  # Input formats
+  def input_to_json(_text, _input_format), do: :erlang.nif_error(:nif_not_loaded)
+
+  # CSV
+  def validate_csv(_compiled_schema, _chunk, _state, _last),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  # OpenAPI documents
+  def openapi_load(_document_json), do: :erlang.nif_error(:nif_not_loaded)
+  def openapi_operations(_document), do: :erlang.nif_error(:nif_not_loaded)
+
+  def openapi_validate_request(_document, _method, _path, _request),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  def openapi_validate_response(_document, _method, _target, _status, _body),
+    do: :erlang.nif_error(:nif_not_loaded)
 end
@@ -9,12 +9,32 @@ defmodule ExJsonschema.Native.ValidationOptions do
   @type t :: %__MODULE__{
           draft: atom(),
           validate_formats: boolean(),
-          regex_engine: atom()
+          regex_engine: atom(),
+          redact: boolean(),
+          redact_keyword: String.t(),
+          redact_pointers: [String.t()],
+          redact_mask: String.t(),
+          validate_content: boolean(),
+          allowed_formats: [String.t()] | nil,
+          denied_formats: [String.t()],
+          reject_unknown_formats: boolean(),
+          infer_discriminators: boolean(),
+          input_format: atom()
         }
 
   defstruct draft: :auto,
             validate_formats: false,
-            regex_engine: :fancy_regex
+            regex_engine: :fancy_regex,
+            redact: false,
+            redact_keyword: "",
+            redact_pointers: [],
+            redact_mask: "",
+            validate_content: false,
+            allowed_formats: nil,
+            denied_formats: [],
+            reject_unknown_formats: false,
+            infer_discriminators: false,
+            input_format: :json
 
   @doc """
   Convert ExJsonschema.Options to native validation options.
@@ -22,10 +42,30 @@ defmodule ExJsonschema.Native.ValidationOptions do
   This is the single transformation point - all Options get converted here.
   """
   def from_options(%ExJsonschema.Options{} = opts) do
-    %__MODULE__{
+    native = %__MODULE__{
       draft: opts.draft,
       validate_formats: opts.validate_formats,
-      regex_engine: opts.regex_engine
+      regex_engine: opts.regex_engine,
+      validate_content: opts.validate_content,
+      allowed_formats: opts.allowed_formats,
+      denied_formats: opts.denied_formats,
+      reject_unknown_formats: opts.unknown_formats == :error,
+      infer_discriminators: opts.infer_discriminators,
+      input_format: opts.input_format
     }
+
+    case ExJsonschema.Options.redaction(opts) do
+      nil ->
+        native
+
+      redaction ->
+        %{
+          native
+          | redact: true,
+            redact_keyword: redaction[:keyword],
+            redact_pointers: redaction[:pointers],
+            redact_mask: redaction[:mask]
+        }
+    end
   end
 end
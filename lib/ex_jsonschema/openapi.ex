@@ -0,0 +1,196 @@
+defmodule ExJsonschema.OpenAPI do
+  @moduledoc """
+  Validates HTTP requests and responses against an OpenAPI 3.0 or 3.1
+  document.
+
+  `load/1` reads the document once and compiles a validator for every
+  schema its operations use: parameters, JSON request bodies and JSON
+  responses. Schemas are validated in the document's dialect (see
+  `:openapi30` and `:openapi31` in `ExJsonschema.Options`), and `#/...`
+  references resolve against the document, for validation and for
+  converting parameters alike. Parameters, request bodies and responses may
+  themselves be `$ref`s into the document.
+
+  ## Requests
+
+  `validate_request/4` finds the operation by method and path, where the
+  path is either the template (`"/users/{id}"`) or a concrete path
+  (`"/users/42"`) that path parameters are read from. The request is a map
+  of:
+
+  - `:body` - The body as JSON text, or a term to encode as JSON
+  - `:query` - Query parameters by name
+  - `:headers` - Headers by name, matched case-insensitively
+  - `:path` - Path parameters by name, overriding those read from the path
+
+  Parameter values are strings, or lists of strings for repeated values,
+  as they arrive over HTTP. They are coerced according to their schemas
+  before validation: `"42"` becomes `42` for an `integer` schema, `"true"`
+  becomes `true` for a `boolean`, and an `array` schema splits
+  `"a,b"` into `["a", "b"]`. Values that do not convert are validated as
+  strings, so the schema reports them.
+
+  ## Errors
+
+  Errors are `ExJsonschema.ValidationError`s. Instance paths start with the
+  part of the request or response they concern (`/body`, `/query/limit`,
+  `/path/id`, `/headers/x-request-id`, or `/status` for an undocumented
+  response status), and schema paths are JSON pointers into the document,
+  such as `/paths/~1users/post/requestBody/content/application~1json/schema/required`.
+
+  Request bodies are validated with `direction: :request` and response
+  bodies with `direction: :response`, so `readOnly` values are rejected in
+  requests and `writeOnly` values in responses.
+
+  Cookie parameters and bodies of media types other than JSON are not
+  validated.
+
+  ## Examples
+
+      {:ok, spec} = ExJsonschema.OpenAPI.load(File.read!("openapi.json"))
+
+      ExJsonschema.OpenAPI.validate_request(spec, "POST", "/users/42", %{
+        body: ~s({"name": "Ada"}),
+        query: %{"notify" => "true"},
+        headers: %{"X-Request-Id" => "abc"}
+      })
+      #=> :ok
+
+      ExJsonschema.OpenAPI.validate_response(spec, {"POST", "/users/{id}"}, 200, ~s({}))
+      #=> {:error, [%ExJsonschema.ValidationError{instance_path: "/body", ...}]}
+  """
+
+  alias ExJsonschema.{CompilationError, Native, ValidationError}
+
+  @enforce_keys [:ref]
+  defstruct [:ref]
+
+  @type t :: %__MODULE__{ref: reference()}
+
+  @typedoc """
+  An HTTP method, such as `"POST"` or `:post`.
+  """
+  @type method :: String.t() | atom()
+
+  @typedoc """
+  An operation: its `operationId`, or its method and path.
+  """
+  @type operation :: String.t() | {method(), String.t()}
+
+  @type parameters :: %{optional(String.t() | atom()) => String.t() | [String.t()]}
+
+  @type request :: %{
+          optional(:body) => term(),
+          optional(:query) => parameters(),
+          optional(:headers) => parameters(),
+          optional(:path) => parameters()
+        }
+
+  @type result :: :ok | {:error, [ValidationError.t()]} | {:error, :unknown_operation}
+
+  @doc """
+  Loads an OpenAPI 3.0 or 3.1 document, given as JSON text or a decoded map.
+
+  Returns `{:error, CompilationError.t()}` when the document is not valid
+  JSON, is not an OpenAPI 3.x document, or has a schema that does not
+  compile.
+  """
+  @spec load(String.t() | map()) :: {:ok, t()} | {:error, CompilationError.t()}
+  def load(document) when is_map(document), do: load(Jason.encode!(document))
+
+  def load(document_json) when is_binary(document_json) do
+    case Native.openapi_load(document_json) do
+      {:ok, ref} -> {:ok, %__MODULE__{ref: ref}}
+      {:error, error_map} -> {:error, CompilationError.from_map(error_map)}
+    end
+  end
+
+  @doc """
+  Like `load/1`, but raises `ArgumentError` on failure.
+  """
+  @spec load!(String.t() | map()) :: t()
+  def load!(document) do
+    case load(document) do
+      {:ok, spec} ->
+        spec
+
+      {:error, %CompilationError{} = error} ->
+        raise ArgumentError, "Failed to load OpenAPI document: #{error}"
+    end
+  end
+
+  @doc """
+  Lists the operations of a loaded document.
+  """
+  @spec operations(t()) :: [
+          %{method: String.t(), path: String.t(), operation_id: String.t() | nil}
+        ]
+  def operations(%__MODULE__{ref: ref}) do
+    for {method, path, operation_id} <- Native.openapi_operations(ref) do
+      %{method: method, path: path, operation_id: operation_id}
+    end
+  end
+
+  @doc """
+  Validates a request for the operation `method` and `path` identify.
+
+  Returns `{:error, :unknown_operation}` when the document describes no
+  such operation.
+  """
+  @spec validate_request(t(), method(), String.t(), request()) :: result()
+  def validate_request(%__MODULE__{ref: ref}, method, path, request \\ %{})
+      when is_binary(path) and is_map(request) do
+    native_request = %{
+      body: encode_body(Map.get(request, :body)),
+      query: parameters(request, :query),
+      headers: parameters(request, :headers),
+      path: parameters(request, :path)
+    }
+
+    ref
+    |> Native.openapi_validate_request(method_name(method), path, native_request)
+    |> result()
+  end
+
+  @doc """
+  Validates a response with `status` and `body` for `operation`.
+
+  The status selects the documented response: the exact code, then its
+  range (`"2XX"`), then `"default"`. A status the operation does not
+  document is an error. The body is JSON text, a term to encode as JSON, or
+  `nil` for none.
+  """
+  @spec validate_response(t(), operation(), pos_integer(), term()) :: result()
+  def validate_response(%__MODULE__{ref: ref}, operation, status, body)
+      when is_integer(status) do
+    {method, target} =
+      case operation do
+        {method, path} when is_binary(path) -> {method_name(method), path}
+        operation_id when is_binary(operation_id) -> {nil, operation_id}
+      end
+
+    ref
+    |> Native.openapi_validate_response(method, target, status, encode_body(body))
+    |> result()
+  end
+
+  defp method_name(method) when is_atom(method), do: method |> Atom.to_string() |> method_name()
+  defp method_name(method) when is_binary(method), do: String.upcase(method)
+
+  defp encode_body(nil), do: nil
+  defp encode_body(body) when is_binary(body), do: body
+  defp encode_body(body), do: Jason.encode!(body)
+
+  defp parameters(request, part) do
+    for {name, value} <- Map.get(request, part, %{}) do
+      {to_string(name), value |> List.wrap() |> Enum.map(&to_string/1)}
+    end
+  end
+
+  defp result(:ok), do: :ok
+  defp result({:error, :unknown_operation}), do: {:error, :unknown_operation}
+
+  defp result({:error, error_maps}) when is_list(error_maps) do
+    {:error, Enum.map(error_maps, &ValidationError.from_detailed_map/1)}
+  end
+end
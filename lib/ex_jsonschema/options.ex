@@ -22,6 +22,168 @@ defmodule ExJsonschema.Options do
       opts = ExJsonschema.Options.new(
         regex_engine: :regex
       )
+
+  ## Formats
+
+  With `validate_formats: true`, every format `jsonschema` knows is checked.
+  `allowed_formats` narrows that to the listed formats and `denied_formats`
+  leaves the listed ones out; other formats are accepted unchecked.
+
+      opts = ExJsonschema.Options.new(
+        validate_formats: true,
+        allowed_formats: ["email", "uuid", "date-time"],
+        unknown_formats: :error
+      )
+
+  `unknown_formats: :error` makes compilation fail when the schema uses a
+  format nobody checks, such as a misspelled `"date_time"`, instead of
+  ignoring it (`:ignore`, the default).
+
+  When `validate_formats` is left `false`, a schema whose meta-schema lists
+  the 2020-12 format-assertion vocabulary still has its formats checked, as
+  the specification requires. The meta-schema must be part of the schema
+  document, e.g. under `$defs` with its own `$id`.
+
+  ## Redaction
+
+  Error messages and verbose error details quote the offending values. With
+  `redact: true`, values the schema marks as sensitive are replaced with a
+  mask in `message`, `instance_value`, `context` and `suggestions`, for
+  every output format and error-producing function. A value is sensitive
+  when a schema that applies to it has `writeOnly: true`,
+  `format: "password"` or `"x-sensitive": true`, or when it matches one of
+  the configured instance pointers. Schemas in every `anyOf`, `oneOf` and
+  `if`/`then`/`else` branch count, whether or not the value matches the
+  branch. Masking a value also masks everything inside it.
+
+      opts = ExJsonschema.Options.new(
+        redact: [
+          keyword: "x-pii",
+          pointers: ["/users/*/email"],
+          mask: "***"
+        ]
+      )
+
+  `redact` accepts `true` for the defaults, or a keyword list of:
+
+  - `:keyword` - Schema keyword marking sensitive values (default: `"x-sensitive"`)
+  - `:pointers` - Instance JSON pointers of sensitive values; a `*` segment
+    matches any property or index (default: `[]`)
+  - `:mask` - Replacement text (default: `"[REDACTED]"`)
+
+  ## Encoded Content
+
+  JSON Schema treats `contentEncoding`, `contentMediaType` and
+  `contentSchema` as annotations. With `validate_content: true`, strings
+  that declare them are decoded (`base64`, `base64url` or
+  `quoted-printable`), parsed when the media type is JSON
+  (`application/json` or a `+json` type), and validated against
+  `contentSchema`. Errors inside the content have instance paths that
+  continue into the decoded document, such as `/envelope/data/id` for the
+  `id` inside the string at `/envelope/data`. Strings that fail to decode or
+  parse are reported against `contentEncoding` or `contentMediaType`.
+
+      opts = ExJsonschema.Options.new(validate_content: true)
+
+  Content is found by following `$ref`, `allOf`, the property keywords and
+  the item keywords down from the root, and into the `anyOf` and `oneOf`
+  branches the value is valid against and the `then` or `else` its `if`
+  picks. Content under `not` and unknown encodings are not checked, nor is
+  content under a `:schema_pointer` passed to `ExJsonschema.validate_at/3`.
+
+  ## Request and Response Direction
+
+  One resource schema often serves both requests and responses, marking
+  server-assigned properties `readOnly` and secrets `writeOnly`. The
+  `direction` validation option enforces them:
+
+      ExJsonschema.validate(compiled, body, direction: :request)
+
+  With `:request`, a value whose schema has `readOnly: true` is an error;
+  with `:response`, a value whose schema has `writeOnly: true` is. The
+  errors' schema paths end in `/readOnly` or `/writeOnly`, which is also
+  their `keyword` in verbose output; no other error uses those keywords.
+  Annotated schemas are found the same way as encoded content, above.
+  Unlike the options above, `direction` is given at validation time, since
+  the same compiled schema checks both.
+
+  ## Discriminators
+
+  A `oneOf` with an OpenAPI `discriminator` is decided by the tag property
+  it names: only the branch the instance's tag selects is evaluated, and a
+  failure reports that branch's errors directly instead of one `oneOf`
+  error listing every branch. Tags come from the discriminator's `mapping`
+  (whose values are `$ref`s or schema names), or else from the last segment
+  of each branch's `$ref`, so `#/components/schemas/Card` is tagged
+  `"Card"`:
+
+      %{
+        "oneOf" => [
+          %{"$ref" => "#/components/schemas/Card"},
+          %{"$ref" => "#/components/schemas/Bank"}
+        ],
+        "discriminator" => %{
+          "propertyName" => "type",
+          "mapping" => %{"card" => "Card", "bank" => "Bank"}
+        }
+      }
+
+  A missing tag or one no branch declares is a single error at the
+  `discriminator`. Discriminators are honoured under every draft, not only
+  the OpenAPI dialects.
+
+  With `infer_discriminators: true`, a `oneOf` without a `discriminator`
+  is treated as having one when a property's `const` (or single-valued
+  `enum`) differs in every branch; tag errors then point at the `oneOf`.
+
+      opts = ExJsonschema.Options.new(infer_discriminators: true)
+
+  ## Input Formats
+
+  Schemas and instances are JSON text by default. With `input_format: :yaml`
+  or `input_format: :toml` they are YAML or TOML text instead, parsed
+  natively: the option is given to `ExJsonschema.compile/2` for the schema,
+  and to `ExJsonschema.validate/3` for the instance, so either can be any
+  format.
+
+      {:ok, compiled} = ExJsonschema.compile(File.read!("schema.yaml"), input_format: :yaml)
+      ExJsonschema.validate(compiled, File.read!("config.toml"), input_format: :toml)
+
+  YAML follows the YAML 1.2 core schema: unquoted `true`, `12` and `1.5`
+  are a boolean and numbers, while quoted scalars are strings. Mapping keys
+  are taken as written, anchors and aliases are expanded, and only the
+  first document of a stream is read. A document that holds more than a
+  million values once its aliases are expanded is a parse error, so that
+  nested aliases cannot multiply a small document out of memory. TOML
+  datetimes become strings.
+  Infinities and NaN, which JSON cannot represent, are parse errors.
+
+  Errors then locate themselves in the original text: each carries an
+  `:instance_position` and a `:schema_position` of the form
+  `%{line: 3, column: 5}` (see `ExJsonschema.ValidationError`). JSON input
+  has no positions. A YAML or TOML schema that does not parse fails to
+  compile with the line and column where parsing stopped in the
+  `CompilationError`'s details.
+
+  Instances may also be MessagePack or CBOR binaries, with
+  `input_format: :msgpack` or `input_format: :cbor` given to
+  `ExJsonschema.validate/3`. They are decoded natively into the JSON data
+  model, so no JSON text is built in between. Values JSON has no
+  equivalent for, such as binary blobs, map keys other than strings,
+  MessagePack extension types and CBOR big integers, make the instance fail
+  with a `CompilationError` of type `:parse_error` whose details say what
+  the value is and where:
+
+      ExJsonschema.validate(compiled, payload, input_format: :msgpack)
+      #=> {:error, %ExJsonschema.CompilationError{type: :parse_error,
+      #=>   details: "Binary data at \"/photo\" cannot be represented in JSON"}}
+
+  With `output: :basic`, which returns no details, such an instance gives
+  `{:error, :validation_failed}` instead. JSON, YAML and TOML instances that
+  do not parse give `{:error, [:validation_error]}` with `:detailed` and
+  `:verbose` output, and `{:error, :validation_failed}` with `:basic`.
+  CBOR tags are dropped in favour of the values they tag. Binary instances
+  have no positions, and schemas cannot be compiled from binary formats.
   """
 
   @typedoc """
@@ -33,12 +195,32 @@ defmodule ExJsonschema.Options do
   - `:draft7` - JSON Schema Draft 7 (2019)
   - `:draft201909` - JSON Schema 2019-09
   - `:draft202012` - JSON Schema 2020-12 (latest)
+  - `:openapi30` - OpenAPI 3.0 Schema Objects
+  - `:openapi31` - OpenAPI 3.1 Schema Objects
 
   When `:auto` is used, the library examines the `$schema` property
   to determine the appropriate draft version. Defaults to `:draft202012`
   if no `$schema` is found.
+
+  OpenAPI 3.0 schemas are validated as Draft 4, which shares their boolean
+  `exclusiveMinimum`/`exclusiveMaximum`, with `nullable: true` adding
+  `"null"` to the `type` next to it. OpenAPI 3.1 schemas are Draft 2020-12
+  plus the OAS base vocabulary; a `$schema` of
+  `https://spec.openapis.org/oas/3.1/dialect/base` is detected as
+  `:openapi31`. In both, `example`, `xml` and `externalDocs` are
+  annotations, and the OpenAPI formats (`int32`, `binary`, ...) count as
+  known formats. `discriminator` selects a `oneOf` branch, as described in
+  "Discriminators" in the module docs.
   """
-  @type draft :: :auto | :draft4 | :draft6 | :draft7 | :draft201909 | :draft202012
+  @type draft ::
+          :auto
+          | :draft4
+          | :draft6
+          | :draft7
+          | :draft201909
+          | :draft202012
+          | :openapi30
+          | :openapi31
 
   @typedoc """
   Regular expression engine used for pattern validation.
@@ -64,25 +246,66 @@ defmodule ExJsonschema.Options do
   """
   @type output_format :: :basic | :detailed | :verbose
 
+  @typedoc """
+  Redaction policy for error output. See "Redaction" in the module docs.
+  """
+  @type redact ::
+          boolean() | [keyword: String.t(), pointers: [String.t()], mask: String.t()]
+
+  @typedoc """
+  Which way a validated instance travels. See "Request and Response
+  Direction" in the module docs.
+  """
+  @type direction :: :request | :response | nil
+
+  @typedoc """
+  The format of a schema or instance. `:msgpack` and `:cbor` are binary
+  formats for instances only, and `:term` is set by
+  `ExJsonschema.validate_term/3`. See "Input Formats" in the module docs.
+  """
+  @type input_format :: :json | :yaml | :toml | :msgpack | :cbor | :term
+
+  @redact_defaults [keyword: "x-sensitive", pointers: [], mask: "[REDACTED]"]
+
   defstruct [
     # Draft specification
     draft: :auto,
 
     # Validation behavior
     validate_formats: false,
+    allowed_formats: nil,
+    denied_formats: [],
+    unknown_formats: :ignore,
+    validate_content: false,
+    infer_discriminators: false,
 
     # Performance settings
     regex_engine: :fancy_regex,
 
     # Output control
-    output_format: :detailed
+    output_format: :detailed,
+    ranked: false,
+    direction: nil,
+    redact: false,
+
+    # Input parsing
+    input_format: :json
   ]
 
   @type t :: %__MODULE__{
           draft: draft(),
           validate_formats: boolean(),
+          allowed_formats: [String.t()] | nil,
+          denied_formats: [String.t()],
+          unknown_formats: :ignore | :error,
+          validate_content: boolean(),
+          infer_discriminators: boolean(),
           regex_engine: regex_engine(),
-          output_format: output_format()
+          output_format: output_format(),
+          ranked: boolean(),
+          direction: direction(),
+          redact: redact(),
+          input_format: input_format()
         }
 
   @doc """
@@ -92,8 +315,19 @@ defmodule ExJsonschema.Options do
 
     * `:draft` - JSON Schema draft to use (default: `:auto`)
     * `:validate_formats` - Enable format validation (default: `false`)
+    * `:allowed_formats` - Check only these formats, `nil` for all (default: `nil`)
+    * `:denied_formats` - Never check these formats (default: `[]`)
+    * `:unknown_formats` - `:ignore` or `:error` on unknown formats (default: `:ignore`)
+    * `:validate_content` - Decode and validate encoded content (default: `false`)
+    * `:infer_discriminators` - Treat `oneOf` branches told apart by a `const`
+      property as discriminated (default: `false`)
     * `:regex_engine` - Regex engine to use (default: `:fancy_regex`)
     * `:output_format` - Error output format (default: `:detailed`)
+    * `:ranked` - Order errors from most to least relevant (default: `false`)
+    * `:direction` - `:request` or `:response` to forbid `readOnly` or
+      `writeOnly` values (default: `nil`)
+    * `:redact` - Mask sensitive values in errors (default: `false`)
+    * `:input_format` - `:json`, `:yaml` or `:toml` text (default: `:json`)
 
   ## Examples
 
@@ -129,7 +363,18 @@ defmodule ExJsonschema.Options do
   end
 
   def new(overrides) when is_list(overrides) do
-    struct(%__MODULE__{}, overrides)
+    struct(%__MODULE__{}, translate_ignore_unknown_formats(overrides))
+  end
+
+  # `ignore_unknown_formats: false` predates `unknown_formats: :error`
+  defp translate_ignore_unknown_formats(overrides) do
+    case Keyword.pop(overrides, :ignore_unknown_formats) do
+      {nil, overrides} ->
+        overrides
+
+      {ignore?, overrides} ->
+        Keyword.put_new(overrides, :unknown_formats, if(ignore?, do: :ignore, else: :error))
+    end
   end
 
   @doc """
@@ -197,6 +442,24 @@ defmodule ExJsonschema.Options do
     |> new()
   end
 
+  @doc """
+  Creates options for OpenAPI 3.0 Schema Objects.
+  """
+  def openapi30(overrides \\ []) do
+    overrides
+    |> Keyword.put(:draft, :openapi30)
+    |> new()
+  end
+
+  @doc """
+  Creates options for OpenAPI 3.1 Schema Objects.
+  """
+  def openapi31(overrides \\ []) do
+    overrides
+    |> Keyword.put(:draft, :openapi31)
+    |> new()
+  end
+
   @doc """
   Validates the options struct and returns {:ok, options} or {:error, reason}.
 
@@ -213,20 +476,95 @@ defmodule ExJsonschema.Options do
   def validate(%__MODULE__{} = options) do
     with :ok <- validate_draft(options.draft),
          :ok <- validate_regex_engine(options.regex_engine),
-         :ok <- validate_output_format(options.output_format) do
+         :ok <- validate_format_names(:allowed_formats, options.allowed_formats),
+         :ok <- validate_format_names(:denied_formats, options.denied_formats),
+         :ok <- validate_unknown_formats(options.unknown_formats),
+         :ok <- validate_output_format(options.output_format),
+         :ok <- validate_ranked(options.ranked),
+         :ok <- validate_direction(options.direction),
+         :ok <- validate_redact(options.redact),
+         :ok <- validate_input_format(options.input_format) do
       {:ok, options}
     end
   end
 
-  defp validate_draft(draft)
-       when draft in [:auto, :draft4, :draft6, :draft7, :draft201909, :draft202012],
-       do: :ok
+  @doc """
+  Returns the redaction settings with defaults filled in, or `nil` when
+  redaction is off.
+
+  ## Examples
+
+      iex> ExJsonschema.Options.redaction(ExJsonschema.Options.new())
+      nil
+
+      iex> ExJsonschema.Options.redaction(ExJsonschema.Options.new(redact: [mask: "***"]))
+      [keyword: "x-sensitive", pointers: [], mask: "***"]
+  """
+  @spec redaction(t()) :: keyword() | nil
+  def redaction(%__MODULE__{redact: false}), do: nil
+  def redaction(%__MODULE__{redact: true}), do: @redact_defaults
+
+  def redaction(%__MODULE__{redact: redact}) when is_list(redact) do
+    Keyword.merge(@redact_defaults, redact)
+  end
+
+  @drafts [:auto, :draft4, :draft6, :draft7, :draft201909, :draft202012, :openapi30, :openapi31]
+
+  defp validate_draft(draft) when draft in @drafts, do: :ok
 
   defp validate_draft(draft), do: {:error, "Invalid draft version: #{inspect(draft)}"}
 
   defp validate_regex_engine(engine) when engine in [:fancy_regex, :regex], do: :ok
   defp validate_regex_engine(engine), do: {:error, "Invalid regex engine: #{inspect(engine)}"}
 
+  defp validate_format_names(:allowed_formats, nil), do: :ok
+
+  defp validate_format_names(option, names) do
+    if is_list(names) and Enum.all?(names, &is_binary/1) do
+      :ok
+    else
+      {:error, "Invalid #{option} option: #{inspect(names)}"}
+    end
+  end
+
+  defp validate_unknown_formats(policy) when policy in [:ignore, :error], do: :ok
+
+  defp validate_unknown_formats(policy),
+    do: {:error, "Invalid unknown_formats option: #{inspect(policy)}"}
+
   defp validate_output_format(format) when format in [:basic, :detailed, :verbose], do: :ok
   defp validate_output_format(format), do: {:error, "Invalid output format: #{inspect(format)}"}
+
+  defp validate_ranked(ranked) when is_boolean(ranked), do: :ok
+  defp validate_ranked(ranked), do: {:error, "Invalid ranked option: #{inspect(ranked)}"}
+
+  defp validate_direction(direction) when direction in [nil, :request, :response], do: :ok
+
+  defp validate_direction(direction),
+    do: {:error, "Invalid direction option: #{inspect(direction)}"}
+
+  defp validate_redact(redact) when is_boolean(redact), do: :ok
+
+  defp validate_redact(redact) when is_list(redact) do
+    valid? =
+      Keyword.keyword?(redact) and
+        Enum.all?(redact, fn
+          {:keyword, keyword} -> is_binary(keyword)
+          {:mask, mask} -> is_binary(mask)
+          {:pointers, pointers} -> is_list(pointers) and Enum.all?(pointers, &is_binary/1)
+          _ -> false
+        end)
+
+    if valid?, do: :ok, else: {:error, "Invalid redact option: #{inspect(redact)}"}
+  end
+
+  defp validate_redact(redact), do: {:error, "Invalid redact option: #{inspect(redact)}"}
+
+  defp validate_input_format(format) when format in [:json, :yaml, :toml], do: :ok
+
+  defp validate_input_format(format) when format in [:msgpack, :cbor, :term],
+    do: {:error, "Invalid input_format option: #{inspect(format)} is for instances only"}
+
+  defp validate_input_format(format),
+    do: {:error, "Invalid input_format option: #{inspect(format)}"}
 end
@@ -0,0 +1,152 @@
+defmodule ExJsonschema.Patch do
+  @moduledoc """
+  Applies patches to JSON documents and validates the result in one step.
+
+  Supports RFC 6902 JSON Patch and RFC 7396 JSON Merge Patch. The patch is
+  applied and the patched document validated inside a single NIF call, so
+  the caller either gets the complete patched document or no document at all.
+
+  ## Error Attribution
+
+  Each validation error is paired with the part of the patch most likely to
+  have caused it:
+
+  - JSON Patch - the zero-based index of the operation
+  - JSON Merge Patch - the JSON pointer of the patch member, e.g. `"/address/zip"`
+  - `nil` when no change touched the error's location or its parent
+
+  A change is blamed for errors at or below the location it wrote to, and
+  then for errors at its ancestors, such as a `required` failure after a
+  `remove`. When several changes qualify, the last one wins.
+
+  ## Incremental Validation
+
+  With `incremental: true`, only the subtrees containing a change are
+  validated, each against the subschema that applies to it. This assumes the
+  document was valid before the patch, as it is for documents validated on
+  write. Where the schema does not allow isolating a subtree (combinators
+  such as `oneOf`, or `const`, `enum`, `uniqueItems` and `contains` above
+  it), the whole document is validated instead.
+
+  ## Examples
+
+      compiled = ExJsonschema.compile!(~s({
+        "type": "object",
+        "properties": {"age": {"type": "integer", "minimum": 0}}
+      }))
+
+      ExJsonschema.Patch.patch(compiled, ~s({"age": 30}), ~s([
+        {"op": "replace", "path": "/age", "value": 31}
+      ]))
+      #=> {:ok, ~s({"age":31})}
+
+      ExJsonschema.Patch.patch(compiled, ~s({"age": 30}), ~s({"age": -1}), format: :merge_patch)
+      #=> {:error, [{"/age", %ExJsonschema.ValidationError{instance_path: "/age", ...}}]}
+  """
+
+  alias ExJsonschema.{CompilationError, Native, PatchError, Schema, Telemetry, ValidationError}
+
+  @type format :: :json_patch | :merge_patch
+
+  @typedoc """
+  The part of a patch blamed for an error: an operation index, a merge patch
+  member pointer, or `nil`.
+  """
+  @type responsible :: non_neg_integer() | String.t() | nil
+
+  @type result ::
+          {:ok, String.t()}
+          | {:error, [{responsible(), ValidationError.t()}]}
+          | {:error, PatchError.t()}
+          | {:error, CompilationError.t()}
+
+  @formats [:json_patch, :merge_patch]
+
+  @doc """
+  Applies `patch_json` to `document_json` and validates the result.
+
+  ## Options
+
+  - `format: :json_patch | :merge_patch` - Patch format (default: `:json_patch`)
+  - `incremental: boolean()` - Validate only the changed subtrees (default: `false`)
+
+  ## Returns
+
+  - `{:ok, patched_json}` - The patch applied and the result is valid
+  - `{:error, [{responsible, ValidationError.t()}]}` - The patched document is invalid
+  - `{:error, PatchError.t()}` - A JSON Patch operation could not be applied
+  - `{:error, CompilationError.t()}` - The document or patch is not valid JSON
+
+  ## Examples
+
+      iex> compiled = ExJsonschema.compile!(~s({"properties": {"tags": {"maxItems": 2}}}))
+      iex> document = ~s({"tags": ["a"]})
+      iex> add = fn value -> ~s({"op": "add", "path": "/tags/-", "value": "#{value}"}) end
+      iex> ExJsonschema.Patch.patch(compiled, document, "[#{add.("b")}]")
+      {:ok, ~s({"tags":["a","b"]})}
+      iex> patch = ~s([{"op": "test", "path": "/tags/0", "value": "a"}, #{add.("b")}, #{add.("c")}])
+      iex> {:error, [{2, error}]} = ExJsonschema.Patch.patch(compiled, document, patch)
+      iex> error.instance_path
+      "/tags"
+      iex> {:error, %ExJsonschema.PatchError{operation: 0}} =
+      ...>   ExJsonschema.Patch.patch(compiled, document, ~s([{"op": "remove", "path": "/name"}]))
+
+  """
+  @spec patch(Schema.t(), String.t(), String.t(), keyword()) :: result()
+  def patch(compiled_schema, document_json, patch_json, opts \\ [])
+      when is_struct(compiled_schema, Schema) and is_binary(document_json) and
+             is_binary(patch_json) and is_list(opts) do
+    format = Keyword.get(opts, :format, :json_patch)
+    incremental = Keyword.get(opts, :incremental, false)
+
+    unless format in @formats do
+      raise ArgumentError,
+            "Invalid patch format: #{inspect(format)}. Must be one of: :json_patch, :merge_patch"
+    end
+
+    unless is_boolean(incremental) do
+      raise ArgumentError,
+            "Invalid incremental option: #{inspect(incremental)}. Must be a boolean"
+    end
+
+    metadata =
+      compiled_schema.ref
+      |> Telemetry.schema_metadata()
+      |> Map.merge(%{
+        output_format: :detailed,
+        instance_size: byte_size(document_json),
+        patch_format: format,
+        incremental: incremental
+      })
+
+    Telemetry.span(:validate, metadata, fn ->
+      {result, measurements} =
+        Native.patch_and_validate(
+          compiled_schema.ref,
+          document_json,
+          patch_json,
+          format == :merge_patch,
+          incremental
+        )
+
+      {patch_result(result), measurements, %{}}
+    end)
+  end
+
+  defp patch_result({:ok, patched}), do: {:ok, patched}
+
+  defp patch_result({:error, errors}) when is_list(errors) do
+    {:error,
+     Enum.map(errors, fn {responsible, error_map} ->
+       {responsible, ValidationError.from_detailed_map(error_map)}
+     end)}
+  end
+
+  defp patch_result({:error, %{"type" => "patch_error"} = error_map}) do
+    {:error, PatchError.from_map(error_map)}
+  end
+
+  defp patch_result({:error, error_map}) when is_map(error_map) do
+    {:error, CompilationError.from_map(error_map)}
+  end
+end
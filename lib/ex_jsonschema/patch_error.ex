@@ -0,0 +1,21 @@
+defmodule ExJsonschema.PatchError do
+  @moduledoc """
+  A JSON Patch operation that could not be applied.
+
+  `:operation` is the zero-based index of the failing operation in the patch.
+  Nothing is applied when any operation fails.
+  """
+
+  defexception [:operation, :message]
+
+  @type t :: %__MODULE__{
+          operation: non_neg_integer(),
+          message: String.t()
+        }
+
+  @doc false
+  @spec from_map(map()) :: t()
+  def from_map(%{"operation" => operation, "details" => details}) do
+    %__MODULE__{operation: operation, message: details}
+  end
+end
@@ -0,0 +1,182 @@
+if Code.ensure_loaded?(Plug.Conn) do
+  defmodule ExJsonschema.Plug do
+    @moduledoc """
+    Validates request bodies against a compiled schema chosen per route, and
+    rejects invalid ones with an RFC 9457 Problem Details response.
+
+    Add it after `Plug.Parsers`, which decodes the body into
+    `conn.body_params`. The params are validated as they are, with
+    `ExJsonschema.validate_term/3`, so the body is not encoded to JSON again.
+    A JSON body that is not an object, which `Plug.Parsers` puts under
+    `"_json"`, is validated unwrapped.
+
+        plug Plug.Parsers, parsers: [:json], json_decoder: Jason
+        plug ExJsonschema.Plug, schema: &MyApp.Schemas.for_request/1
+
+    ## Choosing the Schema
+
+    The `:schema` option is a compiled schema, a function from the conn to a
+    compiled schema or `nil`, or a `{module, function, args}` tuple called
+    with the conn prepended to `args`. Functions can pick the schema from
+    anything known about the request, such as Phoenix's
+    `conn.private.phoenix_action`.
+
+    Without a `:schema` option, the schema is read from the conn's private
+    data under `:ex_jsonschema`, where an earlier plug or a router can put it
+    with `put_schema/2`. When no schema is found, the request passes
+    through unvalidated.
+
+    ## Options
+
+    - `:schema` - How to find the schema, as above
+    - `:output` - `:detailed` (default) or `:verbose`; `:basic` reports no
+      individual errors
+    - `:ranked` - Lists errors from most to least relevant (default: `true`)
+    - `:direction` - Validation direction (default: `:request`, rejecting
+      `readOnly` values)
+    - `:status` - Status of the error response (default: `422`)
+    - `:on_error` - A function from the conn and the errors to the conn to
+      continue with, replacing the Problem Details response. It receives a
+      list of `ExJsonschema.ValidationError`s, `:validation_failed` with
+      `:basic` output, or a `ExJsonschema.CompilationError` for a body JSON
+      cannot represent. The conn it returns is halted.
+
+    ## Problem Details
+
+    Invalid requests get a `422` response of type
+    `application/problem+json`, listing each error with a JSON pointer to
+    it, as in RFC 9457's own example:
+
+        {
+          "type": "about:blank",
+          "title": "Unprocessable Content",
+          "status": 422,
+          "detail": "The request body does not match its schema.",
+          "errors": [
+            {"pointer": "#/items/3/sku", "detail": "4 is not of type \\"string\\"",
+             "keyword": "type", "schema_path": "/properties/items/items/properties/sku/type"}
+          ]
+        }
+
+    `problem_details/2` builds the same map, for responses rendered
+    elsewhere.
+    """
+
+    @behaviour Plug
+
+    alias ExJsonschema.{CompilationError, Schema, ValidationError}
+    alias Plug.Conn
+
+    @private_key :ex_jsonschema
+
+    @validate_options [:output, :ranked, :direction]
+
+    @impl Plug
+    def init(opts) do
+      {validate_opts, opts} = Keyword.split(opts, @validate_options)
+
+      validate_opts =
+        [ranked: true, direction: :request]
+        |> Keyword.merge(validate_opts)
+        |> Keyword.put_new(:output, :detailed)
+
+      opts
+      |> Keyword.take([:schema, :status, :on_error])
+      |> Keyword.put(:validate, validate_opts)
+      |> Keyword.put_new(:status, 422)
+    end
+
+    @impl Plug
+    def call(%Conn{} = conn, opts) do
+      case schema(conn, Keyword.get(opts, :schema)) do
+        nil ->
+          conn
+
+        %Schema{} = compiled ->
+          case ExJsonschema.validate_term(compiled, body(conn), opts[:validate]) do
+            :ok -> conn
+            {:error, errors} -> reject(conn, errors, opts)
+          end
+      end
+    end
+
+    @doc """
+    Puts the schema to validate a request's body against in the conn's
+    private data, for the plug to find without a `:schema` option.
+    """
+    @spec put_schema(Conn.t(), Schema.t()) :: Conn.t()
+    def put_schema(%Conn{} = conn, %Schema{} = compiled) do
+      Conn.put_private(conn, @private_key, compiled)
+    end
+
+    @doc """
+    Builds an RFC 9457 Problem Details map for a request body that failed
+    validation with `errors`.
+    """
+    @spec problem_details(
+            [ValidationError.t()] | :validation_failed | CompilationError.t(),
+            pos_integer()
+          ) :: map()
+    def problem_details(errors, status \\ 422) do
+      problem = %{
+        "type" => "about:blank",
+        "title" => Conn.Status.reason_phrase(status),
+        "status" => status
+      }
+
+      case errors do
+        errors when is_list(errors) ->
+          Map.merge(problem, %{
+            "detail" => "The request body does not match its schema.",
+            "errors" => Enum.map(errors, &error_details/1)
+          })
+
+        :validation_failed ->
+          Map.put(problem, "detail", "The request body does not match its schema.")
+
+        %CompilationError{details: details} ->
+          Map.put(problem, "detail", "The request body cannot be read as JSON: #{details}")
+      end
+    end
+
+    defp error_details(%ValidationError{} = error) do
+      %{
+        "pointer" => "#" <> error.instance_path,
+        "detail" => error.message,
+        "keyword" => error.keyword,
+        "schema_path" => error.schema_path
+      }
+    end
+
+    defp schema(conn, nil), do: Map.get(conn.private, @private_key)
+    defp schema(_conn, %Schema{} = compiled), do: compiled
+    defp schema(conn, fun) when is_function(fun, 1), do: fun.(conn)
+    defp schema(conn, {module, fun, args}), do: apply(module, fun, [conn | args])
+
+    # `Plug.Parsers` wraps JSON bodies that are not objects in "_json"
+    defp body(%Conn{body_params: %Conn.Unfetched{}}) do
+      raise ArgumentError,
+            "ExJsonschema.Plug needs the body parsed first; plug Plug.Parsers before it"
+    end
+
+    defp body(%Conn{body_params: %{"_json" => json} = params}) when map_size(params) == 1,
+      do: json
+
+    defp body(%Conn{body_params: params}), do: params
+
+    defp reject(conn, errors, opts) do
+      case Keyword.get(opts, :on_error) do
+        nil ->
+          status = Keyword.fetch!(opts, :status)
+
+          conn
+          |> Conn.put_resp_content_type("application/problem+json", nil)
+          |> Conn.send_resp(status, Jason.encode!(problem_details(errors, status)))
+          |> Conn.halt()
+
+        on_error when is_function(on_error, 2) ->
+          conn |> on_error.(errors) |> Conn.halt()
+      end
+    end
+  end
+end
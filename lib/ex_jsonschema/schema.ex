@@ -0,0 +1,79 @@
+defmodule ExJsonschema.Schema do
+  @moduledoc """
+  A compiled JSON Schema, as returned by `ExJsonschema.compile/2`.
+
+  The struct wraps the reference to the native validator. Pass it to
+  `ExJsonschema.validate/3` and friends as is; its fields are not meant to be
+  read directly.
+
+  The validator keeps track of how it was built. `info/1` returns the
+  effective draft, the schema's `$id`, a hash of its content and a
+  fingerprint of the compilation options. Inspecting a schema shows its
+  draft, `$id` and the start of its content hash:
+
+      iex> compiled = ExJsonschema.compile!(~s({"$id": "urn:example:user", "type": "object"}))
+      iex> inspect(compiled) =~ ~s(#ExJsonschema.Schema<draft202012 id: "urn:example:user")
+      true
+
+  """
+
+  alias ExJsonschema.Native
+
+  @enforce_keys [:ref]
+  defstruct [:ref]
+
+  @type t :: %__MODULE__{ref: reference()}
+
+  @typedoc """
+  Details about how a schema was compiled.
+
+  - `:id` - The schema's `$id` (`id` in Draft 4), or `nil`
+  - `:draft` - The draft the validator was built for
+  - `:content_hash` - Hex SHA-256 digest of the canonical schema document.
+    Equivalent documents share a hash regardless of key order or whitespace
+  - `:options_fingerprint` - The compilation options that affect validation
+  - `:compiled_at` - When the validator was built
+  """
+  @type info :: %{
+          id: String.t() | nil,
+          draft: ExJsonschema.Native.draft(),
+          content_hash: String.t(),
+          options_fingerprint: String.t(),
+          compiled_at: DateTime.t()
+        }
+
+  @doc false
+  @spec wrap(reference()) :: t()
+  def wrap(ref) when is_reference(ref), do: %__MODULE__{ref: ref}
+
+  @doc """
+  Returns details about how a compiled schema was built.
+
+  ## Examples
+
+      iex> compiled = ExJsonschema.compile!(~s({"type": "string"}), draft: :draft7)
+      iex> info = ExJsonschema.Schema.info(compiled)
+      iex> info.draft
+      :draft7
+      iex> info.id
+      nil
+      iex> byte_size(info.content_hash)
+      64
+
+  """
+  @spec info(t()) :: info()
+  def info(%__MODULE__{ref: ref}) do
+    %{compiled_at: compiled_at} = info = Native.schema_info(ref)
+    %{info | compiled_at: DateTime.from_unix!(compiled_at, :millisecond)}
+  end
+
+  defimpl Inspect do
+    def inspect(schema, _opts) do
+      info = ExJsonschema.Schema.info(schema)
+      id = if info.id, do: " id: #{Kernel.inspect(info.id)}", else: ""
+      hash = String.slice(info.content_hash, 0, 12)
+
+      "#ExJsonschema.Schema<#{info.draft}#{id} hash: #{hash}>"
+    end
+  end
+end
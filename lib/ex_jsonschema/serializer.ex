@@ -0,0 +1,67 @@
+defmodule ExJsonschema.Serializer do
+  @moduledoc """
+  Converts compiled schemas to and from plain binaries.
+
+  A compiled schema is a NIF resource: it cannot be stored in
+  `:persistent_term` snapshots or disk-backed caches, and sending it to
+  another node yields a dangling reference. `serialize/1` captures the
+  canonical schema together with the exact options it was compiled with,
+  and `deserialize/1` rebuilds an equivalent validator from that binary.
+
+  Rebuilding goes straight to the Rust compiler with the recorded options,
+  skipping option normalization, draft detection and the schema cache.
+
+  ## Examples
+
+      compiled = ExJsonschema.compile!(~s({"type": "string"}), draft: :draft7)
+      binary = ExJsonschema.Serializer.serialize(compiled)
+
+      # On another node, or after a restart
+      {:ok, restored} = ExJsonschema.Serializer.deserialize(binary)
+      :ok = ExJsonschema.validate(restored, ~s("hello"))
+
+  The binary format is versioned. Binaries written by an incompatible
+  version are rejected with a `:deserialization_error` instead of being
+  misread.
+  """
+
+  alias ExJsonschema.{CompilationError, Native, Schema}
+
+  @doc """
+  Serializes a compiled schema into a binary.
+
+  ## Examples
+
+      iex> compiled = ExJsonschema.compile!(~s({"type": "integer"}))
+      iex> is_binary(ExJsonschema.Serializer.serialize(compiled))
+      true
+
+  """
+  @spec serialize(ExJsonschema.compiled_schema()) :: binary()
+  def serialize(%Schema{ref: compiled}) do
+    Native.serialize_schema(compiled)
+  end
+
+  @doc """
+  Rebuilds a compiled schema from the output of `serialize/1`.
+
+  ## Examples
+
+      iex> binary = ExJsonschema.Serializer.serialize(ExJsonschema.compile!(~s({"type": "integer"})))
+      iex> {:ok, compiled} = ExJsonschema.Serializer.deserialize(binary)
+      iex> ExJsonschema.valid?(compiled, "42")
+      true
+
+      iex> {:error, %ExJsonschema.CompilationError{type: :deserialization_error}} =
+      ...>   ExJsonschema.Serializer.deserialize("not a schema")
+
+  """
+  @spec deserialize(binary()) ::
+          {:ok, ExJsonschema.compiled_schema()} | {:error, CompilationError.t()}
+  def deserialize(binary) when is_binary(binary) do
+    case Native.deserialize_schema(binary) do
+      {:ok, compiled} -> {:ok, Schema.wrap(compiled)}
+      {:error, error_map} -> {:error, CompilationError.from_map(error_map)}
+    end
+  end
+end
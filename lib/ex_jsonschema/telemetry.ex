@@ -0,0 +1,97 @@
+defmodule ExJsonschema.Telemetry do
+  @moduledoc """
+  Telemetry events emitted by ExJsonschema.
+
+  Compilation, validation and meta-validation run inside `:telemetry.span/3`,
+  so each operation emits a `:start` event, followed by either a `:stop` or
+  an `:exception` event:
+
+  - `[:ex_jsonschema, :compile, :start | :stop | :exception]`
+  - `[:ex_jsonschema, :validate, :start | :stop | :exception]`
+  - `[:ex_jsonschema, :meta_validate, :start | :stop | :exception]`
+
+  ## Measurements
+
+  `:start` events carry `:system_time` and `:monotonic_time`. `:stop` events
+  carry the total `:duration` plus a breakdown timed inside the NIF. All
+  times are in `:native` units; convert them with `System.convert_time_unit/3`.
+
+  - `:parse_time` - Parsing the schema or instance JSON
+  - `:patch_time` - Applying a patch (`ExJsonschema.patch/4` only)
+  - `:build_time` - Building the validator (`:compile` only)
+  - `:validation_time` - Checking the instance or schema
+  - `:enrichment_time` - Collecting error details, when validation fails
+  - `:encode_time` - Converting the result into Elixir terms
+  - `:error_count` - Number of errors found (not reported by `:basic` output)
+
+  Phases an operation did not go through are left out. A compile served from
+  the cache, for example, reports only `:duration`.
+
+  ## Metadata
+
+  - `:compile` - `:draft` and `:schema_id` (resolved on success, otherwise the
+    requested draft), `:output_format`, `:schema_size` and `:cache`
+    (`:hit`, `:miss` or `:none`)
+  - `:validate` - `:draft`, `:schema_id`, `:output_format` and `:instance_size`
+    (`nil` for terms from `ExJsonschema.validate_term/3`), plus `:schema_pointer` and `:instance_pointer` from `ExJsonschema.validate_at/3`
+    and `:patch_format` and `:incremental` from `ExJsonschema.patch/4`
+  - `:meta_validate` - `:output_format` (`:basic`, `:simple` or `:detailed`)
+    and `:schema_size`
+
+  `:schema_id` is the schema's `$id` (`id` in Draft 4), or `nil`.
+
+  ## Example
+
+      :telemetry.attach(
+        "log-slow-validations",
+        [:ex_jsonschema, :validate, :stop],
+        fn _event, %{duration: duration}, metadata, _config ->
+          if System.convert_time_unit(duration, :native, :millisecond) > 10 do
+            Logger.warning("Slow validation against \#{metadata.schema_id}")
+          end
+        end,
+        nil
+      )
+  """
+
+  alias ExJsonschema.Native
+
+  @time_measurements [
+    :parse_time,
+    :patch_time,
+    :build_time,
+    :validation_time,
+    :enrichment_time,
+    :encode_time
+  ]
+
+  @doc false
+  # Runs `fun` in a span named `[:ex_jsonschema, event]`. `fun` returns the
+  # result, the measurements reported by the NIF, and extra stop metadata.
+  @spec span(atom(), map(), (-> {result, map(), map()})) :: result when result: term()
+  def span(event, metadata, fun) do
+    :telemetry.span([:ex_jsonschema, event], metadata, fn ->
+      {result, native_measurements, stop_metadata} = fun.()
+      {result, to_native_units(native_measurements), Map.merge(metadata, stop_metadata)}
+    end)
+  end
+
+  @doc false
+  # Schema id and draft of a compiled schema
+  @spec schema_metadata(reference()) :: %{
+          schema_id: String.t() | nil,
+          draft: atom()
+        }
+  def schema_metadata(compiled_schema), do: Native.schema_metadata(compiled_schema)
+
+  # NIFs report nanoseconds; telemetry convention is native time units
+  defp to_native_units(measurements) do
+    Map.new(measurements, fn
+      {key, nanoseconds} when key in @time_measurements ->
+        {key, System.convert_time_unit(nanoseconds, :nanosecond, :native)}
+
+      other ->
+        other
+    end)
+  end
+end
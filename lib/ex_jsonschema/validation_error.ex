@@ -1,6 +1,55 @@
 defmodule ExJsonschema.ValidationError do
   @moduledoc """
   Represents a JSON Schema validation error with detailed path and message information.
+
+  ## Branches
+
+  When an instance matches none of the subschemas of a `oneOf` or `anyOf`, or
+  more than one subschema of a `oneOf`, the error lists what went wrong in
+  each subschema under `:branches`: one list of errors per subschema, in
+  schema order. A list is empty when the instance matched that subschema.
+  Every nested error carries a `:branch` with the subschema's `:index` and
+  its `:schema_pointer`, and may have branches of its own.
+
+  `allOf` failures need no tree, since each failing subschema is reported as
+  a separate error, and neither does a `oneOf` with a discriminator: the
+  errors of the branch its tag selects are reported in place of it (see
+  "Discriminators" in `ExJsonschema.Options`).
+
+      %ValidationError{
+        schema_path: "/oneOf",
+        branches: [
+          [%ValidationError{schema_path: "/oneOf/0/type", branch: %{index: 0, ...}}],
+          [%ValidationError{schema_path: "/oneOf/1/minimum", branch: %{index: 1, ...}}]
+        ]
+      }
+
+  ## Positions
+
+  When the instance or the schema was YAML or TOML text (see "Input
+  Formats" in `ExJsonschema.Options`), an error from `ExJsonschema.validate/3`
+  gives the line and column of its `:instance_path` in the instance as
+  `:instance_position`, and of its `:schema_path` in the schema as
+  `:schema_position`. Object members are located at their keys; a location
+  that does not exist in the document, such as a missing required property,
+  falls back to its nearest ancestor, and schema paths are followed through
+  `$ref`s within the schema. Positions are `nil` for JSON text, for errors
+  nested under `:branches`, and for schemas restored by
+  `ExJsonschema.Serializer`.
+
+  ## Structured Details
+
+  Besides its message, an error says which `:keyword` failed and, in
+  `:params`, what the keyword checked against, keyed by name: `"limit"` for
+  `minimum` or `maxLength`, `"property"` for `required`, `"expected"` for
+  `type` and `const`, `"unexpected"` for `additionalProperties`, and so on.
+  Keywords without parameters have empty params.
+
+  Errors from `ExJsonschema.validate/3` also split their `:instance_path`
+  into `:instance_segments`: array indices as integers and object keys as
+  strings, so `"/items/3/sku"` is `["items", 3, "sku"]` when `items` is an
+  array. `ExJsonschema.Ecto` uses them to put errors on changesets.
+  Segments are `nil` for errors nested under `:branches`.
   """
 
   defexception [
@@ -12,7 +61,13 @@ defmodule ExJsonschema.ValidationError do
     :schema_value,
     :context,
     :annotations,
-    :suggestions
+    :suggestions,
+    :branch,
+    :branches,
+    :instance_position,
+    :schema_position,
+    :params,
+    :instance_segments
   ]
 
   @type t :: %__MODULE__{
@@ -24,9 +79,21 @@ defmodule ExJsonschema.ValidationError do
           schema_value: any() | nil,
           context: map() | nil,
           annotations: map() | nil,
-          suggestions: [String.t()] | nil
+          suggestions: [String.t()] | nil,
+          branch: branch() | nil,
+          branches: [[t()]] | nil,
+          instance_position: position() | nil,
+          schema_position: position() | nil,
+          params: %{optional(String.t()) => any()} | nil,
+          instance_segments: [String.t() | non_neg_integer()] | nil
         }
 
+  @typedoc "The subschema of a `oneOf` or `anyOf` a nested error belongs to"
+  @type branch :: %{index: non_neg_integer(), schema_pointer: String.t()}
+
+  @typedoc "A line and column in YAML or TOML text, both counted from 1"
+  @type position :: %{line: pos_integer(), column: pos_integer()}
+
   @doc """
   Creates a ValidationError from a map returned by the NIF.
 
@@ -44,7 +111,13 @@ defmodule ExJsonschema.ValidationError do
       schema_value: Map.get(error_map, "schema_value"),
       context: Map.get(error_map, "context", %{}),
       annotations: Map.get(error_map, "annotations", %{}),
-      suggestions: Map.get(error_map, "suggestions", [])
+      suggestions: Map.get(error_map, "suggestions", []),
+      branch: branch_from_map(error_map),
+      branches: branches_from_map(error_map, &from_map/1),
+      instance_position: position_from_map(error_map, "instance_position"),
+      schema_position: position_from_map(error_map, "schema_position"),
+      params: Map.get(error_map, "params"),
+      instance_segments: Map.get(error_map, "instance_segments")
     }
   end
 
@@ -53,24 +126,51 @@ defmodule ExJsonschema.ValidationError do
   Only includes basic error information without verbose context.
   """
   @spec from_detailed_map(map()) :: t()
-  def from_detailed_map(%{
-        "instance_path" => instance_path,
-        "schema_path" => schema_path,
-        "message" => message
-      }) do
+  def from_detailed_map(
+        %{
+          "instance_path" => instance_path,
+          "schema_path" => schema_path,
+          "message" => message
+        } = error_map
+      ) do
     %__MODULE__{
       instance_path: instance_path,
       schema_path: schema_path,
       message: message,
-      keyword: nil,
+      keyword: Map.get(error_map, "keyword"),
       instance_value: nil,
       schema_value: nil,
       context: nil,
       annotations: nil,
-      suggestions: nil
+      suggestions: nil,
+      branch: branch_from_map(error_map),
+      branches: branches_from_map(error_map, &from_detailed_map/1),
+      instance_position: position_from_map(error_map, "instance_position"),
+      schema_position: position_from_map(error_map, "schema_position"),
+      params: Map.get(error_map, "params"),
+      instance_segments: Map.get(error_map, "instance_segments")
     }
   end
 
+  defp branch_from_map(%{"branch" => %{"index" => index, "schema_pointer" => pointer}}) do
+    %{index: index, schema_pointer: pointer}
+  end
+
+  defp branch_from_map(_error_map), do: nil
+
+  defp branches_from_map(%{"branches" => branches}, convert) do
+    Enum.map(branches, &Enum.map(&1, convert))
+  end
+
+  defp branches_from_map(_error_map, _convert), do: nil
+
+  defp position_from_map(error_map, key) do
+    case Map.get(error_map, key) do
+      %{"line" => line, "column" => column} -> %{line: line, column: column}
+      nil -> nil
+    end
+  end
+
   defimpl String.Chars do
     def to_string(%ExJsonschema.ValidationError{} = error) do
       "ValidationError at #{error.instance_path}: #{error.message}"
@@ -55,6 +55,9 @@ defmodule ExJsonschema.MixProject do
       {:rustler, "~> 0.36"},
       {:rustler_precompiled, "~> 0.8"},
       {:jason, "~> 1.4"},
+      {:telemetry, "~> 1.1"},
+      {:ecto, "~> 3.10", optional: true},
+      {:plug, "~> 1.14", optional: true},
       {:ex_doc, "~> 0.27", only: :dev, runtime: false},
       {:credo, "~> 1.7", only: [:dev, :test], runtime: false},
       {:stream_data, "~> 1.0", only: :test}
@@ -109,9 +112,13 @@ defmodule ExJsonschema.MixProject do
         ]
       ],
       groups_for_modules: [
-        Core: [ExJsonschema],
+        Core: [ExJsonschema, ExJsonschema.Schema],
         Configuration: [ExJsonschema.Options, ExJsonschema.Profile],
-        Errors: [ExJsonschema.ValidationError, ExJsonschema.CompilationError],
+        Errors: [
+          ExJsonschema.ValidationError,
+          ExJsonschema.CompilationError,
+          ExJsonschema.PatchError
+        ],
         Behaviors: [ExJsonschema.Cache],
         Internal: [ExJsonschema.Native, ExJsonschema.DraftDetector]
       ]
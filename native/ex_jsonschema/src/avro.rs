@@ -0,0 +1,1179 @@
+use std::collections::{HashMap, HashSet};
+
+use referencing::Draft;
+use serde_json::{json, Map, Value};
+
+use crate::binary::at;
+use crate::dereference::escape_pointer_segment;
+use crate::migrate::schema_url;
+
+// Keywords that describe a schema rather than constrain it, which Avro
+// schemas can do without
+const ANNOTATIONS: &[&str] = &[
+    "$anchor",
+    "$comment",
+    "$defs",
+    "$id",
+    "$schema",
+    "default",
+    "definitions",
+    "deprecated",
+    "description",
+    "examples",
+    "readOnly",
+    "title",
+    "writeOnly",
+];
+
+const PRIMITIVES: &[&str] = &[
+    "null", "boolean", "int", "long", "float", "double", "bytes", "string",
+];
+
+/// Something a conversion could not carry over. `path` is a JSON pointer
+/// into the schema that was converted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConversionIssue {
+    pub path: String,
+    pub keyword: String,
+    pub message: String,
+}
+
+#[derive(Debug)]
+pub struct Conversion {
+    pub schema: Value,
+    pub issues: Vec<ConversionIssue>,
+}
+
+/// Converts a JSON Schema into an Avro schema describing the same data.
+///
+/// Objects with properties become records, objects with only
+/// `additionalProperties` maps, string enums Avro enums, `oneOf`, `anyOf`
+/// and type lists unions, and date, time and UUID formats logical types.
+/// References within the schema are followed, records and enums being
+/// defined once and named after that. The root record is named `name`, or
+/// after the schema's `title`, in `namespace`. Everything Avro cannot
+/// express is dropped and reported as an issue.
+pub fn to_avro(
+    schema: &Value,
+    name: Option<&str>,
+    namespace: Option<&str>,
+) -> Result<Conversion, String> {
+    let mut converter = ToAvro {
+        root: schema,
+        root_name: name.map(str::to_string),
+        namespace: namespace.map(str::to_string),
+        named: HashMap::new(),
+        names: HashSet::new(),
+        expanding: HashSet::new(),
+        depth: 0,
+        issues: Vec::new(),
+    };
+    let schema = converter.convert(schema, "", "Root")?;
+    Ok(Conversion {
+        schema,
+        issues: converter.issues,
+    })
+}
+
+/// Converts an Avro schema into a 2020-12 JSON Schema for the same data.
+///
+/// Records become closed objects whose fields without defaults are
+/// required, maps objects with `additionalProperties`, enums string enums
+/// and unions `anyOf`, or a nullable type when a union is of `null` and one
+/// other type. Named types other than the root are defined under `$defs`.
+/// Logical types become formats; Avro attributes JSON Schema has no place
+/// for are reported as issues.
+pub fn from_avro(avro: &Value) -> Result<Conversion, String> {
+    let mut converter = FromAvro {
+        defs: Map::new(),
+        references: HashMap::new(),
+        issues: Vec::new(),
+    };
+    let mut schema = match converter.convert(avro, "", None)? {
+        Value::Object(map) => map,
+        other => return Err(format!("Unexpected conversion result {}", other)),
+    };
+    schema.insert(
+        "$schema".to_string(),
+        Value::String(schema_url(Draft::Draft202012)),
+    );
+    if !converter.defs.is_empty() {
+        schema.insert("$defs".to_string(), Value::Object(converter.defs));
+    }
+    Ok(Conversion {
+        schema: Value::Object(schema),
+        issues: converter.issues,
+    })
+}
+
+struct ToAvro<'a> {
+    root: &'a Value,
+    root_name: Option<String>,
+    namespace: Option<String>,
+    // Names of the records and enums defined so far, by the pointer of the
+    // schema they were made from
+    named: HashMap<String, String>,
+    names: HashSet<String>,
+    // Pointers of the referenced schemas being converted, to catch
+    // recursion through schemas Avro cannot name
+    expanding: HashSet<String>,
+    // How many named types the one being converted is nested in
+    depth: usize,
+    issues: Vec<ConversionIssue>,
+}
+
+impl ToAvro<'_> {
+    fn convert(&mut self, schema: &Value, pointer: &str, hint: &str) -> Result<Value, String> {
+        let map = match schema {
+            Value::Object(map) => map,
+            Value::Bool(true) => {
+                self.report(
+                    pointer,
+                    "",
+                    "Schema accepts any value, which Avro has no type for; it becomes a string",
+                );
+                return Ok(json!("string"));
+            }
+            Value::Bool(false) => {
+                return Err(format!(
+                    "Schema at {} accepts nothing, which Avro has no type for",
+                    at(pointer)
+                ))
+            }
+            _ => return Err(format!("Schema at {} is not an object", at(pointer))),
+        };
+
+        if let Some(reference) = map.get("$ref").and_then(Value::as_str) {
+            self.dropped(map, pointer, &["$ref"]);
+            return self.reference(reference, pointer);
+        }
+        for keyword in ["oneOf", "anyOf"] {
+            if let Some(Value::Array(branches)) = map.get(keyword) {
+                self.dropped(map, pointer, &[keyword]);
+                let mut union = Vec::new();
+                for (index, branch) in branches.iter().enumerate() {
+                    let branch_pointer = format!("{}/{}/{}", pointer, keyword, index);
+                    let hint = format!("{}{}", hint, index + 1);
+                    let avro = self.convert(branch, &branch_pointer, &hint)?;
+                    self.add_branch(&mut union, avro, &branch_pointer, keyword);
+                }
+                return Ok(single(union));
+            }
+        }
+        if let Some(Value::Array(types)) = map.get("type") {
+            let mut types: Vec<&Value> = types.iter().collect();
+            types.sort_by_key(|name| name.as_str() != Some("null"));
+            let mut union = Vec::new();
+            for name in types {
+                let mut typed = map.clone();
+                typed.insert("type".to_string(), name.clone());
+                let avro = self.convert_typed(&typed, pointer, hint)?;
+                self.add_branch(&mut union, avro, &join(pointer, "type"), "type");
+            }
+            return Ok(single(union));
+        }
+        self.convert_typed(map, pointer, hint)
+    }
+
+    // Converts a schema of at most one type
+    fn convert_typed(
+        &mut self,
+        map: &Map<String, Value>,
+        pointer: &str,
+        hint: &str,
+    ) -> Result<Value, String> {
+        let mut type_name = match map.get("type") {
+            Some(Value::String(name)) => Some(name.as_str()),
+            None => None,
+            Some(other) => {
+                return Err(format!(
+                    "Type {} at {} is not a type name",
+                    other,
+                    at(pointer)
+                ))
+            }
+        };
+        if type_name == Some("null") {
+            return Ok(json!("null"));
+        }
+
+        let mut handled = vec!["type"];
+        let (keyword, values) = match (map.get("enum"), map.get("const")) {
+            (Some(Value::Array(values)), _) => ("enum", Some(values.clone())),
+            (None, Some(value)) => ("const", Some(vec![value.clone()])),
+            _ => ("enum", None),
+        };
+        if let Some(values) = values {
+            handled.push(keyword);
+            let nullable = values.contains(&Value::Null);
+            let values: Vec<&Value> = values.iter().filter(|value| !value.is_null()).collect();
+            let symbols: Vec<&str> = values.iter().filter_map(|value| value.as_str()).collect();
+            if matches!(type_name, None | Some("string"))
+                && !symbols.is_empty()
+                && symbols.len() == values.len()
+                && symbols.iter().all(|symbol| is_name(symbol))
+            {
+                let avro = self.avro_enum(map, pointer, hint, &symbols);
+                self.dropped(map, pointer, &handled);
+                if nullable && type_name.is_none() {
+                    return Ok(json!(["null", avro]));
+                }
+                return Ok(avro);
+            }
+            self.report(
+                &join(pointer, keyword),
+                keyword,
+                "Avro enums hold only names of letters, digits and underscores; \
+                 the allowed values are dropped and only their type is kept",
+            );
+            type_name = type_name.or_else(|| values.first().map(|value| json_type(value)));
+        }
+
+        let type_name = type_name.or_else(|| {
+            if map.contains_key("properties") || map.contains_key("additionalProperties") {
+                Some("object")
+            } else if map.contains_key("items") {
+                Some("array")
+            } else {
+                None
+            }
+        });
+        let avro = match type_name {
+            Some("boolean") => json!("boolean"),
+            Some("integer") => self.integer(map, &mut handled),
+            Some("number") => {
+                let format = map.get("format").and_then(Value::as_str);
+                if matches!(format, Some("float" | "double")) {
+                    handled.push("format");
+                }
+                json!(if format == Some("float") {
+                    "float"
+                } else {
+                    "double"
+                })
+            }
+            Some("string") => self.string(map, pointer, &mut handled),
+            Some("array") => self.array(map, pointer, hint, &mut handled)?,
+            Some("object") => self.object(map, pointer, hint, &mut handled)?,
+            Some(other) => {
+                return Err(format!(
+                    "Type \"{}\" at {} is not a JSON Schema type",
+                    other,
+                    at(pointer)
+                ))
+            }
+            None => {
+                self.report(
+                    pointer,
+                    "type",
+                    "Schema has no type, which Avro needs; it becomes a string",
+                );
+                json!("string")
+            }
+        };
+        self.dropped(map, pointer, &handled);
+        Ok(avro)
+    }
+
+    fn reference(&mut self, reference: &str, pointer: &str) -> Result<Value, String> {
+        let Some(target_pointer) = reference.strip_prefix('#') else {
+            return Err(format!(
+                "Reference \"{}\" at {} leaves the schema; only references within it are followed",
+                reference,
+                at(pointer)
+            ));
+        };
+        if let Some(name) = self.named.get(target_pointer) {
+            return Ok(Value::String(name.clone()));
+        }
+        let Some(target) = self.root.pointer(target_pointer) else {
+            return Err(format!(
+                "Reference \"{}\" at {} does not resolve",
+                reference,
+                at(pointer)
+            ));
+        };
+        if !self.expanding.insert(target_pointer.to_string()) {
+            return Err(format!(
+                "Reference \"{}\" at {} is recursive through a schema Avro cannot name; \
+                 only records may contain themselves",
+                reference,
+                at(pointer)
+            ));
+        }
+        let hint = target_pointer.rsplit('/').next().unwrap_or_default();
+        let result = self.convert(target, target_pointer, hint);
+        self.expanding.remove(target_pointer);
+        result
+    }
+
+    // Adds a converted schema to a union, which Avro allows only one schema
+    // of each type in besides differently named ones, and no unions in
+    fn add_branch(&mut self, union: &mut Vec<Value>, avro: Value, pointer: &str, keyword: &str) {
+        let branches = match avro {
+            Value::Array(branches) => branches,
+            avro => vec![avro],
+        };
+        for avro in branches {
+            let key = branch_key(&avro);
+            if union.iter().any(|branch| branch_key(branch) == key) {
+                self.report(
+                    pointer,
+                    keyword,
+                    &format!(
+                        "Avro unions hold one \"{}\" schema; this branch is dropped",
+                        key
+                    ),
+                );
+            } else {
+                union.push(avro);
+            }
+        }
+    }
+
+    fn integer(&self, map: &Map<String, Value>, handled: &mut Vec<&'static str>) -> Value {
+        let (int_min, int_max) = (i64::from(i32::MIN), i64::from(i32::MAX));
+        let minimum = map.get("minimum").and_then(Value::as_i64);
+        let maximum = map.get("maximum").and_then(Value::as_i64);
+        let format = map.get("format").and_then(Value::as_str);
+        if minimum == Some(int_min) && maximum == Some(int_max) {
+            handled.extend(["minimum", "maximum"]);
+        }
+        if matches!(format, Some("int32" | "int64")) {
+            handled.push("format");
+        }
+        let fits_int = matches!((minimum, maximum), (Some(minimum), Some(maximum))
+            if minimum >= int_min && maximum <= int_max);
+        json!(if fits_int || format == Some("int32") {
+            "int"
+        } else {
+            "long"
+        })
+    }
+
+    fn string(
+        &mut self,
+        map: &Map<String, Value>,
+        pointer: &str,
+        handled: &mut Vec<&'static str>,
+    ) -> Value {
+        if map.get("contentEncoding").and_then(Value::as_str) == Some("base64") {
+            handled.push("contentEncoding");
+            return json!("bytes");
+        }
+        let format = map.get("format").and_then(Value::as_str);
+        let (avro, lost) = match format {
+            Some("date") => (json!({"type": "int", "logicalType": "date"}), None),
+            Some("date-time") => (
+                json!({"type": "long", "logicalType": "timestamp-millis"}),
+                Some("timestamp-millis, which keeps the instant but not its UTC offset"),
+            ),
+            Some("time") => (
+                json!({"type": "int", "logicalType": "time-millis"}),
+                Some("time-millis, which keeps no UTC offset"),
+            ),
+            Some("uuid") => (json!({"type": "string", "logicalType": "uuid"}), None),
+            _ => return json!("string"),
+        };
+        handled.push("format");
+        if let Some(lost) = lost {
+            self.report(
+                &join(pointer, "format"),
+                "format",
+                &format!(
+                    "\"{}\" values become {} or digits past milliseconds",
+                    format.unwrap_or_default(),
+                    lost
+                ),
+            );
+        }
+        avro
+    }
+
+    fn array(
+        &mut self,
+        map: &Map<String, Value>,
+        pointer: &str,
+        hint: &str,
+        handled: &mut Vec<&'static str>,
+    ) -> Result<Value, String> {
+        handled.push("items");
+        let items_pointer = join(pointer, "items");
+        let items = match map.get("items") {
+            Some(items @ Value::Object(_)) => {
+                self.convert(items, &items_pointer, &format!("{}Item", hint))?
+            }
+            Some(Value::Array(_)) => {
+                self.report(
+                    &items_pointer,
+                    "items",
+                    "Tuples have no Avro equivalent; their items become strings",
+                );
+                json!("string")
+            }
+            _ => {
+                self.report(
+                    &items_pointer,
+                    "items",
+                    "Items may be any value, which Avro has no type for; they become strings",
+                );
+                json!("string")
+            }
+        };
+        Ok(json!({"type": "array", "items": items}))
+    }
+
+    fn object(
+        &mut self,
+        map: &Map<String, Value>,
+        pointer: &str,
+        hint: &str,
+        handled: &mut Vec<&'static str>,
+    ) -> Result<Value, String> {
+        let additional = map.get("additionalProperties");
+        let Some(properties) = map.get("properties").and_then(Value::as_object) else {
+            handled.push("additionalProperties");
+            let values_pointer = join(pointer, "additionalProperties");
+            let values = match additional {
+                Some(values @ Value::Object(_)) => {
+                    self.convert(values, &values_pointer, &format!("{}Value", hint))?
+                }
+                Some(Value::Bool(false)) => {
+                    self.report(
+                        &values_pointer,
+                        "additionalProperties",
+                        "Objects that must be empty have no Avro equivalent; \
+                         they become maps of strings",
+                    );
+                    json!("string")
+                }
+                _ => {
+                    self.report(
+                        pointer,
+                        "additionalProperties",
+                        "Members may be any value, which Avro has no type for; \
+                         they become maps of strings",
+                    );
+                    json!("string")
+                }
+            };
+            return Ok(json!({"type": "map", "values": values}));
+        };
+
+        handled.extend(["properties", "required"]);
+        if additional == Some(&Value::Bool(false)) {
+            handled.push("additionalProperties");
+        }
+        let required: Vec<&str> = map
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|names| names.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        for name in &required {
+            if !properties.contains_key(*name) {
+                self.report(
+                    &join(pointer, "required"),
+                    "required",
+                    &format!(
+                        "\"{}\" is required but has no schema under properties; \
+                         Avro records hold only declared fields",
+                        name
+                    ),
+                );
+            }
+        }
+
+        let name = self.define(map, pointer, hint);
+        let mut record = Map::new();
+        record.insert("type".to_string(), json!("record"));
+        self.name_definition(&mut record, &name, map);
+
+        self.depth += 1;
+        let mut fields = Vec::new();
+        for (property, schema) in properties {
+            let field_pointer = join(&join(pointer, "properties"), property);
+            let field = self.field(
+                property,
+                schema,
+                &field_pointer,
+                required.contains(&&**property),
+            );
+            match field {
+                Ok(field) => fields.push(field),
+                Err(error) => {
+                    self.depth -= 1;
+                    return Err(error);
+                }
+            }
+        }
+        self.depth -= 1;
+        record.insert("fields".to_string(), Value::Array(fields));
+        Ok(Value::Object(record))
+    }
+
+    fn field(
+        &mut self,
+        property: &str,
+        schema: &Value,
+        pointer: &str,
+        required: bool,
+    ) -> Result<Value, String> {
+        let name = avro_name(property);
+        if name != property {
+            self.report(
+                pointer,
+                "properties",
+                &format!(
+                    "\"{}\" becomes the field \"{}\"; Avro names hold only letters, \
+                     digits and underscores",
+                    property, name
+                ),
+            );
+        }
+        let mut avro = self.convert(schema, pointer, &type_name(property))?;
+        let mut default = schema.get("default").cloned();
+        if !required && matches!(default, None | Some(Value::Null)) {
+            avro = match avro {
+                Value::Array(mut union) => {
+                    if !union.contains(&json!("null")) {
+                        union.insert(0, json!("null"));
+                    }
+                    Value::Array(union)
+                }
+                avro => json!(["null", avro]),
+            };
+            default = Some(Value::Null);
+        }
+        if let Some(value) = &default {
+            let first = match &avro {
+                Value::Array(union) => union.first().unwrap_or(&Value::Null),
+                avro => avro,
+            };
+            if !value.is_null() && encoded_differently(first) {
+                self.report(
+                    &join(pointer, "default"),
+                    "default",
+                    "Avro writes defaults of bytes and of date and time types in its own \
+                     encoding rather than as JSON values; the default is dropped",
+                );
+                default = None;
+            }
+        }
+
+        let mut field = Map::new();
+        field.insert("name".to_string(), Value::String(name));
+        field.insert("type".to_string(), avro);
+        if let Some(description) = schema.get("description") {
+            field.insert("doc".to_string(), description.clone());
+        }
+        if let Some(default) = default {
+            field.insert("default".to_string(), default);
+        }
+        Ok(Value::Object(field))
+    }
+
+    fn avro_enum(
+        &mut self,
+        map: &Map<String, Value>,
+        pointer: &str,
+        hint: &str,
+        symbols: &[&str],
+    ) -> Value {
+        let name = self.define(map, pointer, hint);
+        let mut avro = Map::new();
+        avro.insert("type".to_string(), json!("enum"));
+        self.name_definition(&mut avro, &name, map);
+        avro.insert("symbols".to_string(), json!(symbols));
+        Value::Object(avro)
+    }
+
+    // Picks the name of a record or enum made from the schema at `pointer`,
+    // for references to it to use
+    fn define(&mut self, map: &Map<String, Value>, pointer: &str, hint: &str) -> String {
+        let base = match (&self.root_name, map.get("title").and_then(Value::as_str)) {
+            (Some(name), _) if pointer.is_empty() => name.clone(),
+            (_, Some(title)) => title.to_string(),
+            _ => hint.to_string(),
+        };
+        let base = type_name(&base);
+        let mut name = base.clone();
+        let mut suffix = 1;
+        while !self.names.insert(name.clone()) {
+            suffix += 1;
+            name = format!("{}{}", base, suffix);
+        }
+        self.named.insert(pointer.to_string(), name.clone());
+        name
+    }
+
+    // Names a record or enum, in the namespace unless it is nested in
+    // another named type, whose namespace it takes
+    fn name_definition(&self, avro: &mut Map<String, Value>, name: &str, map: &Map<String, Value>) {
+        avro.insert("name".to_string(), Value::String(name.to_string()));
+        if let (Some(namespace), 0) = (&self.namespace, self.depth) {
+            avro.insert("namespace".to_string(), Value::String(namespace.clone()));
+        }
+        if let Some(description) = map.get("description") {
+            avro.insert("doc".to_string(), description.clone());
+        }
+    }
+
+    // Reports the keywords of a schema that were neither used nor are
+    // annotations
+    fn dropped(&mut self, map: &Map<String, Value>, pointer: &str, handled: &[&str]) {
+        for keyword in map.keys() {
+            if !handled.contains(&keyword.as_str()) && !ANNOTATIONS.contains(&keyword.as_str()) {
+                self.report(
+                    &join(pointer, keyword),
+                    keyword,
+                    &format!("{} has no Avro equivalent and is dropped", keyword),
+                );
+            }
+        }
+    }
+
+    // Schemas reached through several references are converted once per
+    // reference, so the same issue is reported once
+    fn report(&mut self, path: &str, keyword: &str, message: &str) {
+        let issue = ConversionIssue {
+            path: path.to_string(),
+            keyword: keyword.to_string(),
+            message: message.to_string(),
+        };
+        if !self.issues.contains(&issue) {
+            self.issues.push(issue);
+        }
+    }
+}
+
+struct FromAvro {
+    defs: Map<String, Value>,
+    // References to the named types defined so far, by full name
+    references: HashMap<String, String>,
+    issues: Vec<ConversionIssue>,
+}
+
+impl FromAvro {
+    fn convert(
+        &mut self,
+        avro: &Value,
+        pointer: &str,
+        namespace: Option<&str>,
+    ) -> Result<Value, String> {
+        let map = match avro {
+            Value::String(name) => return self.named(name, pointer, namespace),
+            Value::Array(branches) => return self.union(branches, pointer, namespace),
+            Value::Object(map) => map,
+            _ => {
+                return Err(format!(
+                    "Avro schema at {} is not a name, an object or a union",
+                    at(pointer)
+                ))
+            }
+        };
+        match map.get("type") {
+            Some(Value::String(type_name)) => match type_name.as_str() {
+                "record" | "error" => self.record(map, pointer, namespace),
+                "enum" => self.avro_enum(map, pointer, namespace),
+                "fixed" => self.fixed(map, pointer, namespace),
+                "array" => {
+                    let items = self.required(map, "items", pointer)?;
+                    let items = self.convert(items, &join(pointer, "items"), namespace)?;
+                    Ok(json!({"type": "array", "items": items}))
+                }
+                "map" => {
+                    let values = self.required(map, "values", pointer)?;
+                    let values = self.convert(values, &join(pointer, "values"), namespace)?;
+                    Ok(json!({"type": "object", "additionalProperties": values}))
+                }
+                primitive if PRIMITIVES.contains(&primitive) => {
+                    match map.get("logicalType").and_then(Value::as_str) {
+                        Some(logical_type) => {
+                            Ok(self.logical(primitive, logical_type, map, pointer))
+                        }
+                        None => Ok(primitive_schema(primitive)),
+                    }
+                }
+                name => self.named(name, &join(pointer, "type"), namespace),
+            },
+            Some(nested) => self.convert(nested, &join(pointer, "type"), namespace),
+            None => Err(format!("Avro schema at {} has no type", at(pointer))),
+        }
+    }
+
+    // A primitive type, or a reference to a named type defined earlier
+    fn named(
+        &mut self,
+        name: &str,
+        pointer: &str,
+        namespace: Option<&str>,
+    ) -> Result<Value, String> {
+        if PRIMITIVES.contains(&name) {
+            return Ok(primitive_schema(name));
+        }
+        let full_name = match namespace {
+            Some(namespace) if !name.contains('.') => format!("{}.{}", namespace, name),
+            _ => name.to_string(),
+        };
+        match self
+            .references
+            .get(&full_name)
+            .or_else(|| self.references.get(name))
+        {
+            Some(reference) => Ok(json!({ "$ref": reference })),
+            None => Err(format!(
+                "Type \"{}\" at {} is not defined before it is used",
+                name,
+                at(pointer)
+            )),
+        }
+    }
+
+    fn union(
+        &mut self,
+        branches: &[Value],
+        pointer: &str,
+        namespace: Option<&str>,
+    ) -> Result<Value, String> {
+        let mut nullable = false;
+        let mut schemas = Vec::new();
+        for (index, branch) in branches.iter().enumerate() {
+            if branch == "null" || branch.get("type") == Some(&json!("null")) {
+                nullable = true;
+                continue;
+            }
+            let branch_pointer = format!("{}/{}", pointer, index);
+            schemas.push(self.convert(branch, &branch_pointer, namespace)?);
+        }
+        Ok(match (nullable, schemas.len()) {
+            (_, 0) => json!({"type": "null"}),
+            (false, 1) => schemas.remove(0),
+            (true, 1) => nullable_schema(schemas.remove(0)),
+            (nullable, _) => {
+                if nullable {
+                    schemas.insert(0, json!({"type": "null"}));
+                }
+                json!({ "anyOf": schemas })
+            }
+        })
+    }
+
+    fn record(
+        &mut self,
+        map: &Map<String, Value>,
+        pointer: &str,
+        namespace: Option<&str>,
+    ) -> Result<Value, String> {
+        let (name, full_name, namespace) = self.declare(map, pointer, namespace)?;
+        let Some(fields) = map.get("fields").and_then(Value::as_array) else {
+            return Err(format!(
+                "Record \"{}\" at {} has no fields",
+                full_name,
+                at(pointer)
+            ));
+        };
+
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        for (index, field) in fields.iter().enumerate() {
+            let field_pointer = format!("{}/fields/{}", pointer, index);
+            let Some(field) = field.as_object() else {
+                return Err(format!("Field at {} is not an object", at(&field_pointer)));
+            };
+            let Some(field_name) = field.get("name").and_then(Value::as_str) else {
+                return Err(format!("Field at {} has no name", at(&field_pointer)));
+            };
+            let field_type = self.required(field, "type", &field_pointer)?;
+            let schema = self.convert(
+                field_type,
+                &join(&field_pointer, "type"),
+                namespace.as_deref(),
+            )?;
+            let mut schema = match schema {
+                Value::Object(schema) => schema,
+                other => return Err(format!("Unexpected conversion result {}", other)),
+            };
+            if let Some(doc) = field.get("doc") {
+                schema.insert("description".to_string(), doc.clone());
+            }
+            match field.get("default") {
+                Some(_) if encoded_differently(first_branch(field_type)) => {
+                    self.report(
+                        &join(&field_pointer, "default"),
+                        "default",
+                        "Avro writes defaults of bytes, fixed and logical types in its own \
+                         encoding rather than as JSON values; the default is dropped",
+                    );
+                }
+                Some(default) => {
+                    schema.insert("default".to_string(), default.clone());
+                }
+                None => required.push(json!(field_name)),
+            }
+            if let Some(order) = field.get("order").filter(|order| *order != "ascending") {
+                self.report(
+                    &join(&field_pointer, "order"),
+                    "order",
+                    &format!(
+                        "Sort order {} has no JSON Schema equivalent and is dropped",
+                        order
+                    ),
+                );
+            }
+            self.aliases(field, &field_pointer);
+            properties.insert(field_name.to_string(), Value::Object(schema));
+        }
+
+        let mut object = Map::new();
+        object.insert("type".to_string(), json!("object"));
+        object.insert("title".to_string(), json!(name));
+        if let Some(doc) = map.get("doc") {
+            object.insert("description".to_string(), doc.clone());
+        }
+        object.insert("properties".to_string(), Value::Object(properties));
+        if !required.is_empty() {
+            object.insert("required".to_string(), Value::Array(required));
+        }
+        object.insert("additionalProperties".to_string(), Value::Bool(false));
+        self.aliases(map, pointer);
+        Ok(self.define(full_name, pointer, Value::Object(object)))
+    }
+
+    fn avro_enum(
+        &mut self,
+        map: &Map<String, Value>,
+        pointer: &str,
+        namespace: Option<&str>,
+    ) -> Result<Value, String> {
+        let (name, full_name, _) = self.declare(map, pointer, namespace)?;
+        let Some(symbols) = map.get("symbols").and_then(Value::as_array) else {
+            return Err(format!(
+                "Enum \"{}\" at {} has no symbols",
+                full_name,
+                at(pointer)
+            ));
+        };
+        let mut schema = json!({"type": "string", "title": name, "enum": symbols});
+        if let Some(doc) = map.get("doc") {
+            schema["description"] = doc.clone();
+        }
+        if map.contains_key("default") {
+            self.report(
+                &join(pointer, "default"),
+                "default",
+                "Enum defaults stand in for symbols unknown to a reader, \
+                 which JSON Schema has no equivalent for; the default is dropped",
+            );
+        }
+        self.aliases(map, pointer);
+        Ok(self.define(full_name, pointer, schema))
+    }
+
+    fn fixed(
+        &mut self,
+        map: &Map<String, Value>,
+        pointer: &str,
+        namespace: Option<&str>,
+    ) -> Result<Value, String> {
+        let (name, full_name, _) = self.declare(map, pointer, namespace)?;
+        let Some(size) = map.get("size").and_then(Value::as_u64) else {
+            return Err(format!(
+                "Fixed \"{}\" at {} has no size",
+                full_name,
+                at(pointer)
+            ));
+        };
+        let schema = match map.get("logicalType").and_then(Value::as_str) {
+            Some("decimal") => self.decimal(map, pointer),
+            logical_type => {
+                if let Some(logical_type) = logical_type {
+                    self.unknown_logical_type(logical_type, "fixed", pointer);
+                }
+                // Base64 text of `size` bytes is always this long
+                let length = size.div_ceil(3) * 4;
+                json!({
+                    "type": "string",
+                    "title": name,
+                    "contentEncoding": "base64",
+                    "minLength": length,
+                    "maxLength": length
+                })
+            }
+        };
+        self.aliases(map, pointer);
+        Ok(self.define(full_name, pointer, schema))
+    }
+
+    fn logical(
+        &mut self,
+        primitive: &str,
+        logical_type: &str,
+        map: &Map<String, Value>,
+        pointer: &str,
+    ) -> Value {
+        let format = match (primitive, logical_type) {
+            ("int", "date") => "date",
+            ("string", "uuid") => "uuid",
+            ("long", "timestamp-millis" | "timestamp-micros" | "timestamp-nanos") => "date-time",
+            ("long", "local-timestamp-millis" | "local-timestamp-micros")
+            | ("long", "local-timestamp-nanos") => {
+                self.report(
+                    &join(pointer, "logicalType"),
+                    "logicalType",
+                    &format!(
+                        "{} values have no time zone, which \"date-time\" strings need",
+                        logical_type
+                    ),
+                );
+                "date-time"
+            }
+            ("int", "time-millis") | ("long", "time-micros") => {
+                self.report(
+                    &join(pointer, "logicalType"),
+                    "logicalType",
+                    &format!(
+                        "{} values have no UTC offset, which \"time\" strings need",
+                        logical_type
+                    ),
+                );
+                "time"
+            }
+            ("bytes", "decimal") => return self.decimal(map, pointer),
+            _ => {
+                self.unknown_logical_type(logical_type, primitive, pointer);
+                return primitive_schema(primitive);
+            }
+        };
+        json!({"type": "string", "format": format})
+    }
+
+    fn decimal(&mut self, map: &Map<String, Value>, pointer: &str) -> Value {
+        let precision = map.get("precision").cloned().unwrap_or(Value::Null);
+        let scale = map.get("scale").cloned().unwrap_or(json!(0));
+        self.report(
+            &join(pointer, "logicalType"),
+            "logicalType",
+            &format!(
+                "decimal({}, {}) values become numbers, whose precision and scale are not kept",
+                precision, scale
+            ),
+        );
+        json!({"type": "number"})
+    }
+
+    // Unknown logical types are read as their underlying type, as Avro
+    // readers do
+    fn unknown_logical_type(&mut self, logical_type: &str, primitive: &str, pointer: &str) {
+        self.report(
+            &join(pointer, "logicalType"),
+            "logicalType",
+            &format!(
+                "Logical type \"{}\" on {} has no JSON Schema format; values are read as {}",
+                logical_type, primitive, primitive
+            ),
+        );
+    }
+
+    // The name, full name and namespace of a named type, which may not be
+    // defined twice
+    fn declare(
+        &mut self,
+        map: &Map<String, Value>,
+        pointer: &str,
+        namespace: Option<&str>,
+    ) -> Result<(String, String, Option<String>), String> {
+        let Some(name) = map.get("name").and_then(Value::as_str) else {
+            return Err(format!("Named type at {} has no name", at(pointer)));
+        };
+        let namespace = match name.rsplit_once('.') {
+            Some((namespace, _)) => Some(namespace.to_string()),
+            None => map
+                .get("namespace")
+                .and_then(Value::as_str)
+                .filter(|namespace| !namespace.is_empty())
+                .or(namespace)
+                .map(str::to_string),
+        };
+        let short_name = name.rsplit('.').next().unwrap_or(name).to_string();
+        let full_name = match &namespace {
+            Some(namespace) => format!("{}.{}", namespace, short_name),
+            None => short_name.clone(),
+        };
+        if self.references.contains_key(&full_name) {
+            return Err(format!(
+                "Type \"{}\" at {} is defined twice",
+                full_name,
+                at(pointer)
+            ));
+        }
+        // Registered before its fields are read, which may refer to it
+        let reference = match pointer {
+            "" => "#".to_string(),
+            _ => format!("#/$defs/{}", escape_pointer_segment(&full_name)),
+        };
+        self.references.insert(full_name.clone(), reference);
+        Ok((short_name, full_name, namespace))
+    }
+
+    // The schema of a named type at the root, or a reference to it under
+    // `$defs`
+    fn define(&mut self, full_name: String, pointer: &str, schema: Value) -> Value {
+        if pointer.is_empty() {
+            return schema;
+        }
+        let reference = self.references[&full_name].clone();
+        self.defs.insert(full_name, schema);
+        json!({ "$ref": reference })
+    }
+
+    fn aliases(&mut self, avro: &Map<String, Value>, pointer: &str) {
+        if avro.get("aliases").is_some() {
+            self.report(
+                &join(pointer, "aliases"),
+                "aliases",
+                "Aliases have no JSON Schema equivalent and are dropped",
+            );
+        }
+    }
+
+    fn required<'a>(
+        &self,
+        avro: &'a Map<String, Value>,
+        attribute: &str,
+        pointer: &str,
+    ) -> Result<&'a Value, String> {
+        avro.get(attribute)
+            .ok_or_else(|| format!("Avro schema at {} has no \"{}\"", at(pointer), attribute))
+    }
+
+    fn report(&mut self, path: &str, keyword: &str, message: &str) {
+        self.issues.push(ConversionIssue {
+            path: path.to_string(),
+            keyword: keyword.to_string(),
+            message: message.to_string(),
+        });
+    }
+}
+
+fn primitive_schema(primitive: &str) -> Value {
+    match primitive {
+        "null" => json!({"type": "null"}),
+        "boolean" => json!({"type": "boolean"}),
+        "int" => json!({"type": "integer", "minimum": i32::MIN, "maximum": i32::MAX}),
+        "long" => json!({"type": "integer"}),
+        "float" | "double" => json!({"type": "number"}),
+        "bytes" => json!({"type": "string", "contentEncoding": "base64"}),
+        _ => json!({"type": "string"}),
+    }
+}
+
+// Allows null as well, in the type list when there is one
+fn nullable_schema(mut schema: Value) -> Value {
+    match schema.get("type") {
+        Some(Value::String(type_name)) if schema.get("enum").is_none() => {
+            schema["type"] = json!([type_name.clone(), "null"]);
+            schema
+        }
+        _ => json!({"anyOf": [{"type": "null"}, schema]}),
+    }
+}
+
+// Whether Avro writes values of a type differently from JSON, as it does
+// bytes, fixed and the numbers behind dates and times
+fn encoded_differently(avro: &Value) -> bool {
+    match avro {
+        Value::String(name) => name == "bytes",
+        Value::Object(map) => {
+            matches!(
+                map.get("type").and_then(Value::as_str),
+                Some("bytes" | "fixed")
+            ) || map
+                .get("logicalType")
+                .is_some_and(|logical_type| logical_type != "uuid")
+        }
+        _ => false,
+    }
+}
+
+// The branch of a union that a field's default belongs to
+fn first_branch(avro: &Value) -> &Value {
+    match avro {
+        Value::Array(branches) => branches.first().unwrap_or(avro),
+        avro => avro,
+    }
+}
+
+// What tells the schemas of a union apart: their names, or their types
+fn branch_key(avro: &Value) -> String {
+    match avro {
+        Value::String(name) => name.clone(),
+        Value::Object(map) => map
+            .get("name")
+            .or_else(|| map.get("type"))
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn single(mut union: Vec<Value>) -> Value {
+    if union.len() == 1 {
+        union.remove(0)
+    } else {
+        Value::Array(union)
+    }
+}
+
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Bool(_) => "boolean",
+        Value::Number(number) if number.is_i64() || number.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+        Value::Null => "null",
+    }
+}
+
+// Avro names start with a letter or underscore, followed by letters, digits
+// and underscores
+fn is_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// "first-name" becomes "first_name"
+fn avro_name(name: &str) -> String {
+    let mut avro: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !avro.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        avro.insert(0, '_');
+    }
+    avro
+}
+
+// "order item" and "order_item" become "OrderItem"
+fn type_name(hint: &str) -> String {
+    let name: String = hint
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect();
+    avro_name(&name)
+}
+
+fn join(pointer: &str, segment: &str) -> String {
+    format!("{}/{}", pointer, escape_pointer_segment(segment))
+}
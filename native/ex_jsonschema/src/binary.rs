@@ -0,0 +1,187 @@
+use ciborium::value::Value as Cbor;
+use rmpv::Value as MessagePack;
+use serde_json::{Map, Number, Value};
+
+use crate::dereference::escape_pointer_segment;
+
+// CBOR tags of big integers, whose bytes hold the magnitude
+const BIGNUM_TAGS: &[u64] = &[2, 3];
+
+/// Decodes one MessagePack value into the JSON data model. Map keys must be
+/// strings, and binary data, extension types and non-finite floats have no
+/// JSON equivalent; such values are errors that say where they are.
+pub fn decode_msgpack(bytes: &[u8]) -> Result<Value, String> {
+    let mut reader = bytes;
+    let value = rmpv::decode::read_value(&mut reader).map_err(|error| error.to_string())?;
+    ensure_consumed(reader)?;
+    msgpack_value(value, "")
+}
+
+/// Decodes one CBOR data item into the JSON data model, under the same rules
+/// as `decode_msgpack`. Tags are dropped in favour of the values they tag,
+/// so an RFC 3339 datetime (tag 0) becomes its string; big integers and
+/// integers beyond 64 bits are errors.
+pub fn decode_cbor(bytes: &[u8]) -> Result<Value, String> {
+    let mut reader = bytes;
+    let value: Cbor = ciborium::de::from_reader(&mut reader).map_err(|error| error.to_string())?;
+    ensure_consumed(reader)?;
+    cbor_value(value, "")
+}
+
+fn msgpack_value(value: MessagePack, pointer: &str) -> Result<Value, String> {
+    match value {
+        MessagePack::Nil => Ok(Value::Null),
+        MessagePack::Boolean(boolean) => Ok(Value::Bool(boolean)),
+        // Every MessagePack integer fits one or the other
+        MessagePack::Integer(integer) => Ok(integer
+            .as_i64()
+            .map(Value::from)
+            .or_else(|| integer.as_u64().map(Value::from))
+            .unwrap_or(Value::Null)),
+        MessagePack::F32(float) => float_value(f64::from(float), pointer),
+        MessagePack::F64(float) => float_value(float, pointer),
+        MessagePack::String(string) => string
+            .into_str()
+            .map(Value::String)
+            .ok_or_else(|| format!("String at {} is not valid UTF-8", at(pointer))),
+        MessagePack::Binary(_) => Err(unrepresentable("Binary data", pointer)),
+        MessagePack::Ext(kind, _) => Err(unrepresentable(
+            &format!("Extension type {}", kind),
+            pointer,
+        )),
+        MessagePack::Array(items) => items
+            .into_iter()
+            .enumerate()
+            .map(|(index, item)| msgpack_value(item, &format!("{}/{}", pointer, index)))
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        MessagePack::Map(entries) => {
+            let mut object = Map::new();
+            for (key, value) in entries {
+                let key = match key {
+                    MessagePack::String(key) => key
+                        .into_str()
+                        .ok_or_else(|| format!("Map key at {} is not valid UTF-8", at(pointer)))?,
+                    MessagePack::Nil => return Err(non_string_key("null", pointer)),
+                    MessagePack::Boolean(_) => return Err(non_string_key("a boolean", pointer)),
+                    MessagePack::Integer(_) => return Err(non_string_key("an integer", pointer)),
+                    MessagePack::F32(_) | MessagePack::F64(_) => {
+                        return Err(non_string_key("a float", pointer))
+                    }
+                    MessagePack::Binary(_) => return Err(non_string_key("binary data", pointer)),
+                    MessagePack::Array(_) => return Err(non_string_key("an array", pointer)),
+                    MessagePack::Map(_) => return Err(non_string_key("a map", pointer)),
+                    MessagePack::Ext(..) => {
+                        return Err(non_string_key("an extension type", pointer))
+                    }
+                };
+                let member = member_pointer(&mut object, &key, pointer)?;
+                let value = msgpack_value(value, &member)?;
+                object.insert(key, value);
+            }
+            Ok(Value::Object(object))
+        }
+    }
+}
+
+fn cbor_value(value: Cbor, pointer: &str) -> Result<Value, String> {
+    match value {
+        Cbor::Null => Ok(Value::Null),
+        Cbor::Bool(boolean) => Ok(Value::Bool(boolean)),
+        Cbor::Integer(integer) => {
+            let integer = i128::from(integer);
+            i64::try_from(integer)
+                .map(Value::from)
+                .or_else(|_| u64::try_from(integer).map(Value::from))
+                .map_err(|_| unrepresentable(&format!("Integer {}", integer), pointer))
+        }
+        Cbor::Float(float) => float_value(float, pointer),
+        Cbor::Text(text) => Ok(Value::String(text)),
+        Cbor::Bytes(_) => Err(unrepresentable("Binary data", pointer)),
+        Cbor::Tag(tag, _) if BIGNUM_TAGS.contains(&tag) => {
+            Err(unrepresentable("Big integer", pointer))
+        }
+        Cbor::Tag(_, value) => cbor_value(*value, pointer),
+        Cbor::Array(items) => items
+            .into_iter()
+            .enumerate()
+            .map(|(index, item)| cbor_value(item, &format!("{}/{}", pointer, index)))
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        Cbor::Map(entries) => {
+            let mut object = Map::new();
+            for (key, value) in entries {
+                let key = match key {
+                    Cbor::Text(key) => key,
+                    Cbor::Null => return Err(non_string_key("null", pointer)),
+                    Cbor::Bool(_) => return Err(non_string_key("a boolean", pointer)),
+                    Cbor::Integer(_) => return Err(non_string_key("an integer", pointer)),
+                    Cbor::Float(_) => return Err(non_string_key("a float", pointer)),
+                    Cbor::Bytes(_) => return Err(non_string_key("binary data", pointer)),
+                    Cbor::Array(_) => return Err(non_string_key("an array", pointer)),
+                    Cbor::Map(_) => return Err(non_string_key("a map", pointer)),
+                    _ => return Err(non_string_key("a tagged value", pointer)),
+                };
+                let member = member_pointer(&mut object, &key, pointer)?;
+                let value = cbor_value(value, &member)?;
+                object.insert(key, value);
+            }
+            Ok(Value::Object(object))
+        }
+        _ => Err(unrepresentable("An unsupported CBOR value", pointer)),
+    }
+}
+
+/// The pointer of a new member of the map at `pointer`, whose keys must be
+/// distinct to become an object.
+pub fn member_pointer(
+    object: &mut Map<String, Value>,
+    key: &str,
+    pointer: &str,
+) -> Result<String, String> {
+    if object.contains_key(key) {
+        return Err(format!(
+            "Map at {} has the key {:?} twice",
+            at(pointer),
+            key
+        ));
+    }
+    Ok(format!("{}/{}", pointer, escape_pointer_segment(key)))
+}
+
+/// A float as a JSON number, which cannot be infinite or NaN.
+pub fn float_value(float: f64, pointer: &str) -> Result<Value, String> {
+    Number::from_f64(float)
+        .map(Value::Number)
+        .ok_or_else(|| unrepresentable(&format!("Float {}", float), pointer))
+}
+
+fn ensure_consumed(rest: &[u8]) -> Result<(), String> {
+    match rest.len() {
+        0 => Ok(()),
+        count => Err(format!("{} unexpected bytes after the value", count)),
+    }
+}
+
+/// Message for a value at `pointer` JSON has no equivalent for.
+pub fn unrepresentable(what: &str, pointer: &str) -> String {
+    format!("{} at {} cannot be represented in JSON", what, at(pointer))
+}
+
+/// Message for a map key at `pointer` that is not a string.
+pub fn non_string_key(kind: &str, pointer: &str) -> String {
+    format!(
+        "Map key at {} is {}; JSON object keys must be strings",
+        at(pointer),
+        kind
+    )
+}
+
+/// Where a value is, for messages.
+pub fn at(pointer: &str) -> String {
+    if pointer.is_empty() {
+        "the root".to_string()
+    } else {
+        format!("{:?}", pointer)
+    }
+}
@@ -0,0 +1,209 @@
+use referencing::Draft;
+use serde_json::{Map, Value};
+
+use crate::walk::{walk, Cursor, Finding, ValidatorFor};
+
+/// Decodes the strings in `instance` whose schema declares `contentEncoding`
+/// (`base64`, `base64url` or `quoted-printable`), parses those declaring a
+/// JSON `contentMediaType`, and validates the result against their
+/// `contentSchema`. `validator_for` returns the validator for the
+/// `contentSchema` at a location.
+///
+/// Findings are strings whose encoded content is invalid, or errors inside
+/// that content, whose instance paths continue from the string's path into
+/// the decoded document.
+pub fn check(
+    schema: &Value,
+    draft: Draft,
+    instance: &Value,
+    validator_for: &ValidatorFor,
+) -> Vec<Finding> {
+    let mut errors = Vec::new();
+    walk(
+        schema,
+        draft,
+        instance,
+        Some(validator_for),
+        &mut |map, value, cursor| {
+            if let Value::String(text) = value {
+                check_content(map, text, cursor, validator_for, &mut errors);
+            }
+        },
+    );
+    errors
+}
+
+fn check_content(
+    map: &Map<String, Value>,
+    text: &str,
+    cursor: &Cursor,
+    validator_for: &ValidatorFor,
+    errors: &mut Vec<Finding>,
+) {
+    let encoding = map.get("contentEncoding").and_then(Value::as_str);
+    let media_type = map.get("contentMediaType").and_then(Value::as_str);
+
+    let decoded = match encoding {
+        Some(encoding) => match decode(encoding, text) {
+            Some(Some(bytes)) => bytes,
+            Some(None) => {
+                errors.push(Finding {
+                    instance_path: cursor.path.clone(),
+                    schema_path: format!("{}/contentEncoding", cursor.schema_path),
+                    message: format!(
+                        "{} is not compliant with \"{}\" content encoding",
+                        Value::String(text.to_string()),
+                        encoding
+                    ),
+                    instance_value: Value::String(text.to_string()),
+                });
+                return;
+            }
+            // Unknown encodings are left alone
+            None => return,
+        },
+        None => text.as_bytes().to_vec(),
+    };
+    let Some(media_type) = media_type.filter(|media_type| is_json(media_type)) else {
+        return;
+    };
+
+    let content = std::str::from_utf8(&decoded)
+        .ok()
+        .and_then(|json| serde_json::from_str::<Value>(json).ok());
+    let Some(content) = content else {
+        errors.push(Finding {
+            instance_path: cursor.path.clone(),
+            schema_path: format!("{}/contentMediaType", cursor.schema_path),
+            message: format!(
+                "{} is not compliant with \"{}\" media type",
+                Value::String(text.to_string()),
+                media_type
+            ),
+            instance_value: Value::String(text.to_string()),
+        });
+        return;
+    };
+
+    if !map.contains_key("contentSchema") {
+        return;
+    }
+    let Some(location) = cursor
+        .location
+        .as_ref()
+        .and_then(|location| location.child(&["contentSchema".to_string()]))
+    else {
+        return;
+    };
+    let Ok(validator) = validator_for(&location) else {
+        return;
+    };
+    for error in validator.iter_errors(&content) {
+        // Fragment validators report paths from their wrapper's `$ref`
+        let schema_path = error.schema_path.to_string();
+        let schema_path = schema_path.strip_prefix("/$ref").unwrap_or(&schema_path);
+        errors.push(Finding {
+            instance_path: format!("{}{}", cursor.path, error.instance_path),
+            schema_path: format!("{}/contentSchema{}", cursor.schema_path, schema_path),
+            message: error.to_string(),
+            instance_value: error.instance.clone().into_owned(),
+        });
+    }
+}
+
+// The bytes `text` encodes, `Some(None)` when it is not valid in `encoding`,
+// or `None` for encodings this module does not know
+fn decode(encoding: &str, text: &str) -> Option<Option<Vec<u8>>> {
+    match encoding.to_ascii_lowercase().as_str() {
+        "base64" => Some(decode_base64(text, false)),
+        "base64url" => Some(decode_base64(text, true)),
+        "quoted-printable" => Some(decode_quoted_printable(text)),
+        _ => None,
+    }
+}
+
+// RFC 4648 base64, or its URL-safe alphabet. Line breaks are skipped, as
+// RFC 2045 allows, and padding is optional.
+fn decode_base64(text: &str, url_safe: bool) -> Option<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(text.len() * 3 / 4);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    let mut symbols = 0;
+    let mut padding = 0;
+
+    for byte in text.bytes() {
+        if byte.is_ascii_whitespace() {
+            continue;
+        }
+        if byte == b'=' {
+            padding += 1;
+            continue;
+        }
+        if padding > 0 {
+            return None;
+        }
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' if !url_safe => 62,
+            b'/' if !url_safe => 63,
+            b'-' if url_safe => 62,
+            b'_' if url_safe => 63,
+            _ => return None,
+        };
+        symbols += 1;
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+
+    // A lone trailing symbol carries no whole byte, and padding can only
+    // complete the last group of four
+    let complete = padding == 0 || (symbols + padding) % 4 == 0;
+    (bits < 6 && padding <= 2 && complete).then_some(decoded)
+}
+
+// RFC 2045 quoted-printable: `=XX` escapes and `=` soft line breaks
+fn decode_quoted_printable(text: &str) -> Option<Vec<u8>> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        if bytes[index] != b'=' {
+            decoded.push(bytes[index]);
+            index += 1;
+            continue;
+        }
+        let rest = &bytes[index + 1..];
+        if rest.starts_with(b"\r\n") {
+            index += 3;
+        } else if rest.starts_with(b"\n") {
+            index += 2;
+        } else {
+            let hex = rest
+                .get(..2)
+                .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))?;
+            let hex = std::str::from_utf8(hex).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            index += 3;
+        }
+    }
+    Some(decoded)
+}
+
+// `application/json` and `+json` types, with or without parameters
+fn is_json(media_type: &str) -> bool {
+    let essence = media_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    essence == "application/json" || essence.ends_with("+json")
+}
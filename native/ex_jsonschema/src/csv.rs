@@ -0,0 +1,230 @@
+use referencing::Draft;
+use serde_json::{Map, Value};
+
+use crate::dereference::{build_registry, follow_references};
+use crate::document::{coerce_scalar, schema_type};
+use crate::subschema::{parse_pointer, property_schemas};
+
+const BYTE_ORDER_MARK: &[u8] = b"\xEF\xBB\xBF";
+
+/// A CSV record and the line it starts on, counted from 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub line: usize,
+    pub fields: Vec<String>,
+}
+
+/// The complete records at the start of a chunk of CSV text, and how far
+/// into the chunk they go: the bytes after `consumed` belong to a record
+/// the next chunk completes.
+#[derive(Debug)]
+pub struct Chunk {
+    pub records: Vec<Record>,
+    pub consumed: usize,
+    pub next_line: usize,
+}
+
+/// Reads the records of RFC 4180 CSV text: fields split by `separator`,
+/// records by `\n` or `\r\n`, and fields in double quotes may hold either
+/// and `""` for a quote. `first_line` is the line the text starts on.
+///
+/// Unless `last` is set, a record without its line break may continue in
+/// the next chunk, so it is left unread. Blank lines are skipped, and a
+/// byte order mark at the start of line 1 is ignored.
+pub fn read(bytes: &[u8], separator: u8, first_line: usize, last: bool) -> Result<Chunk, String> {
+    let start = if first_line == 1 && bytes.starts_with(BYTE_ORDER_MARK) {
+        BYTE_ORDER_MARK.len()
+    } else {
+        0
+    };
+    let mut chunk = Chunk {
+        records: Vec::new(),
+        consumed: start,
+        next_line: first_line,
+    };
+    let mut position = start;
+    let mut line = first_line;
+
+    while position < bytes.len() {
+        let record_line = line;
+        let mut fields = Vec::new();
+        let mut field = Vec::new();
+        let mut complete = false;
+
+        while position < bytes.len() {
+            let byte = bytes[position];
+            if byte == b'"' && field.is_empty() {
+                let quote_line = line;
+                position += 1;
+                loop {
+                    match bytes.get(position) {
+                        None if last => {
+                            return Err(format!(
+                                "Line {}: quoted field is never closed",
+                                quote_line
+                            ))
+                        }
+                        None => return Ok(chunk),
+                        Some(b'"') if bytes.get(position + 1) == Some(&b'"') => {
+                            field.push(b'"');
+                            position += 2;
+                        }
+                        Some(b'"') => {
+                            position += 1;
+                            break;
+                        }
+                        Some(&byte) => {
+                            if byte == b'\n' {
+                                line += 1;
+                            }
+                            field.push(byte);
+                            position += 1;
+                        }
+                    }
+                }
+                match bytes.get(position) {
+                    Some(&byte) if byte == separator || byte == b'\n' || byte == b'\r' => {}
+                    None => {}
+                    Some(_) => {
+                        return Err(format!(
+                            "Line {}: unexpected text after a quoted field",
+                            line
+                        ))
+                    }
+                }
+                continue;
+            }
+            if byte == separator {
+                fields.push(text(field, record_line)?);
+                field = Vec::new();
+                position += 1;
+            } else if byte == b'\n' {
+                position += 1;
+                line += 1;
+                complete = true;
+                break;
+            } else if byte == b'\r' {
+                match bytes.get(position + 1) {
+                    Some(b'\n') => {
+                        position += 2;
+                        line += 1;
+                        complete = true;
+                        break;
+                    }
+                    None if !last => return Ok(chunk),
+                    _ => {
+                        field.push(byte);
+                        position += 1;
+                    }
+                }
+            } else {
+                field.push(byte);
+                position += 1;
+            }
+        }
+
+        if !complete && !last {
+            return Ok(chunk);
+        }
+        fields.push(text(field, record_line)?);
+        if !(fields.len() == 1 && fields[0].is_empty()) {
+            chunk.records.push(Record {
+                line: record_line,
+                fields,
+            });
+        }
+        chunk.consumed = position;
+        chunk.next_line = line;
+    }
+    Ok(chunk)
+}
+
+fn text(field: Vec<u8>, line: usize) -> Result<String, String> {
+    String::from_utf8(field).map_err(|_| format!("Line {} is not valid UTF-8", line))
+}
+
+/// The columns of a CSV file, named by its header, with the type the schema
+/// declares for the property each one fills.
+#[derive(Debug)]
+pub struct Columns {
+    pub names: Vec<String>,
+    types: Vec<Option<String>>,
+}
+
+impl Columns {
+    /// Matches `names` to the properties of `schema`, a `draft` schema,
+    /// following `$ref`s as the validator does. Columns the schema has no
+    /// property for take the type of a matching `patternProperties` entry or
+    /// of `additionalProperties`, if it has one.
+    pub fn new(names: Vec<String>, schema: &Value, draft: Draft) -> Result<Self, String> {
+        for (index, name) in names.iter().enumerate() {
+            if name.is_empty() {
+                return Err(format!("Column {} has no header", index + 1));
+            }
+            if names[..index].contains(name) {
+                return Err(format!("Header \"{}\" appears twice", name));
+            }
+        }
+
+        let (registry, base_uri) = build_registry(schema, draft)?;
+        let resolver = registry
+            .try_resolver(&base_uri)
+            .map_err(|e| e.to_string())?;
+        let (root, resolver, draft) = resolver
+            .lookup("#")
+            .map_err(|e| e.to_string())?
+            .into_inner();
+        let object =
+            follow_references(root, resolver, draft).and_then(|(object, resolver, draft)| {
+                let resolver = resolver
+                    .in_subresource(draft.create_resource_ref(object))
+                    .ok()?;
+                Some((object.as_object()?, resolver, draft))
+            });
+
+        let types = names
+            .iter()
+            .map(|name| {
+                let (object, resolver, draft) = object.as_ref()?;
+                property_schemas(object, name)
+                    .into_iter()
+                    .find_map(|(_, property)| {
+                        let (property, _, _) =
+                            follow_references(property, resolver.clone(), *draft)?;
+                        schema_type(property)
+                    })
+                    .map(str::to_string)
+            })
+            .collect();
+        Ok(Columns { names, types })
+    }
+
+    /// The object a record stands for: each field under its column's name,
+    /// converted to the column's type when it converts. Empty fields are
+    /// left out, for `required` to report, and so are fields past the last
+    /// column.
+    pub fn row(&self, fields: &[String]) -> Value {
+        let mut row = Map::new();
+        for ((name, schema_type), field) in self.names.iter().zip(&self.types).zip(fields) {
+            if !field.is_empty() {
+                row.insert(name.clone(), coerce_scalar(field, schema_type.as_deref()));
+            }
+        }
+        Value::Object(row)
+    }
+}
+
+/// The column an error in a row is about: the property its instance path
+/// starts with, or the property a `required` error found missing. Errors
+/// about the row as a whole have none.
+pub fn column(instance_path: &str, params: &Value) -> Option<String> {
+    parse_pointer(instance_path)
+        .ok()
+        .and_then(|segments| segments.into_iter().next())
+        .or_else(|| {
+            params
+                .get("property")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        })
+}
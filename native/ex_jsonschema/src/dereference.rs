@@ -0,0 +1,237 @@
+use referencing::{Draft, Registry, Resolver};
+use serde_json::{Map, Value};
+
+use crate::walk::{ignores_ref_siblings, join_pointer, subschemas_mut};
+
+// Default base URI used by the jsonschema crate when a schema has no `$id`
+const DEFAULT_ROOT_URI: &str = "json-schema:///";
+
+/// How many `$ref`s in a row to follow before giving up, so that reference
+/// cycles end
+pub const MAX_REFERENCE_DEPTH: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CyclePolicy {
+    Error,
+    Keep,
+}
+
+#[derive(Debug)]
+pub enum DereferenceError {
+    Registry(String),
+    Unresolvable { reference: String, details: String },
+    Cycle { reference: String, path: String },
+}
+
+impl DereferenceError {
+    pub fn message(&self) -> String {
+        match self {
+            DereferenceError::Registry(_) => "Failed to build schema registry".to_string(),
+            DereferenceError::Unresolvable { reference, .. } => {
+                format!("Unable to resolve reference '{}'", reference)
+            }
+            DereferenceError::Cycle { reference, .. } => {
+                format!("Cyclic reference '{}'", reference)
+            }
+        }
+    }
+
+    pub fn details(&self) -> String {
+        match self {
+            DereferenceError::Registry(details) => details.clone(),
+            DereferenceError::Unresolvable { details, .. } => details.clone(),
+            DereferenceError::Cycle { path, .. } => {
+                format!(
+                    "Reference at '{}' points back to one of its ancestors",
+                    path
+                )
+            }
+        }
+    }
+}
+
+/// Builds a registry for `schema` the same way the validator does: the root
+/// resource is registered under its `$id` (or the default root URI) and any
+/// external references are retrieved with the default retriever.
+pub fn build_registry(schema: &Value, draft: Draft) -> Result<(Registry, String), String> {
+    let resource = draft.create_resource(schema.clone());
+    let base_uri = draft
+        .create_resource_ref(schema)
+        .id()
+        .map(|id| id.trim_end_matches('#').to_string())
+        .unwrap_or_else(|| DEFAULT_ROOT_URI.to_string());
+
+    let registry = Registry::options()
+        .draft(draft)
+        .build([(base_uri.as_str(), resource)])
+        .map_err(|e| e.to_string())?;
+
+    Ok((registry, base_uri))
+}
+
+/// Follows `$ref`s from `node` until it reaches a schema without one, with
+/// the resolver and draft in effect there. `None` when a reference does not
+/// resolve or the chain is longer than `MAX_REFERENCE_DEPTH`.
+pub fn follow_references<'r>(
+    mut node: &'r Value,
+    mut resolver: Resolver<'r>,
+    mut draft: Draft,
+) -> Option<(&'r Value, Resolver<'r>, Draft)> {
+    for _ in 0..=MAX_REFERENCE_DEPTH {
+        let Some(reference) = node.get("$ref").and_then(Value::as_str) else {
+            return Some((node, resolver, draft));
+        };
+        resolver = resolver
+            .in_subresource(draft.create_resource_ref(node))
+            .ok()?;
+        (node, resolver, draft) = resolver.lookup(reference).ok()?.into_inner();
+    }
+    None
+}
+
+/// Returns a copy of `schema` with every `$ref` replaced by the schema it
+/// points to. References that loop back to an ancestor are either reported
+/// or left in place, depending on `policy`.
+pub fn dereference(
+    schema: &Value,
+    draft: Draft,
+    policy: CyclePolicy,
+) -> Result<Value, DereferenceError> {
+    let (registry, base_uri) = build_registry(schema, draft).map_err(DereferenceError::Registry)?;
+    let resolver = registry
+        .try_resolver(&base_uri)
+        .map_err(|e| DereferenceError::Registry(e.to_string()))?;
+
+    // Walk the registry's copy of the root so that `"$ref": "#"` is seen as a cycle
+    let (root, root_resolver, root_draft) = resolver
+        .lookup("#")
+        .map_err(|e| DereferenceError::Registry(e.to_string()))?
+        .into_inner();
+
+    let mut walker = Walker {
+        policy,
+        ancestors: vec![root as *const Value as usize],
+        kept_cycles: 0,
+    };
+    let mut result = walker.walk(root, &root_resolver, root_draft, "")?;
+
+    // Definitions are only needed while references remain in the output
+    if walker.kept_cycles == 0 {
+        if let Value::Object(map) = &mut result {
+            map.remove("$defs");
+            map.remove("definitions");
+        }
+    }
+
+    Ok(result)
+}
+
+struct Walker {
+    policy: CyclePolicy,
+    // Addresses of the resolved targets currently being inlined
+    ancestors: Vec<usize>,
+    kept_cycles: usize,
+}
+
+impl Walker {
+    fn walk(
+        &mut self,
+        value: &Value,
+        resolver: &Resolver,
+        draft: Draft,
+        path: &str,
+    ) -> Result<Value, DereferenceError> {
+        match value {
+            Value::Object(map) => {
+                let resolver = resolver
+                    .in_subresource(draft.create_resource_ref(value))
+                    .map_err(|e| DereferenceError::Registry(e.to_string()))?;
+
+                match map.get("$ref") {
+                    Some(Value::String(reference)) => {
+                        self.walk_ref(map, reference, &resolver, draft, path)
+                    }
+                    _ => self.walk_object(map, &resolver, draft, path),
+                }
+            }
+            other => Ok(other.clone()),
+        }
+    }
+
+    fn walk_object(
+        &mut self,
+        map: &Map<String, Value>,
+        resolver: &Resolver,
+        draft: Draft,
+        path: &str,
+    ) -> Result<Value, DereferenceError> {
+        let mut result = map.clone();
+        for (segments, subschema) in subschemas_mut(&mut result) {
+            let child_path = join_pointer(path, &segments);
+            *subschema = self.walk(subschema, resolver, draft, &child_path)?;
+        }
+        Ok(Value::Object(result))
+    }
+
+    fn walk_ref(
+        &mut self,
+        map: &Map<String, Value>,
+        reference: &str,
+        resolver: &Resolver,
+        draft: Draft,
+        path: &str,
+    ) -> Result<Value, DereferenceError> {
+        let resolved = resolver
+            .lookup(reference)
+            .map_err(|e| DereferenceError::Unresolvable {
+                reference: reference.to_string(),
+                details: e.to_string(),
+            })?;
+        let (contents, target_resolver, target_draft) = resolved.into_inner();
+        let target = contents as *const Value as usize;
+
+        if self.ancestors.contains(&target) {
+            return match self.policy {
+                CyclePolicy::Error => Err(DereferenceError::Cycle {
+                    reference: reference.to_string(),
+                    path: path.to_string(),
+                }),
+                CyclePolicy::Keep => {
+                    self.kept_cycles += 1;
+                    Ok(Value::Object(map.clone()))
+                }
+            };
+        }
+
+        self.ancestors.push(target);
+        let inlined = self.walk(contents, &target_resolver, target_draft, path);
+        self.ancestors.pop();
+        let inlined = inlined?;
+
+        let siblings: Map<String, Value> = map
+            .iter()
+            .filter(|(key, _)| key.as_str() != "$ref")
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        if siblings.is_empty() || ignores_ref_siblings(draft) {
+            return Ok(inlined);
+        }
+
+        // Newer drafts evaluate siblings alongside the reference, which `allOf` preserves
+        let mut result = match self.walk_object(&siblings, resolver, draft, path)? {
+            Value::Object(result) => result,
+            _ => Map::new(),
+        };
+        match result.get_mut("allOf") {
+            Some(Value::Array(all_of)) => all_of.push(inlined),
+            _ => {
+                result.insert("allOf".to_string(), Value::Array(vec![inlined]));
+            }
+        }
+        Ok(Value::Object(result))
+    }
+}
+
+pub fn escape_pointer_segment(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
@@ -0,0 +1,75 @@
+use std::collections::HashSet;
+
+use referencing::Draft;
+use serde_json::Value;
+
+use crate::walk::{walk, Finding, ValidatorFor};
+
+/// Which way an instance travels. A request must not carry `readOnly`
+/// values, and a response must not carry `writeOnly` values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Request,
+    Response,
+}
+
+impl Direction {
+    // The annotation that forbids a value in this direction
+    fn forbidding_keyword(self) -> &'static str {
+        match self {
+            Direction::Request => "readOnly",
+            Direction::Response => "writeOnly",
+        }
+    }
+}
+
+/// Values in `instance` whose schema marks them `readOnly` (for requests) or
+/// `writeOnly` (for responses), one finding per value. Findings end their
+/// schema path with that keyword, which validators never fail on, so they
+/// stand apart from ordinary errors. `validator_for` picks the `anyOf`,
+/// `oneOf` and `if` branches that apply, as in `walk`.
+pub fn check(
+    schema: &Value,
+    draft: Draft,
+    instance: &Value,
+    direction: Direction,
+    validator_for: &ValidatorFor,
+) -> Vec<Finding> {
+    let keyword = direction.forbidding_keyword();
+    let mut reported = HashSet::new();
+    let mut errors = Vec::new();
+
+    walk(
+        schema,
+        draft,
+        instance,
+        Some(validator_for),
+        &mut |map, value, cursor| {
+            if map.get(keyword) != Some(&Value::Bool(true)) {
+                return;
+            }
+            if !reported.insert(cursor.path.clone()) {
+                return;
+            }
+            let (access, messages) = match direction {
+                Direction::Request => ("read-only", "requests"),
+                Direction::Response => ("write-only", "responses"),
+            };
+            let subject = if cursor.path.is_empty() {
+                "The instance".to_string()
+            } else {
+                format!("'{}'", cursor.path)
+            };
+            errors.push(Finding {
+                instance_path: cursor.path.clone(),
+                schema_path: format!("{}/{}", cursor.schema_path, keyword),
+                message: format!(
+                    "{} is {} and must not appear in {}",
+                    subject, access, messages
+                ),
+                instance_value: value.clone(),
+            });
+        },
+    );
+    errors
+}
@@ -0,0 +1,372 @@
+use referencing::{Draft, Resolver};
+use serde_json::{json, Map, Value};
+
+use crate::dereference::{build_registry, escape_pointer_segment};
+use crate::ranking::Rankable;
+use crate::subschema::keyword_target;
+use crate::walk::{subschemas_mut, Finding};
+
+// Marks the wrapper that guards a `oneOf` branch with its tags, naming the
+// tag property
+const GUARD_KEYWORD: &str = "x-ex-jsonschema-discriminator";
+
+/// A converted validation error whose `oneOf` branches can be collapsed.
+pub trait Resolvable: Rankable {
+    fn from_finding(finding: Finding) -> Self;
+    fn branches_mut(&mut self) -> &mut Vec<Vec<Self>>;
+    /// Forgets the branch the error was listed under
+    fn detach(&mut self);
+    /// Applies `f` to every schema path in the error and its branches
+    fn map_schema_paths(&mut self, f: &mut dyn FnMut(&mut String));
+}
+
+/// Rewrites each `oneOf` whose branches a tag property tells apart so that
+/// every branch checks the tag before anything else, and only the tagged
+/// branch is evaluated in full. The tags come from an OpenAPI
+/// `discriminator` (`propertyName`, `mapping`, and otherwise the names of the
+/// `$ref`ed schemas), or when `infer` is set from a property every branch
+/// gives a distinct `const`. Rewriting a rewritten schema changes nothing.
+pub fn guard(schema: Value, draft: Draft, infer: bool) -> Value {
+    let Ok((registry, base_uri)) = build_registry(&schema, draft) else {
+        return schema;
+    };
+    let Ok(resolver) = registry.try_resolver(&base_uri) else {
+        return schema;
+    };
+
+    let mut guarded = schema.clone();
+    guard_node(&mut guarded, &resolver, infer);
+    guarded
+}
+
+/// Replaces every failed guarded `oneOf` in `errors` with what explains it:
+/// the errors of the branch the instance's tag selects, or a single error
+/// when the tag is missing or unknown. Schema paths are given back the shape
+/// of the original schema.
+pub fn resolve<T: Resolvable>(
+    errors: Vec<T>,
+    schema: &Value,
+    draft: Draft,
+    instance: &Value,
+) -> Vec<T> {
+    let mut resolved = Vec::with_capacity(errors.len());
+    for mut error in errors {
+        // Nested `oneOf`s are found by their paths in the guarded schema, so
+        // they are resolved before this one's guards leave their paths
+        let branches = std::mem::take(error.branches_mut());
+        *error.branches_mut() = branches
+            .into_iter()
+            .map(|branch| resolve(branch, schema, draft, instance))
+            .collect();
+
+        let guarded = error
+            .schema_path()
+            .strip_suffix("/oneOf")
+            .filter(|_| !error.branches().is_empty())
+            .and_then(|node_path| keyword_target(schema, draft, node_path))
+            .and_then(|node| Tags::of_guarded(&node));
+        let Some(tags) = guarded else {
+            resolved.push(error);
+            continue;
+        };
+
+        let one_of_path = error.schema_path().to_string();
+        let value = instance
+            .pointer(error.instance_path())
+            .unwrap_or(&Value::Null);
+        match tags.select(value) {
+            Selection::Branch(index)
+                if error
+                    .branches()
+                    .get(index)
+                    .is_some_and(|branch| !branch.is_empty()) =>
+            {
+                let mut branch = std::mem::take(&mut error.branches_mut()[index]);
+                for error in &mut branch {
+                    error.detach();
+                    unwrap_branch(error, &one_of_path, index);
+                }
+                resolved.extend(branch);
+            }
+            // Other branches matched too, which only untagged schemas allow
+            Selection::Branch(_) | Selection::NotAnObject => {
+                for (index, branch) in error.branches_mut().iter_mut().enumerate() {
+                    for error in branch {
+                        unwrap_branch(error, &one_of_path, index);
+                    }
+                }
+                resolved.push(error);
+            }
+            Selection::Missing => resolved.push(T::from_finding(Finding {
+                instance_path: error.instance_path().to_string(),
+                schema_path: tags.schema_path(&one_of_path),
+                message: format!("Missing discriminator property \"{}\"", tags.property),
+                instance_value: value.clone(),
+            })),
+            Selection::Unknown(tag) => resolved.push(T::from_finding(Finding {
+                instance_path: format!(
+                    "{}/{}",
+                    error.instance_path(),
+                    escape_pointer_segment(&tags.property)
+                ),
+                schema_path: tags.schema_path(&one_of_path),
+                message: format!(
+                    "{} is not a known \"{}\"; expected one of {}",
+                    tag,
+                    tags.property,
+                    tags.known()
+                ),
+                instance_value: tag,
+            })),
+        }
+    }
+    resolved
+}
+
+// Tag values of each branch of a `oneOf`
+struct Tags {
+    property: String,
+    branches: Vec<Vec<String>>,
+    // Whether an OpenAPI `discriminator` named the property
+    declared: bool,
+}
+
+enum Selection {
+    Branch(usize),
+    Missing,
+    Unknown(Value),
+    NotAnObject,
+}
+
+impl Tags {
+    // Reads the tags back from the guards `guard` wrapped a `oneOf` in
+    fn of_guarded(node: &Value) -> Option<Tags> {
+        let branches = node.get("oneOf")?.as_array()?;
+        let property = branches.first()?.get(GUARD_KEYWORD)?.as_str()?.to_string();
+        let branches = branches
+            .iter()
+            .map(|branch| {
+                let tags = branch
+                    .pointer("/allOf/0/properties")?
+                    .get(&property)?
+                    .get("enum")?
+                    .as_array()?;
+                tags.iter()
+                    .map(|tag| tag.as_str().map(str::to_string))
+                    .collect::<Option<Vec<_>>>()
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Tags {
+            property,
+            branches,
+            declared: node.get("discriminator").is_some(),
+        })
+    }
+
+    fn select(&self, value: &Value) -> Selection {
+        let Value::Object(object) = value else {
+            return Selection::NotAnObject;
+        };
+        let Some(tag) = object.get(&self.property) else {
+            return Selection::Missing;
+        };
+        let index = tag.as_str().and_then(|tag| {
+            self.branches
+                .iter()
+                .position(|tags| tags.iter().any(|known| known == tag))
+        });
+        match index {
+            Some(index) => Selection::Branch(index),
+            None => Selection::Unknown(tag.clone()),
+        }
+    }
+
+    // Tag errors point at the `discriminator` when there is one
+    fn schema_path(&self, one_of_path: &str) -> String {
+        match one_of_path.strip_suffix("/oneOf") {
+            Some(node_path) if self.declared => format!("{}/discriminator", node_path),
+            _ => one_of_path.to_string(),
+        }
+    }
+
+    fn known(&self) -> String {
+        let mut known: Vec<String> = self
+            .branches
+            .iter()
+            .flatten()
+            .map(|tag| Value::String(tag.clone()).to_string())
+            .collect();
+        known.sort();
+        known.join(", ")
+    }
+}
+
+// Drops the guard wrapper of branch `index` from the schema paths of an
+// error listed under it
+fn unwrap_branch<T: Resolvable>(error: &mut T, one_of_path: &str, index: usize) {
+    let wrapped = format!("{}/{}/allOf/1", one_of_path, index);
+    error.map_schema_paths(&mut |path| {
+        if let Some(rest) = path.strip_prefix(&wrapped) {
+            *path = format!("{}/{}{}", one_of_path, index, rest);
+        }
+    });
+}
+
+fn guard_node(node: &mut Value, resolver: &Resolver, infer: bool) {
+    let Value::Object(map) = node else {
+        return;
+    };
+    if let Some(tags) = branch_tags(map, resolver, infer) {
+        wrap_branches(map, tags);
+    }
+    for (_, subschema) in subschemas_mut(map) {
+        guard_node(subschema, resolver, infer);
+    }
+}
+
+// The tag property and each branch's tags, when every branch has one
+fn branch_tags(
+    map: &Map<String, Value>,
+    resolver: &Resolver,
+    infer: bool,
+) -> Option<(String, Vec<Vec<String>>)> {
+    let branches = map.get("oneOf")?.as_array()?;
+    if branches.is_empty()
+        || branches
+            .iter()
+            .any(|branch| branch.get(GUARD_KEYWORD).is_some())
+    {
+        return None;
+    }
+
+    let declared = map
+        .get("discriminator")
+        .and_then(|discriminator| discriminator.get("propertyName"))
+        .and_then(Value::as_str);
+    let (property, tags): (String, Vec<Vec<String>>) = match declared {
+        Some(property) => {
+            let mapping = map
+                .get("discriminator")
+                .and_then(|discriminator| discriminator.get("mapping"))
+                .and_then(Value::as_object);
+            let tags = branches
+                .iter()
+                .map(|branch| declared_tags(branch, property, mapping, resolver))
+                .collect();
+            (property.to_string(), tags)
+        }
+        None if infer => {
+            let resolved: Vec<&Value> = branches
+                .iter()
+                .map(|branch| resolve_branch(branch, resolver))
+                .collect();
+            let property = inferred_property(&resolved)?;
+            let tags = resolved
+                .iter()
+                .map(|branch| const_tag(branch, &property).into_iter().collect())
+                .collect();
+            (property, tags)
+        }
+        None => return None,
+    };
+
+    tags.iter()
+        .all(|tags| !tags.is_empty())
+        .then_some((property, tags))
+}
+
+// OpenAPI tags of a branch: `mapping` entries naming its `$ref`, else the
+// name of the schema it refers to, else the `const` it gives the property
+fn declared_tags(
+    branch: &Value,
+    property: &str,
+    mapping: Option<&Map<String, Value>>,
+    resolver: &Resolver,
+) -> Vec<String> {
+    let reference = branch.get("$ref").and_then(Value::as_str);
+    if let Some(reference) = reference {
+        let name = schema_name(reference);
+        let mapped: Vec<String> = mapping
+            .into_iter()
+            .flatten()
+            .filter(|(_, target)| {
+                target
+                    .as_str()
+                    .is_some_and(|target| target == reference || target == name)
+            })
+            .map(|(tag, _)| tag.clone())
+            .collect();
+        if !mapped.is_empty() {
+            return mapped;
+        }
+        return vec![name.to_string()];
+    }
+    const_tag(resolve_branch(branch, resolver), property)
+        .into_iter()
+        .collect()
+}
+
+// The first property, in name order, every branch gives a different `const`
+fn inferred_property(branches: &[&Value]) -> Option<String> {
+    let candidates = branches.first()?.get("properties")?.as_object()?;
+    candidates
+        .keys()
+        .find(|property| {
+            let tags: Option<Vec<String>> = branches
+                .iter()
+                .map(|branch| const_tag(branch, property))
+                .collect();
+            tags.is_some_and(|tags| {
+                let mut distinct = tags.clone();
+                distinct.sort();
+                distinct.dedup();
+                distinct.len() == tags.len()
+            })
+        })
+        .cloned()
+}
+
+// The string a schema requires `property` to be, through `const` or a
+// single-valued `enum`
+fn const_tag(schema: &Value, property: &str) -> Option<String> {
+    let property = schema.get("properties")?.get(property)?;
+    let tag = match property.get("const") {
+        Some(tag) => tag,
+        None => match property.get("enum")?.as_array()?.as_slice() {
+            [tag] => tag,
+            _ => return None,
+        },
+    };
+    tag.as_str().map(str::to_string)
+}
+
+// The schema a branch refers to, or the branch itself
+fn resolve_branch<'a>(branch: &'a Value, resolver: &'a Resolver) -> &'a Value {
+    branch
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|reference| resolver.lookup(reference).ok())
+        .map_or(branch, |resolved| resolved.contents())
+}
+
+// `Card` for `#/components/schemas/Card`
+fn schema_name(reference: &str) -> &str {
+    reference.rsplit('/').next().unwrap_or(reference)
+}
+
+fn wrap_branches(map: &mut Map<String, Value>, (property, tags): (String, Vec<Vec<String>>)) {
+    let Some(Value::Array(branches)) = map.get_mut("oneOf") else {
+        return;
+    };
+    for (branch, tags) in branches.iter_mut().zip(tags) {
+        let original = std::mem::take(branch);
+        *branch = json!({
+            GUARD_KEYWORD: property,
+            "allOf": [
+                {"properties": {property.as_str(): {"enum": tags}}, "required": [property]},
+                original,
+            ],
+        });
+    }
+}
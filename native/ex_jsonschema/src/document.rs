@@ -0,0 +1,404 @@
+use serde_json::{Map, Number, Value};
+
+use crate::dereference::{escape_pointer_segment, MAX_REFERENCE_DEPTH};
+use crate::openapi::Dialect;
+
+// HTTP methods an OpenAPI path item may describe
+const METHODS: &[&str] = &[
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// The operations of an OpenAPI 3.0 or 3.1 document, with the locations of
+/// the schemas that validate their parameters and bodies.
+#[derive(Debug, Clone)]
+pub struct Document {
+    pub dialect: Dialect,
+    /// The whole document, which schemas refer into
+    pub document: Value,
+    pub operations: Vec<Operation>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Operation {
+    /// Upper case, as in `POST`
+    pub method: String,
+    /// The path template, as in `/users/{id}`
+    pub path: String,
+    pub operation_id: Option<String>,
+    /// Pointer to the Operation Object in the document
+    pub pointer: String,
+    pub parameters: Vec<Parameter>,
+    pub body: Option<Body>,
+    pub responses: Vec<Response>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParameterLocation {
+    Path,
+    Query,
+    Header,
+}
+
+impl ParameterLocation {
+    /// The first segment of the instance paths of errors in parameters
+    pub fn name(self) -> &'static str {
+        match self {
+            ParameterLocation::Path => "path",
+            ParameterLocation::Query => "query",
+            ParameterLocation::Header => "headers",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Parameter {
+    /// Lower case for headers, which are matched case-insensitively
+    pub name: String,
+    pub location: ParameterLocation,
+    pub required: bool,
+    /// Pointer to the Parameter Object, after `$ref`s
+    pub pointer: String,
+    pub schema: Option<Value>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Body {
+    pub required: bool,
+    /// Pointer to the Request Body Object, after `$ref`s
+    pub pointer: String,
+    /// The schema of the JSON media type and its pointer, if there is one
+    pub schema: Option<(Value, String)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Response {
+    /// A status code, a range such as `2XX`, or `default`
+    pub status: String,
+    /// The schema of the JSON media type and its pointer, if there is one
+    pub schema: Option<(Value, String)>,
+}
+
+impl Document {
+    /// Reads the operations out of an OpenAPI document. Parameters,
+    /// request bodies and responses may be `$ref`s into the document.
+    pub fn parse(document: &Value) -> Result<Document, String> {
+        let version = document
+            .get("openapi")
+            .and_then(Value::as_str)
+            .ok_or_else(|| "Not an OpenAPI 3.x document: no openapi version".to_string())?;
+        let dialect = if version.starts_with("3.0") {
+            Dialect::OpenApi30
+        } else if version.starts_with("3.1") {
+            Dialect::OpenApi31
+        } else {
+            return Err(format!("Unsupported OpenAPI version {}", version));
+        };
+
+        let mut operations = Vec::new();
+        let paths = document.get("paths").and_then(Value::as_object);
+        for (path, item) in paths.into_iter().flatten() {
+            let item_pointer = format!("/paths/{}", escape_pointer_segment(path));
+            let (item, item_pointer) = follow(document, item, item_pointer)?;
+            let shared = parameters(document, item, &item_pointer)?;
+
+            for method in METHODS {
+                let Some(operation) = item.get(*method) else {
+                    continue;
+                };
+                let pointer = format!("{}/{}", item_pointer, method);
+                let mut parameters = shared.clone();
+                // Operation parameters override path item parameters
+                for parameter in self::parameters(document, operation, &pointer)? {
+                    parameters.retain(|shared: &Parameter| {
+                        shared.name != parameter.name || shared.location != parameter.location
+                    });
+                    parameters.push(parameter);
+                }
+
+                operations.push(Operation {
+                    method: method.to_uppercase(),
+                    path: path.clone(),
+                    operation_id: operation
+                        .get("operationId")
+                        .and_then(Value::as_str)
+                        .map(str::to_string),
+                    parameters,
+                    body: body(document, operation, &pointer)?,
+                    responses: responses(document, operation, &pointer)?,
+                    pointer,
+                });
+            }
+        }
+
+        Ok(Document {
+            dialect,
+            document: document.clone(),
+            operations,
+        })
+    }
+
+    /// The operation for `method` whose template matches `path`, with the
+    /// path parameters read from it. `path` may be the template itself.
+    /// Templates without parameters win over templated ones, as in OpenAPI.
+    pub fn find(&self, method: &str, path: &str) -> Option<(&Operation, Vec<(String, String)>)> {
+        self.operations
+            .iter()
+            .filter(|operation| operation.method.eq_ignore_ascii_case(method))
+            .filter_map(|operation| {
+                if operation.path == path {
+                    return Some((operation, Vec::new()));
+                }
+                match_template(&operation.path, path).map(|parameters| (operation, parameters))
+            })
+            .min_by_key(|(_, parameters)| parameters.len())
+    }
+
+    pub fn find_by_id(&self, operation_id: &str) -> Option<&Operation> {
+        self.operations
+            .iter()
+            .find(|operation| operation.operation_id.as_deref() == Some(operation_id))
+    }
+
+    /// A schema standalone validators can be built from: `schema` with the
+    /// document's top-level members, such as `components`, next to it, so
+    /// that references into the document resolve as they do in it.
+    pub fn schema_root(&self, schema: &Value) -> Value {
+        let mut root = match schema {
+            Value::Object(map) => map.clone(),
+            other => {
+                let mut map = Map::new();
+                map.insert("allOf".to_string(), Value::Array(vec![other.clone()]));
+                map
+            }
+        };
+        for (name, member) in self.document.as_object().into_iter().flatten() {
+            if !root.contains_key(name) {
+                root.insert(name.clone(), member.clone());
+            }
+        }
+        Value::Object(root)
+    }
+
+    /// Converts the string values of a parameter to what its schema expects:
+    /// numbers, booleans, or for arrays each comma-separated or repeated
+    /// value. Values that do not convert stay strings for the schema to
+    /// reject.
+    pub fn coerce(&self, values: &[String], schema: &Value) -> Value {
+        let schema = self.resolve(schema);
+        match schema_type(schema) {
+            Some("array") => {
+                let items = schema.get("items").map(|items| self.resolve(items));
+                let values: Vec<&str> = match values {
+                    [value] if value.is_empty() => Vec::new(),
+                    [value] => value.split(',').collect(),
+                    values => values.iter().map(String::as_str).collect(),
+                };
+                let item_type = items.and_then(schema_type);
+                Value::Array(
+                    values
+                        .into_iter()
+                        .map(|value| coerce_scalar(value, item_type))
+                        .collect(),
+                )
+            }
+            item_type => match values {
+                [value] => coerce_scalar(value, item_type),
+                values => Value::Array(
+                    values
+                        .iter()
+                        .map(|value| coerce_scalar(value, item_type))
+                        .collect(),
+                ),
+            },
+        }
+    }
+
+    // Follows references into the document the way its operations are read
+    fn resolve<'a>(&'a self, schema: &'a Value) -> &'a Value {
+        follow(&self.document, schema, String::new()).map_or(schema, |(schema, _)| schema)
+    }
+}
+
+// The path parameters `path` gives a template, if it matches
+fn match_template(template: &str, path: &str) -> Option<Vec<(String, String)>> {
+    let templates: Vec<&str> = template.trim_end_matches('/').split('/').collect();
+    let segments: Vec<&str> = path.trim_end_matches('/').split('/').collect();
+    if templates.len() != segments.len() {
+        return None;
+    }
+
+    let mut parameters = Vec::new();
+    for (template, segment) in templates.iter().zip(segments) {
+        match template
+            .strip_prefix('{')
+            .and_then(|name| name.strip_suffix('}'))
+        {
+            Some(name) if !segment.is_empty() => {
+                parameters.push((name.to_string(), segment.to_string()))
+            }
+            Some(_) => return None,
+            None if *template == segment => {}
+            None => return None,
+        }
+    }
+    Some(parameters)
+}
+
+/// The first type a schema names, other than `null`.
+pub fn schema_type(schema: &Value) -> Option<&str> {
+    match schema.get("type")? {
+        Value::String(name) => Some(name),
+        Value::Array(names) => names
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|name| *name != "null"),
+        _ => None,
+    }
+}
+
+/// Converts text to the number or boolean `schema_type` names, leaving it
+/// a string when it does not convert.
+pub fn coerce_scalar(value: &str, schema_type: Option<&str>) -> Value {
+    let coerced = match schema_type {
+        Some("integer") => value.parse::<i64>().ok().map(Value::from),
+        Some("number") => value.parse::<i64>().ok().map(Value::from).or_else(|| {
+            value
+                .parse::<f64>()
+                .ok()
+                .and_then(Number::from_f64)
+                .map(Value::Number)
+        }),
+        Some("boolean") => match value {
+            "true" => Some(Value::Bool(true)),
+            "false" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        _ => None,
+    };
+    coerced.unwrap_or_else(|| Value::String(value.to_string()))
+}
+
+// Follows `$ref`s from `value`, found at `pointer`, to the value they name
+fn follow<'a>(
+    document: &'a Value,
+    mut value: &'a Value,
+    mut pointer: String,
+) -> Result<(&'a Value, String), String> {
+    for _ in 0..MAX_REFERENCE_DEPTH {
+        let Some(reference) = value.get("$ref").and_then(Value::as_str) else {
+            return Ok((value, pointer));
+        };
+        let target = reference
+            .strip_prefix('#')
+            .ok_or_else(|| format!("Unsupported reference {} at {}", reference, pointer))?;
+        value = document
+            .pointer(target)
+            .ok_or_else(|| format!("Unresolvable reference {} at {}", reference, pointer))?;
+        pointer = target.to_string();
+    }
+    Err(format!("Too many nested references at {}", pointer))
+}
+
+fn parameters(document: &Value, owner: &Value, pointer: &str) -> Result<Vec<Parameter>, String> {
+    let mut parameters = Vec::new();
+    let list = owner.get("parameters").and_then(Value::as_array);
+    for (index, parameter) in list.into_iter().flatten().enumerate() {
+        let (parameter, pointer) = follow(
+            document,
+            parameter,
+            format!("{}/parameters/{}", pointer, index),
+        )?;
+        let location = match parameter.get("in").and_then(Value::as_str) {
+            Some("path") => ParameterLocation::Path,
+            Some("query") => ParameterLocation::Query,
+            Some("header") => ParameterLocation::Header,
+            // Cookies are left to the application
+            _ => continue,
+        };
+        let Some(name) = parameter.get("name").and_then(Value::as_str) else {
+            return Err(format!("Parameter without a name at {}", pointer));
+        };
+
+        parameters.push(Parameter {
+            name: match location {
+                ParameterLocation::Header => name.to_ascii_lowercase(),
+                _ => name.to_string(),
+            },
+            location,
+            // Path parameters are always required
+            required: location == ParameterLocation::Path
+                || parameter.get("required") == Some(&Value::Bool(true)),
+            schema: parameter.get("schema").cloned(),
+            pointer,
+        });
+    }
+    Ok(parameters)
+}
+
+fn body(document: &Value, operation: &Value, pointer: &str) -> Result<Option<Body>, String> {
+    let Some(body) = operation.get("requestBody") else {
+        return Ok(None);
+    };
+    let (body, pointer) = follow(document, body, format!("{}/requestBody", pointer))?;
+
+    Ok(Some(Body {
+        required: body.get("required") == Some(&Value::Bool(true)),
+        schema: json_schema(body, &pointer),
+        pointer,
+    }))
+}
+
+fn responses(document: &Value, operation: &Value, pointer: &str) -> Result<Vec<Response>, String> {
+    let mut responses = Vec::new();
+    let map = operation.get("responses").and_then(Value::as_object);
+    for (status, response) in map.into_iter().flatten() {
+        let pointer = format!("{}/responses/{}", pointer, escape_pointer_segment(status));
+        let (response, pointer) = follow(document, response, pointer)?;
+        responses.push(Response {
+            status: status.to_ascii_uppercase(),
+            schema: json_schema(response, &pointer),
+        });
+    }
+    Ok(responses)
+}
+
+// The schema of the JSON media type in a body's `content`, preferring
+// `application/json` over other `+json` types
+fn json_schema(body: &Value, pointer: &str) -> Option<(Value, String)> {
+    let content = body.get("content")?.as_object()?;
+    let media_type = content
+        .keys()
+        .find(|media_type| media_type.as_str() == "application/json")
+        .or_else(|| {
+            content.keys().find(|media_type| {
+                let essence = media_type.split(';').next().unwrap_or_default().trim();
+                essence == "application/json" || essence.ends_with("+json")
+            })
+        })?;
+    let schema = content.get(media_type)?.get("schema")?;
+
+    Some((
+        schema.clone(),
+        format!(
+            "{}/content/{}/schema",
+            pointer,
+            escape_pointer_segment(media_type)
+        ),
+    ))
+}
+
+impl Operation {
+    /// The response documented for `status`: the exact code, then its range
+    /// (`2XX`), then `default`.
+    pub fn response(&self, status: u16) -> Option<&Response> {
+        let exact = status.to_string();
+        let range = format!("{}XX", status / 100);
+        [exact.as_str(), range.as_str(), "DEFAULT"]
+            .iter()
+            .find_map(|key| {
+                self.responses
+                    .iter()
+                    .find(|response| response.status == *key)
+            })
+    }
+}
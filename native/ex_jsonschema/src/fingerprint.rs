@@ -0,0 +1,149 @@
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::input::InputFormat;
+use crate::snapshot::{BuildOptions, RegexEngine};
+
+// Largest integer an f64 represents exactly
+const MAX_EXACT_FLOAT: f64 = 9_007_199_254_740_992.0;
+
+/// Cache key for `schema` compiled with `options`: a SHA-256 digest of the
+/// canonical schema and the options fingerprint, hex encoded. Schemas read
+/// from YAML or TOML also hash their format, as they keep source positions
+/// their JSON equivalents lack.
+pub fn cache_key(schema: &Value, options: &BuildOptions, format: InputFormat) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(canonical_json(schema).as_bytes());
+    hasher.update(b"\n");
+    hasher.update(options_fingerprint(options).as_bytes());
+    if format != InputFormat::Json {
+        hasher.update(b"\ninput_format=");
+        hasher.update(format.name().as_bytes());
+    }
+    hex(hasher)
+}
+
+/// SHA-256 digest of the canonical schema alone, hex encoded. Equivalent
+/// documents hash identically whatever options they are compiled with.
+pub fn content_hash(schema: &Value) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(canonical_json(schema).as_bytes());
+    hex(hasher)
+}
+
+fn hex(hasher: Sha256) -> String {
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Serializes `value` with sorted object keys, no insignificant whitespace and
+/// integral floats written as integers, so equivalent documents serialize
+/// identically.
+pub fn canonical_json(value: &Value) -> String {
+    let mut output = String::new();
+    write_canonical(value, &mut output);
+    output
+}
+
+fn write_canonical(value: &Value, output: &mut String) {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_unstable_by_key(|(key, _)| *key);
+
+            output.push('{');
+            for (index, (key, child)) in entries.into_iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+                output.push_str(&Value::String(key.clone()).to_string());
+                output.push(':');
+                write_canonical(child, output);
+            }
+            output.push('}');
+        }
+        Value::Array(items) => {
+            output.push('[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+                write_canonical(item, output);
+            }
+            output.push(']');
+        }
+        Value::Number(number) => match number.as_f64() {
+            Some(float)
+                if !number.is_i64()
+                    && !number.is_u64()
+                    && float.fract() == 0.0
+                    && float.abs() < MAX_EXACT_FLOAT =>
+            {
+                output.push_str(&(float as i64).to_string())
+            }
+            _ => output.push_str(&number.to_string()),
+        },
+        other => output.push_str(&other.to_string()),
+    }
+}
+
+/// Readable summary of the options that affect compilation, such as
+/// `draft=Draft7;validate_formats=true;regex_engine=default`, where an
+/// OpenAPI dialect stands in for its draft (`draft=openapi30`). Redaction, when
+/// set, is appended as `;redact=<keyword>,<mask>,<pointers>`, content
+/// validation, when enabled, as `;validate_content=true`, a format policy
+/// other than the default as `;formats=<allowed|*>,<denied>,<reject_unknown>`,
+/// and discriminator inference, when enabled, as `;infer_discriminators=true`.
+/// Strings and lists in these are written as JSON, so that no separator in
+/// them runs two values together.
+pub fn options_fingerprint(options: &BuildOptions) -> String {
+    let draft = match (options.dialect, options.draft) {
+        (Some(dialect), _) => dialect.name().to_string(),
+        (None, Some(draft)) => format!("{:?}", draft),
+        (None, None) => "auto".to_string(),
+    };
+    let validate_formats =
+        options
+            .validate_formats
+            .map_or("default", |enabled| if enabled { "true" } else { "false" });
+    let regex_engine = match options.regex_engine {
+        Some(RegexEngine::FancyRegex) => "fancy_regex",
+        Some(RegexEngine::Regex) => "regex",
+        None => "default",
+    };
+
+    let mut fingerprint = format!(
+        "draft={};validate_formats={};regex_engine={}",
+        draft, validate_formats, regex_engine
+    );
+    if let Some(redaction) = &options.redaction {
+        fingerprint.push_str(&format!(
+            ";redact={},{},{}",
+            json!(redaction.keyword),
+            json!(redaction.mask),
+            json!(redaction.pointers)
+        ));
+    }
+    if options.validate_content {
+        fingerprint.push_str(";validate_content=true");
+    }
+    if !options.formats.is_default() {
+        fingerprint.push_str(&format!(
+            ";formats={},{},{}",
+            options
+                .formats
+                .allow
+                .as_ref()
+                .map_or_else(|| "*".to_string(), |allow| json!(allow).to_string()),
+            json!(options.formats.deny),
+            options.formats.reject_unknown
+        ));
+    }
+    if options.infer_discriminators {
+        fingerprint.push_str(";infer_discriminators=true");
+    }
+    fingerprint
+}
@@ -0,0 +1,170 @@
+use jsonschema::error::ValidationErrorKind;
+use referencing::{Draft, Resolver};
+use serde_json::{json, Value};
+
+use crate::dereference::{build_registry, escape_pointer_segment, MAX_REFERENCE_DEPTH};
+use crate::redact::Redaction;
+use crate::subschema::{follow_keyword_path, is_within};
+use crate::walk::join_pointer;
+
+/// RFC 6902 operations that repair the errors `validator` finds in
+/// `instance`, where a repair follows from the schema alone. Errors without
+/// an obvious repair are left alone, so the patched instance may still be
+/// invalid. So are values `redaction` masks, as a truncated string or a
+/// clamped number would give them away.
+pub fn suggest(
+    schema: &Value,
+    draft: Draft,
+    validator: &jsonschema::Validator,
+    instance: &Value,
+    redaction: Option<&Redaction>,
+) -> Result<Vec<Value>, String> {
+    let (registry, base_uri) = build_registry(schema, draft)?;
+    let resolver = registry
+        .try_resolver(&base_uri)
+        .map_err(|e| e.to_string())?;
+
+    let redactor = redaction.map(|redaction| redaction.redactor(schema, draft, instance));
+
+    let mut changes: Vec<Value> = Vec::new();
+    let mut removals: Vec<String> = Vec::new();
+    let mut touched: Vec<String> = Vec::new();
+
+    for error in validator.iter_errors(instance) {
+        let path = error.instance_path.to_string();
+        let value = instance.pointer(&path);
+        let masked = redactor
+            .as_ref()
+            .is_some_and(|redactor| redactor.is_masked(&path));
+
+        let replacement = match &error.kind {
+            ValidationErrorKind::Required { property } => {
+                let Some(property) = property.as_str() else {
+                    continue;
+                };
+                let keyword_path = error.schema_path.to_string();
+                let parent = keyword_path
+                    .rsplit_once('/')
+                    .map_or("", |(parent, _)| parent);
+                if let Some(default) = property_default(&resolver, parent, property) {
+                    let target = format!("{}/{}", path, escape_pointer_segment(property));
+                    push_change(&mut changes, &mut touched, "add", target, default);
+                }
+                continue;
+            }
+            ValidationErrorKind::AdditionalProperties { unexpected }
+            | ValidationErrorKind::UnevaluatedProperties { unexpected } => {
+                for name in unexpected {
+                    let target = format!("{}/{}", path, escape_pointer_segment(name));
+                    if !removals.contains(&target) {
+                        removals.push(target);
+                    }
+                }
+                continue;
+            }
+            _ if masked => None,
+            ValidationErrorKind::Maximum { limit } | ValidationErrorKind::Minimum { limit } => {
+                Some(limit.clone())
+            }
+            ValidationErrorKind::MaxLength { limit } => value
+                .and_then(Value::as_str)
+                .map(|text| Value::String(text.chars().take(*limit as usize).collect())),
+            ValidationErrorKind::Enum { options } => {
+                value.and_then(|value| nearest_option(options, value))
+            }
+            ValidationErrorKind::Constant { expected_value } => Some(expected_value.clone()),
+            _ => None,
+        };
+
+        if let Some(replacement) = replacement {
+            push_change(&mut changes, &mut touched, "replace", path, replacement);
+        }
+    }
+
+    // Removals go last, and nothing is changed inside a removed property
+    changes.retain(|change| {
+        let path = change["path"].as_str().unwrap_or_default();
+        !removals.iter().any(|removed| is_within(path, removed))
+    });
+    changes.extend(
+        removals
+            .into_iter()
+            .map(|path| json!({ "op": "remove", "path": path })),
+    );
+    Ok(changes)
+}
+
+// Keeps the first change to each location
+fn push_change(
+    changes: &mut Vec<Value>,
+    touched: &mut Vec<String>,
+    op: &str,
+    path: String,
+    value: Value,
+) {
+    if touched.contains(&path) {
+        return;
+    }
+    changes.push(json!({ "op": op, "path": path, "value": value }));
+    touched.push(path);
+}
+
+// The `default` of `property` in the schema object at `keyword_path`,
+// through `$ref`s in its property schema
+fn property_default(resolver: &Resolver, keyword_path: &str, property: &str) -> Option<Value> {
+    let mut path = join_pointer(
+        keyword_path,
+        &["properties".to_string(), property.to_string()],
+    );
+
+    for _ in 0..=MAX_REFERENCE_DEPTH {
+        let (node, _, _) = follow_keyword_path(resolver, &path)?;
+        if let Some(default) = node.get("default") {
+            return Some(default.clone());
+        }
+        node.get("$ref")?;
+        path.push_str("/$ref");
+    }
+    None
+}
+
+// The option closest to `value`: by edit distance for strings and by
+// difference for numbers. A single option is always the closest.
+fn nearest_option(options: &Value, value: &Value) -> Option<Value> {
+    let options = options.as_array()?;
+    if let [only] = options.as_slice() {
+        return Some(only.clone());
+    }
+
+    let distance = |option: &Value| -> Option<f64> {
+        match (option, value) {
+            (Value::String(a), Value::String(b)) => {
+                Some(edit_distance(&a.to_lowercase(), &b.to_lowercase()) as f64)
+            }
+            (Value::Number(a), Value::Number(b)) => Some((a.as_f64()? - b.as_f64()?).abs()),
+            _ => None,
+        }
+    };
+
+    options
+        .iter()
+        .filter_map(|option| distance(option).map(|distance| (distance, option)))
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, option)| option.clone())
+}
+
+// Levenshtein distance between two strings, counted in characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
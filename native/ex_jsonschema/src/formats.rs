@@ -0,0 +1,125 @@
+use referencing::Draft;
+use serde_json::Value;
+
+use crate::dereference::build_registry;
+use crate::walk::{join_pointer, subschemas};
+
+/// Formats `jsonschema` checks, across all drafts.
+pub const KNOWN_FORMATS: &[&str] = &[
+    "date",
+    "date-time",
+    "duration",
+    "email",
+    "hostname",
+    "idn-email",
+    "idn-hostname",
+    "ipv4",
+    "ipv6",
+    "iri",
+    "iri-reference",
+    "json-pointer",
+    "regex",
+    "relative-json-pointer",
+    "time",
+    "uri",
+    "uri-reference",
+    "uri-template",
+    "uuid",
+];
+
+// A meta-schema listing this vocabulary makes `format` an assertion
+const FORMAT_ASSERTION_VOCABULARY: &str =
+    "https://json-schema.org/draft/2020-12/vocab/format-assertion";
+
+/// Which formats to check when format validation is on, and whether
+/// schemas may use formats nobody checks.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FormatPolicy {
+    /// Formats to check; `None` checks every known format
+    pub allow: Option<Vec<String>>,
+    /// Formats never to check, even when allowed
+    pub deny: Vec<String>,
+    /// Reject schemas that use a format missing from `KNOWN_FORMATS`
+    pub reject_unknown: bool,
+}
+
+impl FormatPolicy {
+    pub fn is_default(&self) -> bool {
+        *self == FormatPolicy::default()
+    }
+
+    /// Whether `format` is checked when format validation is on.
+    pub fn checks(&self, format: &str) -> bool {
+        let allowed = self
+            .allow
+            .as_ref()
+            .is_none_or(|allow| allow.iter().any(|name| name == format));
+        allowed && !self.deny.iter().any(|name| name == format)
+    }
+
+    /// Replaces the check of every known format this policy leaves out with
+    /// one that accepts anything.
+    pub fn apply(
+        &self,
+        mut builder: jsonschema::ValidationOptions,
+    ) -> jsonschema::ValidationOptions {
+        for format in KNOWN_FORMATS.iter().filter(|format| !self.checks(format)) {
+            builder = builder.with_format(*format, |_: &str| true);
+        }
+        builder
+    }
+
+    /// Fails on the first unknown format in `schema` when unknown formats
+    /// are rejected. `extra` names formats the schema's dialect defines.
+    pub fn check_known(&self, schema: &Value, extra: &[&str]) -> Result<(), String> {
+        if !self.reject_unknown {
+            return Ok(());
+        }
+        match find_unknown(schema, extra, String::new()) {
+            Some((path, format)) => Err(format!("Unknown format \"{}\" at '{}'", format, path)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Whether the meta-schema `schema` names in `$schema` lists the 2020-12
+/// format-assertion vocabulary. Only meta-schemas the schema document
+/// itself contains can be inspected.
+pub fn asserts_formats(schema: &Value, draft: Draft) -> bool {
+    let Some(meta_schema) = schema.get("$schema").and_then(Value::as_str) else {
+        return false;
+    };
+    let Ok((registry, base_uri)) = build_registry(schema, draft) else {
+        return false;
+    };
+    let Ok(resolver) = registry.try_resolver(&base_uri) else {
+        return false;
+    };
+    let Ok(resolved) = resolver.lookup(meta_schema) else {
+        return false;
+    };
+
+    resolved
+        .contents()
+        .get("$vocabulary")
+        .and_then(Value::as_object)
+        .is_some_and(|vocabularies| vocabularies.contains_key(FORMAT_ASSERTION_VOCABULARY))
+}
+
+// The location and name of the first format `jsonschema` does not check
+fn find_unknown(node: &Value, extra: &[&str], path: String) -> Option<(String, String)> {
+    let Value::Object(map) = node else {
+        return None;
+    };
+    if let Some(Value::String(format)) = map.get("format") {
+        let format_name = format.as_str();
+        if !KNOWN_FORMATS.contains(&format_name) && !extra.contains(&format_name) {
+            return Some((format!("{}/format", path), format.clone()));
+        }
+    }
+    subschemas(map)
+        .into_iter()
+        .find_map(|(segments, subschema)| {
+            find_unknown(subschema, extra, join_pointer(&path, &segments))
+        })
+}
@@ -0,0 +1,272 @@
+use referencing::Draft;
+use serde_json::{json, Map, Number, Value};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::migrate::schema_url;
+
+// Formats worth detecting, in the order they are tried
+const FORMATS: &[&str] = &["date-time", "email", "uuid"];
+
+pub struct InferOptions {
+    pub draft: Draft,
+    /// Largest number of distinct strings that is still reported as an enum
+    pub enum_threshold: usize,
+}
+
+/// Builds a schema that accepts every one of `samples`, tightened with the
+/// constraints they all share.
+pub fn infer(samples: &[Value], options: &InferOptions) -> Result<Value, String> {
+    let detector = FormatDetector::new()?;
+    let mut shape = Shape::default();
+    for sample in samples {
+        shape.observe(sample, &detector);
+    }
+
+    let mut schema = match shape.to_schema(options) {
+        Value::Object(map) => map,
+        _ => Map::new(),
+    };
+    schema.insert(
+        "$schema".to_string(),
+        Value::String(schema_url(options.draft)),
+    );
+    Ok(Value::Object(schema))
+}
+
+// Reuses the validator's own format checks so inferred formats always validate
+struct FormatDetector {
+    validators: Vec<(&'static str, jsonschema::Validator)>,
+}
+
+impl FormatDetector {
+    fn new() -> Result<Self, String> {
+        let validators = FORMATS
+            .iter()
+            .map(|format| {
+                jsonschema::options()
+                    .should_validate_formats(true)
+                    .build(&json!({ "format": format }))
+                    .map(|validator| (*format, validator))
+                    .map_err(|e| e.to_string())
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(FormatDetector { validators })
+    }
+
+    fn detect(&self, value: &Value) -> Option<&'static str> {
+        self.validators
+            .iter()
+            .find(|(_, validator)| validator.is_valid(value))
+            .map(|(format, _)| *format)
+    }
+}
+
+/// Everything observed at one location across all samples.
+#[derive(Default)]
+struct Shape {
+    nulls: usize,
+    booleans: usize,
+    numbers: Option<NumberShape>,
+    strings: Option<StringShape>,
+    arrays: Option<ArrayShape>,
+    objects: Option<ObjectShape>,
+}
+
+struct NumberShape {
+    all_integers: bool,
+    minimum: Number,
+    maximum: Number,
+}
+
+struct StringShape {
+    count: usize,
+    // `None` once there are more distinct values than any enum could hold
+    values: Option<BTreeSet<String>>,
+    // `None` once two strings disagree on their format
+    format: Option<&'static str>,
+}
+
+#[derive(Default)]
+struct ArrayShape {
+    items: Option<Box<Shape>>,
+}
+
+#[derive(Default)]
+struct ObjectShape {
+    count: usize,
+    properties: BTreeMap<String, (usize, Shape)>,
+}
+
+// Upper bound on the distinct strings tracked per location
+const MAX_TRACKED_VALUES: usize = 256;
+
+impl Shape {
+    fn observe(&mut self, value: &Value, detector: &FormatDetector) {
+        match value {
+            Value::Null => self.nulls += 1,
+            Value::Bool(_) => self.booleans += 1,
+            Value::Number(number) => self.observe_number(number),
+            Value::String(string) => self.observe_string(value, string, detector),
+            Value::Array(items) => {
+                let arrays = self.arrays.get_or_insert_with(ArrayShape::default);
+                for item in items {
+                    arrays
+                        .items
+                        .get_or_insert_with(Box::default)
+                        .observe(item, detector);
+                }
+            }
+            Value::Object(map) => {
+                let objects = self.objects.get_or_insert_with(ObjectShape::default);
+                objects.count += 1;
+                for (key, child) in map {
+                    let (seen, shape) = objects.properties.entry(key.clone()).or_default();
+                    *seen += 1;
+                    shape.observe(child, detector);
+                }
+            }
+        }
+    }
+
+    fn observe_number(&mut self, number: &Number) {
+        let is_integer = number.is_i64() || number.is_u64();
+        match &mut self.numbers {
+            None => {
+                self.numbers = Some(NumberShape {
+                    all_integers: is_integer,
+                    minimum: number.clone(),
+                    maximum: number.clone(),
+                })
+            }
+            Some(numbers) => {
+                numbers.all_integers &= is_integer;
+                if as_f64(number) < as_f64(&numbers.minimum) {
+                    numbers.minimum = number.clone();
+                }
+                if as_f64(number) > as_f64(&numbers.maximum) {
+                    numbers.maximum = number.clone();
+                }
+            }
+        }
+    }
+
+    fn observe_string(&mut self, value: &Value, string: &str, detector: &FormatDetector) {
+        let format = detector.detect(value);
+        match &mut self.strings {
+            None => {
+                self.strings = Some(StringShape {
+                    count: 1,
+                    values: Some(BTreeSet::from([string.to_string()])),
+                    format,
+                })
+            }
+            Some(strings) => {
+                strings.count += 1;
+                if let Some(values) = &mut strings.values {
+                    values.insert(string.to_string());
+                    if values.len() > MAX_TRACKED_VALUES {
+                        strings.values = None;
+                    }
+                }
+                if strings.format != format {
+                    strings.format = None;
+                }
+            }
+        }
+    }
+
+    fn to_schema(&self, options: &InferOptions) -> Value {
+        let mut types = Vec::new();
+        let mut schema = Map::new();
+
+        if self.nulls > 0 {
+            types.push("null");
+        }
+        if self.booleans > 0 {
+            types.push("boolean");
+        }
+        if let Some(numbers) = &self.numbers {
+            types.push(if numbers.all_integers {
+                "integer"
+            } else {
+                "number"
+            });
+            schema.insert(
+                "minimum".to_string(),
+                Value::Number(numbers.minimum.clone()),
+            );
+            schema.insert(
+                "maximum".to_string(),
+                Value::Number(numbers.maximum.clone()),
+            );
+        }
+        if let Some(strings) = &self.strings {
+            types.push("string");
+            strings.constrain(&mut schema, options);
+        }
+        if let Some(arrays) = &self.arrays {
+            types.push("array");
+            if let Some(items) = &arrays.items {
+                schema.insert("items".to_string(), items.to_schema(options));
+            }
+        }
+        if let Some(objects) = &self.objects {
+            types.push("object");
+            objects.constrain(&mut schema, options);
+        }
+
+        match types.as_slice() {
+            [] => {}
+            [single] => {
+                schema.insert("type".to_string(), json!(single));
+            }
+            _ => {
+                schema.insert("type".to_string(), json!(types));
+            }
+        }
+        Value::Object(schema)
+    }
+}
+
+impl StringShape {
+    fn constrain(&self, schema: &mut Map<String, Value>, options: &InferOptions) {
+        // Only call it an enum when values actually repeat
+        if let Some(values) = &self.values {
+            if values.len() <= options.enum_threshold && values.len() < self.count {
+                schema.insert("enum".to_string(), json!(values));
+                return;
+            }
+        }
+        if let Some(format) = self.format {
+            schema.insert("format".to_string(), json!(format));
+        }
+    }
+}
+
+impl ObjectShape {
+    fn constrain(&self, schema: &mut Map<String, Value>, options: &InferOptions) {
+        let properties: Map<String, Value> = self
+            .properties
+            .iter()
+            .map(|(key, (_, shape))| (key.clone(), shape.to_schema(options)))
+            .collect();
+        let required: Vec<&String> = self
+            .properties
+            .iter()
+            .filter(|(_, (seen, _))| *seen == self.count)
+            .map(|(key, _)| key)
+            .collect();
+
+        if !properties.is_empty() {
+            schema.insert("properties".to_string(), Value::Object(properties));
+        }
+        // Draft 4 does not allow an empty `required` array
+        if !required.is_empty() {
+            schema.insert("required".to_string(), json!(required));
+        }
+    }
+}
+
+fn as_f64(number: &Number) -> f64 {
+    number.as_f64().unwrap_or(0.0)
+}
@@ -0,0 +1,641 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use serde_json::{Map, Number, Value};
+use toml_edit::{ImDocument, Item, Table};
+use yaml_rust2::parser::{Event, MarkedEventReceiver, Parser, Tag};
+use yaml_rust2::scanner::{Marker, TScalarStyle};
+
+use crate::binary;
+use crate::dereference::escape_pointer_segment;
+
+// Handles the core YAML tags (`!!str`, `!!int`, ...) are reported with
+const CORE_TAG_HANDLES: &[&str] = &["!!", "tag:yaml.org,2002:"];
+
+// How many values a YAML document may hold once its aliases are expanded,
+// so that nested aliases cannot blow a small document up exponentially
+const MAX_YAML_VALUES: usize = 1_000_000;
+
+/// The formats schemas and instances can be given in. MessagePack and CBOR
+/// are binary and only read for instances.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InputFormat {
+    #[default]
+    Json,
+    Yaml,
+    Toml,
+    MessagePack,
+    Cbor,
+}
+
+impl InputFormat {
+    pub fn name(self) -> &'static str {
+        match self {
+            InputFormat::Json => "JSON",
+            InputFormat::Yaml => "YAML",
+            InputFormat::Toml => "TOML",
+            InputFormat::MessagePack => "MessagePack",
+            InputFormat::Cbor => "CBOR",
+        }
+    }
+}
+
+/// A line and column in a source document, both counted from 1. Columns
+/// count characters, not bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Where the values of a parsed document were in its source text, by JSON
+/// pointer. Object members are located at their keys.
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    positions: HashMap<String, Position>,
+    // Targets of local `$ref`s, by the pointer of the object holding them
+    references: HashMap<String, String>,
+}
+
+impl SourceMap {
+    /// The position of the value at `pointer`, or else of its nearest
+    /// ancestor that has one.
+    pub fn position(&self, pointer: &str) -> Option<Position> {
+        let mut pointer = pointer;
+        loop {
+            if let Some(position) = self.positions.get(pointer) {
+                return Some(*position);
+            }
+            pointer = &pointer[..pointer.rfind('/')?];
+        }
+    }
+
+    /// The position of the keyword a schema path leads to. Schema paths run
+    /// through the `$ref`s they follow, which are resolved when they point
+    /// into the same document; otherwise the `$ref` itself is located.
+    pub fn schema_position(&self, schema_path: &str) -> Option<Position> {
+        let mut pointer = String::new();
+        for segment in schema_path.split('/').skip(1) {
+            if segment == "$ref" {
+                if let Some(target) = self.references.get(&pointer) {
+                    pointer = target.clone();
+                    continue;
+                }
+                pointer.push_str("/$ref");
+                break;
+            }
+            pointer.push('/');
+            pointer.push_str(segment);
+        }
+        self.position(&pointer)
+    }
+
+    // The first position recorded for a pointer is kept, so that members
+    // stay located at their keys
+    fn record(&mut self, pointer: &str, position: Position) {
+        self.positions
+            .entry(pointer.to_string())
+            .or_insert(position);
+    }
+
+    fn record_references(&mut self, value: &Value, pointer: &str) {
+        match value {
+            Value::Object(object) => {
+                let target = object
+                    .get("$ref")
+                    .and_then(Value::as_str)
+                    .and_then(|reference| reference.strip_prefix('#'))
+                    .filter(|fragment| fragment.is_empty() || fragment.starts_with('/'));
+                if let Some(target) = target {
+                    self.references
+                        .insert(pointer.to_string(), target.to_string());
+                }
+                for (key, value) in object {
+                    let pointer = format!("{}/{}", pointer, escape_pointer_segment(key));
+                    self.record_references(value, &pointer);
+                }
+            }
+            Value::Array(items) => {
+                for (index, item) in items.iter().enumerate() {
+                    self.record_references(item, &format!("{}/{}", pointer, index));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Why a document could not be parsed, and where.
+#[derive(Debug)]
+pub struct ParseError {
+    pub message: String,
+    pub position: Option<Position>,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.position {
+            Some(position) => write!(
+                f,
+                "line {}, column {}: {}",
+                position.line, position.column, self.message
+            ),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// Parses `text` as a document in `format`. YAML and TOML documents come
+/// with a source map; JSON is parsed as before, without one.
+///
+/// YAML is read with the YAML 1.2 core schema: unquoted `null`, `true`,
+/// `12` or `1.5` are null, booleans and numbers, and quoted scalars are
+/// strings. Mapping keys are taken as written. Only the first document of a
+/// stream is read. TOML datetimes become strings. Infinities and NaN have no
+/// JSON equivalent and are rejected.
+pub fn parse(text: &str, format: InputFormat) -> Result<(Value, Option<SourceMap>), ParseError> {
+    let (value, mut source_map) = match format {
+        InputFormat::Json => {
+            return serde_json::from_str(text)
+                .map(|value| (value, None))
+                .map_err(|error| ParseError {
+                    message: error.to_string(),
+                    position: None,
+                })
+        }
+        InputFormat::Yaml => parse_yaml(text)?,
+        InputFormat::Toml => parse_toml(text)?,
+        InputFormat::MessagePack | InputFormat::Cbor => return decode(text.as_bytes(), format),
+    };
+    source_map.record_references(&value, "");
+    Ok((value, Some(source_map)))
+}
+
+/// Decodes `bytes` as a document in `format`. Text formats must be UTF-8 and
+/// are parsed as by `parse`; MessagePack and CBOR are decoded into the JSON
+/// data model, without a source map.
+pub fn decode(bytes: &[u8], format: InputFormat) -> Result<(Value, Option<SourceMap>), ParseError> {
+    let decoded = match format {
+        InputFormat::MessagePack => binary::decode_msgpack(bytes),
+        InputFormat::Cbor => binary::decode_cbor(bytes),
+        _ => {
+            let text = std::str::from_utf8(bytes).map_err(|error| ParseError {
+                message: error.to_string(),
+                position: None,
+            })?;
+            return parse(text, format);
+        }
+    };
+    decoded
+        .map(|value| (value, None))
+        .map_err(|message| ParseError {
+            message,
+            position: None,
+        })
+}
+
+fn parse_yaml(text: &str) -> Result<(Value, SourceMap), ParseError> {
+    let mut builder = YamlBuilder::default();
+    Parser::new_from_str(text)
+        .load(&mut builder, false)
+        .map_err(|error| ParseError {
+            message: error.info().to_string(),
+            position: Some(marker_position(error.marker())),
+        })?;
+    if let Some(error) = builder.error {
+        return Err(error);
+    }
+    Ok((builder.root.unwrap_or(Value::Null), builder.source_map))
+}
+
+fn marker_position(marker: &Marker) -> Position {
+    Position {
+        line: marker.line(),
+        column: marker.col() + 1,
+    }
+}
+
+// Builds a document from parser events, recording where each node starts
+#[derive(Default)]
+struct YamlBuilder {
+    stack: Vec<Node>,
+    // Anchored values and how many values each holds, itself included
+    anchors: HashMap<usize, (Value, usize)>,
+    // Values in the document so far, with aliases expanded
+    values: usize,
+    root: Option<Value>,
+    source_map: SourceMap,
+    error: Option<ParseError>,
+}
+
+// A sequence or mapping being built
+struct Node {
+    pointer: String,
+    anchor: usize,
+    content: Content,
+    // Values in the node so far, itself included
+    size: usize,
+}
+
+enum Content {
+    Sequence(Vec<Value>),
+    // The entries so far, and the key waiting for its value
+    Mapping(Map<String, Value>, Option<String>),
+}
+
+impl MarkedEventReceiver for YamlBuilder {
+    fn on_event(&mut self, event: Event, marker: Marker) {
+        if self.error.is_some() {
+            return;
+        }
+        let position = marker_position(&marker);
+        let result = match event {
+            Event::Scalar(text, style, anchor, tag) => {
+                self.scalar(text, style, anchor, tag.as_ref(), position)
+            }
+            Event::Alias(anchor) => self.alias(anchor, position),
+            Event::SequenceStart(anchor, _) => {
+                self.start(Content::Sequence(Vec::new()), anchor, position)
+            }
+            Event::MappingStart(anchor, _) => {
+                self.start(Content::Mapping(Map::new(), None), anchor, position)
+            }
+            Event::SequenceEnd | Event::MappingEnd => {
+                self.end();
+                Ok(())
+            }
+            _ => Ok(()),
+        };
+        if let Err(error) = result {
+            self.error = Some(error);
+        }
+    }
+}
+
+impl YamlBuilder {
+    // The pointer of the next node, or `None` when it is a mapping key
+    fn next_pointer(&self) -> Option<String> {
+        match self.stack.last() {
+            None => Some(String::new()),
+            Some(node) => match &node.content {
+                Content::Sequence(items) => Some(format!("{}/{}", node.pointer, items.len())),
+                Content::Mapping(_, Some(key)) => {
+                    Some(format!("{}/{}", node.pointer, escape_pointer_segment(key)))
+                }
+                Content::Mapping(_, None) => None,
+            },
+        }
+    }
+
+    fn scalar(
+        &mut self,
+        text: String,
+        style: TScalarStyle,
+        anchor: usize,
+        tag: Option<&Tag>,
+        position: Position,
+    ) -> Result<(), ParseError> {
+        let Some(pointer) = self.next_pointer() else {
+            return self.key(text, position);
+        };
+        self.source_map.record(&pointer, position);
+        let value = resolve_scalar(text, style, tag).map_err(|message| ParseError {
+            message,
+            position: Some(position),
+        })?;
+        self.count(1, position)?;
+        self.complete(value, anchor, 1);
+        Ok(())
+    }
+
+    fn alias(&mut self, anchor: usize, position: Position) -> Result<(), ParseError> {
+        let Some(size) = self.anchors.get(&anchor).map(|(_, size)| *size) else {
+            return Err(ParseError {
+                message: "alias to an unknown anchor".to_string(),
+                position: Some(position),
+            });
+        };
+        let Some(pointer) = self.next_pointer() else {
+            return match &self.anchors[&anchor].0 {
+                Value::String(key) => self.key(key.clone(), position),
+                _ => Err(non_scalar_key(position)),
+            };
+        };
+        // Counted before the copy is made, which is what the limit prevents
+        self.count(size, position)?;
+        let value = self.anchors[&anchor].0.clone();
+        self.source_map.record(&pointer, position);
+        self.complete(value, 0, size);
+        Ok(())
+    }
+
+    // Adds `size` values to the document, failing past `MAX_YAML_VALUES`
+    fn count(&mut self, size: usize, position: Position) -> Result<(), ParseError> {
+        self.values = self.values.saturating_add(size);
+        if self.values > MAX_YAML_VALUES {
+            return Err(ParseError {
+                message: format!(
+                    "the document holds more than {} values once aliases are expanded",
+                    MAX_YAML_VALUES
+                ),
+                position: Some(position),
+            });
+        }
+        Ok(())
+    }
+
+    fn key(&mut self, key: String, position: Position) -> Result<(), ParseError> {
+        let Some(node) = self.stack.last_mut() else {
+            return Ok(());
+        };
+        let Content::Mapping(entries, pending) = &mut node.content else {
+            return Ok(());
+        };
+        if entries.contains_key(&key) {
+            return Err(ParseError {
+                message: format!("duplicate key {:?}", key),
+                position: Some(position),
+            });
+        }
+        let pointer = format!("{}/{}", node.pointer, escape_pointer_segment(&key));
+        self.source_map.record(&pointer, position);
+        *pending = Some(key);
+        Ok(())
+    }
+
+    fn start(
+        &mut self,
+        content: Content,
+        anchor: usize,
+        position: Position,
+    ) -> Result<(), ParseError> {
+        let pointer = self
+            .next_pointer()
+            .ok_or_else(|| non_scalar_key(position))?;
+        self.source_map.record(&pointer, position);
+        self.count(1, position)?;
+        self.stack.push(Node {
+            pointer,
+            anchor,
+            content,
+            size: 1,
+        });
+        Ok(())
+    }
+
+    fn end(&mut self) {
+        let Some(node) = self.stack.pop() else {
+            return;
+        };
+        let value = match node.content {
+            Content::Sequence(items) => Value::Array(items),
+            Content::Mapping(entries, _) => Value::Object(entries),
+        };
+        self.complete(value, node.anchor, node.size);
+    }
+
+    // Places a finished node of `size` values in its parent
+    fn complete(&mut self, value: Value, anchor: usize, size: usize) {
+        if anchor > 0 {
+            self.anchors.insert(anchor, (value.clone(), size));
+        }
+        let Some(node) = self.stack.last_mut() else {
+            self.root = Some(value);
+            return;
+        };
+        node.size += size;
+        match &mut node.content {
+            Content::Sequence(items) => items.push(value),
+            Content::Mapping(entries, pending) => {
+                if let Some(key) = pending.take() {
+                    entries.insert(key, value);
+                }
+            }
+        }
+    }
+}
+
+fn non_scalar_key(position: Position) -> ParseError {
+    ParseError {
+        message: "mapping keys must be strings, numbers, booleans or null".to_string(),
+        position: Some(position),
+    }
+}
+
+// Quoted and block scalars are strings; plain ones are resolved by the core
+// schema, which an explicit core tag must agree with
+fn resolve_scalar(text: String, style: TScalarStyle, tag: Option<&Tag>) -> Result<Value, String> {
+    let tag = tag
+        .filter(|tag| CORE_TAG_HANDLES.contains(&tag.handle.as_str()))
+        .map(|tag| tag.suffix.as_str());
+    match tag {
+        Some("str") => return Ok(Value::String(text)),
+        None if style != TScalarStyle::Plain => return Ok(Value::String(text)),
+        _ => {}
+    }
+
+    let value = resolve_plain(&text)?;
+    let agrees = match tag {
+        Some("null") => value.is_null(),
+        Some("bool") => value.is_boolean(),
+        Some("int") => value.is_i64() || value.is_u64(),
+        Some("float") => value.is_number(),
+        _ => true,
+    };
+    match tag {
+        Some(tag) if !agrees => Err(format!("{:?} is not a valid !!{}", text, tag)),
+        _ => Ok(value),
+    }
+}
+
+fn resolve_plain(text: &str) -> Result<Value, String> {
+    match text {
+        "" | "~" | "null" | "Null" | "NULL" => return Ok(Value::Null),
+        "true" | "True" | "TRUE" => return Ok(Value::Bool(true)),
+        "false" | "False" | "FALSE" => return Ok(Value::Bool(false)),
+        _ => {}
+    }
+
+    let radix = [("0x", 16), ("0o", 8)]
+        .into_iter()
+        .find_map(|(prefix, radix)| {
+            let digits = text.strip_prefix(prefix)?;
+            u64::from_str_radix(digits, radix).ok()
+        });
+    if let Some(number) = radix {
+        return Ok(Value::from(number));
+    }
+
+    if is_integer(text) {
+        if let Ok(number) = text.parse::<i64>() {
+            return Ok(Value::from(number));
+        }
+        if let Ok(number) = text.parse::<u64>() {
+            return Ok(Value::from(number));
+        }
+    }
+    if is_integer(text) || is_float(text) {
+        // Integers too large for 64 bits are kept as floats, as JSON would
+        if let Some(number) = text.parse().ok().and_then(Number::from_f64) {
+            return Ok(Value::Number(number));
+        }
+    }
+
+    let unsigned = text.strip_prefix(['-', '+']).unwrap_or(text);
+    if matches!(
+        unsigned,
+        ".inf" | ".Inf" | ".INF" | ".nan" | ".NaN" | ".NAN"
+    ) {
+        return Err(format!("{} is not a number JSON can represent", text));
+    }
+    Ok(Value::String(text.to_string()))
+}
+
+fn is_digits(text: &str) -> bool {
+    !text.is_empty() && text.bytes().all(|byte| byte.is_ascii_digit())
+}
+
+// `[-+]?[0-9]+`
+fn is_integer(text: &str) -> bool {
+    is_digits(text.strip_prefix(['-', '+']).unwrap_or(text))
+}
+
+// `[-+]?(\.[0-9]+|[0-9]+(\.[0-9]*)?)([eE][-+]?[0-9]+)?`
+fn is_float(text: &str) -> bool {
+    let text = text.strip_prefix(['-', '+']).unwrap_or(text);
+    let (mantissa, exponent) = match text.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+        None => (text, None),
+    };
+    let mantissa = match mantissa.split_once('.') {
+        Some(("", fraction)) => is_digits(fraction),
+        Some((whole, fraction)) => is_digits(whole) && (fraction.is_empty() || is_digits(fraction)),
+        None => is_digits(mantissa),
+    };
+    mantissa && exponent.is_none_or(is_integer)
+}
+
+fn parse_toml(text: &str) -> Result<(Value, SourceMap), ParseError> {
+    let lines = LineIndex::new(text);
+    let document = ImDocument::parse(text).map_err(|error| ParseError {
+        message: error.message().to_string(),
+        position: error.span().map(|span| lines.position(span.start)),
+    })?;
+
+    let mut source_map = SourceMap::default();
+    source_map.record("", Position { line: 1, column: 1 });
+    let value = TomlConverter {
+        lines: &lines,
+        source_map: &mut source_map,
+    }
+    .table(document.as_table(), "")?;
+    Ok((value, source_map))
+}
+
+// Converts a TOML document, recording where its values are
+struct TomlConverter<'a> {
+    lines: &'a LineIndex<'a>,
+    source_map: &'a mut SourceMap,
+}
+
+impl TomlConverter<'_> {
+    fn record(&mut self, pointer: &str, span: Option<std::ops::Range<usize>>) {
+        if let Some(span) = span {
+            self.source_map
+                .record(pointer, self.lines.position(span.start));
+        }
+    }
+
+    fn table(&mut self, table: &Table, pointer: &str) -> Result<Value, ParseError> {
+        let mut object = Map::new();
+        for (name, _) in table.iter() {
+            let Some((key, item)) = table.get_key_value(name) else {
+                continue;
+            };
+            let pointer = format!("{}/{}", pointer, escape_pointer_segment(name));
+            self.record(&pointer, key.span().or_else(|| item.span()));
+
+            let value = match item {
+                Item::None => continue,
+                Item::Value(value) => self.value(value, &pointer)?,
+                Item::Table(table) => self.table(table, &pointer)?,
+                Item::ArrayOfTables(tables) => {
+                    let mut items = Vec::with_capacity(tables.len());
+                    for (index, table) in tables.iter().enumerate() {
+                        let pointer = format!("{}/{}", pointer, index);
+                        self.record(&pointer, table.span());
+                        items.push(self.table(table, &pointer)?);
+                    }
+                    Value::Array(items)
+                }
+            };
+            object.insert(name.to_string(), value);
+        }
+        Ok(Value::Object(object))
+    }
+
+    fn value(&mut self, value: &toml_edit::Value, pointer: &str) -> Result<Value, ParseError> {
+        use toml_edit::Value as Toml;
+
+        Ok(match value {
+            Toml::String(string) => Value::String(string.value().clone()),
+            Toml::Integer(integer) => Value::from(*integer.value()),
+            Toml::Float(float) => match Number::from_f64(*float.value()) {
+                Some(number) => Value::Number(number),
+                None => {
+                    return Err(ParseError {
+                        message: format!("{} is not a number JSON can represent", float.value()),
+                        position: value.span().map(|span| self.lines.position(span.start)),
+                    })
+                }
+            },
+            Toml::Boolean(boolean) => Value::Bool(*boolean.value()),
+            Toml::Datetime(datetime) => Value::String(datetime.value().to_string()),
+            Toml::Array(array) => {
+                let mut items = Vec::with_capacity(array.len());
+                for (index, item) in array.iter().enumerate() {
+                    let pointer = format!("{}/{}", pointer, index);
+                    self.record(&pointer, item.span());
+                    items.push(self.value(item, &pointer)?);
+                }
+                Value::Array(items)
+            }
+            Toml::InlineTable(table) => {
+                let mut object = Map::new();
+                for (name, item) in table.iter() {
+                    let pointer = format!("{}/{}", pointer, escape_pointer_segment(name));
+                    let key_span = table.key(name).and_then(|key| key.span());
+                    self.record(&pointer, key_span.or_else(|| item.span()));
+                    object.insert(name.to_string(), self.value(item, &pointer)?);
+                }
+                Value::Object(object)
+            }
+        })
+    }
+}
+
+// Turns byte offsets into positions
+struct LineIndex<'a> {
+    text: &'a str,
+    // Byte offset of the start of each line
+    starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    fn new(text: &'a str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        LineIndex { text, starts }
+    }
+
+    fn position(&self, offset: usize) -> Position {
+        let line = self.starts.partition_point(|&start| start <= offset);
+        let start = self.starts[line - 1];
+        let column = self
+            .text
+            .get(start..offset)
+            .map_or(1, |text| text.chars().count() + 1);
+        Position { line, column }
+    }
+}
@@ -2,8 +2,35 @@ use rustler::{Atom, Encoder, Env, ResourceArc, Term};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 use thiserror::Error;
 
+mod avro;
+mod binary;
+mod content;
+mod csv;
+mod dereference;
+mod direction;
+mod discriminator;
+mod document;
+mod fingerprint;
+mod fixes;
+mod formats;
+mod infer;
+mod input;
+mod migrate;
+mod openapi;
+mod patch;
+mod ranking;
+mod redact;
+mod snapshot;
+mod structured;
+mod subschema;
+mod terms;
+mod timing;
+mod walk;
+
 mod atoms {
     rustler::atoms! {
         ok,
@@ -20,6 +47,20 @@ mod atoms {
         draft7,
         draft201909,
         draft202012,
+        // OpenAPI dialects
+        openapi30,
+        openapi31,
+        // Validation directions
+        request,
+        response,
+        // OpenAPI operation lookup
+        unknown_operation,
+        // Input formats
+        yaml,
+        toml,
+        msgpack,
+        cbor,
+        term,
         // Regex engines
         fancy_regex,
         regex,
@@ -28,6 +69,23 @@ mod atoms {
         false_atom = "false",
         type_ = "type",
         message = "message",
+        // Dereference cycle policies
+        keep,
+        // Telemetry measurements
+        parse_time,
+        patch_time,
+        build_time,
+        validation_time,
+        enrichment_time,
+        encode_time,
+        error_count,
+        // Schema metadata
+        schema_id,
+        draft,
+        id,
+        content_hash,
+        options_fingerprint,
+        compiled_at,
     }
 }
 
@@ -46,6 +104,185 @@ pub struct ValidationErrorDetail {
     pub instance_path: String,
     pub schema_path: String,
     pub message: String,
+    pub keyword: String,
+    // What the keyword checked against, see `structured::params`
+    pub params: Value,
+    // Set on errors listed under a failed `oneOf` or `anyOf`
+    pub branch: Option<Branch>,
+    // Errors of each subschema of a failed `oneOf` or `anyOf`
+    pub branches: Vec<Vec<ValidationErrorDetail>>,
+}
+
+impl ValidationErrorDetail {
+    fn from_error(error: &jsonschema::ValidationError) -> Self {
+        let schema_path = error.schema_path.to_string();
+        let branches = branch_errors(error, &schema_path, Self::from_error, |error, branch| {
+            error.branch = Some(branch)
+        });
+        ValidationErrorDetail {
+            instance_path: error.instance_path.to_string(),
+            schema_path,
+            message: error.to_string(),
+            keyword: extract_keyword_from_error(error),
+            params: structured::params(&error.kind),
+            branch: None,
+            branches,
+        }
+    }
+
+    // Applies `f` to this error and to every error nested in its branches
+    fn for_each_mut(&mut self, f: &mut impl FnMut(&mut ValidationErrorDetail)) {
+        f(self);
+        for error in self.branches.iter_mut().flatten() {
+            error.for_each_mut(f);
+        }
+    }
+}
+
+impl ranking::Rankable for ValidationErrorDetail {
+    fn instance_path(&self) -> &str {
+        &self.instance_path
+    }
+
+    fn schema_path(&self) -> &str {
+        &self.schema_path
+    }
+
+    fn branches(&self) -> &[Vec<Self>] {
+        &self.branches
+    }
+}
+
+impl ranking::Rankable for VerboseValidationErrorDetail {
+    fn instance_path(&self) -> &str {
+        &self.instance_path
+    }
+
+    fn schema_path(&self) -> &str {
+        &self.schema_path
+    }
+
+    fn branches(&self) -> &[Vec<Self>] {
+        &self.branches
+    }
+}
+
+impl discriminator::Resolvable for ValidationErrorDetail {
+    fn from_finding(finding: walk::Finding) -> Self {
+        let keyword = finding.schema_path.rsplit('/').next().unwrap_or_default();
+        ValidationErrorDetail {
+            keyword: keyword.to_string(),
+            params: Value::Object(serde_json::Map::new()),
+            instance_path: finding.instance_path,
+            schema_path: finding.schema_path,
+            message: finding.message,
+            branch: None,
+            branches: Vec::new(),
+        }
+    }
+
+    fn branches_mut(&mut self) -> &mut Vec<Vec<Self>> {
+        &mut self.branches
+    }
+
+    fn detach(&mut self) {
+        self.branch = None;
+    }
+
+    fn map_schema_paths(&mut self, f: &mut dyn FnMut(&mut String)) {
+        self.for_each_mut(&mut |error| {
+            f(&mut error.schema_path);
+            if let Some(branch) = &mut error.branch {
+                f(&mut branch.schema_pointer);
+            }
+        });
+    }
+}
+
+impl discriminator::Resolvable for VerboseValidationErrorDetail {
+    fn from_finding(finding: walk::Finding) -> Self {
+        let keyword = finding.schema_path.rsplit('/').next().unwrap_or_default();
+        VerboseValidationErrorDetail {
+            keyword: keyword.to_string(),
+            instance_path: finding.instance_path,
+            schema_path: finding.schema_path,
+            message: finding.message,
+            instance_value: finding.instance_value,
+            schema_value: Value::Null,
+            params: Value::Object(serde_json::Map::new()),
+            context: HashMap::new(),
+            annotations: HashMap::new(),
+            suggestions: Vec::new(),
+            branch: None,
+            branches: Vec::new(),
+        }
+    }
+
+    fn branches_mut(&mut self) -> &mut Vec<Vec<Self>> {
+        &mut self.branches
+    }
+
+    fn detach(&mut self) {
+        self.branch = None;
+    }
+
+    fn map_schema_paths(&mut self, f: &mut dyn FnMut(&mut String)) {
+        f(&mut self.schema_path);
+        if let Some(branch) = &mut self.branch {
+            f(&mut branch.schema_pointer);
+        }
+        for error in self.branches.iter_mut().flatten() {
+            error.map_schema_paths(f);
+        }
+    }
+}
+
+/// The subschema of a `oneOf` or `anyOf` that a nested error belongs to.
+#[derive(Debug, Clone)]
+pub struct Branch {
+    pub index: usize,
+    pub schema_pointer: String,
+}
+
+// Converts the per-subschema errors jsonschema collects for a failed `oneOf`
+// or `anyOf` with `convert`, tagging each with its branch through `tag`.
+// `allOf` failures are reported as separate errors instead, so other
+// keywords have no branches.
+fn branch_errors<T>(
+    error: &jsonschema::ValidationError,
+    schema_path: &str,
+    mut convert: impl FnMut(&jsonschema::ValidationError) -> T,
+    tag: impl Fn(&mut T, Branch),
+) -> Vec<Vec<T>> {
+    use jsonschema::error::ValidationErrorKind;
+
+    let context = match &error.kind {
+        ValidationErrorKind::AnyOf { context } | ValidationErrorKind::OneOfNotValid { context } => {
+            context
+        }
+        _ => return Vec::new(),
+    };
+    context
+        .iter()
+        .enumerate()
+        .map(|(index, errors)| {
+            let schema_pointer = format!("{}/{}", schema_path, index);
+            errors
+                .iter()
+                .map(|error| {
+                    let mut converted = convert(error);
+                    tag(
+                        &mut converted,
+                        Branch {
+                            index,
+                            schema_pointer: schema_pointer.clone(),
+                        },
+                    );
+                    converted
+                })
+                .collect()
+        })
+        .collect()
 }
 
 #[derive(Debug, Clone)]
@@ -54,11 +291,14 @@ pub struct VerboseValidationErrorDetail {
     pub schema_path: String,
     pub message: String,
     pub keyword: String,
+    pub params: Value,
     pub instance_value: Value,
     pub schema_value: Value,
     pub context: HashMap<String, Value>,
     pub annotations: HashMap<String, Value>,
     pub suggestions: Vec<String>,
+    pub branch: Option<Branch>,
+    pub branches: Vec<Vec<VerboseValidationErrorDetail>>,
 }
 
 #[derive(rustler::NifStruct)]
@@ -67,166 +307,330 @@ pub struct ValidationOptionsStruct {
     pub draft: Atom,
     pub validate_formats: bool,
     pub regex_engine: Atom,
+    pub redact: bool,
+    pub redact_keyword: String,
+    pub redact_pointers: Vec<String>,
+    pub redact_mask: String,
+    pub validate_content: bool,
+    pub allowed_formats: Option<Vec<String>>,
+    pub denied_formats: Vec<String>,
+    pub reject_unknown_formats: bool,
+    pub infer_discriminators: bool,
+    pub input_format: Atom,
 }
 
 pub struct CompiledSchema {
-    validator: AssertUnwindSafe<jsonschema::Validator>,
+    validator: AssertUnwindSafe<Arc<jsonschema::Validator>>,
+    // The schema as given, for dereferencing, serializing and hashing
+    source: Value,
+    // The schema the validator was built from, with the dialect translated
+    // and discriminators guarded; error schema paths point into it
     schema: Value,
+    options: snapshot::BuildOptions,
+    content_hash: String,
+    compiled_at: SystemTime,
+    // Validators for subschemas, built on first use by `validate_at`
+    fragments: subschema::Fragments,
+    // Where the schema's values are in the YAML or TOML it was compiled from
+    source_map: Option<input::SourceMap>,
 }
 
 impl CompiledSchema {
     fn new(schema: Value) -> Result<Self, JsonSchemaError> {
-        let validator = jsonschema::validator_for(&schema)
-            .map_err(|e| JsonSchemaError::CompilationError(e.to_string()))?;
-
-        Ok(CompiledSchema {
-            validator: AssertUnwindSafe(validator),
-            schema: schema.clone(),
-        })
+        Self::build(schema, snapshot::BuildOptions::default())
     }
 
     fn new_with_draft(schema: Value, draft: Atom) -> Result<Self, JsonSchemaError> {
-        let validator = if draft == atoms::draft4() {
-            jsonschema::draft4::new(&schema)
-                .map_err(|e| JsonSchemaError::CompilationError(e.to_string()))?
-        } else if draft == atoms::draft6() {
-            jsonschema::draft6::new(&schema)
-                .map_err(|e| JsonSchemaError::CompilationError(e.to_string()))?
-        } else if draft == atoms::draft7() {
-            jsonschema::draft7::new(&schema)
-                .map_err(|e| JsonSchemaError::CompilationError(e.to_string()))?
-        } else if draft == atoms::draft201909() {
-            jsonschema::draft201909::new(&schema)
-                .map_err(|e| JsonSchemaError::CompilationError(e.to_string()))?
-        } else if draft == atoms::draft202012() {
-            jsonschema::draft202012::new(&schema)
-                .map_err(|e| JsonSchemaError::CompilationError(e.to_string()))?
-        } else {
-            // Default to generic validator for unknown drafts
-            jsonschema::validator_for(&schema)
-                .map_err(|e| JsonSchemaError::CompilationError(e.to_string()))?
-        };
-
-        Ok(CompiledSchema {
-            validator: AssertUnwindSafe(validator),
-            schema: schema.clone(),
-        })
+        // Unknown drafts fall back to the generic validator
+        Self::build(
+            schema,
+            snapshot::BuildOptions {
+                draft: draft_from_atom(draft),
+                dialect: dialect_from_atom(draft),
+                ..Default::default()
+            },
+        )
     }
 
     fn new_with_options(
         schema: Value,
         options: ValidationOptionsStruct,
     ) -> Result<Self, JsonSchemaError> {
-        // Start with the jsonschema::options() builder
-        let mut builder = jsonschema::options();
-
-        // Set draft version if not auto
-        if options.draft != atoms::auto() {
-            builder = if options.draft == atoms::draft4() {
-                builder.with_draft(jsonschema::Draft::Draft4)
-            } else if options.draft == atoms::draft6() {
-                builder.with_draft(jsonschema::Draft::Draft6)
-            } else if options.draft == atoms::draft7() {
-                builder.with_draft(jsonschema::Draft::Draft7)
-            } else if options.draft == atoms::draft201909() {
-                builder.with_draft(jsonschema::Draft::Draft201909)
-            } else if options.draft == atoms::draft202012() {
-                builder.with_draft(jsonschema::Draft::Draft202012)
-            } else {
-                builder // Unknown draft, use default
-            };
-        }
-
-        // Configure format validation
-        if options.validate_formats {
-            builder = builder.should_validate_formats(true);
-        }
-
-        // Configure regex engine for security
-        if options.regex_engine == atoms::regex() {
-            // Use safer regex engine
-            builder = builder.with_pattern_options(jsonschema::PatternOptions::regex());
-        } else {
-            // Use fancy_regex with security limits
-            builder = builder.with_pattern_options(
-                jsonschema::PatternOptions::fancy_regex().backtrack_limit(10_000),
-            );
-        }
-
-        // All supported options are now implemented above.
         // External reference resolution, annotation collection, and
         // stop-on-first-error are not supported by jsonschema 0.33
+        Self::build(schema, build_options(&options))
+    }
 
-        // Build the validator
-        let validator = builder
+    fn build(schema: Value, options: snapshot::BuildOptions) -> Result<Self, JsonSchemaError> {
+        let content_hash = fingerprint::content_hash(&schema);
+        let source = schema.clone();
+        let schema = options.prepare(schema);
+        let validator = options
             .build(&schema)
-            .map_err(|e| JsonSchemaError::CompilationError(e.to_string()))?;
+            .map_err(JsonSchemaError::CompilationError)?;
 
         Ok(CompiledSchema {
-            validator: AssertUnwindSafe(validator),
-            schema: schema.clone(),
+            validator: AssertUnwindSafe(Arc::new(validator)),
+            content_hash,
+            compiled_at: SystemTime::now(),
+            fragments: subschema::Fragments::default(),
+            source_map: None,
+            source,
+            schema,
+            options,
         })
     }
 
     fn validate(&self, instance: &Value) -> Result<(), JsonSchemaError> {
-        if self.validator.is_valid(instance) {
+        if self.is_valid(instance, None) {
             Ok(())
         } else {
-            let error_details: Vec<ValidationErrorDetail> = self
-                .validator
-                .iter_errors(instance)
-                .map(|error| ValidationErrorDetail {
-                    instance_path: error.instance_path.to_string(),
-                    schema_path: error.schema_path.to_string(),
-                    message: error.to_string(),
-                })
-                .collect();
+            Err(JsonSchemaError::ValidationError(
+                self.errors(instance, None),
+            ))
+        }
+    }
 
-            Err(JsonSchemaError::ValidationError(error_details))
+    fn errors(
+        &self,
+        instance: &Value,
+        direction: Option<direction::Direction>,
+    ) -> Vec<ValidationErrorDetail> {
+        let errors = self
+            .validator
+            .iter_errors(instance)
+            .map(|error| ValidationErrorDetail::from_error(&error))
+            .collect();
+        let mut errors = discriminator::resolve(errors, &self.schema, self.draft(), instance);
+        for finding in self.findings(instance, direction) {
+            if !is_reported(&errors, &finding.instance_path, &finding.schema_path) {
+                errors.push(discriminator::Resolvable::from_finding(finding));
+            }
         }
+        self.redact(&mut errors, instance);
+        errors
     }
 
-    fn validate_verbose(&self, instance: &Value) -> Result<(), Vec<VerboseValidationErrorDetail>> {
-        if self.validator.is_valid(instance) {
-            Ok(())
-        } else {
-            let verbose_errors: Vec<VerboseValidationErrorDetail> = self
-                .validator
-                .iter_errors(instance)
-                .map(|error| {
-                    let keyword = extract_keyword_from_error(&error);
-                    let (instance_value, schema_value) =
-                        extract_values_from_error(&error, instance, &self.schema);
-                    let context =
-                        build_error_context(&error, &instance_value, &schema_value, &keyword);
-                    let annotations = extract_annotations_from_error(&error, &self.schema);
-                    let suggestions = generate_suggestions_for_error(
-                        &error,
-                        &keyword,
-                        &instance_value,
-                        &schema_value,
-                    );
+    fn verbose_errors(
+        &self,
+        instance: &Value,
+        direction: Option<direction::Direction>,
+    ) -> Vec<VerboseValidationErrorDetail> {
+        let errors = self
+            .validator
+            .iter_errors(instance)
+            .map(|error| self.verbose_error(&error, instance))
+            .collect();
+        let mut errors = discriminator::resolve(errors, &self.schema, self.draft(), instance);
+        for finding in self.findings(instance, direction) {
+            if !is_reported(&errors, &finding.instance_path, &finding.schema_path) {
+                errors.push(discriminator::Resolvable::from_finding(finding));
+            }
+        }
+
+        if let Some(redaction) = self
+            .options
+            .redaction
+            .as_ref()
+            .filter(|_| !errors.is_empty())
+        {
+            let redactor = redaction.redactor(&self.schema, self.draft(), instance);
+            if !redactor.is_empty() {
+                for error in &mut errors {
+                    redact_verbose_error(&redactor, error);
+                }
+            }
+        }
+        errors
+    }
+
+    fn verbose_error(
+        &self,
+        error: &jsonschema::ValidationError,
+        instance: &Value,
+    ) -> VerboseValidationErrorDetail {
+        let keyword = extract_keyword_from_error(error);
+        let (instance_value, schema_value) =
+            extract_values_from_error(error, instance, &self.schema);
+        let context = build_error_context(error, &instance_value, &schema_value, &keyword);
+        let annotations = extract_annotations_from_error(error, &self.schema);
+        let suggestions =
+            generate_suggestions_for_error(error, &keyword, &instance_value, &schema_value);
+        let schema_path = error.schema_path.to_string();
+        let branches = branch_errors(
+            error,
+            &schema_path,
+            |error| self.verbose_error(error, instance),
+            |error, branch| error.branch = Some(branch),
+        );
+
+        VerboseValidationErrorDetail {
+            instance_path: error.instance_path.to_string(),
+            schema_path,
+            message: error.to_string(),
+            keyword,
+            params: structured::params(&error.kind),
+            instance_value,
+            schema_value,
+            context,
+            annotations,
+            suggestions,
+            branch: None,
+            branches,
+        }
+    }
+
+    // Registry of the schema, for finding its subschemas
+    fn root(&self) -> Result<&subschema::Root, String> {
+        self.fragments.root(&self.schema, self.draft())
+    }
 
-                    VerboseValidationErrorDetail {
-                        instance_path: error.instance_path.to_string(),
-                        schema_path: error.schema_path.to_string(),
-                        message: error.to_string(),
-                        keyword,
-                        instance_value,
-                        schema_value,
-                        context,
-                        annotations,
-                        suggestions,
+    // Validator for the subschema at `location`: the root validator for the
+    // root, otherwise one built on first use. The flag is true when this call
+    // built it.
+    fn fragment(
+        &self,
+        location: &subschema::Location,
+    ) -> Result<(subschema::Fragment, bool), String> {
+        self.fragments
+            .get_or_build(&self.validator, &self.schema, self.draft(), location)
+    }
+
+    // Errors of the value at `prefix` in `document` against `fragment`, with
+    // paths relative to the roots
+    fn fragment_errors(
+        &self,
+        fragment: &subschema::Fragment,
+        prefix: &str,
+        document: &Value,
+    ) -> Vec<ValidationErrorDetail> {
+        let subtree = document.pointer(prefix).unwrap_or(&Value::Null);
+        let errors = fragment
+            .validator
+            .iter_errors(subtree)
+            .map(|error| {
+                let mut detail = ValidationErrorDetail::from_error(&error);
+                detail.for_each_mut(&mut |error| {
+                    error.instance_path = format!("{}{}", prefix, error.instance_path);
+                    error.schema_path = fragment.schema_path(&error.schema_path);
+                    if let Some(branch) = &mut error.branch {
+                        branch.schema_pointer = fragment.schema_path(&branch.schema_pointer);
                     }
-                })
-                .collect();
+                });
+                detail
+            })
+            .collect();
+        let mut errors = discriminator::resolve(errors, &self.schema, self.draft(), document);
+        self.redact(&mut errors, document);
+        errors
+    }
+
+    // Masks sensitive values in error messages when the schema was compiled
+    // with a redaction policy
+    fn redact(&self, errors: &mut [ValidationErrorDetail], instance: &Value) {
+        let Some(redaction) = &self.options.redaction else {
+            return;
+        };
+        if errors.is_empty() {
+            return;
+        }
+        let redactor = redaction.redactor(&self.schema, self.draft(), instance);
+        if redactor.is_empty() {
+            return;
+        }
+        for error in errors {
+            error.for_each_mut(&mut |error| {
+                error.message = redactor.text(&error.instance_path, &error.message)
+            });
+        }
+    }
+
+    // The draft the validator was built for, as named by the options or `$schema`
+    fn draft(&self) -> jsonschema::Draft {
+        self.options.draft.unwrap_or_else(|| {
+            self.schema
+                .get("$schema")
+                .and_then(Value::as_str)
+                .map(draft_from_schema_url)
+                .unwrap_or(jsonschema::Draft::Draft202012)
+        })
+    }
+
+    // The draft or OpenAPI dialect the schema was compiled as
+    fn draft_atom(&self) -> Atom {
+        self.options
+            .dialect
+            .map_or_else(|| draft_to_atom(self.draft()), dialect_to_atom)
+    }
+
+    // Draft 4 names the identifier `id`, later drafts `$id`
+    fn schema_id(&self) -> Option<&str> {
+        let key = if self.draft() == jsonschema::Draft::Draft4 {
+            "id"
+        } else {
+            "$id"
+        };
+        self.schema.get(key).and_then(Value::as_str)
+    }
 
-            Err(verbose_errors)
+    fn is_valid(&self, instance: &Value, direction: Option<direction::Direction>) -> bool {
+        self.validator.is_valid(instance) && self.findings(instance, direction).is_empty()
+    }
+
+    // Errors the validator does not look for: in encoded content, when the
+    // schema was compiled to validate it, and values `direction` forbids
+    fn findings(
+        &self,
+        instance: &Value,
+        direction: Option<direction::Direction>,
+    ) -> Vec<walk::Finding> {
+        let validator_for = |location: &subschema::Location| {
+            self.fragment(location)
+                .map(|(fragment, _)| fragment.validator)
+        };
+        let mut findings = Vec::new();
+        if self.options.validate_content {
+            findings.extend(content::check(
+                &self.schema,
+                self.draft(),
+                instance,
+                &validator_for,
+            ));
+        }
+        if let Some(direction) = direction {
+            findings.extend(direction::check(
+                &self.schema,
+                self.draft(),
+                instance,
+                direction,
+                &validator_for,
+            ));
         }
+        findings
     }
+}
+
+// Whether the validator already reported an error, as Draft 6 and 7 do for
+// `contentEncoding` and `contentMediaType`
+fn is_reported<T: ranking::Rankable>(errors: &[T], instance_path: &str, schema_path: &str) -> bool {
+    errors
+        .iter()
+        .any(|error| error.instance_path() == instance_path && error.schema_path() == schema_path)
+}
 
-    fn is_valid(&self, instance: &Value) -> bool {
-        self.validator.is_valid(instance)
+// Masks sensitive values in a verbose error and the errors in its branches
+fn redact_verbose_error(redactor: &redact::Redactor, error: &mut VerboseValidationErrorDetail) {
+    let path = &error.instance_path;
+    error.instance_value = redactor.instance_value(path, &error.instance_value);
+    error.message = redactor.text(path, &error.message);
+    for suggestion in &mut error.suggestions {
+        *suggestion = redactor.text(path, suggestion);
+    }
+    for value in error.context.values_mut() {
+        *value = redactor.value(path, value);
+    }
+    for nested in error.branches.iter_mut().flatten() {
+        redact_verbose_error(redactor, nested);
     }
 }
 
@@ -370,155 +774,856 @@ fn validate(compiled_schema: ResourceArc<CompiledSchema>, instance_json: String)
 }
 
 #[rustler::nif]
-fn validate_detailed(
+fn validate_detailed<'a>(
+    env: Env<'a>,
+    compiled_schema: ResourceArc<CompiledSchema>,
+    instance: Term<'a>,
+    ranked: bool,
+    direction: Option<Atom>,
+    input_format: Atom,
+) -> Term<'a> {
+    let mut stopwatch = timing::Stopwatch::start();
+    let mut measurements = timing::Measurements::default();
+
+    let (instance_value, source_map) = match read_instance(env, instance, input_format) {
+        Ok(read) => read,
+        Err(result) => {
+            measurements.parse_time = Some(stopwatch.lap());
+            return (result, encode_measurements(env, &measurements)).encode(env);
+        }
+    };
+    measurements.parse_time = Some(stopwatch.lap());
+
+    let direction = direction.and_then(direction_from_atom);
+    let is_valid = compiled_schema.is_valid(&instance_value, direction);
+    measurements.validation_time = Some(stopwatch.lap());
+
+    let result = if is_valid {
+        measurements.error_count = Some(0);
+        atoms::ok().encode(env)
+    } else {
+        let mut errors = compiled_schema.errors(&instance_value, direction);
+        if ranked {
+            ranking::sort(&mut errors);
+        }
+        measurements.enrichment_time = Some(stopwatch.lap());
+        measurements.error_count = Some(errors.len());
+
+        let error_terms: Vec<Term> = errors
+            .iter()
+            .map(|error| {
+                let map = encode_segments(
+                    env,
+                    encode_error_detail(env, error),
+                    &error.instance_path,
+                    &instance_value,
+                );
+                encode_positions(
+                    env,
+                    map,
+                    &error.instance_path,
+                    &error.schema_path,
+                    source_map.as_ref(),
+                    compiled_schema.source_map.as_ref(),
+                )
+            })
+            .collect();
+
+        (atoms::error(), error_terms).encode(env)
+    };
+    measurements.encode_time = Some(stopwatch.lap());
+
+    (result, encode_measurements(env, &measurements)).encode(env)
+}
+
+#[rustler::nif]
+fn validate_at(
     env: Env,
     compiled_schema: ResourceArc<CompiledSchema>,
     instance_json: String,
+    schema_pointer: Option<String>,
+    instance_pointer: Option<String>,
+    detailed: bool,
 ) -> Term {
-    let instance_value: Value = match serde_json::from_str(&instance_json) {
+    let mut stopwatch = timing::Stopwatch::start();
+    let mut measurements = timing::Measurements::default();
+    let finish = |result: Term<'_>, measurements: &timing::Measurements| {
+        (result, encode_measurements(env, measurements)).encode(env)
+    };
+
+    let parsed = serde_json::from_str::<Value>(&instance_json);
+    measurements.parse_time = Some(stopwatch.lap());
+    let instance_value = match parsed {
         Ok(value) => value,
-        Err(_) => return (atoms::error(), atoms::json_parse_error()).encode(env),
+        Err(_) => {
+            let result = (atoms::error(), atoms::json_parse_error()).encode(env);
+            return finish(result, &measurements);
+        }
     };
 
-    match compiled_schema.validate(&instance_value) {
-        Ok(_) => atoms::ok().encode(env),
-        Err(JsonSchemaError::ValidationError(errors)) => {
-            let error_terms: Vec<Term> = errors
-                .iter()
-                .map(|error| {
-                    let error_map = rustler::types::map::map_new(env)
-                        .map_put("instance_path".encode(env), error.instance_path.encode(env))
-                        .unwrap()
-                        .map_put("schema_path".encode(env), error.schema_path.encode(env))
-                        .unwrap()
-                        .map_put("message".encode(env), error.message.encode(env))
-                        .unwrap();
-                    error_map
-                })
+    let prefix = instance_pointer.unwrap_or_default();
+    let target = match (&schema_pointer, instance_value.pointer(&prefix)) {
+        (_, None) => Err(format!("The instance has no value at '{}'", prefix)),
+        (Some(pointer), Some(_)) => compiled_schema
+            .root()
+            .and_then(|root| subschema::resolve(root, pointer))
+            .map(subschema::Target::Schema),
+        // Encoded content is found by walking down from the root
+        (None, Some(_)) if prefix.is_empty() || compiled_schema.options.validate_content => {
+            Ok(subschema::Target::Whole)
+        }
+        (None, Some(_)) => compiled_schema
+            .root()
+            .and_then(|root| subschema::locate(root, &instance_value, &prefix)),
+    };
+    let target = match target {
+        Ok(target) => target,
+        Err(details) => {
+            let result = error_tuple(env, "pointer_error", "Invalid pointer", &details);
+            return finish(result, &measurements);
+        }
+    };
+    let subtree = instance_value.pointer(&prefix).unwrap_or(&Value::Null);
+
+    let errors = match &target {
+        subschema::Target::Unconstrained => {
+            measurements.validation_time = Some(stopwatch.lap());
+            Vec::new()
+        }
+        subschema::Target::Schema(location) => {
+            let fragment = match compiled_schema.fragment(location) {
+                Ok((fragment, built)) => {
+                    if built {
+                        measurements.build_time = Some(stopwatch.lap());
+                    }
+                    fragment
+                }
+                Err(details) => {
+                    let result = error_tuple(
+                        env,
+                        "compilation_error",
+                        "Schema compilation failed",
+                        &details,
+                    );
+                    return finish(result, &measurements);
+                }
+            };
+
+            let is_valid = fragment.validator.is_valid(subtree);
+            measurements.validation_time = Some(stopwatch.lap());
+
+            if is_valid {
+                Vec::new()
+            } else if !detailed {
+                return finish(atoms::error().encode(env), &measurements);
+            } else {
+                let errors = compiled_schema.fragment_errors(&fragment, &prefix, &instance_value);
+                measurements.enrichment_time = Some(stopwatch.lap());
+                errors
+            }
+        }
+        subschema::Target::Whole => {
+            // Errors elsewhere in the document do not count against the subtree
+            let errors: Vec<ValidationErrorDetail> = compiled_schema
+                .errors(&instance_value, None)
+                .into_iter()
+                .filter(|error| subschema::is_within(&error.instance_path, &prefix))
                 .collect();
+            measurements.validation_time = Some(stopwatch.lap());
 
-            (atoms::error(), error_terms).encode(env)
+            if !errors.is_empty() && !detailed {
+                return finish(atoms::error().encode(env), &measurements);
+            }
+            errors
         }
-        Err(_) => (atoms::error(), atoms::validation_error()).encode(env),
-    }
+    };
+    measurements.error_count = Some(errors.len());
+
+    let result = if errors.is_empty() {
+        atoms::ok().encode(env)
+    } else {
+        let error_terms: Vec<Term> = errors
+            .iter()
+            .map(|error| encode_error_detail(env, error))
+            .collect();
+        (atoms::error(), error_terms).encode(env)
+    };
+    measurements.encode_time = Some(stopwatch.lap());
+
+    finish(result, &measurements)
 }
 
-#[rustler::nif]
-fn valid(compiled_schema: ResourceArc<CompiledSchema>, instance_json: String) -> bool {
-    let instance_value: Value = serde_json::from_str(&instance_json).unwrap_or(Value::Null);
+fn encode_error_detail<'a>(env: Env<'a>, error: &ValidationErrorDetail) -> Term<'a> {
+    let map = rustler::types::map::map_new(env)
+        .map_put("instance_path".encode(env), error.instance_path.encode(env))
+        .unwrap()
+        .map_put("schema_path".encode(env), error.schema_path.encode(env))
+        .unwrap()
+        .map_put("message".encode(env), error.message.encode(env))
+        .unwrap()
+        .map_put("keyword".encode(env), error.keyword.encode(env))
+        .unwrap()
+        .map_put("params".encode(env), encode_json_value(env, &error.params))
+        .unwrap();
+    encode_branches(
+        env,
+        map,
+        &error.branch,
+        &error.branches,
+        encode_error_detail,
+    )
+}
 
-    compiled_schema.is_valid(&instance_value)
+// Adds "branch" and "branches" to an encoded error, when it has them
+fn encode_branches<'a, T>(
+    env: Env<'a>,
+    mut map: Term<'a>,
+    branch: &Option<Branch>,
+    branches: &[Vec<T>],
+    encode: fn(Env<'a>, &T) -> Term<'a>,
+) -> Term<'a> {
+    if let Some(branch) = branch {
+        let encoded = rustler::types::map::map_new(env)
+            .map_put("index".encode(env), branch.index.encode(env))
+            .unwrap()
+            .map_put(
+                "schema_pointer".encode(env),
+                branch.schema_pointer.encode(env),
+            )
+            .unwrap();
+        map = map.map_put("branch".encode(env), encoded).unwrap();
+    }
+    if !branches.is_empty() {
+        let encoded: Vec<Vec<Term>> = branches
+            .iter()
+            .map(|errors| errors.iter().map(|error| encode(env, error)).collect())
+            .collect();
+        map = map.map_put("branches".encode(env), encoded).unwrap();
+    }
+    map
 }
 
 #[rustler::nif]
-fn detect_draft_from_schema(env: Env, schema_json: String) -> Term {
-    let schema_value: Value = match serde_json::from_str(&schema_json) {
-        Ok(value) => value,
+fn patch_and_validate(
+    env: Env,
+    compiled_schema: ResourceArc<CompiledSchema>,
+    document_json: String,
+    patch_json: String,
+    merge: bool,
+    incremental: bool,
+) -> Term {
+    let mut stopwatch = timing::Stopwatch::start();
+    let mut measurements = timing::Measurements::default();
+    let finish = |result: Term<'_>, measurements: &timing::Measurements| {
+        (result, encode_measurements(env, measurements)).encode(env)
+    };
+
+    let parsed = serde_json::from_str::<Value>(&document_json).and_then(|document| {
+        serde_json::from_str::<Value>(&patch_json).map(|patch| (document, patch))
+    });
+    measurements.parse_time = Some(stopwatch.lap());
+    let (mut document, patch) = match parsed {
+        Ok(parsed) => parsed,
         Err(e) => {
-            let error_map = rustler::types::map::map_new(env)
-                .map_put("type".encode(env), "json_parse_error".encode(env))
-                .unwrap()
-                .map_put("message".encode(env), "Invalid JSON".encode(env))
+            let result = error_tuple(env, "json_parse_error", "Invalid JSON", &e.to_string());
+            return finish(result, &measurements);
+        }
+    };
+
+    let changes = if merge {
+        Ok(patch::apply_merge_patch(&mut document, &patch))
+    } else {
+        patch::apply_json_patch(&mut document, &patch)
+    };
+    measurements.patch_time = Some(stopwatch.lap());
+    let changes = match changes {
+        Ok(changes) => changes,
+        Err(error) => {
+            let result = rustler::types::map::map_new(env)
+                .map_put("type".encode(env), "patch_error".encode(env))
                 .unwrap()
                 .map_put(
-                    "details".encode(env),
-                    format!("Failed to parse JSON: {}", e).encode(env),
+                    "message".encode(env),
+                    "Patch could not be applied".encode(env),
                 )
+                .unwrap()
+                .map_put("details".encode(env), error.message.encode(env))
+                .unwrap()
+                .map_put("operation".encode(env), error.operation.encode(env))
                 .unwrap();
-            return (atoms::error(), error_map).encode(env);
+            return finish((atoms::error(), result).encode(env), &measurements);
         }
     };
 
-    // Try to detect draft from $schema property
-    if let Some(schema_url) = schema_value.get("$schema") {
-        if let Some(url_str) = schema_url.as_str() {
-            let draft = match url_str {
-                url if url.contains("draft-04") || url.contains("draft/04") => atoms::draft4(),
-                url if url.contains("draft-06") || url.contains("draft/06") => atoms::draft6(),
-                url if url.contains("draft-07") || url.contains("draft/07") => atoms::draft7(),
-                url if url.contains("2019-09") => atoms::draft201909(),
-                url if url.contains("2020-12") => atoms::draft202012(),
-                _ => atoms::draft202012(), // Default to latest
-            };
-            return (atoms::ok(), draft).encode(env);
+    // Re-checking only the changed subtrees assumes the rest of the document
+    // was valid before the patch
+    let subtrees = if incremental && !compiled_schema.options.validate_content {
+        isolated_subtrees(&compiled_schema, &document, &changes)
+    } else {
+        None
+    };
+    let errors = match subtrees {
+        Some(subtrees) => {
+            let mut errors = Vec::new();
+            for (prefix, location) in subtrees {
+                let fragment = match compiled_schema.fragment(&location) {
+                    Ok((fragment, _)) => fragment,
+                    Err(details) => {
+                        let result = error_tuple(
+                            env,
+                            "compilation_error",
+                            "Schema compilation failed",
+                            &details,
+                        );
+                        return finish(result, &measurements);
+                    }
+                };
+                let subtree = document.pointer(&prefix).unwrap_or(&Value::Null);
+                if !fragment.validator.is_valid(subtree) {
+                    errors.extend(compiled_schema.fragment_errors(&fragment, &prefix, &document));
+                }
+            }
+            errors
         }
-    }
+        None => compiled_schema.errors(&document, None),
+    };
+    measurements.validation_time = Some(stopwatch.lap());
+    measurements.error_count = Some(errors.len());
+
+    let result = if errors.is_empty() {
+        match serde_json::to_string(&document) {
+            Ok(json) => (atoms::ok(), json).encode(env),
+            Err(e) => error_tuple(
+                env,
+                "json_parse_error",
+                "Failed to serialize the patched document",
+                &e.to_string(),
+            ),
+        }
+    } else {
+        let error_terms: Vec<Term> = errors
+            .iter()
+            .map(|error| {
+                let responsible = match changes.responsible(&error.instance_path) {
+                    Some(patch::Responsible::Operation(index)) => index.encode(env),
+                    Some(patch::Responsible::Member(pointer)) => pointer.encode(env),
+                    None => atoms::nil().encode(env),
+                };
+                (responsible, encode_error_detail(env, error)).encode(env)
+            })
+            .collect();
+        (atoms::error(), error_terms).encode(env)
+    };
+    measurements.encode_time = Some(stopwatch.lap());
 
-    // Default to latest draft if no $schema or unrecognized
-    (atoms::ok(), atoms::draft202012()).encode(env)
+    finish(result, &measurements)
+}
+
+// The changed subtrees of `document` with the subschemas that fully decide
+// their validity, or None when the whole document has to be validated
+fn isolated_subtrees(
+    compiled_schema: &CompiledSchema,
+    document: &Value,
+    changes: &patch::Changes,
+) -> Option<Vec<(String, subschema::Location)>> {
+    let root = compiled_schema.root().ok()?;
+    let mut subtrees = Vec::new();
+
+    for container in changes.containers() {
+        if container.is_empty() {
+            return None;
+        }
+        // Gone after a later operation, which then changed one of its ancestors
+        if document.pointer(&container).is_none() {
+            continue;
+        }
+        match subschema::locate_isolated(root, document, &container) {
+            Ok(subschema::Target::Schema(location)) => subtrees.push((container, location)),
+            Ok(subschema::Target::Unconstrained) => {}
+            Ok(subschema::Target::Whole) | Err(_) => return None,
+        }
+    }
+    Some(subtrees)
 }
 
 #[rustler::nif]
-fn validate_verbose(
+fn suggest_fixes(
     env: Env,
     compiled_schema: ResourceArc<CompiledSchema>,
     instance_json: String,
 ) -> Term {
     let instance_value: Value = match serde_json::from_str(&instance_json) {
         Ok(value) => value,
-        Err(_) => return (atoms::error(), atoms::json_parse_error()).encode(env),
+        Err(e) => return error_tuple(env, "json_parse_error", "Invalid JSON", &e.to_string()),
     };
 
-    match compiled_schema.validate_verbose(&instance_value) {
-        Ok(_) => atoms::ok().encode(env),
-        Err(verbose_errors) => {
-            let error_terms: Vec<Term> = verbose_errors
-                .iter()
-                .map(|error| {
-                    // Convert HashMap<String, Value> to Elixir map
-                    let mut context_map = rustler::types::map::map_new(env);
-                    for (key, value) in &error.context {
-                        context_map = context_map
-                            .map_put(key.encode(env), encode_json_value(env, value))
-                            .unwrap();
-                    }
-
-                    let mut annotations_map = rustler::types::map::map_new(env);
-                    for (key, value) in &error.annotations {
-                        annotations_map = annotations_map
-                            .map_put(key.encode(env), encode_json_value(env, value))
-                            .unwrap();
-                    }
-
-                    let suggestions_list: Vec<Term> =
-                        error.suggestions.iter().map(|s| s.encode(env)).collect();
+    match fixes::suggest(
+        &compiled_schema.schema,
+        compiled_schema.draft(),
+        &compiled_schema.validator,
+        &instance_value,
+        compiled_schema.options.redaction.as_ref(),
+    ) {
+        Ok(operations) => (
+            atoms::ok(),
+            encode_json_value(env, &Value::Array(operations)),
+        )
+            .encode(env),
+        Err(details) => error_tuple(
+            env,
+            "compilation_error",
+            "Failed to resolve schema references",
+            &details,
+        ),
+    }
+}
 
-                    let error_map = rustler::types::map::map_new(env)
-                        .map_put("instance_path".encode(env), error.instance_path.encode(env))
-                        .unwrap()
-                        .map_put("schema_path".encode(env), error.schema_path.encode(env))
-                        .unwrap()
-                        .map_put("message".encode(env), error.message.encode(env))
-                        .unwrap()
-                        .map_put("keyword".encode(env), error.keyword.encode(env))
-                        .unwrap()
-                        .map_put(
-                            "instance_value".encode(env),
-                            encode_json_value(env, &error.instance_value),
-                        )
-                        .unwrap()
-                        .map_put(
-                            "schema_value".encode(env),
-                            encode_json_value(env, &error.schema_value),
-                        )
-                        .unwrap()
-                        .map_put("context".encode(env), context_map)
-                        .unwrap()
-                        .map_put("annotations".encode(env), annotations_map)
-                        .unwrap()
-                        .map_put("suggestions".encode(env), suggestions_list)
-                        .unwrap();
-                    error_map
-                })
-                .collect();
+#[rustler::nif]
+fn best_error(
+    env: Env,
+    compiled_schema: ResourceArc<CompiledSchema>,
+    instance_json: String,
+) -> Term {
+    let instance_value: Value = match serde_json::from_str(&instance_json) {
+        Ok(value) => value,
+        Err(e) => return error_tuple(env, "json_parse_error", "Invalid JSON", &e.to_string()),
+    };
 
-            (atoms::error(), error_terms).encode(env)
-        }
+    if compiled_schema.is_valid(&instance_value, None) {
+        return rustler::types::atom::nil().encode(env);
+    }
+    let errors = compiled_schema.errors(&instance_value, None);
+    match ranking::best(&errors) {
+        Some(error) => encode_error_detail(env, error),
+        None => rustler::types::atom::nil().encode(env),
     }
 }
 
-// Helper function to encode serde_json::Value to Rustler Term
-fn encode_json_value<'a>(env: Env<'a>, value: &Value) -> Term<'a> {
+#[rustler::nif]
+fn valid<'a>(
+    env: Env<'a>,
+    compiled_schema: ResourceArc<CompiledSchema>,
+    instance: Term<'a>,
+    direction: Option<Atom>,
+    input_format: Atom,
+) -> Term<'a> {
+    let mut stopwatch = timing::Stopwatch::start();
+    let mut measurements = timing::Measurements::default();
+
+    let instance_value = read_instance(env, instance, input_format)
+        .ok()
+        .map(|(value, _)| value);
+    measurements.parse_time = Some(stopwatch.lap());
+
+    // Input that does not decode is valid against no schema
+    let direction = direction.and_then(direction_from_atom);
+    let is_valid = instance_value
+        .is_some_and(|instance_value| compiled_schema.is_valid(&instance_value, direction));
+    measurements.validation_time = Some(stopwatch.lap());
+
+    (is_valid, encode_measurements(env, &measurements)).encode(env)
+}
+
+#[rustler::nif]
+fn detect_draft_from_schema(env: Env, schema_json: String) -> Term {
+    let schema_value: Value = match serde_json::from_str(&schema_json) {
+        Ok(value) => value,
+        Err(e) => {
+            let error_map = rustler::types::map::map_new(env)
+                .map_put("type".encode(env), "json_parse_error".encode(env))
+                .unwrap()
+                .map_put("message".encode(env), "Invalid JSON".encode(env))
+                .unwrap()
+                .map_put(
+                    "details".encode(env),
+                    format!("Failed to parse JSON: {}", e).encode(env),
+                )
+                .unwrap();
+            return (atoms::error(), error_map).encode(env);
+        }
+    };
+
+    // Default to latest draft if no $schema or unrecognized
+    let schema_url = schema_value.get("$schema").and_then(Value::as_str);
+    if let Some(dialect) = schema_url.and_then(openapi::Dialect::from_schema_url) {
+        return (atoms::ok(), dialect_to_atom(dialect)).encode(env);
+    }
+    let draft = schema_url
+        .map(draft_from_schema_url)
+        .unwrap_or(jsonschema::Draft::Draft202012);
+
+    (atoms::ok(), draft_to_atom(draft)).encode(env)
+}
+
+#[rustler::nif]
+fn validate_verbose<'a>(
+    env: Env<'a>,
+    compiled_schema: ResourceArc<CompiledSchema>,
+    instance: Term<'a>,
+    ranked: bool,
+    direction: Option<Atom>,
+    input_format: Atom,
+) -> Term<'a> {
+    let mut stopwatch = timing::Stopwatch::start();
+    let mut measurements = timing::Measurements::default();
+
+    let (instance_value, source_map) = match read_instance(env, instance, input_format) {
+        Ok(read) => read,
+        Err(result) => {
+            measurements.parse_time = Some(stopwatch.lap());
+            return (result, encode_measurements(env, &measurements)).encode(env);
+        }
+    };
+    measurements.parse_time = Some(stopwatch.lap());
+
+    let direction = direction.and_then(direction_from_atom);
+    let is_valid = compiled_schema.is_valid(&instance_value, direction);
+    measurements.validation_time = Some(stopwatch.lap());
+
+    let result = if is_valid {
+        measurements.error_count = Some(0);
+        atoms::ok().encode(env)
+    } else {
+        let mut verbose_errors = compiled_schema.verbose_errors(&instance_value, direction);
+        if ranked {
+            ranking::sort(&mut verbose_errors);
+        }
+        measurements.enrichment_time = Some(stopwatch.lap());
+        measurements.error_count = Some(verbose_errors.len());
+
+        let error_terms: Vec<Term> = verbose_errors
+            .iter()
+            .map(|error| {
+                let map = encode_segments(
+                    env,
+                    encode_verbose_error(env, error),
+                    &error.instance_path,
+                    &instance_value,
+                );
+                encode_positions(
+                    env,
+                    map,
+                    &error.instance_path,
+                    &error.schema_path,
+                    source_map.as_ref(),
+                    compiled_schema.source_map.as_ref(),
+                )
+            })
+            .collect();
+
+        (atoms::error(), error_terms).encode(env)
+    };
+    measurements.encode_time = Some(stopwatch.lap());
+
+    (result, encode_measurements(env, &measurements)).encode(env)
+}
+
+fn encode_verbose_error<'a>(env: Env<'a>, error: &VerboseValidationErrorDetail) -> Term<'a> {
+    // Convert HashMap<String, Value> to Elixir map
+    let mut context_map = rustler::types::map::map_new(env);
+    for (key, value) in &error.context {
+        context_map = context_map
+            .map_put(key.encode(env), encode_json_value(env, value))
+            .unwrap();
+    }
+
+    let mut annotations_map = rustler::types::map::map_new(env);
+    for (key, value) in &error.annotations {
+        annotations_map = annotations_map
+            .map_put(key.encode(env), encode_json_value(env, value))
+            .unwrap();
+    }
+
+    let suggestions_list: Vec<Term> = error.suggestions.iter().map(|s| s.encode(env)).collect();
+
+    let map = rustler::types::map::map_new(env)
+        .map_put("instance_path".encode(env), error.instance_path.encode(env))
+        .unwrap()
+        .map_put("schema_path".encode(env), error.schema_path.encode(env))
+        .unwrap()
+        .map_put("message".encode(env), error.message.encode(env))
+        .unwrap()
+        .map_put("keyword".encode(env), error.keyword.encode(env))
+        .unwrap()
+        .map_put("params".encode(env), encode_json_value(env, &error.params))
+        .unwrap()
+        .map_put(
+            "instance_value".encode(env),
+            encode_json_value(env, &error.instance_value),
+        )
+        .unwrap()
+        .map_put(
+            "schema_value".encode(env),
+            encode_json_value(env, &error.schema_value),
+        )
+        .unwrap()
+        .map_put("context".encode(env), context_map)
+        .unwrap()
+        .map_put("annotations".encode(env), annotations_map)
+        .unwrap()
+        .map_put("suggestions".encode(env), suggestions_list)
+        .unwrap();
+    encode_branches(
+        env,
+        map,
+        &error.branch,
+        &error.branches,
+        encode_verbose_error,
+    )
+}
+
+// Adds the segments of an error's instance path, told apart as array indices
+// and object keys by the instance
+fn encode_segments<'a>(
+    env: Env<'a>,
+    map: Term<'a>,
+    instance_path: &str,
+    instance: &Value,
+) -> Term<'a> {
+    let segments: Vec<Term> = structured::instance_segments(instance, instance_path)
+        .iter()
+        .map(|segment| encode_json_value(env, segment))
+        .collect();
+    map.map_put("instance_segments".encode(env), segments)
+        .unwrap()
+}
+
+// Adds where an error is in the YAML or TOML documents its instance and
+// schema were read from
+fn encode_positions<'a>(
+    env: Env<'a>,
+    mut map: Term<'a>,
+    instance_path: &str,
+    schema_path: &str,
+    instance_source: Option<&input::SourceMap>,
+    schema_source: Option<&input::SourceMap>,
+) -> Term<'a> {
+    let positions = [
+        (
+            "instance_position",
+            instance_source.and_then(|source| source.position(instance_path)),
+        ),
+        (
+            "schema_position",
+            schema_source.and_then(|source| source.schema_position(schema_path)),
+        ),
+    ];
+    for (key, position) in positions {
+        if let Some(position) = position {
+            let encoded = rustler::types::map::map_new(env)
+                .map_put("line".encode(env), position.line.encode(env))
+                .unwrap()
+                .map_put("column".encode(env), position.column.encode(env))
+                .unwrap();
+            map = map.map_put(key.encode(env), encoded).unwrap();
+        }
+    }
+    map
+}
+
+// Errors for documents that do not parse. JSON ones are reported as they
+// always were; YAML and TOML ones say where parsing stopped, and MessagePack
+// and CBOR ones what could not be decoded.
+fn parse_error_tuple<'a>(
+    env: Env<'a>,
+    format: input::InputFormat,
+    error: &input::ParseError,
+) -> Term<'a> {
+    match format {
+        input::InputFormat::Json => {
+            let error_map = rustler::types::map::map_new(env)
+                .map_put("type".encode(env), "json_parse_error".encode(env))
+                .unwrap()
+                .map_put("message".encode(env), error.to_string().encode(env))
+                .unwrap();
+            (atoms::error(), error_map).encode(env)
+        }
+        _ => error_tuple(
+            env,
+            "parse_error",
+            &format!("Invalid {}", format.name()),
+            &error.to_string(),
+        ),
+    }
+}
+
+// The instance a validation NIF was given, read as `input_format` names: a
+// term with `:term`, otherwise a binary to decode. On failure, the error to
+// return. Text instances fail as they always have; MessagePack, CBOR and
+// term ones say what could not be read.
+fn read_instance<'a>(
+    env: Env<'a>,
+    instance: Term<'a>,
+    input_format: Atom,
+) -> Result<(Value, Option<input::SourceMap>), Term<'a>> {
+    if input_format == atoms::term() {
+        return terms::to_json(instance)
+            .map(|value| (value, None))
+            .map_err(|details| error_tuple(env, "parse_error", "Invalid term", &details));
+    }
+    let format = input_format_from_atom(input_format);
+    let bytes = match instance.decode::<rustler::Binary>() {
+        Ok(binary) => binary,
+        Err(_) => return Err((atoms::error(), atoms::json_parse_error()).encode(env)),
+    };
+    input::decode(bytes.as_slice(), format).map_err(|e| match format {
+        input::InputFormat::MessagePack | input::InputFormat::Cbor => {
+            parse_error_tuple(env, format, &e)
+        }
+        _ => (atoms::error(), atoms::json_parse_error()).encode(env),
+    })
+}
+
+// Translates compile options coming from Elixir into validator build options
+fn build_options(options: &ValidationOptionsStruct) -> snapshot::BuildOptions {
+    let regex_engine = if options.regex_engine == atoms::regex() {
+        snapshot::RegexEngine::Regex
+    } else {
+        snapshot::RegexEngine::FancyRegex
+    };
+
+    snapshot::BuildOptions {
+        // Set draft version if not auto (unknown drafts use the default)
+        draft: draft_from_atom(options.draft),
+        dialect: dialect_from_atom(options.draft),
+        validate_formats: options.validate_formats.then_some(true),
+        regex_engine: Some(regex_engine),
+        formats: formats::FormatPolicy {
+            allow: options.allowed_formats.clone(),
+            deny: options.denied_formats.clone(),
+            reject_unknown: options.reject_unknown_formats,
+        },
+        redaction: options.redact.then(|| redact::Redaction {
+            keyword: options.redact_keyword.clone(),
+            pointers: options.redact_pointers.clone(),
+            mask: options.redact_mask.clone(),
+        }),
+        validate_content: options.validate_content,
+        infer_discriminators: options.infer_discriminators,
+    }
+}
+
+// Maps a draft atom to the jsonschema draft it names, `None` for `:auto`.
+// OpenAPI dialects map to the draft they extend.
+fn draft_from_atom(draft: Atom) -> Option<jsonschema::Draft> {
+    if let Some(dialect) = dialect_from_atom(draft) {
+        Some(dialect.draft())
+    } else if draft == atoms::draft4() {
+        Some(jsonschema::Draft::Draft4)
+    } else if draft == atoms::draft6() {
+        Some(jsonschema::Draft::Draft6)
+    } else if draft == atoms::draft7() {
+        Some(jsonschema::Draft::Draft7)
+    } else if draft == atoms::draft201909() {
+        Some(jsonschema::Draft::Draft201909)
+    } else if draft == atoms::draft202012() {
+        Some(jsonschema::Draft::Draft202012)
+    } else {
+        None
+    }
+}
+
+fn input_format_from_atom(input_format: Atom) -> input::InputFormat {
+    if input_format == atoms::yaml() {
+        input::InputFormat::Yaml
+    } else if input_format == atoms::toml() {
+        input::InputFormat::Toml
+    } else if input_format == atoms::msgpack() {
+        input::InputFormat::MessagePack
+    } else if input_format == atoms::cbor() {
+        input::InputFormat::Cbor
+    } else {
+        input::InputFormat::Json
+    }
+}
+
+fn dialect_from_atom(draft: Atom) -> Option<openapi::Dialect> {
+    if draft == atoms::openapi30() {
+        Some(openapi::Dialect::OpenApi30)
+    } else if draft == atoms::openapi31() {
+        Some(openapi::Dialect::OpenApi31)
+    } else {
+        None
+    }
+}
+
+fn direction_from_atom(direction: Atom) -> Option<direction::Direction> {
+    if direction == atoms::request() {
+        Some(direction::Direction::Request)
+    } else if direction == atoms::response() {
+        Some(direction::Direction::Response)
+    } else {
+        None
+    }
+}
+
+fn dialect_to_atom(dialect: openapi::Dialect) -> Atom {
+    match dialect {
+        openapi::Dialect::OpenApi30 => atoms::openapi30(),
+        openapi::Dialect::OpenApi31 => atoms::openapi31(),
+    }
+}
+
+fn draft_to_atom(draft: jsonschema::Draft) -> Atom {
+    match draft {
+        jsonschema::Draft::Draft4 => atoms::draft4(),
+        jsonschema::Draft::Draft6 => atoms::draft6(),
+        jsonschema::Draft::Draft7 => atoms::draft7(),
+        jsonschema::Draft::Draft201909 => atoms::draft201909(),
+        _ => atoms::draft202012(),
+    }
+}
+
+// Detects the draft named by a `$schema` URL, defaulting to the latest
+fn draft_from_schema_url(url: &str) -> jsonschema::Draft {
+    match url {
+        url if url.contains("draft-04") || url.contains("draft/04") => jsonschema::Draft::Draft4,
+        url if url.contains("draft-06") || url.contains("draft/06") => jsonschema::Draft::Draft6,
+        url if url.contains("draft-07") || url.contains("draft/07") => jsonschema::Draft::Draft7,
+        url if url.contains("2019-09") => jsonschema::Draft::Draft201909,
+        _ => jsonschema::Draft::Draft202012,
+    }
+}
+
+// Builds the `{:error, %{"type" => ..., "message" => ..., "details" => ...}}` shape
+fn error_tuple<'a>(env: Env<'a>, error_type: &str, message: &str, details: &str) -> Term<'a> {
+    let error_map = rustler::types::map::map_new(env)
+        .map_put("type".encode(env), error_type.encode(env))
+        .unwrap()
+        .map_put("message".encode(env), message.encode(env))
+        .unwrap()
+        .map_put("details".encode(env), details.encode(env))
+        .unwrap();
+    (atoms::error(), error_map).encode(env)
+}
+
+// Encodes NIF timings as a map of atom keys, leaving out phases that did not run
+fn encode_measurements<'a>(env: Env<'a>, measurements: &timing::Measurements) -> Term<'a> {
+    let timings = [
+        (atoms::parse_time(), measurements.parse_time),
+        (atoms::patch_time(), measurements.patch_time),
+        (atoms::build_time(), measurements.build_time),
+        (atoms::validation_time(), measurements.validation_time),
+        (atoms::enrichment_time(), measurements.enrichment_time),
+        (atoms::encode_time(), measurements.encode_time),
+    ];
+
+    let mut map = rustler::types::map::map_new(env);
+    for (key, value) in timings {
+        if let Some(value) = value {
+            map = map.map_put(key.encode(env), value.encode(env)).unwrap();
+        }
+    }
+    if let Some(error_count) = measurements.error_count {
+        map = map
+            .map_put(atoms::error_count().encode(env), error_count.encode(env))
+            .unwrap();
+    }
+    map
+}
+
+// Helper function to encode serde_json::Value to Rustler Term
+fn encode_json_value<'a>(env: Env<'a>, value: &Value) -> Term<'a> {
     match value {
         Value::Null => atoms::nil().encode(env),
         Value::Bool(b) => b.encode(env),
@@ -946,225 +2051,897 @@ fn generate_suggestions_for_error(
 // Meta-validation functions
 #[rustler::nif]
 fn meta_is_valid(env: Env, schema_json: String) -> Term {
-    let schema_value: Value = match serde_json::from_str(&schema_json) {
-        Ok(value) => value,
-        Err(e) => {
-            let error_map = rustler::types::map::map_new(env)
-                .map_put("type".encode(env), "json_parse_error".encode(env))
-                .unwrap()
-                .map_put("message".encode(env), "Invalid JSON".encode(env))
-                .unwrap()
-                .map_put(
-                    "details".encode(env),
-                    format!("Failed to parse JSON: {}", e).encode(env),
-                )
-                .unwrap();
-            return (atoms::error(), error_map).encode(env);
+    timed_meta_validation(env, &schema_json, |env, schema_value| {
+        // Use jsonschema::meta::is_valid to check if schema is valid
+        // Handle potential panic with catch_unwind
+        let result = std::panic::catch_unwind(|| jsonschema::meta::is_valid(schema_value));
+
+        // If panic occurred, assume invalid schema
+        let is_valid = result.unwrap_or(false);
+        ((atoms::ok(), is_valid).encode(env), usize::from(!is_valid))
+    })
+}
+
+#[rustler::nif]
+fn meta_validate(env: Env, schema_json: String) -> Term {
+    timed_meta_validation(env, &schema_json, |env, schema_value| {
+        // Use jsonschema::meta::validate to get detailed validation results
+        // Handle potential panic with catch_unwind
+        let result = std::panic::catch_unwind(|| {
+            jsonschema::meta::validate(schema_value).map_err(|error| error.to_string())
+        });
+
+        match result {
+            Ok(Ok(_)) => (atoms::ok().encode(env), 0),
+            Ok(Err(error)) => (
+                error_tuple(
+                    env,
+                    "meta_validation_error",
+                    "Schema meta-validation failed",
+                    &error,
+                ),
+                1,
+            ),
+            Err(_) => (
+                error_tuple(
+                    env,
+                    "meta_validation_error",
+                    "Meta-validation not supported for this schema format",
+                    "Unknown or unsupported $schema specification",
+                ),
+                1,
+            ),
         }
+    })
+}
+
+#[rustler::nif]
+fn meta_validate_detailed(env: Env, schema_json: String) -> Term {
+    timed_meta_validation(env, &schema_json, |env, schema_value| {
+        // Try to compile as a validator to get detailed meta-validation errors
+        let compilation_result = std::panic::catch_unwind(|| {
+            jsonschema::validator_for(schema_value).map_err(|error| error.to_string())
+        });
+
+        let failure = match compilation_result {
+            Ok(Ok(_validator)) => {
+                // Schema is valid for compilation, now check meta-validation
+                let meta_result = std::panic::catch_unwind(|| {
+                    jsonschema::meta::validate(schema_value).map_err(|error| error.to_string())
+                });
+
+                match meta_result {
+                    Ok(Ok(_)) => None,
+                    Ok(Err(error)) => Some((error, "meta")),
+                    // Meta-validation panicked (unsupported $schema)
+                    Err(_) => Some((
+                        "Meta-validation not supported for this schema format".to_string(),
+                        "meta",
+                    )),
+                }
+            }
+            // Schema has compilation errors
+            Ok(Err(compilation_error)) => Some((compilation_error, "compilation")),
+            // Compilation panicked
+            Err(_) => Some((
+                "Schema compilation failed due to unsupported format".to_string(),
+                "compilation",
+            )),
+        };
+
+        match failure {
+            None => (atoms::ok().encode(env), 0),
+            Some((message, keyword)) => {
+                let error_details = rustler::types::map::map_new(env)
+                    .map_put("instance_path".encode(env), "".encode(env))
+                    .unwrap()
+                    .map_put("schema_path".encode(env), "".encode(env))
+                    .unwrap()
+                    .map_put("message".encode(env), message.encode(env))
+                    .unwrap()
+                    .map_put("keyword".encode(env), keyword.encode(env))
+                    .unwrap();
+
+                ((atoms::error(), vec![error_details]).encode(env), 1)
+            }
+        }
+    })
+}
+
+// Parses `schema_json`, runs `check` on it and returns `{result, measurements}`.
+// `check` returns the encoded result and the number of errors it found.
+fn timed_meta_validation<'a>(
+    env: Env<'a>,
+    schema_json: &str,
+    check: impl FnOnce(Env<'a>, &Value) -> (Term<'a>, usize),
+) -> Term<'a> {
+    let mut stopwatch = timing::Stopwatch::start();
+    let mut measurements = timing::Measurements::default();
+
+    let parsed = serde_json::from_str::<Value>(schema_json);
+    measurements.parse_time = Some(stopwatch.lap());
+
+    let result = match parsed {
+        Ok(schema_value) => {
+            let (result, error_count) = check(env, &schema_value);
+            measurements.validation_time = Some(stopwatch.lap());
+            measurements.error_count = Some(error_count);
+            result
+        }
+        Err(e) => error_tuple(
+            env,
+            "json_parse_error",
+            "Invalid JSON",
+            &format!("Failed to parse JSON: {}", e),
+        ),
     };
 
-    // Use jsonschema::meta::is_valid to check if schema is valid
-    // Handle potential panic with catch_unwind
-    let result = std::panic::catch_unwind(|| jsonschema::meta::is_valid(&schema_value));
+    (result, encode_measurements(env, &measurements)).encode(env)
+}
 
-    match result {
-        Ok(is_valid) => (atoms::ok(), is_valid).encode(env),
-        Err(_) => {
-            // If panic occurred, assume invalid schema
-            (atoms::ok(), false).encode(env)
+#[rustler::nif]
+fn compile_schema_with_options(
+    env: Env,
+    schema_json: String,
+    options: ValidationOptionsStruct,
+) -> Term {
+    let mut stopwatch = timing::Stopwatch::start();
+    let mut measurements = timing::Measurements::default();
+
+    let format = input_format_from_atom(options.input_format);
+    let result = match input::parse(&schema_json, format) {
+        Err(e) => {
+            measurements.parse_time = Some(stopwatch.lap());
+            parse_error_tuple(env, format, &e)
         }
+        Ok((schema_value, source_map)) => {
+            measurements.parse_time = Some(stopwatch.lap());
+            let compiled = CompiledSchema::new_with_options(schema_value, options);
+            measurements.build_time = Some(stopwatch.lap());
+
+            match compiled {
+                Ok(mut compiled) => {
+                    compiled.source_map = source_map;
+                    (atoms::ok(), ResourceArc::new(compiled)).encode(env)
+                }
+                Err(e) => {
+                    let error_map = rustler::types::map::map_new(env)
+                        .map_put("type".encode(env), "compilation_error".encode(env))
+                        .unwrap()
+                        .map_put("message".encode(env), e.to_string().encode(env))
+                        .unwrap();
+                    (atoms::error(), error_map).encode(env)
+                }
+            }
+        }
+    };
+    measurements.encode_time = Some(stopwatch.lap());
+
+    (result, encode_measurements(env, &measurements)).encode(env)
+}
+
+#[rustler::nif]
+fn dereference_schema(
+    env: Env,
+    compiled_schema: ResourceArc<CompiledSchema>,
+    cycles: Atom,
+) -> Term {
+    let policy = if cycles == atoms::keep() {
+        dereference::CyclePolicy::Keep
+    } else {
+        dereference::CyclePolicy::Error
+    };
+
+    match dereference::dereference(
+        &compiled_schema.source,
+        compiled_schema.validator.draft(),
+        policy,
+    ) {
+        Ok(schema) => (atoms::ok(), encode_json_value(env, &schema)).encode(env),
+        Err(e) => error_tuple(env, "dereference_error", &e.message(), &e.details()),
     }
 }
 
 #[rustler::nif]
-fn meta_validate(env: Env, schema_json: String) -> Term {
+fn migrate_schema(env: Env, schema_json: String, from: Atom, to: Atom) -> Term {
     let schema_value: Value = match serde_json::from_str(&schema_json) {
         Ok(value) => value,
         Err(e) => {
-            let error_map = rustler::types::map::map_new(env)
-                .map_put("type".encode(env), "json_parse_error".encode(env))
-                .unwrap()
-                .map_put("message".encode(env), "Invalid JSON".encode(env))
-                .unwrap()
-                .map_put(
-                    "details".encode(env),
-                    format!("Failed to parse JSON: {}", e).encode(env),
-                )
-                .unwrap();
-            return (atoms::error(), error_map).encode(env);
+            return error_tuple(
+                env,
+                "json_parse_error",
+                "Invalid JSON",
+                &format!("Failed to parse JSON: {}", e),
+            )
         }
     };
 
-    // Use jsonschema::meta::validate to get detailed validation results
-    // Handle potential panic with catch_unwind
-    let result = std::panic::catch_unwind(|| jsonschema::meta::validate(&schema_value));
+    // Schemas without $schema are assumed to be legacy draft-04 documents
+    let from = draft_from_atom(from).unwrap_or_else(|| {
+        schema_value
+            .get("$schema")
+            .and_then(Value::as_str)
+            .map(draft_from_schema_url)
+            .unwrap_or(jsonschema::Draft::Draft4)
+    });
+    let Some(to) = draft_from_atom(to) else {
+        return error_tuple(
+            env,
+            "migration_error",
+            "Invalid migration target",
+            "Target draft must be :draft201909 or :draft202012",
+        );
+    };
 
-    match result {
-        Ok(Ok(_)) => atoms::ok().encode(env),
-        Ok(Err(error)) => {
-            let error_map = rustler::types::map::map_new(env)
-                .map_put("type".encode(env), "meta_validation_error".encode(env))
-                .unwrap()
-                .map_put(
-                    "message".encode(env),
-                    "Schema meta-validation failed".encode(env),
-                )
-                .unwrap()
-                .map_put("details".encode(env), error.to_string().encode(env))
-                .unwrap();
-            (atoms::error(), error_map).encode(env)
-        }
-        Err(_) => {
-            let error_map = rustler::types::map::map_new(env)
-                .map_put("type".encode(env), "meta_validation_error".encode(env))
-                .unwrap()
-                .map_put(
-                    "message".encode(env),
-                    "Meta-validation not supported for this schema format".encode(env),
-                )
-                .unwrap()
-                .map_put(
-                    "details".encode(env),
-                    "Unknown or unsupported $schema specification".encode(env),
-                )
-                .unwrap();
-            (atoms::error(), error_map).encode(env)
+    match migrate::migrate(&schema_value, from, to) {
+        Ok(migration) => {
+            let issue_terms: Vec<Term> = migration
+                .issues
+                .iter()
+                .map(|issue| {
+                    rustler::types::map::map_new(env)
+                        .map_put("path".encode(env), issue.path.encode(env))
+                        .unwrap()
+                        .map_put("keyword".encode(env), issue.keyword.encode(env))
+                        .unwrap()
+                        .map_put("message".encode(env), issue.message.encode(env))
+                        .unwrap()
+                })
+                .collect();
+
+            (
+                atoms::ok(),
+                encode_json_value(env, &migration.schema),
+                issue_terms,
+            )
+                .encode(env)
         }
+        Err(details) => error_tuple(env, "migration_error", "Schema migration failed", &details),
     }
 }
 
 #[rustler::nif]
-fn meta_validate_detailed(env: Env, schema_json: String) -> Term {
+fn schema_to_avro(
+    env: Env,
+    schema_json: String,
+    name: Option<String>,
+    namespace: Option<String>,
+) -> Term {
     let schema_value: Value = match serde_json::from_str(&schema_json) {
         Ok(value) => value,
         Err(e) => {
-            let error_map = rustler::types::map::map_new(env)
-                .map_put("type".encode(env), "json_parse_error".encode(env))
-                .unwrap()
-                .map_put("message".encode(env), "Invalid JSON".encode(env))
-                .unwrap()
-                .map_put(
-                    "details".encode(env),
-                    format!("Failed to parse JSON: {}", e).encode(env),
-                )
-                .unwrap();
-            return (atoms::error(), error_map).encode(env);
+            return error_tuple(
+                env,
+                "json_parse_error",
+                "Invalid JSON",
+                &format!("Failed to parse JSON: {}", e),
+            )
         }
     };
+    encode_conversion(
+        env,
+        avro::to_avro(&schema_value, name.as_deref(), namespace.as_deref()),
+    )
+}
 
-    // Try to compile as a validator to get detailed meta-validation errors
-    let compilation_result = std::panic::catch_unwind(|| jsonschema::validator_for(&schema_value));
-
-    match compilation_result {
-        Ok(Ok(_validator)) => {
-            // Schema is valid for compilation, now check meta-validation
-            let meta_result =
-                std::panic::catch_unwind(|| jsonschema::meta::validate(&schema_value));
-
-            match meta_result {
-                Ok(Ok(_)) => atoms::ok().encode(env),
-                Ok(Err(error)) => {
-                    // Create a detailed error response
-                    let error_details = rustler::types::map::map_new(env)
-                        .map_put("instance_path".encode(env), "".encode(env))
+#[rustler::nif]
+fn avro_to_schema(env: Env, avro_json: String) -> Term {
+    let avro_value: Value = match serde_json::from_str(&avro_json) {
+        Ok(value) => value,
+        Err(e) => {
+            return error_tuple(
+                env,
+                "json_parse_error",
+                "Invalid JSON",
+                &format!("Failed to parse JSON: {}", e),
+            )
+        }
+    };
+    encode_conversion(env, avro::from_avro(&avro_value))
+}
+
+fn encode_conversion<'a>(env: Env<'a>, conversion: Result<avro::Conversion, String>) -> Term<'a> {
+    match conversion {
+        Ok(conversion) => {
+            let issue_terms: Vec<Term> = conversion
+                .issues
+                .iter()
+                .map(|issue| {
+                    rustler::types::map::map_new(env)
+                        .map_put("path".encode(env), issue.path.encode(env))
                         .unwrap()
-                        .map_put("schema_path".encode(env), "".encode(env))
+                        .map_put("keyword".encode(env), issue.keyword.encode(env))
                         .unwrap()
-                        .map_put("message".encode(env), error.to_string().encode(env))
+                        .map_put("message".encode(env), issue.message.encode(env))
                         .unwrap()
-                        .map_put("keyword".encode(env), "meta".encode(env))
-                        .unwrap();
+                })
+                .collect();
+
+            (
+                atoms::ok(),
+                encode_json_value(env, &conversion.schema),
+                issue_terms,
+            )
+                .encode(env)
+        }
+        Err(details) => error_tuple(env, "conversion_error", "Avro conversion failed", &details),
+    }
+}
+
+#[rustler::nif]
+fn infer_schema(env: Env, samples_json: String, draft: Atom, enum_threshold: usize) -> Term {
+    let samples: Vec<Value> = match serde_json::from_str(&samples_json) {
+        Ok(samples) => samples,
+        Err(e) => {
+            return error_tuple(
+                env,
+                "json_parse_error",
+                "Invalid JSON",
+                &format!("Failed to parse JSON: {}", e),
+            )
+        }
+    };
+
+    let options = infer::InferOptions {
+        draft: draft_from_atom(draft).unwrap_or(jsonschema::Draft::Draft202012),
+        enum_threshold,
+    };
+
+    match infer::infer(&samples, &options) {
+        Ok(schema) => (atoms::ok(), encode_json_value(env, &schema)).encode(env),
+        Err(details) => error_tuple(env, "inference_error", "Schema inference failed", &details),
+    }
+}
+
+#[rustler::nif]
+fn schema_metadata(env: Env, compiled_schema: ResourceArc<CompiledSchema>) -> Term {
+    rustler::types::map::map_new(env)
+        .map_put(
+            atoms::schema_id().encode(env),
+            compiled_schema.schema_id().encode(env),
+        )
+        .unwrap()
+        .map_put(
+            atoms::draft().encode(env),
+            compiled_schema.draft_atom().encode(env),
+        )
+        .unwrap()
+}
+
+#[rustler::nif]
+fn schema_info(env: Env, compiled_schema: ResourceArc<CompiledSchema>) -> Term {
+    // Milliseconds since the Unix epoch, for DateTime.from_unix!/2
+    let compiled_at = compiled_schema
+        .compiled_at
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64);
+
+    rustler::types::map::map_new(env)
+        .map_put(
+            atoms::id().encode(env),
+            compiled_schema.schema_id().encode(env),
+        )
+        .unwrap()
+        .map_put(
+            atoms::draft().encode(env),
+            compiled_schema.draft_atom().encode(env),
+        )
+        .unwrap()
+        .map_put(
+            atoms::content_hash().encode(env),
+            compiled_schema.content_hash.encode(env),
+        )
+        .unwrap()
+        .map_put(
+            atoms::options_fingerprint().encode(env),
+            fingerprint::options_fingerprint(&compiled_schema.options).encode(env),
+        )
+        .unwrap()
+        .map_put(atoms::compiled_at().encode(env), compiled_at.encode(env))
+        .unwrap()
+}
+
+#[rustler::nif]
+fn serialize_schema(compiled_schema: ResourceArc<CompiledSchema>) -> String {
+    snapshot::encode(&compiled_schema.source, &compiled_schema.options)
+}
+
+#[rustler::nif]
+fn deserialize_schema<'a>(env: Env<'a>, serialized: rustler::Binary<'a>) -> Term<'a> {
+    let decoded = std::str::from_utf8(serialized.as_slice())
+        .map_err(|_| "Serialized schema is not valid UTF-8".to_string())
+        .and_then(snapshot::decode);
+    let (schema, options) = match decoded {
+        Ok(decoded) => decoded,
+        Err(details) => {
+            return error_tuple(
+                env,
+                "deserialization_error",
+                "Invalid serialized schema",
+                &details,
+            )
+        }
+    };
 
-                    (atoms::error(), vec![error_details]).encode(env)
+    match CompiledSchema::build(schema, options) {
+        Ok(compiled) => (atoms::ok(), ResourceArc::new(compiled)).encode(env),
+        Err(e) => error_tuple(
+            env,
+            "compilation_error",
+            "Schema compilation failed",
+            &e.to_string(),
+        ),
+    }
+}
+
+#[rustler::nif]
+fn schema_cache_key(env: Env, schema_json: String, options: ValidationOptionsStruct) -> Term {
+    let format = input_format_from_atom(options.input_format);
+    match input::parse(&schema_json, format) {
+        Ok((schema, _)) => {
+            let key = fingerprint::cache_key(&schema, &build_options(&options), format);
+            (atoms::ok(), key).encode(env)
+        }
+        Err(e) if format == input::InputFormat::Json => error_tuple(
+            env,
+            "json_parse_error",
+            "Invalid JSON",
+            &format!("Failed to parse JSON: {}", e),
+        ),
+        Err(e) => parse_error_tuple(env, format, &e),
+    }
+}
+
+#[rustler::nif]
+fn input_to_json(env: Env, text: String, input_format: Atom) -> Term {
+    let format = input_format_from_atom(input_format);
+    match input::parse(&text, format) {
+        Ok((value, _)) => (atoms::ok(), value.to_string()).encode(env),
+        Err(e) => parse_error_tuple(env, format, &e),
+    }
+}
+
+// An OpenAPI document loaded for validating requests and responses, with a
+// validator for every schema its operations use
+pub struct OpenApiDocument {
+    document: document::Document,
+    // Keyed by the pointer to the schema in the document
+    validators: HashMap<String, CompiledSchema>,
+}
+
+#[rustler::resource_impl]
+impl rustler::Resource for OpenApiDocument {}
+
+impl OpenApiDocument {
+    fn load(value: &Value) -> Result<Self, String> {
+        let document = document::Document::parse(value)?;
+        let options = snapshot::BuildOptions {
+            draft: Some(document.dialect.draft()),
+            dialect: Some(document.dialect),
+            ..Default::default()
+        };
+
+        let mut validators = HashMap::new();
+        for operation in &document.operations {
+            let parameters = operation.parameters.iter().filter_map(|parameter| {
+                let schema = parameter.schema.as_ref()?;
+                Some((schema, format!("{}/schema", parameter.pointer)))
+            });
+            let bodies = operation
+                .body
+                .iter()
+                .filter_map(|body| body.schema.as_ref())
+                .chain(
+                    operation
+                        .responses
+                        .iter()
+                        .filter_map(|response| response.schema.as_ref()),
+                )
+                .map(|(schema, pointer)| (schema, pointer.clone()));
+
+            for (schema, pointer) in parameters.chain(bodies) {
+                if validators.contains_key(&pointer) {
+                    continue;
                 }
-                Err(_) => {
-                    // Meta-validation panicked (unsupported $schema)
-                    let error_details = rustler::types::map::map_new(env)
-                        .map_put("instance_path".encode(env), "".encode(env))
-                        .unwrap()
-                        .map_put("schema_path".encode(env), "".encode(env))
-                        .unwrap()
-                        .map_put(
-                            "message".encode(env),
-                            "Meta-validation not supported for this schema format".encode(env),
-                        )
-                        .unwrap()
-                        .map_put("keyword".encode(env), "meta".encode(env))
-                        .unwrap();
+                let compiled = CompiledSchema::build(document.schema_root(schema), options.clone())
+                    .map_err(|e| match e {
+                        JsonSchemaError::CompilationError(details) => {
+                            format!("Schema at {} failed to compile: {}", pointer, details)
+                        }
+                        other => other.to_string(),
+                    })?;
+                validators.insert(pointer, compiled);
+            }
+        }
+
+        Ok(OpenApiDocument {
+            document,
+            validators,
+        })
+    }
 
-                    (atoms::error(), vec![error_details]).encode(env)
+    // Errors of the parameters and body of a request for `operation`
+    fn request_errors(
+        &self,
+        operation: &document::Operation,
+        request: &HttpRequest,
+    ) -> Vec<ValidationErrorDetail> {
+        let mut errors = Vec::new();
+
+        for parameter in &operation.parameters {
+            let prefix = format!(
+                "/{}/{}",
+                parameter.location.name(),
+                dereference::escape_pointer_segment(&parameter.name)
+            );
+            let values = request.values(parameter);
+            if values.is_empty() {
+                if parameter.required {
+                    errors.push(openapi_error(
+                        prefix,
+                        format!("{}/required", parameter.pointer),
+                        format!(
+                            "Missing required {} parameter \"{}\"",
+                            match parameter.location {
+                                document::ParameterLocation::Path => "path",
+                                document::ParameterLocation::Query => "query",
+                                document::ParameterLocation::Header => "header",
+                            },
+                            parameter.name
+                        ),
+                    ));
                 }
+                continue;
+            }
+            if let Some(schema) = &parameter.schema {
+                let value = self.document.coerce(values, schema);
+                let pointer = format!("{}/schema", parameter.pointer);
+                errors.extend(self.errors(&pointer, &value, &prefix, None));
             }
         }
-        Ok(Err(compilation_error)) => {
-            // Schema has compilation errors, create detailed error response
-            let error_details = rustler::types::map::map_new(env)
-                .map_put("instance_path".encode(env), "".encode(env))
-                .unwrap()
-                .map_put("schema_path".encode(env), "".encode(env))
-                .unwrap()
-                .map_put(
-                    "message".encode(env),
-                    compilation_error.to_string().encode(env),
-                )
-                .unwrap()
-                .map_put("keyword".encode(env), "compilation".encode(env))
-                .unwrap();
 
-            (atoms::error(), vec![error_details]).encode(env)
+        if let Some(body) = &operation.body {
+            match (request.body.as_deref(), &body.schema) {
+                (None | Some(""), _) if body.required => errors.push(openapi_error(
+                    "/body".to_string(),
+                    format!("{}/required", body.pointer),
+                    "Missing required request body".to_string(),
+                )),
+                (None | Some(""), _) | (_, None) => {}
+                (Some(text), Some((_, pointer))) => errors.extend(self.body_errors(
+                    text,
+                    pointer,
+                    "Request",
+                    direction::Direction::Request,
+                )),
+            }
         }
-        Err(_) => {
-            // Compilation panicked
-            let error_details = rustler::types::map::map_new(env)
-                .map_put("instance_path".encode(env), "".encode(env))
-                .unwrap()
-                .map_put("schema_path".encode(env), "".encode(env))
-                .unwrap()
-                .map_put(
-                    "message".encode(env),
-                    "Schema compilation failed due to unsupported format".encode(env),
-                )
-                .unwrap()
-                .map_put("keyword".encode(env), "compilation".encode(env))
-                .unwrap();
+        errors
+    }
 
-            (atoms::error(), vec![error_details]).encode(env)
+    // Errors of a response body, or of the status when it is undocumented
+    fn response_errors(
+        &self,
+        operation: &document::Operation,
+        status: u16,
+        body: Option<&str>,
+    ) -> Vec<ValidationErrorDetail> {
+        let Some(response) = operation.response(status) else {
+            return vec![openapi_error(
+                "/status".to_string(),
+                format!("{}/responses", operation.pointer),
+                format!(
+                    "Status {} is not documented for {} {}",
+                    status, operation.method, operation.path
+                ),
+            )];
+        };
+        match (body, &response.schema) {
+            (Some(text), Some((_, pointer))) if !text.is_empty() => {
+                self.body_errors(text, pointer, "Response", direction::Direction::Response)
+            }
+            _ => Vec::new(),
         }
     }
+
+    fn body_errors(
+        &self,
+        text: &str,
+        pointer: &str,
+        kind: &str,
+        direction: direction::Direction,
+    ) -> Vec<ValidationErrorDetail> {
+        match serde_json::from_str::<Value>(text) {
+            Ok(value) => self.errors(pointer, &value, "/body", Some(direction)),
+            Err(e) => vec![openapi_error(
+                "/body".to_string(),
+                pointer.to_string(),
+                format!("{} body is not valid JSON: {}", kind, e),
+            )],
+        }
+    }
+
+    // Errors of `instance` against the schema at `pointer`, with instance
+    // paths under `prefix` and schema paths from the document root
+    fn errors(
+        &self,
+        pointer: &str,
+        instance: &Value,
+        prefix: &str,
+        direction: Option<direction::Direction>,
+    ) -> Vec<ValidationErrorDetail> {
+        let Some(compiled) = self.validators.get(pointer) else {
+            return Vec::new();
+        };
+        if compiled.is_valid(instance, direction) {
+            return Vec::new();
+        }
+        let mut errors = compiled.errors(instance, direction);
+        for error in &mut errors {
+            error.for_each_mut(&mut |error| {
+                error.instance_path = format!("{}{}", prefix, error.instance_path);
+                error.schema_path = format!("{}{}", pointer, error.schema_path);
+                if let Some(branch) = &mut error.branch {
+                    branch.schema_pointer = format!("{}{}", pointer, branch.schema_pointer);
+                }
+            });
+        }
+        errors
+    }
+}
+
+// The parts of an HTTP request, each parameter with its raw string values
+#[derive(rustler::NifMap)]
+struct HttpRequest {
+    path: Vec<(String, Vec<String>)>,
+    query: Vec<(String, Vec<String>)>,
+    headers: Vec<(String, Vec<String>)>,
+    body: Option<String>,
+}
+
+impl HttpRequest {
+    fn values(&self, parameter: &document::Parameter) -> &[String] {
+        let (values, case_sensitive) = match parameter.location {
+            document::ParameterLocation::Path => (&self.path, true),
+            document::ParameterLocation::Query => (&self.query, true),
+            document::ParameterLocation::Header => (&self.headers, false),
+        };
+        values
+            .iter()
+            .find(|(name, _)| {
+                if case_sensitive {
+                    *name == parameter.name
+                } else {
+                    name.eq_ignore_ascii_case(&parameter.name)
+                }
+            })
+            .map(|(_, values)| values.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+// An error about a request or response as a whole rather than a schema
+fn openapi_error(
+    instance_path: String,
+    schema_path: String,
+    message: String,
+) -> ValidationErrorDetail {
+    let keyword = schema_path.rsplit('/').next().unwrap_or_default();
+    ValidationErrorDetail {
+        keyword: keyword.to_string(),
+        params: Value::Object(serde_json::Map::new()),
+        instance_path,
+        schema_path,
+        message,
+        branch: None,
+        branches: Vec::new(),
+    }
+}
+
+fn encode_openapi_result<'a>(env: Env<'a>, errors: &[ValidationErrorDetail]) -> Term<'a> {
+    if errors.is_empty() {
+        return atoms::ok().encode(env);
+    }
+    let error_terms: Vec<Term> = errors
+        .iter()
+        .map(|error| encode_error_detail(env, error))
+        .collect();
+    (atoms::error(), error_terms).encode(env)
 }
 
 #[rustler::nif]
-fn compile_schema_with_options(
+fn openapi_load(env: Env, document_json: String) -> Term {
+    let value: Value = match serde_json::from_str(&document_json) {
+        Ok(value) => value,
+        Err(e) => return error_tuple(env, "json_parse_error", "Invalid JSON", &e.to_string()),
+    };
+    match OpenApiDocument::load(&value) {
+        Ok(document) => (atoms::ok(), ResourceArc::new(document)).encode(env),
+        Err(details) => error_tuple(
+            env,
+            "compilation_error",
+            "OpenAPI document could not be loaded",
+            &details,
+        ),
+    }
+}
+
+#[rustler::nif]
+fn openapi_operations(
+    document: ResourceArc<OpenApiDocument>,
+) -> Vec<(String, String, Option<String>)> {
+    document
+        .document
+        .operations
+        .iter()
+        .map(|operation| {
+            (
+                operation.method.clone(),
+                operation.path.clone(),
+                operation.operation_id.clone(),
+            )
+        })
+        .collect()
+}
+
+#[rustler::nif]
+fn openapi_validate_request(
     env: Env,
-    schema_json: String,
-    options: ValidationOptionsStruct,
+    document: ResourceArc<OpenApiDocument>,
+    method: String,
+    path: String,
+    request: HttpRequest,
 ) -> Term {
-    let schema_value: Value = match serde_json::from_str(&schema_json) {
-        Ok(value) => value,
-        Err(e) => {
-            let error_map = rustler::types::map::map_new(env)
-                .map_put("type".encode(env), "json_parse_error".encode(env))
-                .unwrap()
-                .map_put("message".encode(env), e.to_string().encode(env))
-                .unwrap();
-            return (atoms::error(), error_map).encode(env);
+    let Some((operation, matched)) = document.document.find(&method, &path) else {
+        return (atoms::error(), atoms::unknown_operation()).encode(env);
+    };
+    let mut request = request;
+    // Parameters given explicitly win over those read from the path
+    for (name, value) in matched {
+        if !request.path.iter().any(|(given, _)| *given == name) {
+            request.path.push((name, vec![value]));
         }
+    }
+
+    encode_openapi_result(env, &document.request_errors(operation, &request))
+}
+
+#[rustler::nif]
+fn openapi_validate_response(
+    env: Env,
+    document: ResourceArc<OpenApiDocument>,
+    method: Option<String>,
+    target: String,
+    status: u16,
+    body: Option<String>,
+) -> Term {
+    // Without a method, the target is an operationId
+    let operation = match method {
+        Some(method) => document
+            .document
+            .find(&method, &target)
+            .map(|(operation, _)| operation),
+        None => document.document.find_by_id(&target),
+    };
+    let Some(operation) = operation else {
+        return (atoms::error(), atoms::unknown_operation()).encode(env);
     };
+    encode_openapi_result(
+        env,
+        &document.response_errors(operation, status, body.as_deref()),
+    )
+}
 
-    let compiled = match CompiledSchema::new_with_options(schema_value, options) {
-        Ok(compiled) => compiled,
-        Err(e) => {
-            let error_map = rustler::types::map::map_new(env)
-                .map_put("type".encode(env), "compilation_error".encode(env))
+// Where reading a CSV file has got to, carried from one chunk to the next
+#[derive(rustler::NifMap)]
+struct CsvState {
+    headers: Option<Vec<String>>,
+    line: usize,
+    separator: u8,
+    ranked: bool,
+}
+
+// Validates the rows of a chunk of CSV text, the first record naming the
+// columns unless the state has headers. Returns the headers, the line the
+// next chunk starts on, how much of this chunk was read (the rest is a
+// record the next chunk completes) and the errors of each row read.
+#[rustler::nif]
+fn validate_csv<'a>(
+    env: Env<'a>,
+    compiled_schema: ResourceArc<CompiledSchema>,
+    chunk: rustler::Binary<'a>,
+    state: CsvState,
+    last: bool,
+) -> Term<'a> {
+    let read = match csv::read(chunk.as_slice(), state.separator, state.line, last) {
+        Ok(read) => read,
+        Err(details) => return error_tuple(env, "parse_error", "Invalid CSV", &details),
+    };
+    let mut records = read.records.into_iter();
+    let (names, header_line) = match state.headers {
+        Some(names) => (names, None),
+        None => match records.next() {
+            Some(record) => (record.fields, Some(record.line)),
+            None => {
+                let errors: Vec<Term> = Vec::new();
+                return (
+                    atoms::ok(),
+                    None::<Vec<String>>,
+                    read.next_line,
+                    read.consumed,
+                    errors,
+                )
+                    .encode(env);
+            }
+        },
+    };
+    let columns = match csv::Columns::new(names, &compiled_schema.schema, compiled_schema.draft()) {
+        Ok(columns) => columns,
+        Err(details) => {
+            let details = match header_line {
+                Some(line) => format!("Line {}: {}", line, details),
+                None => details,
+            };
+            return error_tuple(env, "parse_error", "Invalid CSV headers", &details);
+        }
+    };
+
+    let mut error_terms = Vec::new();
+    for record in records {
+        let mut errors = Vec::new();
+        if record.fields.len() > columns.names.len() {
+            errors.push(ValidationErrorDetail {
+                instance_path: String::new(),
+                schema_path: String::new(),
+                message: format!(
+                    "Row has {} fields but there are {} columns",
+                    record.fields.len(),
+                    columns.names.len()
+                ),
+                keyword: "columns".to_string(),
+                params: serde_json::json!({ "limit": columns.names.len() }),
+                branch: None,
+                branches: Vec::new(),
+            });
+        }
+        let row = columns.row(&record.fields);
+        if !compiled_schema.is_valid(&row, None) {
+            let mut found = compiled_schema.errors(&row, None);
+            if state.ranked {
+                ranking::sort(&mut found);
+            }
+            errors.extend(found);
+        }
+
+        for error in &errors {
+            let detail = encode_segments(
+                env,
+                encode_error_detail(env, error),
+                &error.instance_path,
+                &row,
+            );
+            let map = rustler::types::map::map_new(env)
+                .map_put("line".encode(env), record.line.encode(env))
+                .unwrap()
+                .map_put(
+                    "column".encode(env),
+                    csv::column(&error.instance_path, &error.params).encode(env),
+                )
                 .unwrap()
-                .map_put("message".encode(env), e.to_string().encode(env))
+                .map_put("error".encode(env), detail)
                 .unwrap();
-            return (atoms::error(), error_map).encode(env);
+            error_terms.push(map);
         }
-    };
+    }
 
-    let resource = ResourceArc::new(compiled);
-    (atoms::ok(), resource).encode(env)
+    (
+        atoms::ok(),
+        Some(columns.names),
+        read.next_line,
+        read.consumed,
+        error_terms,
+    )
+        .encode(env)
 }
 
 rustler::init!("Elixir.ExJsonschema.Native");
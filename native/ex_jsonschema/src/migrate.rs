@@ -0,0 +1,393 @@
+use referencing::Draft;
+use serde_json::{Map, Value};
+
+use crate::dereference::escape_pointer_segment;
+
+// Keywords holding a single subschema
+const SCHEMA_KEYWORDS: &[&str] = &[
+    "additionalProperties",
+    "contains",
+    "contentSchema",
+    "else",
+    "if",
+    "not",
+    "propertyNames",
+    "then",
+    "unevaluatedItems",
+    "unevaluatedProperties",
+];
+
+// Keywords holding an array of subschemas
+const SCHEMA_ARRAY_KEYWORDS: &[&str] = &["allOf", "anyOf", "oneOf", "prefixItems"];
+
+// Keywords holding an object of named subschemas
+const SCHEMA_MAP_KEYWORDS: &[&str] = &[
+    "$defs",
+    "definitions",
+    "dependentSchemas",
+    "patternProperties",
+    "properties",
+];
+
+// Keywords that were never evaluated next to `$ref` and carry no assertions
+const REF_NEUTRAL_KEYWORDS: &[&str] = &[
+    "$comment",
+    "$defs",
+    "$id",
+    "$schema",
+    "definitions",
+    "description",
+    "id",
+    "title",
+];
+
+#[derive(Debug, Clone)]
+pub struct MigrationIssue {
+    pub path: String,
+    pub keyword: String,
+    pub message: String,
+}
+
+pub struct Migration {
+    pub schema: Value,
+    pub issues: Vec<MigrationIssue>,
+}
+
+/// Rewrites `schema` from the `from` draft into the `to` draft, collecting
+/// anything that could not be converted automatically.
+pub fn migrate(schema: &Value, from: Draft, to: Draft) -> Result<Migration, String> {
+    if to < Draft::Draft201909 {
+        return Err("Migration target must be draft2019-09 or draft2020-12".to_string());
+    }
+    if from > to {
+        return Err("Cannot migrate a schema to an older draft".to_string());
+    }
+
+    let mut migrator = Migrator {
+        from,
+        to,
+        issues: Vec::new(),
+    };
+    let mut schema = migrator.migrate_schema(schema, "");
+
+    if let Value::Object(map) = &mut schema {
+        map.insert("$schema".to_string(), Value::String(schema_url(to)));
+    }
+
+    Ok(Migration {
+        schema,
+        issues: migrator.issues,
+    })
+}
+
+pub fn schema_url(draft: Draft) -> String {
+    match draft {
+        Draft::Draft4 => "http://json-schema.org/draft-04/schema#",
+        Draft::Draft6 => "http://json-schema.org/draft-06/schema#",
+        Draft::Draft7 => "http://json-schema.org/draft-07/schema#",
+        Draft::Draft201909 => "https://json-schema.org/draft/2019-09/schema",
+        _ => "https://json-schema.org/draft/2020-12/schema",
+    }
+    .to_string()
+}
+
+struct Migrator {
+    from: Draft,
+    to: Draft,
+    issues: Vec<MigrationIssue>,
+}
+
+impl Migrator {
+    fn migrate_schema(&mut self, value: &Value, path: &str) -> Value {
+        let map = match value {
+            Value::Object(map) => map,
+            other => return other.clone(),
+        };
+
+        let mut result = Map::new();
+        let items_is_array = matches!(map.get("items"), Some(Value::Array(_)));
+
+        for (key, child) in map {
+            let child_path = join_path(path, key);
+
+            match key.as_str() {
+                "$schema" => {}
+                "id" if self.from == Draft::Draft4 && child.is_string() => {
+                    self.migrate_id(&mut result, child.as_str().unwrap_or_default(), &child_path);
+                }
+                "$id" if child.is_string() => {
+                    self.migrate_id(&mut result, child.as_str().unwrap_or_default(), &child_path);
+                }
+                "$ref" if child.is_string() => {
+                    let reference = child.as_str().unwrap_or_default();
+                    result.insert(
+                        key.clone(),
+                        Value::String(self.migrate_ref(reference, &child_path)),
+                    );
+                    self.check_ref_siblings(map, &child_path);
+                }
+                "definitions" | "$defs" => {
+                    let migrated = self.migrate_schema_map(child, &child_path);
+                    merge_object(&mut result, "$defs", migrated);
+                }
+                "dependencies" => self.migrate_dependencies(&mut result, child, &child_path),
+                "items" if items_is_array && self.to >= Draft::Draft202012 => {
+                    let migrated = self.migrate_schema_array(child, &child_path);
+                    result.insert("prefixItems".to_string(), migrated);
+                }
+                "additionalItems" => {
+                    if !items_is_array {
+                        self.report(
+                            &child_path,
+                            key,
+                            "additionalItems has no effect without array-form items and was removed",
+                        );
+                    } else if self.to >= Draft::Draft202012 {
+                        result.insert("items".to_string(), self.migrate_schema(child, &child_path));
+                    } else {
+                        result.insert(key.clone(), self.migrate_schema(child, &child_path));
+                    }
+                }
+                "exclusiveMinimum" | "exclusiveMaximum" if child.is_boolean() => {}
+                "$recursiveRef" | "$recursiveAnchor" if self.to >= Draft::Draft202012 => {
+                    self.report(
+                        &child_path,
+                        key,
+                        "Recursive references must be rewritten by hand using $dynamicRef and $dynamicAnchor",
+                    );
+                    result.insert(key.clone(), child.clone());
+                }
+                "items" => {
+                    let migrated = if items_is_array {
+                        self.migrate_schema_array(child, &child_path)
+                    } else {
+                        self.migrate_schema(child, &child_path)
+                    };
+                    result.insert(key.clone(), migrated);
+                }
+                k if SCHEMA_KEYWORDS.contains(&k) => {
+                    result.insert(key.clone(), self.migrate_schema(child, &child_path));
+                }
+                k if SCHEMA_ARRAY_KEYWORDS.contains(&k) => {
+                    result.insert(key.clone(), self.migrate_schema_array(child, &child_path));
+                }
+                k if SCHEMA_MAP_KEYWORDS.contains(&k) => {
+                    let migrated = self.migrate_schema_map(child, &child_path);
+                    merge_object(&mut result, k, migrated);
+                }
+                _ => {
+                    result.insert(key.clone(), child.clone());
+                }
+            }
+        }
+
+        self.migrate_exclusive_bound(map, &mut result, path, "exclusiveMinimum", "minimum");
+        self.migrate_exclusive_bound(map, &mut result, path, "exclusiveMaximum", "maximum");
+
+        Value::Object(result)
+    }
+
+    fn migrate_schema_array(&mut self, value: &Value, path: &str) -> Value {
+        match value {
+            Value::Array(items) => Value::Array(
+                items
+                    .iter()
+                    .enumerate()
+                    .map(|(index, item)| self.migrate_schema(item, &format!("{}/{}", path, index)))
+                    .collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+
+    fn migrate_schema_map(&mut self, value: &Value, path: &str) -> Value {
+        match value {
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(name, schema)| {
+                        (
+                            name.clone(),
+                            self.migrate_schema(schema, &join_path(path, name)),
+                        )
+                    })
+                    .collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+
+    // Draft 4 `id` and draft 6/7 `$id` may carry a plain-name fragment, which
+    // 2019-09 moved into `$anchor`
+    fn migrate_id(&mut self, result: &mut Map<String, Value>, id: &str, path: &str) {
+        let (base, fragment) = id.split_once('#').unwrap_or((id, ""));
+
+        if !base.is_empty() {
+            result.insert("$id".to_string(), Value::String(base.to_string()));
+        }
+        if fragment.is_empty() {
+            return;
+        }
+        if fragment.starts_with('/') {
+            self.report(
+                path,
+                "$id",
+                "JSON Pointer fragments are not allowed in $id and were removed",
+            );
+        } else {
+            result.insert("$anchor".to_string(), Value::String(fragment.to_string()));
+        }
+    }
+
+    fn migrate_ref(&mut self, reference: &str, path: &str) -> String {
+        let Some((base, fragment)) = reference.split_once('#') else {
+            return reference.to_string();
+        };
+        let migrated = self.migrate_pointer(fragment);
+        if migrated == fragment {
+            return reference.to_string();
+        }
+        if !base.is_empty() {
+            self.report(
+                path,
+                "$ref",
+                "Reference into an external document was not rewritten; migrate that document too",
+            );
+            return reference.to_string();
+        }
+        format!("#{}", migrated)
+    }
+
+    // Renames keyword segments of a JSON Pointer, leaving property names alone
+    fn migrate_pointer(&self, pointer: &str) -> String {
+        if !pointer.starts_with('/') {
+            return pointer.to_string();
+        }
+
+        let segments: Vec<&str> = pointer[1..].split('/').collect();
+        let mut migrated = Vec::with_capacity(segments.len());
+        let mut previous: Option<&str> = None;
+
+        for (index, &segment) in segments.iter().enumerate() {
+            let is_name =
+                previous.is_some_and(|p| SCHEMA_MAP_KEYWORDS.contains(&p) || p == "dependencies");
+            let next_is_index = segments
+                .get(index + 1)
+                .is_some_and(|s| s.parse::<usize>().is_ok());
+
+            let renamed = match segment {
+                _ if is_name => segment,
+                "definitions" => "$defs",
+                "dependencies" => "dependentSchemas",
+                "items" if next_is_index && self.to >= Draft::Draft202012 => "prefixItems",
+                "additionalItems" if self.to >= Draft::Draft202012 => "items",
+                _ => segment,
+            };
+            migrated.push(renamed);
+            // Names are a single segment, after which we are back at a schema
+            previous = if is_name { None } else { Some(segment) };
+        }
+
+        format!("/{}", migrated.join("/"))
+    }
+
+    fn check_ref_siblings(&mut self, map: &Map<String, Value>, path: &str) {
+        if self.from > Draft::Draft7 {
+            return;
+        }
+        let has_assertions = map
+            .keys()
+            .any(|key| key != "$ref" && !REF_NEUTRAL_KEYWORDS.contains(&key.as_str()));
+        if has_assertions {
+            self.report(
+                path,
+                "$ref",
+                "Keywords next to $ref were ignored before 2019-09 and are now evaluated",
+            );
+        }
+    }
+
+    fn migrate_dependencies(&mut self, result: &mut Map<String, Value>, value: &Value, path: &str) {
+        let Value::Object(dependencies) = value else {
+            result.insert("dependencies".to_string(), value.clone());
+            return;
+        };
+
+        let mut required = Map::new();
+        let mut schemas = Map::new();
+        for (name, dependency) in dependencies {
+            match dependency {
+                Value::Array(_) => {
+                    required.insert(name.clone(), dependency.clone());
+                }
+                _ => {
+                    let migrated = self.migrate_schema(dependency, &join_path(path, name));
+                    schemas.insert(name.clone(), migrated);
+                }
+            }
+        }
+
+        if !required.is_empty() {
+            merge_object(result, "dependentRequired", Value::Object(required));
+        }
+        if !schemas.is_empty() {
+            merge_object(result, "dependentSchemas", Value::Object(schemas));
+        }
+    }
+
+    // Draft 4 expressed exclusive bounds as a boolean modifier of minimum/maximum
+    fn migrate_exclusive_bound(
+        &mut self,
+        original: &Map<String, Value>,
+        result: &mut Map<String, Value>,
+        path: &str,
+        keyword: &str,
+        bound: &str,
+    ) {
+        let Some(Value::Bool(exclusive)) = original.get(keyword) else {
+            return;
+        };
+        if !exclusive {
+            return;
+        }
+
+        match result.remove(bound) {
+            Some(limit) if limit.is_number() => {
+                result.insert(keyword.to_string(), limit);
+            }
+            other => {
+                if let Some(other) = other {
+                    result.insert(bound.to_string(), other);
+                }
+                self.report(
+                    &join_path(path, keyword),
+                    keyword,
+                    &format!(
+                        "{}: true without a numeric {} has no effect and was removed",
+                        keyword, bound
+                    ),
+                );
+            }
+        }
+    }
+
+    fn report(&mut self, path: &str, keyword: &str, message: &str) {
+        self.issues.push(MigrationIssue {
+            path: path.to_string(),
+            keyword: keyword.to_string(),
+            message: message.to_string(),
+        });
+    }
+}
+
+fn merge_object(result: &mut Map<String, Value>, key: &str, value: Value) {
+    if let (Some(Value::Object(existing)), Value::Object(additions)) = (result.get_mut(key), &value)
+    {
+        existing.extend(additions.clone());
+        return;
+    }
+    result.insert(key.to_string(), value);
+}
+
+fn join_path(path: &str, segment: &str) -> String {
+    format!("{}/{}", path, escape_pointer_segment(segment))
+}
@@ -0,0 +1,113 @@
+use referencing::Draft;
+use serde_json::{Map, Value};
+
+use crate::walk::subschemas_mut;
+
+/// `$schema` URLs of the OpenAPI 3.1 base dialect and its vocabulary's
+/// meta-schema.
+pub const SCHEMA_URLS_3_1: &[&str] = &[
+    "https://spec.openapis.org/oas/3.1/dialect/base",
+    "https://spec.openapis.org/oas/3.1/meta/base",
+];
+
+/// Formats OpenAPI defines on top of JSON Schema's. They are annotations
+/// only, but schemas may use them when unknown formats are rejected.
+pub const FORMATS: &[&str] = &[
+    "int32", "int64", "float", "double", "byte", "binary", "password",
+];
+
+const DRAFT_2020_12_URL: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// The Schema Object dialects of OpenAPI documents. Each is validated as the
+/// JSON Schema draft it extends, after translating the keywords that draft
+/// does not know.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dialect {
+    /// OpenAPI 3.0: an extended subset of Draft 4 (boolean
+    /// `exclusiveMinimum`, `$ref` siblings ignored) plus `nullable`
+    OpenApi30,
+    /// OpenAPI 3.1: Draft 2020-12 plus the OAS base vocabulary, whose
+    /// keywords (`xml`, `externalDocs`, `example`) only annotate, apart from
+    /// `discriminator`, which every draft honours
+    OpenApi31,
+}
+
+impl Dialect {
+    /// The draft the dialect's schemas are validated with.
+    pub fn draft(self) -> Draft {
+        match self {
+            Dialect::OpenApi30 => Draft::Draft4,
+            Dialect::OpenApi31 => Draft::Draft202012,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Dialect::OpenApi30 => "openapi30",
+            Dialect::OpenApi31 => "openapi31",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Dialect> {
+        match name {
+            "openapi30" => Some(Dialect::OpenApi30),
+            "openapi31" => Some(Dialect::OpenApi31),
+            _ => None,
+        }
+    }
+
+    /// The dialect a `$schema` URL names, if any. OpenAPI 3.0 has none.
+    pub fn from_schema_url(url: &str) -> Option<Dialect> {
+        let url = url.trim_end_matches('#');
+        SCHEMA_URLS_3_1.contains(&url).then_some(Dialect::OpenApi31)
+    }
+
+    /// Rewrites `schema` into the equivalent schema for `self.draft()`:
+    /// 3.0 `nullable: true` adds `"null"` to the `type` next to it, and 3.1
+    /// dialect `$schema`s point at Draft 2020-12, which validates the same
+    /// way. Translating a translated schema changes nothing.
+    pub fn translate(self, mut schema: Value) -> Value {
+        translate_node(self, &mut schema);
+        schema
+    }
+}
+
+fn translate_node(dialect: Dialect, node: &mut Value) {
+    let Value::Object(map) = node else {
+        return;
+    };
+    match dialect {
+        Dialect::OpenApi30 => add_null_type(map),
+        Dialect::OpenApi31 => point_at_draft(map),
+    }
+    for (segments, subschema) in subschemas_mut(map) {
+        // Extensions hold arbitrary data, like the data keywords
+        if !segments[0].starts_with("x-") {
+            translate_node(dialect, subschema);
+        }
+    }
+}
+
+// As 3.0.3 clarifies, `nullable` only widens a `type` in the same schema
+fn add_null_type(map: &mut Map<String, Value>) {
+    if map.get("nullable") != Some(&Value::Bool(true)) {
+        return;
+    }
+    if let Some(Value::String(name)) = map.get("type") {
+        if name != "null" {
+            let types = vec![Value::String(name.clone()), Value::from("null")];
+            map.insert("type".to_string(), Value::Array(types));
+        }
+    }
+}
+
+fn point_at_draft(map: &mut Map<String, Value>) {
+    let declares_dialect = map
+        .get("$schema")
+        .and_then(Value::as_str)
+        .and_then(Dialect::from_schema_url)
+        .is_some();
+    if declares_dialect {
+        map.insert("$schema".to_string(), Value::from(DRAFT_2020_12_URL));
+    }
+}
@@ -0,0 +1,305 @@
+use serde_json::{Map, Value};
+
+use crate::dereference::escape_pointer_segment;
+use crate::subschema::{is_within, parse_pointer};
+
+/// An operation that could not be applied.
+#[derive(Debug, PartialEq)]
+pub struct PatchError {
+    /// Index of the operation in the patch
+    pub operation: usize,
+    pub message: String,
+}
+
+/// What a patch changed: for each change, the patch member responsible and
+/// the document locations it wrote to or removed.
+#[derive(Debug, Default, PartialEq)]
+pub struct Changes {
+    pub entries: Vec<(Responsible, Vec<String>)>,
+}
+
+/// The part of a patch that made a change: an operation index for JSON
+/// Patch, or the pointer to a member of a JSON Merge Patch.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Responsible {
+    Operation(usize),
+    Member(String),
+}
+
+impl Changes {
+    /// The patch member most likely to have caused an error at
+    /// `instance_path`. Changes at or above the error's location win over
+    /// changes below it, and later changes win over earlier ones.
+    pub fn responsible(&self, instance_path: &str) -> Option<&Responsible> {
+        let find = |matches: &dyn Fn(&str) -> bool| {
+            self.entries
+                .iter()
+                .rev()
+                .find(|(_, paths)| paths.iter().any(|path| matches(path)))
+                .map(|(responsible, _)| responsible)
+        };
+
+        find(&|path| is_within(instance_path, path))
+            .or_else(|| find(&|path| is_within(path, instance_path)))
+    }
+
+    /// The smallest set of subtrees that contain every change together with
+    /// its parent, whose keywords (`required`, `maxItems`, ...) it may affect.
+    pub fn containers(&self) -> Vec<String> {
+        let mut containers: Vec<String> = self
+            .entries
+            .iter()
+            .flat_map(|(_, paths)| paths.iter().map(|path| parent(path)))
+            .collect();
+        containers.sort();
+        containers.dedup();
+
+        let mut outermost: Vec<String> = Vec::new();
+        for container in containers {
+            // Sorting places ancestors before their descendants
+            if !outermost
+                .iter()
+                .any(|ancestor| is_within(&container, ancestor))
+            {
+                outermost.push(container);
+            }
+        }
+        outermost
+    }
+}
+
+/// Applies an RFC 6902 JSON Patch to `document`. Either every operation
+/// applies or `document` is left untouched.
+pub fn apply_json_patch(document: &mut Value, patch: &Value) -> Result<Changes, PatchError> {
+    let Value::Array(operations) = patch else {
+        return Err(PatchError {
+            operation: 0,
+            message: "A JSON Patch must be an array of operations".to_string(),
+        });
+    };
+
+    let mut patched = document.clone();
+    let mut changes = Changes::default();
+    for (index, operation) in operations.iter().enumerate() {
+        let paths = apply_operation(&mut patched, operation).map_err(|message| PatchError {
+            operation: index,
+            message,
+        })?;
+        if !paths.is_empty() {
+            changes.entries.push((Responsible::Operation(index), paths));
+        }
+    }
+
+    *document = patched;
+    Ok(changes)
+}
+
+/// Applies an RFC 7396 JSON Merge Patch to `document`. Merge patches cannot fail.
+pub fn apply_merge_patch(document: &mut Value, patch: &Value) -> Changes {
+    let mut changes = Changes::default();
+    merge(document, patch, String::new(), &mut changes);
+    changes
+}
+
+fn merge(target: &mut Value, patch: &Value, path: String, changes: &mut Changes) {
+    let Value::Object(members) = patch else {
+        *target = patch.clone();
+        changes
+            .entries
+            .push((Responsible::Member(path.clone()), vec![path]));
+        return;
+    };
+
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+        changes
+            .entries
+            .push((Responsible::Member(path.clone()), vec![path.clone()]));
+    }
+    let Value::Object(object) = target else {
+        unreachable!()
+    };
+
+    for (key, value) in members {
+        let member_path = format!("{}/{}", path, escape_pointer_segment(key));
+        if value.is_null() {
+            if object.remove(key).is_some() {
+                changes
+                    .entries
+                    .push((Responsible::Member(member_path.clone()), vec![member_path]));
+            }
+        } else {
+            let child = object.entry(key.clone()).or_insert(Value::Null);
+            merge(child, value, member_path, changes);
+        }
+    }
+}
+
+// Applies one operation and returns the locations it changed
+fn apply_operation(document: &mut Value, operation: &Value) -> Result<Vec<String>, String> {
+    let member = |name: &str| {
+        operation
+            .get(name)
+            .and_then(Value::as_str)
+            .ok_or_else(|| format!("The operation has no \"{}\" member", name))
+    };
+    let value = || {
+        operation
+            .get("value")
+            .cloned()
+            .ok_or_else(|| "The operation has no \"value\" member".to_string())
+    };
+
+    let path = member("path")?;
+    match member("op")? {
+        "add" => {
+            let path = add(document, path, value()?)?;
+            Ok(vec![path])
+        }
+        "remove" => {
+            remove(document, path)?;
+            Ok(vec![path.to_string()])
+        }
+        "replace" => {
+            let value = value()?;
+            let target = document.pointer_mut(path).ok_or_else(|| missing(path))?;
+            *target = value;
+            Ok(vec![path.to_string()])
+        }
+        "move" => {
+            let from = member("from")?;
+            if from == path {
+                return Ok(Vec::new());
+            }
+            if is_within(path, from) {
+                return Err(format!(
+                    "Cannot move '{}' into its own child '{}'",
+                    from, path
+                ));
+            }
+            let value = remove(document, from)?;
+            let path = add(document, path, value)?;
+            Ok(vec![from.to_string(), path])
+        }
+        "copy" => {
+            let from = member("from")?;
+            let value = document
+                .pointer(from)
+                .cloned()
+                .ok_or_else(|| missing(from))?;
+            let path = add(document, path, value)?;
+            Ok(vec![path])
+        }
+        "test" => {
+            let actual = document.pointer(path).ok_or_else(|| missing(path))?;
+            if json_equal(actual, &value()?) {
+                Ok(Vec::new())
+            } else {
+                Err(format!("The value at '{}' does not match", path))
+            }
+        }
+        other => Err(format!("Unknown operation \"{}\"", other)),
+    }
+}
+
+// Adds `value` at `path` and returns the location it ended up at, which
+// differs from `path` when appending to an array with "-"
+fn add(document: &mut Value, path: &str, value: Value) -> Result<String, String> {
+    if path.is_empty() {
+        *document = value;
+        return Ok(String::new());
+    }
+    let (parent_path, key) = split_last(path)?;
+    let parent = document
+        .pointer_mut(&parent_path)
+        .ok_or_else(|| missing(&parent_path))?;
+
+    match parent {
+        Value::Object(object) => {
+            object.insert(key, value);
+            Ok(path.to_string())
+        }
+        Value::Array(items) => {
+            let index = if key == "-" {
+                items.len()
+            } else {
+                parse_index(&key)
+                    .filter(|index| *index <= items.len())
+                    .ok_or_else(|| {
+                        format!(
+                            "'{}' is not a valid position in the array at '{}'",
+                            key, parent_path
+                        )
+                    })?
+            };
+            items.insert(index, value);
+            Ok(format!("{}/{}", parent_path, index))
+        }
+        _ => Err(format!("The value at '{}' is not a container", parent_path)),
+    }
+}
+
+fn remove(document: &mut Value, path: &str) -> Result<Value, String> {
+    if path.is_empty() {
+        return Err("Cannot remove the whole document".to_string());
+    }
+    let (parent_path, key) = split_last(path)?;
+    let removed = match document.pointer_mut(&parent_path) {
+        Some(Value::Object(object)) => object.remove(&key),
+        Some(Value::Array(items)) => parse_index(&key)
+            .filter(|index| *index < items.len())
+            .map(|index| items.remove(index)),
+        _ => None,
+    };
+    removed.ok_or_else(|| missing(path))
+}
+
+// Splits a pointer into its parent pointer and unescaped last token
+fn split_last(path: &str) -> Result<(String, String), String> {
+    let mut tokens = parse_pointer(path)?;
+    let key = tokens.pop().unwrap_or_default();
+    let parent = tokens
+        .iter()
+        .map(|token| format!("/{}", escape_pointer_segment(token)))
+        .collect();
+    Ok((parent, key))
+}
+
+// Array indices are decimal without leading zeros
+fn parse_index(token: &str) -> Option<usize> {
+    if token.is_empty()
+        || !token.bytes().all(|byte| byte.is_ascii_digit())
+        || (token.len() > 1 && token.starts_with('0'))
+    {
+        return None;
+    }
+    token.parse().ok()
+}
+
+// RFC 6902 compares numbers by value, so 1 and 1.0 are equal
+fn json_equal(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(a), Value::Number(b)) => match (a.as_i64(), b.as_i64()) {
+            (Some(a), Some(b)) => a == b,
+            _ => a.as_f64() == b.as_f64(),
+        },
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| json_equal(a, b))
+        }
+        (Value::Object(a), Value::Object(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(key, value)| b.get(key).is_some_and(|other| json_equal(value, other)))
+        }
+        _ => left == right,
+    }
+}
+
+fn parent(path: &str) -> String {
+    path.rsplit_once('/')
+        .map_or_else(String::new, |(parent, _)| parent.to_string())
+}
+
+fn missing(path: &str) -> String {
+    format!("The document has no value at '{}'", path)
+}
@@ -0,0 +1,70 @@
+use std::cmp::Reverse;
+
+// Keywords whose errors only summarise what went wrong in their subschemas
+const WEAK_KEYWORDS: &[&str] = &["anyOf", "oneOf"];
+
+/// A validation error the ranking heuristic can inspect.
+pub trait Rankable: Sized {
+    fn instance_path(&self) -> &str;
+    fn schema_path(&self) -> &str;
+    /// Errors of each subschema of a failed `oneOf` or `anyOf`
+    fn branches(&self) -> &[Vec<Self>];
+}
+
+/// Orders `errors` from most to least relevant, keeping the original order
+/// among equally relevant errors.
+pub fn sort<T: Rankable>(errors: &mut [T]) {
+    errors.sort_by_key(|error| Reverse(relevance(error)));
+}
+
+/// The error most likely to explain why the instance is invalid, in the
+/// spirit of Python jsonschema's `best_match`. Deeper errors win over
+/// shallower ones, and specific keywords over `anyOf` and `oneOf`. A winning
+/// `anyOf` or `oneOf` is replaced by the best error of the branch the
+/// instance came closest to matching.
+pub fn best<T: Rankable>(errors: &[T]) -> Option<&T> {
+    let mut best = most_relevant(errors)?;
+    while let Some(branch) = closest_branch(best) {
+        match most_relevant(branch) {
+            Some(error) => best = error,
+            None => break,
+        }
+    }
+    Some(best)
+}
+
+// The first of the most relevant errors
+fn most_relevant<T: Rankable>(errors: &[T]) -> Option<&T> {
+    errors.iter().min_by_key(|error| Reverse(relevance(*error)))
+}
+
+// Higher is more relevant: deeper instance locations first, then errors
+// from keywords other than `anyOf` and `oneOf`
+fn relevance<T: Rankable>(error: &T) -> (usize, bool) {
+    let depth = error.instance_path().matches('/').count();
+    (depth, !WEAK_KEYWORDS.contains(&keyword(error)))
+}
+
+// The branch with the fewest errors, preferring branches whose `type` the
+// instance has. None when a branch matched, as when several `oneOf`
+// subschemas do, since no branch explains that failure.
+fn closest_branch<T: Rankable>(error: &T) -> Option<&[T]> {
+    let branches = error.branches();
+    if branches.iter().any(Vec::is_empty) {
+        return None;
+    }
+    branches
+        .iter()
+        .min_by_key(|errors| {
+            let wrong_type = errors.iter().any(|nested| {
+                keyword(nested) == "type" && nested.instance_path() == error.instance_path()
+            });
+            (wrong_type, errors.len())
+        })
+        .map(Vec::as_slice)
+}
+
+// Error locations end with the keyword that failed
+fn keyword<T: Rankable>(error: &T) -> &str {
+    error.schema_path().rsplit('/').next().unwrap_or_default()
+}
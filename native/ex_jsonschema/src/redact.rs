@@ -0,0 +1,185 @@
+use referencing::Draft;
+use serde_json::{Map, Value};
+
+use crate::dereference::escape_pointer_segment;
+use crate::subschema::{is_within, parse_pointer};
+use crate::walk::walk;
+
+/// Which instance values must not appear in error output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Redaction {
+    /// Schema keyword that marks a value as sensitive when set to `true`,
+    /// in addition to `writeOnly: true` and `format: "password"`
+    pub keyword: String,
+    /// Instance pointers whose values are sensitive. A `*` segment matches
+    /// any property name or array index.
+    pub pointers: Vec<String>,
+    /// Replacement for sensitive values
+    pub mask: String,
+}
+
+impl Redaction {
+    /// Finds the sensitive values in `instance`. Values a schema in any
+    /// `anyOf`, `oneOf` or `if` branch marks are sensitive, whether or not
+    /// the branch applies.
+    pub fn redactor<'a>(
+        &'a self,
+        schema: &Value,
+        draft: Draft,
+        instance: &'a Value,
+    ) -> Redactor<'a> {
+        let mut sensitive = Vec::new();
+
+        walk(schema, draft, instance, None, &mut |map, _, cursor| {
+            if self.is_marked(map) {
+                sensitive.push(cursor.path.clone());
+            }
+        });
+        for pattern in &self.pointers {
+            if let Ok(tokens) = parse_pointer(pattern) {
+                expand(&tokens, instance, String::new(), &mut sensitive);
+            }
+        }
+
+        // Keep only the outermost sensitive locations
+        sensitive.sort();
+        sensitive.dedup();
+        let mut outermost: Vec<String> = Vec::new();
+        for path in sensitive {
+            if !outermost.iter().any(|ancestor| is_within(&path, ancestor)) {
+                outermost.push(path);
+            }
+        }
+
+        Redactor {
+            mask: &self.mask,
+            instance,
+            sensitive: outermost,
+        }
+    }
+
+    fn is_marked(&self, map: &Map<String, Value>) -> bool {
+        map.get("writeOnly") == Some(&Value::Bool(true))
+            || map.get("format").and_then(Value::as_str) == Some("password")
+            || map.get(&self.keyword) == Some(&Value::Bool(true))
+    }
+}
+
+/// Masks the sensitive values of one instance in error output.
+pub struct Redactor<'a> {
+    mask: &'a str,
+    instance: &'a Value,
+    // Paths of the outermost sensitive values
+    sensitive: Vec<String>,
+}
+
+impl Redactor<'_> {
+    pub fn is_empty(&self) -> bool {
+        self.sensitive.is_empty()
+    }
+
+    /// Whether the value at `path` is sensitive or inside a sensitive value.
+    pub fn is_masked(&self, path: &str) -> bool {
+        self.sensitive
+            .iter()
+            .any(|sensitive| is_within(path, sensitive))
+    }
+
+    /// The value at `path` with its sensitive parts masked.
+    pub fn instance_value(&self, path: &str, value: &Value) -> Value {
+        if self.is_masked(path) {
+            return Value::String(self.mask.to_string());
+        }
+
+        let mut value = value.clone();
+        for sensitive in &self.sensitive {
+            if !is_within(sensitive, path) {
+                continue;
+            }
+            if let Some(target) = value.pointer_mut(&sensitive[path.len()..]) {
+                *target = Value::String(self.mask.to_string());
+            }
+        }
+        value
+    }
+
+    /// `text` from an error at `path`, with the value there masked where it
+    /// quotes it. Messages quote their instance before anything else, so
+    /// only the first quote is masked; sensitive values elsewhere in the
+    /// instance are not quoted, so a short one such as `1` leaves numbers in
+    /// the rest of the text alone.
+    pub fn text(&self, path: &str, text: &str) -> String {
+        let Some(value) = self.instance.pointer(path) else {
+            return text.to_string();
+        };
+        let masked = self.instance_value(path, value);
+        if masked == *value {
+            return text.to_string();
+        }
+        text.replacen(&value.to_string(), &masked.to_string(), 1)
+    }
+
+    /// `value`, a detail of an error at `path`, with the value there masked
+    /// where it is or quotes it.
+    pub fn value(&self, path: &str, value: &Value) -> Value {
+        if self.instance.pointer(path) == Some(value) {
+            return self.instance_value(path, value);
+        }
+        match value {
+            Value::String(text) => Value::String(self.text(path, text)),
+            Value::Array(items) => {
+                Value::Array(items.iter().map(|item| self.value(path, item)).collect())
+            }
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(key, item)| (key.clone(), self.value(path, item)))
+                    .collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+}
+
+// Collects the locations in `instance` matching the pointer `tokens`
+fn expand(tokens: &[String], instance: &Value, path: String, out: &mut Vec<String>) {
+    let Some((token, rest)) = tokens.split_first() else {
+        out.push(path);
+        return;
+    };
+
+    let mut descend = |key: String, value: &Value| {
+        expand(
+            rest,
+            value,
+            format!("{}/{}", path, escape_pointer_segment(&key)),
+            out,
+        )
+    };
+    match instance {
+        Value::Object(object) if token == "*" => {
+            for (key, value) in object {
+                descend(key.clone(), value);
+            }
+        }
+        Value::Object(object) => {
+            if let Some(value) = object.get(token) {
+                descend(token.clone(), value);
+            }
+        }
+        Value::Array(items) if token == "*" => {
+            for (index, value) in items.iter().enumerate() {
+                descend(index.to_string(), value);
+            }
+        }
+        Value::Array(items) => {
+            if let Some(value) = token
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| items.get(index))
+            {
+                descend(token.clone(), value);
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,248 @@
+use referencing::Draft;
+use serde_json::{json, Map, Value};
+
+use crate::discriminator;
+use crate::formats::{self, FormatPolicy};
+use crate::openapi::{self, Dialect};
+use crate::redact::Redaction;
+
+// Identifies serialized validators and the layout they were written with
+const FORMAT: &str = "ex_jsonschema/compiled_schema";
+const VERSION: u64 = 6;
+// Versions 1 to 5 predate redaction, content validation, format policies,
+// OpenAPI dialects and discriminator inference respectively, which they
+// leave out
+const READABLE_VERSIONS: &[u64] = &[1, 2, 3, 4, 5, 6];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RegexEngine {
+    FancyRegex,
+    Regex,
+}
+
+/// The options a validator was built with. `None` leaves the choice to the
+/// `jsonschema` defaults, exactly as the original compile call did.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BuildOptions {
+    pub draft: Option<Draft>,
+    /// The OpenAPI dialect the schema is written in, if any; `draft` is then
+    /// the draft it extends
+    pub dialect: Option<Dialect>,
+    pub validate_formats: Option<bool>,
+    pub regex_engine: Option<RegexEngine>,
+    pub formats: FormatPolicy,
+    /// Not a validator setting: which values to mask in error output
+    pub redaction: Option<Redaction>,
+    /// Not a validator setting: whether to decode and validate encoded
+    /// content (`contentEncoding`, `contentMediaType`, `contentSchema`)
+    pub validate_content: bool,
+    /// Whether a `oneOf` without a `discriminator` is treated as having one
+    /// when a property's `const` tells its branches apart
+    pub infer_discriminators: bool,
+}
+
+impl BuildOptions {
+    /// The schema validators are built from: `schema`, translated out of
+    /// its OpenAPI dialect if any, with discriminated `oneOf` branches
+    /// guarded by their tags.
+    pub fn prepare(&self, schema: Value) -> Value {
+        let schema = match self.dialect {
+            Some(dialect) => dialect.translate(schema),
+            None => schema,
+        };
+        let draft = self
+            .draft
+            .unwrap_or_else(|| Draft::default().detect(&schema).unwrap_or_default());
+        discriminator::guard(schema, draft, self.infer_discriminators)
+    }
+
+    pub fn build(&self, schema: &Value) -> Result<jsonschema::Validator, String> {
+        let extra_formats = if self.dialect.is_some() {
+            openapi::FORMATS
+        } else {
+            &[]
+        };
+        self.formats.check_known(schema, extra_formats)?;
+        self.builder(schema)
+            .build(schema)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Validator options configured with these settings for `schema`, for
+    /// callers that need to add their own on top.
+    pub fn builder(&self, schema: &Value) -> jsonschema::ValidationOptions {
+        let mut builder = jsonschema::options();
+
+        if let Some(draft) = self.draft {
+            builder = builder.with_draft(draft);
+        }
+        // Left unset, the meta-schema decides whether `format` asserts
+        let validate_formats = self.validate_formats.or_else(|| {
+            formats::asserts_formats(schema, self.draft.unwrap_or_default()).then_some(true)
+        });
+        if let Some(validate_formats) = validate_formats {
+            builder = builder.should_validate_formats(validate_formats);
+        }
+        builder = self.formats.apply(builder);
+        match self.regex_engine {
+            // Use safer regex engine
+            Some(RegexEngine::Regex) => {
+                builder = builder.with_pattern_options(jsonschema::PatternOptions::regex());
+            }
+            // Use fancy_regex with security limits
+            Some(RegexEngine::FancyRegex) => {
+                builder = builder.with_pattern_options(
+                    jsonschema::PatternOptions::fancy_regex().backtrack_limit(10_000),
+                );
+            }
+            None => {}
+        }
+
+        builder
+    }
+}
+
+/// Serializes a schema together with the options needed to rebuild its
+/// validator.
+pub fn encode(schema: &Value, options: &BuildOptions) -> String {
+    json!({
+        "format": FORMAT,
+        "version": VERSION,
+        "draft": options.draft.map(draft_name),
+        "dialect": options.dialect.map(Dialect::name),
+        "validate_formats": options.validate_formats,
+        "regex_engine": options.regex_engine.map(|engine| match engine {
+            RegexEngine::FancyRegex => "fancy_regex",
+            RegexEngine::Regex => "regex",
+        }),
+        "redaction": options.redaction.as_ref().map(|redaction| json!({
+            "keyword": redaction.keyword,
+            "pointers": redaction.pointers,
+            "mask": redaction.mask,
+        })),
+        "validate_content": options.validate_content,
+        "infer_discriminators": options.infer_discriminators,
+        "formats": {
+            "allow": options.formats.allow,
+            "deny": options.formats.deny,
+            "reject_unknown": options.formats.reject_unknown,
+        },
+        "schema": schema,
+    })
+    .to_string()
+}
+
+/// Reads back the output of `encode`.
+pub fn decode(snapshot: &str) -> Result<(Value, BuildOptions), String> {
+    let Ok(Value::Object(mut fields)) = serde_json::from_str::<Value>(snapshot) else {
+        return Err("Not a serialized compiled schema".to_string());
+    };
+    if fields.get("format").and_then(Value::as_str) != Some(FORMAT) {
+        return Err("Not a serialized compiled schema".to_string());
+    }
+    match fields.get("version").and_then(Value::as_u64) {
+        Some(version) if READABLE_VERSIONS.contains(&version) => {}
+        other => {
+            return Err(format!(
+                "Unsupported serialization version {}, expected {}",
+                other.map_or_else(|| "unknown".to_string(), |v| v.to_string()),
+                VERSION
+            ))
+        }
+    }
+
+    let options = BuildOptions {
+        draft: optional_field(&fields, "draft", |value| {
+            value.as_str().and_then(draft_from_name)
+        })?,
+        dialect: optional_field(&fields, "dialect", |value| {
+            value.as_str().and_then(Dialect::from_name)
+        })?,
+        validate_formats: optional_field(&fields, "validate_formats", Value::as_bool)?,
+        regex_engine: optional_field(&fields, "regex_engine", |value| match value.as_str() {
+            Some("fancy_regex") => Some(RegexEngine::FancyRegex),
+            Some("regex") => Some(RegexEngine::Regex),
+            _ => None,
+        })?,
+        redaction: optional_field(&fields, "redaction", decode_redaction)?,
+        validate_content: optional_field(&fields, "validate_content", Value::as_bool)?
+            .unwrap_or(false),
+        formats: optional_field(&fields, "formats", decode_formats)?.unwrap_or_default(),
+        infer_discriminators: optional_field(&fields, "infer_discriminators", Value::as_bool)?
+            .unwrap_or(false),
+    };
+    let schema = fields
+        .remove("schema")
+        .ok_or_else(|| "Serialized compiled schema has no schema".to_string())?;
+
+    Ok((schema, options))
+}
+
+fn decode_redaction(value: &Value) -> Option<Redaction> {
+    let pointers = value
+        .get("pointers")?
+        .as_array()?
+        .iter()
+        .map(|pointer| pointer.as_str().map(str::to_string))
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(Redaction {
+        keyword: value.get("keyword")?.as_str()?.to_string(),
+        pointers,
+        mask: value.get("mask")?.as_str()?.to_string(),
+    })
+}
+
+fn decode_formats(value: &Value) -> Option<FormatPolicy> {
+    let names = |value: &Value| {
+        value
+            .as_array()?
+            .iter()
+            .map(|name| name.as_str().map(str::to_string))
+            .collect::<Option<Vec<_>>>()
+    };
+    let allow = match value.get("allow")? {
+        Value::Null => None,
+        allow => Some(names(allow)?),
+    };
+
+    Some(FormatPolicy {
+        allow,
+        deny: names(value.get("deny")?)?,
+        reject_unknown: value.get("reject_unknown")?.as_bool()?,
+    })
+}
+
+fn optional_field<T>(
+    fields: &Map<String, Value>,
+    name: &str,
+    parse: impl Fn(&Value) -> Option<T>,
+) -> Result<Option<T>, String> {
+    match fields.get(name) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => parse(value)
+            .map(Some)
+            .ok_or_else(|| format!("Invalid {} in serialized compiled schema: {}", name, value)),
+    }
+}
+
+fn draft_name(draft: Draft) -> &'static str {
+    match draft {
+        Draft::Draft4 => "draft4",
+        Draft::Draft6 => "draft6",
+        Draft::Draft7 => "draft7",
+        Draft::Draft201909 => "draft201909",
+        _ => "draft202012",
+    }
+}
+
+fn draft_from_name(name: &str) -> Option<Draft> {
+    match name {
+        "draft4" => Some(Draft::Draft4),
+        "draft6" => Some(Draft::Draft6),
+        "draft7" => Some(Draft::Draft7),
+        "draft201909" => Some(Draft::Draft201909),
+        "draft202012" => Some(Draft::Draft202012),
+        _ => None,
+    }
+}
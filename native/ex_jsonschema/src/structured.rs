@@ -0,0 +1,88 @@
+use jsonschema::error::{TypeKind, ValidationErrorKind};
+use serde_json::{json, Map, Value};
+
+use crate::subschema::parse_pointer;
+
+/// The values a failed keyword was checked against, by name: the `limit`
+/// of `minimum` or `maxLength`, the missing `property` of `required`, the
+/// `expected` types of `type`, and so on. Keywords without parameters, such
+/// as `uniqueItems` or `not`, have none.
+pub fn params(kind: &ValidationErrorKind) -> Value {
+    match kind {
+        ValidationErrorKind::Minimum { limit }
+        | ValidationErrorKind::Maximum { limit }
+        | ValidationErrorKind::ExclusiveMinimum { limit }
+        | ValidationErrorKind::ExclusiveMaximum { limit } => json!({ "limit": limit }),
+        ValidationErrorKind::MinLength { limit }
+        | ValidationErrorKind::MaxLength { limit }
+        | ValidationErrorKind::MinItems { limit }
+        | ValidationErrorKind::MaxItems { limit }
+        | ValidationErrorKind::MinProperties { limit }
+        | ValidationErrorKind::MaxProperties { limit } => json!({ "limit": limit }),
+        ValidationErrorKind::AdditionalItems { limit } => json!({ "limit": limit }),
+        ValidationErrorKind::Required { property } => json!({ "property": property }),
+        ValidationErrorKind::AdditionalProperties { unexpected }
+        | ValidationErrorKind::UnevaluatedProperties { unexpected }
+        | ValidationErrorKind::UnevaluatedItems { unexpected } => {
+            json!({ "unexpected": unexpected })
+        }
+        ValidationErrorKind::Type { kind } => {
+            let expected = match kind {
+                TypeKind::Single(kind) => json!(kind.to_string()),
+                TypeKind::Multiple(kinds) => {
+                    json!(kinds
+                        .iter()
+                        .map(|kind| kind.to_string())
+                        .collect::<Vec<_>>())
+                }
+            };
+            json!({ "expected": expected })
+        }
+        ValidationErrorKind::Enum { options } => json!({ "options": options }),
+        ValidationErrorKind::Constant { expected_value } => json!({ "expected": expected_value }),
+        ValidationErrorKind::MultipleOf { multiple_of } => json!({ "multiple_of": multiple_of }),
+        ValidationErrorKind::Pattern { pattern } => json!({ "pattern": pattern }),
+        ValidationErrorKind::Format { format } => json!({ "format": format }),
+        ValidationErrorKind::ContentEncoding { content_encoding } => {
+            json!({ "content_encoding": content_encoding })
+        }
+        ValidationErrorKind::ContentMediaType { content_media_type } => {
+            json!({ "content_media_type": content_media_type })
+        }
+        _ => Value::Object(Map::new()),
+    }
+}
+
+/// Splits the pointer to a location in `instance` into its segments: array
+/// indices as numbers and object keys as strings, even keys made of digits.
+/// Segments past the end of the instance, such as a missing property, are
+/// strings.
+pub fn instance_segments(instance: &Value, pointer: &str) -> Vec<Value> {
+    let Ok(tokens) = parse_pointer(pointer) else {
+        return Vec::new();
+    };
+    let mut current = Some(instance);
+    tokens
+        .into_iter()
+        .map(|token| match current {
+            Some(Value::Array(items)) => match token.parse::<usize>() {
+                Ok(index) => {
+                    current = items.get(index);
+                    Value::from(index)
+                }
+                Err(_) => {
+                    current = None;
+                    Value::String(token)
+                }
+            },
+            Some(Value::Object(object)) => {
+                current = object.get(&token);
+                Value::String(token)
+            }
+            _ => {
+                current = None;
+                Value::String(token)
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,510 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use referencing::{Draft, Registry, Resolver};
+use serde_json::{json, Map, Value};
+
+use crate::dereference::{build_registry, escape_pointer_segment};
+use crate::walk::ignores_ref_siblings;
+
+// Base URI of the `$ref` wrappers that point into the root schema, kept apart
+// from the root's own URI so the two never collide in the registry
+const FRAGMENT_BASE_URI: &str = "urn:ex-jsonschema:fragment";
+
+// Keywords that apply subschemas to an object's properties or an array's items
+const CHILD_KEYWORDS: &[&str] = &[
+    "properties",
+    "additionalProperties",
+    "items",
+    "prefixItems",
+    "additionalItems",
+];
+
+// Keywords whose effect on a child cannot be isolated to a single subschema
+const COMPOUND_KEYWORDS: &[&str] = &[
+    "allOf",
+    "anyOf",
+    "oneOf",
+    "not",
+    "if",
+    "then",
+    "else",
+    "dependentSchemas",
+    "dependencies",
+    "patternProperties",
+    "unevaluatedProperties",
+    "unevaluatedItems",
+    "$dynamicRef",
+    "$recursiveRef",
+];
+
+// Keywords that look past a value's direct children, so a change deep inside
+// it can flip their outcome
+const DEEP_KEYWORDS: &[&str] = &[
+    "const",
+    "enum",
+    "uniqueItems",
+    "contains",
+    "minContains",
+    "maxContains",
+];
+
+/// A schema inside the registry: the URI of a resource and a fragment,
+/// either a JSON pointer or an anchor name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    pub uri: String,
+    pub fragment: String,
+}
+
+impl Location {
+    pub fn reference(&self) -> String {
+        format!("{}#{}", self.uri, self.fragment)
+    }
+
+    /// The location `segments` below this one. Pointer fragments can be
+    /// extended, anchors cannot.
+    pub fn child(&self, segments: &[String]) -> Option<Location> {
+        if !self.fragment.is_empty() && !self.fragment.starts_with('/') {
+            return None;
+        }
+
+        let mut fragment = self.fragment.clone();
+        for segment in segments {
+            fragment.push('/');
+            fragment.push_str(&escape_pointer_segment(segment));
+        }
+        Some(Location {
+            uri: self.uri.clone(),
+            fragment,
+        })
+    }
+}
+
+/// The registry of a root schema, built once and shared by every lookup
+/// into it.
+pub struct Root {
+    registry: Registry,
+    base_uri: String,
+}
+
+impl Root {
+    pub fn new(schema: &Value, draft: Draft) -> Result<Root, String> {
+        let (registry, base_uri) = build_registry(schema, draft)?;
+        Ok(Root { registry, base_uri })
+    }
+
+    fn resolver(&self) -> Result<Resolver<'_>, String> {
+        self.registry
+            .try_resolver(&self.base_uri)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Whether `location` is the root schema itself.
+    pub fn is_root(&self, location: &Location) -> bool {
+        location.fragment.is_empty()
+            && (location.uri == self.base_uri
+                || self
+                    .resolver()
+                    .is_ok_and(|resolver| location.uri == resolver.base_uri().as_str()))
+    }
+}
+
+/// Where to validate an instance subtree.
+#[derive(Debug, PartialEq)]
+pub enum Target {
+    /// Against the schema at this location
+    Schema(Location),
+    /// Nothing in the schema constrains the subtree
+    Unconstrained,
+    /// Combinators decide what applies, so validate the whole instance and
+    /// keep the errors under the subtree
+    Whole,
+}
+
+/// Resolves `pointer` (`#/$defs/Address`, `/$defs/Address`, `#anchor` or a
+/// URI reference) against `root`.
+pub fn resolve(root: &Root, pointer: &str) -> Result<Location, String> {
+    let reference = if pointer.starts_with('/') {
+        format!("#{}", pointer)
+    } else {
+        pointer.to_string()
+    };
+
+    let resolver = root.resolver()?;
+    resolver
+        .lookup(&reference)
+        .map_err(|e| format!("Unable to resolve '{}': {}", pointer, e))?;
+    locate_reference(&resolver, &reference)
+}
+
+/// Finds the subschema that applies to the value at `pointer` in `instance`
+/// by following `properties`, `items` and their relatives, and `$ref`s, down
+/// from `root`.
+pub fn locate(root: &Root, instance: &Value, pointer: &str) -> Result<Target, String> {
+    locate_with(root, instance, pointer, false)
+}
+
+/// Like `locate`, but also returns `Target::Whole` when a schema above the
+/// subtree checks it as a whole (`const`, `enum`, `uniqueItems`, `contains`).
+/// Validating the subtree alone then catches every error a change inside it
+/// can cause anywhere in the document.
+pub fn locate_isolated(root: &Root, instance: &Value, pointer: &str) -> Result<Target, String> {
+    locate_with(root, instance, pointer, true)
+}
+
+fn locate_with(
+    root: &Root,
+    instance: &Value,
+    pointer: &str,
+    isolated: bool,
+) -> Result<Target, String> {
+    let resolver = root.resolver()?;
+    let (mut node, mut resolver, mut draft) = resolver
+        .lookup("#")
+        .map_err(|e| e.to_string())?
+        .into_inner();
+    let mut location = Some(Location {
+        uri: root.base_uri.clone(),
+        fragment: String::new(),
+    });
+    let tokens = parse_pointer(pointer)?;
+    if instance.pointer(pointer).is_none() {
+        return Err(missing_instance(pointer));
+    }
+    let mut instance = instance;
+
+    for token in tokens {
+        // Follow references until the node applies its own keywords
+        while let Value::Object(map) = node {
+            resolver = resolver
+                .in_subresource(draft.create_resource_ref(node))
+                .map_err(|e| e.to_string())?;
+            if isolated && has_deep_keywords(map) {
+                return Ok(Target::Whole);
+            }
+            let Some(reference) = map.get("$ref").and_then(Value::as_str) else {
+                break;
+            };
+
+            if !ignores_ref_siblings(draft) && applies_to_children(map) {
+                return Ok(Target::Whole);
+            }
+
+            location = Some(locate_reference(&resolver, reference)?);
+            let resolved = resolver
+                .lookup(reference)
+                .map_err(|e| format!("Unable to resolve '{}': {}", reference, e))?;
+            (node, resolver, draft) = resolved.into_inner();
+        }
+
+        let map = match node {
+            Value::Object(map) => map,
+            Value::Bool(true) => return Ok(Target::Unconstrained),
+            // A false schema rejects every child, which validating against it reports
+            _ => break,
+        };
+        if COMPOUND_KEYWORDS
+            .iter()
+            .any(|keyword| map.contains_key(*keyword))
+        {
+            return Ok(Target::Whole);
+        }
+
+        let child = match instance {
+            Value::Object(object) => {
+                instance = object
+                    .get(&token)
+                    .ok_or_else(|| missing_instance(pointer))?;
+                // `patternProperties` made the node compound above, so at
+                // most one subschema applies
+                property_schemas(map, &token).pop()
+            }
+            Value::Array(items) => {
+                let index = token
+                    .parse::<usize>()
+                    .map_err(|_| missing_instance(pointer))?;
+                instance = items.get(index).ok_or_else(|| missing_instance(pointer))?;
+                item_schema(map, index, draft)
+            }
+            _ => return Err(missing_instance(pointer)),
+        };
+
+        let Some((segments, subschema)) = child else {
+            return Ok(Target::Unconstrained);
+        };
+        location = location.and_then(|location| location.child(&segments));
+        node = subschema;
+    }
+
+    Ok(location.map_or(Target::Whole, Target::Schema))
+}
+
+/// The schema a keyword location points at, following `$ref` segments the
+/// way validators report them.
+pub fn keyword_target(schema: &Value, draft: Draft, path: &str) -> Option<Value> {
+    let (registry, base_uri) = build_registry(schema, draft).ok()?;
+    let resolver = registry.try_resolver(&base_uri).ok()?;
+    follow_keyword_path(&resolver, path).map(|(node, _, _)| node.clone())
+}
+
+/// `keyword_target` from the root of `resolver`'s registry, with the
+/// resolver and draft in effect at the target.
+pub fn follow_keyword_path<'r>(
+    resolver: &Resolver<'r>,
+    path: &str,
+) -> Option<(&'r Value, Resolver<'r>, Draft)> {
+    let (mut node, mut resolver, mut draft) = resolver.lookup("#").ok()?.into_inner();
+
+    for token in parse_pointer(path).ok()? {
+        node = match node {
+            Value::Object(map) if token == "$ref" => {
+                resolver = resolver
+                    .in_subresource(draft.create_resource_ref(node))
+                    .ok()?;
+                let reference = map.get("$ref")?.as_str()?;
+                let resolved = resolver.lookup(reference).ok()?;
+                (node, resolver, draft) = resolved.into_inner();
+                continue;
+            }
+            Value::Object(map) => {
+                resolver = resolver
+                    .in_subresource(draft.create_resource_ref(node))
+                    .ok()?;
+                map.get(&token)?
+            }
+            Value::Array(items) => items.get(token.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some((node, resolver, draft))
+}
+
+/// Compiles a validator for the schema at `location` with the options
+/// `root_validator` was compiled with. References inside it resolve against
+/// the root schema, exactly as they do for the root validator.
+///
+/// jsonschema cannot validate against part of a compiled validator, so this
+/// compiles a `$ref` to the location, which only compiles what it reaches.
+/// Registries cannot be shared between validators, so each call builds a
+/// fresh one from `schema`.
+pub fn build_validator(
+    root_validator: &jsonschema::Validator,
+    schema: &Value,
+    draft: Draft,
+    location: &Location,
+) -> Result<jsonschema::Validator, String> {
+    let (registry, _) = build_registry(schema, draft)?;
+
+    (*root_validator.config())
+        .clone()
+        .with_draft(draft)
+        .with_registry(registry)
+        .with_base_uri(FRAGMENT_BASE_URI)
+        .build(&json!({ "$ref": location.reference() }))
+        .map_err(|e| e.to_string())
+}
+
+/// The validator for the schema at a location.
+pub struct Fragment {
+    pub validator: Arc<jsonschema::Validator>,
+    location: Location,
+    // Whether `validator` checks a `$ref` to the location rather than being
+    // the root validator
+    wrapped: bool,
+}
+
+impl Fragment {
+    /// Rewrites a schema path reported by the validator, which for a wrapper
+    /// starts at its `$ref`, so that it starts at the fragment's resource.
+    pub fn schema_path(&self, path: &str) -> String {
+        let fragment = &self.location.fragment;
+        match path.strip_prefix("/$ref") {
+            Some(rest) if self.wrapped && (fragment.is_empty() || fragment.starts_with('/')) => {
+                format!("{}{}", fragment, rest)
+            }
+            _ => path.to_string(),
+        }
+    }
+}
+
+/// The root schema's registry, for looking up subschemas, and the fragment
+/// validators built so far, keyed by location.
+#[derive(Default)]
+pub struct Fragments {
+    root: OnceLock<Result<Root, String>>,
+    validators: Mutex<HashMap<String, Arc<jsonschema::Validator>>>,
+}
+
+impl Fragments {
+    /// The registry of `schema`, built on first use.
+    pub fn root(&self, schema: &Value, draft: Draft) -> Result<&Root, String> {
+        self.root
+            .get_or_init(|| Root::new(schema, draft))
+            .as_ref()
+            .map_err(Clone::clone)
+    }
+
+    /// Returns the validator for `location`: `root_validator` for the root
+    /// schema itself, otherwise a validator built on first use. The flag is
+    /// true when this call built it.
+    pub fn get_or_build(
+        &self,
+        root_validator: &Arc<jsonschema::Validator>,
+        schema: &Value,
+        draft: Draft,
+        location: &Location,
+    ) -> Result<(Fragment, bool), String> {
+        if self.root(schema, draft)?.is_root(location) {
+            let fragment = Fragment {
+                validator: Arc::clone(root_validator),
+                location: location.clone(),
+                wrapped: false,
+            };
+            return Ok((fragment, false));
+        }
+
+        let key = location.reference();
+        let cached = self.lock().get(&key).cloned();
+        let (validator, built) = match cached {
+            Some(validator) => (validator, false),
+            None => {
+                // Build outside the lock; a concurrent build of the same fragment wins or loses harmlessly
+                let validator = Arc::new(build_validator(root_validator, schema, draft, location)?);
+                (self.lock().entry(key).or_insert(validator).clone(), true)
+            }
+        };
+        let fragment = Fragment {
+            validator,
+            location: location.clone(),
+            wrapped: true,
+        };
+        Ok((fragment, built))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Arc<jsonschema::Validator>>> {
+        self.validators
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Splits an RFC 6901 JSON pointer into unescaped tokens.
+pub fn parse_pointer(pointer: &str) -> Result<Vec<String>, String> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(rest) = pointer.strip_prefix('/') else {
+        return Err(format!("'{}' is not a JSON pointer", pointer));
+    };
+
+    Ok(rest
+        .split('/')
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+/// Whether the pointer `path` is `ancestor` or lies below it.
+pub fn is_within(path: &str, ancestor: &str) -> bool {
+    path.strip_prefix(ancestor)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Absolute location of `reference`: the URI of the resource it lands in and its fragment.
+pub fn locate_reference(resolver: &Resolver, reference: &str) -> Result<Location, String> {
+    let (uri, fragment) = reference.split_once('#').unwrap_or((reference, ""));
+    let document = resolver
+        .lookup(if uri.is_empty() { "#" } else { uri })
+        .map_err(|e| format!("Unable to resolve '{}': {}", reference, e))?;
+
+    Ok(Location {
+        uri: document.resolver().base_uri().as_str().to_string(),
+        fragment: fragment.to_string(),
+    })
+}
+
+fn has_deep_keywords(map: &Map<String, Value>) -> bool {
+    DEEP_KEYWORDS
+        .iter()
+        .any(|keyword| map.contains_key(*keyword))
+}
+
+fn applies_to_children(map: &Map<String, Value>) -> bool {
+    map.keys().any(|key| {
+        CHILD_KEYWORDS.contains(&key.as_str()) || COMPOUND_KEYWORDS.contains(&key.as_str())
+    })
+}
+
+/// The subschemas for property `name` and the path segments leading to
+/// each: its entry in `properties` and every `patternProperties` entry whose
+/// pattern matches it, or else `additionalProperties`.
+pub fn property_schemas<'a>(
+    map: &'a Map<String, Value>,
+    name: &str,
+) -> Vec<(Vec<String>, &'a Value)> {
+    let mut schemas = Vec::new();
+    if let Some(schema) = map
+        .get("properties")
+        .and_then(Value::as_object)
+        .and_then(|properties| properties.get(name))
+    {
+        schemas.push((vec!["properties".to_string(), name.to_string()], schema));
+    }
+    if let Some(patterns) = map.get("patternProperties").and_then(Value::as_object) {
+        for (pattern, schema) in patterns {
+            // Patterns the validator would reject never match
+            let matches = fancy_regex::Regex::new(pattern)
+                .ok()
+                .and_then(|regex| regex.is_match(name).ok())
+                .unwrap_or(false);
+            if matches {
+                schemas.push((
+                    vec!["patternProperties".to_string(), pattern.clone()],
+                    schema,
+                ));
+            }
+        }
+    }
+    if schemas.is_empty() {
+        if let Some(schema) = map.get("additionalProperties") {
+            schemas.push((vec!["additionalProperties".to_string()], schema));
+        }
+    }
+    schemas
+}
+
+/// The subschema for the item at `index` and the path segments leading to it.
+pub fn item_schema(
+    map: &Map<String, Value>,
+    index: usize,
+    draft: Draft,
+) -> Option<(Vec<String>, &Value)> {
+    let legacy = matches!(
+        draft,
+        Draft::Draft4 | Draft::Draft6 | Draft::Draft7 | Draft::Draft201909
+    );
+    let (tuple_keyword, rest_keyword) = if legacy {
+        ("items", "additionalItems")
+    } else {
+        ("prefixItems", "items")
+    };
+
+    let rest = || {
+        map.get(rest_keyword)
+            .map(|schema| (vec![rest_keyword.to_string()], schema))
+    };
+
+    match map.get(tuple_keyword) {
+        Some(Value::Array(tuple)) => match tuple.get(index) {
+            Some(schema) => Some((vec![tuple_keyword.to_string(), index.to_string()], schema)),
+            None => rest(),
+        },
+        // Before 2020-12 a single `items` schema applies to every item
+        Some(schema) if legacy => Some((vec!["items".to_string()], schema)),
+        _ => rest(),
+    }
+}
+
+fn missing_instance(pointer: &str) -> String {
+    format!("The instance has no value at '{}'", pointer)
+}
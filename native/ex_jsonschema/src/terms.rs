@@ -0,0 +1,125 @@
+use rustler::types::map::MapIterator;
+use rustler::{Atom, Binary, ListIterator, Term};
+use serde_json::{Map, Value};
+
+use crate::binary::{at, float_value, member_pointer, non_string_key, unrepresentable};
+
+/// Reads an Elixir term as a JSON value, the way Jason would encode it:
+/// `nil`, `true` and `false` are null and booleans, other atoms are
+/// strings, and map keys may be strings, atoms or integers. Structs,
+/// tuples, non-UTF-8 binaries and other terms JSON has no equivalent for
+/// are errors that say where they are.
+pub fn to_json(term: Term) -> Result<Value, String> {
+    json_value(term, "")
+}
+
+fn json_value(term: Term, pointer: &str) -> Result<Value, String> {
+    if term.is_atom() {
+        let name = term.atom_to_string().unwrap_or_default();
+        return Ok(match name.as_str() {
+            "nil" => Value::Null,
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            _ => Value::String(name),
+        });
+    }
+    if term.is_binary() {
+        return string(term, pointer).map(Value::String);
+    }
+    if term.is_number() {
+        if let Ok(integer) = term.decode::<i64>() {
+            return Ok(Value::from(integer));
+        }
+        if let Ok(integer) = term.decode::<u64>() {
+            return Ok(Value::from(integer));
+        }
+        return match term.decode::<f64>() {
+            Ok(float) => float_value(float, pointer),
+            Err(_) => Err(unrepresentable("Integer beyond 64 bits", pointer)),
+        };
+    }
+    if term.is_list() {
+        let items: ListIterator = term
+            .decode()
+            .map_err(|_| unrepresentable("Improper list", pointer))?;
+        return items
+            .enumerate()
+            .map(|(index, item)| json_value(item, &format!("{}/{}", pointer, index)))
+            .collect::<Result<_, _>>()
+            .map(Value::Array);
+    }
+    if term.is_map() {
+        return object(term, pointer);
+    }
+    Err(unrepresentable(&capitalize(kind(term)), pointer))
+}
+
+fn object(term: Term, pointer: &str) -> Result<Value, String> {
+    let module = Atom::from_str(term.get_env(), "__struct__")
+        .ok()
+        .and_then(|key| term.map_get(key).ok());
+    if let Some(module) = module {
+        let module = module.atom_to_string().unwrap_or_default();
+        let module = module.strip_prefix("Elixir.").unwrap_or(&module);
+        return Err(unrepresentable(&format!("%{}{{}} struct", module), pointer));
+    }
+
+    let entries = MapIterator::new(term).ok_or_else(|| unrepresentable("Map", pointer))?;
+    let mut object = Map::new();
+    for (key, value) in entries {
+        let key = if key.is_binary() {
+            string(key, pointer)?
+        } else if key.is_atom() {
+            key.atom_to_string().unwrap_or_default()
+        } else if let Ok(integer) = key.decode::<i64>() {
+            integer.to_string()
+        } else {
+            return Err(non_string_key(kind(key), pointer));
+        };
+        let member = member_pointer(&mut object, &key, pointer)?;
+        let value = json_value(value, &member)?;
+        object.insert(key, value);
+    }
+    Ok(Value::Object(object))
+}
+
+fn string(term: Term, pointer: &str) -> Result<String, String> {
+    let binary: Binary = term
+        .decode()
+        .map_err(|_| unrepresentable("Binary", pointer))?;
+    std::str::from_utf8(binary.as_slice())
+        .map(str::to_string)
+        .map_err(|_| format!("Binary at {} is not valid UTF-8", at(pointer)))
+}
+
+// What a term JSON has no equivalent for is, for messages
+fn kind(term: Term) -> &'static str {
+    if term.is_number() {
+        "a number"
+    } else if term.is_list() {
+        "a list"
+    } else if term.is_map() {
+        "a map"
+    } else if term.is_tuple() {
+        "a tuple"
+    } else if term.is_pid() {
+        "a pid"
+    } else if term.is_ref() {
+        "a reference"
+    } else if term.is_fun() {
+        "a function"
+    } else if term.is_port() {
+        "a port"
+    } else {
+        "a term"
+    }
+}
+
+// "a tuple" becomes "A tuple"
+fn capitalize(kind: &str) -> String {
+    let mut chars = kind.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
@@ -0,0 +1,35 @@
+use std::time::Instant;
+
+/// Measures consecutive phases of a NIF call.
+pub struct Stopwatch {
+    last: Instant,
+}
+
+impl Stopwatch {
+    pub fn start() -> Self {
+        Stopwatch {
+            last: Instant::now(),
+        }
+    }
+
+    /// Nanoseconds since the previous lap (or since `start`).
+    pub fn lap(&mut self) -> u64 {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last).as_nanos() as u64;
+        self.last = now;
+        elapsed
+    }
+}
+
+/// Time spent in each phase of a NIF call, in nanoseconds. Phases a call
+/// does not go through are left out.
+#[derive(Debug, Default, Clone)]
+pub struct Measurements {
+    pub parse_time: Option<u64>,
+    pub patch_time: Option<u64>,
+    pub build_time: Option<u64>,
+    pub validation_time: Option<u64>,
+    pub enrichment_time: Option<u64>,
+    pub encode_time: Option<u64>,
+    pub error_count: Option<usize>,
+}
@@ -0,0 +1,282 @@
+use std::sync::Arc;
+
+use referencing::{Draft, Resolver};
+use serde_json::{Map, Value};
+
+use crate::dereference::{build_registry, escape_pointer_segment, MAX_REFERENCE_DEPTH};
+use crate::subschema::{item_schema, locate_reference, property_schemas, Location};
+
+// Keywords whose values are instance data rather than subschemas
+const DATA_KEYWORDS: &[&str] = &["const", "default", "enum", "example", "examples"];
+
+// Keywords whose values map names of the schema author's choosing to
+// subschemas, so those names are never keywords
+const SCHEMA_MAPS: &[&str] = &[
+    "$defs",
+    "definitions",
+    "dependencies",
+    "dependentSchemas",
+    "patternProperties",
+    "properties",
+];
+
+/// An error found by walking the instance alongside its schema, outside the
+/// validator.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub instance_path: String,
+    pub schema_path: String,
+    pub message: String,
+    pub instance_value: Value,
+}
+
+/// Called with a schema object, the instance value it applies to, and where
+/// the walk is.
+pub type Visit<'v> = dyn FnMut(&Map<String, Value>, &Value, &Cursor) + 'v;
+
+/// Returns the validator for the subschema at a location.
+pub type ValidatorFor<'v> = dyn Fn(&Location) -> Result<Arc<jsonschema::Validator>, String> + 'v;
+
+/// Where a walk is in the instance and the schema.
+#[derive(Clone)]
+pub struct Cursor {
+    pub path: String,
+    /// Keyword location as validators report it, through `$ref`s
+    pub schema_path: String,
+    /// Absolute location, unknown below anchors
+    pub location: Option<Location>,
+}
+
+impl Cursor {
+    fn schema_child(&self, segments: &[String]) -> Cursor {
+        Cursor {
+            path: self.path.clone(),
+            schema_path: join_pointer(&self.schema_path, segments),
+            location: self
+                .location
+                .as_ref()
+                .and_then(|location| location.child(segments)),
+        }
+    }
+}
+
+/// Whether `draft` ignores every keyword next to `$ref`, as drafts before
+/// 2019-09 do.
+pub fn ignores_ref_siblings(draft: Draft) -> bool {
+    matches!(draft, Draft::Draft4 | Draft::Draft6 | Draft::Draft7)
+}
+
+/// The schema objects directly inside the schema object `map`, each with
+/// the segments of the path to it: the keyword, then the name or index
+/// under it for keywords holding several subschemas.
+///
+/// Data keywords such as `default` and `enum` hold no subschemas, but
+/// properties and definitions named after them do. Values of unknown
+/// keywords are taken for subschemas.
+pub fn subschemas(map: &Map<String, Value>) -> Vec<(Vec<String>, &Value)> {
+    let mut subschemas = Vec::new();
+    for (keyword, value) in map {
+        if DATA_KEYWORDS.contains(&keyword.as_str()) {
+            continue;
+        }
+        if SCHEMA_MAPS.contains(&keyword.as_str()) {
+            if let Value::Object(members) = value {
+                for (name, member) in members {
+                    subschemas.push((vec![keyword.clone(), name.clone()], member));
+                }
+            }
+            continue;
+        }
+        match value {
+            Value::Array(items) => {
+                for (index, item) in items.iter().enumerate() {
+                    subschemas.push((vec![keyword.clone(), index.to_string()], item));
+                }
+            }
+            value => subschemas.push((vec![keyword.clone()], value)),
+        }
+    }
+    subschemas.retain(|(_, subschema)| subschema.is_object());
+    subschemas
+}
+
+/// `subschemas`, for changing them in place.
+pub fn subschemas_mut(map: &mut Map<String, Value>) -> Vec<(Vec<String>, &mut Value)> {
+    let mut subschemas = Vec::new();
+    for (keyword, value) in map.iter_mut() {
+        if DATA_KEYWORDS.contains(&keyword.as_str()) {
+            continue;
+        }
+        if SCHEMA_MAPS.contains(&keyword.as_str()) {
+            if let Value::Object(members) = value {
+                for (name, member) in members.iter_mut() {
+                    subschemas.push((vec![keyword.clone(), name.clone()], member));
+                }
+            }
+            continue;
+        }
+        match value {
+            Value::Array(items) => {
+                for (index, item) in items.iter_mut().enumerate() {
+                    subschemas.push((vec![keyword.clone(), index.to_string()], item));
+                }
+            }
+            value => subschemas.push((vec![keyword.clone()], value)),
+        }
+    }
+    subschemas.retain(|(_, subschema)| subschema.is_object());
+    subschemas
+}
+
+/// `pointer` extended by `segments`, escaped.
+pub fn join_pointer(pointer: &str, segments: &[String]) -> String {
+    let mut pointer = pointer.to_string();
+    for segment in segments {
+        pointer.push('/');
+        pointer.push_str(&escape_pointer_segment(segment));
+    }
+    pointer
+}
+
+/// Calls `visit` with every schema object that applies to each value in
+/// `instance`, and where the walk found it.
+///
+/// Schemas reached through `$ref`, `allOf`, `anyOf`, `oneOf`, `if`/`then`/
+/// `else`, the property keywords and the item keywords are visited; `not`
+/// is not. With `validator_for`, only the `anyOf` and `oneOf` branches the
+/// value is valid against are visited, and `then` or `else` as `if` decides.
+/// Without it, or where a branch's location is unknown, every branch is.
+pub fn walk(
+    schema: &Value,
+    draft: Draft,
+    instance: &Value,
+    validator_for: Option<&ValidatorFor>,
+    visit: &mut Visit,
+) {
+    let Ok((registry, base_uri)) = build_registry(schema, draft) else {
+        return;
+    };
+    let Ok(resolver) = registry.try_resolver(&base_uri) else {
+        return;
+    };
+    let Ok(root) = resolver.lookup("#") else {
+        return;
+    };
+
+    let (node, resolver, draft) = root.into_inner();
+    let cursor = Cursor {
+        path: String::new(),
+        schema_path: String::new(),
+        location: Some(Location {
+            uri: base_uri.clone(),
+            fragment: String::new(),
+        }),
+    };
+    let mut walker = Walker {
+        validator_for,
+        visit,
+    };
+    walker.visit_node(node, resolver, draft, instance, cursor, 0);
+}
+
+struct Walker<'w, 'v> {
+    validator_for: Option<&'w ValidatorFor<'w>>,
+    visit: &'w mut Visit<'v>,
+}
+
+impl Walker<'_, '_> {
+    // Whether `instance` is valid against the subschema at `cursor`, or
+    // `None` when that cannot be told
+    fn accepts(&self, cursor: &Cursor, instance: &Value) -> Option<bool> {
+        let validator_for = self.validator_for?;
+        let location = cursor.location.as_ref()?;
+        let validator = validator_for(location).ok()?;
+        Some(validator.is_valid(instance))
+    }
+
+    fn visit_node(
+        &mut self,
+        node: &Value,
+        resolver: Resolver,
+        draft: Draft,
+        instance: &Value,
+        cursor: Cursor,
+        depth: usize,
+    ) {
+        let Value::Object(map) = node else {
+            return;
+        };
+        let Ok(resolver) = resolver.in_subresource(draft.create_resource_ref(node)) else {
+            return;
+        };
+
+        if let Some(reference) = map.get("$ref").and_then(Value::as_str) {
+            if depth < MAX_REFERENCE_DEPTH {
+                if let Ok(resolved) = resolver.lookup(reference) {
+                    let mut target = cursor.schema_child(&["$ref".to_string()]);
+                    target.location = locate_reference(&resolver, reference).ok();
+                    let (node, resolver, draft) = resolved.into_inner();
+                    self.visit_node(node, resolver, draft, instance, target, depth + 1);
+                }
+            }
+            if ignores_ref_siblings(draft) {
+                return;
+            }
+        }
+
+        let mut branches = Vec::new();
+        for keyword in ["allOf", "anyOf", "oneOf"] {
+            let Some(Value::Array(subschemas)) = map.get(keyword) else {
+                continue;
+            };
+            for (index, subschema) in subschemas.iter().enumerate() {
+                let child = cursor.schema_child(&[keyword.to_string(), index.to_string()]);
+                if keyword == "allOf" || self.accepts(&child, instance) != Some(false) {
+                    branches.push((subschema, child));
+                }
+            }
+        }
+        if let Some(condition) = map.get("if") {
+            let child = cursor.schema_child(&["if".to_string()]);
+            let accepted = self.accepts(&child, instance);
+            if accepted != Some(false) {
+                branches.push((condition, child));
+            }
+            for (keyword, taken) in [("then", true), ("else", false)] {
+                if let Some(subschema) = map.get(keyword) {
+                    if accepted.is_none_or(|accepted| accepted == taken) {
+                        let child = cursor.schema_child(&[keyword.to_string()]);
+                        branches.push((subschema, child));
+                    }
+                }
+            }
+        }
+        for (subschema, child) in branches {
+            self.visit_node(subschema, resolver.clone(), draft, instance, child, depth);
+        }
+
+        (self.visit)(map, instance, &cursor);
+
+        match instance {
+            Value::Object(object) => {
+                for (name, value) in object {
+                    for (segments, subschema) in property_schemas(map, name) {
+                        let mut child = cursor.schema_child(&segments);
+                        child.path = format!("{}/{}", cursor.path, escape_pointer_segment(name));
+                        self.visit_node(subschema, resolver.clone(), draft, value, child, 0);
+                    }
+                }
+            }
+            Value::Array(items) => {
+                for (index, item) in items.iter().enumerate() {
+                    if let Some((segments, subschema)) = item_schema(map, index, draft) {
+                        let mut child = cursor.schema_child(&segments);
+                        child.path = format!("{}/{}", cursor.path, index);
+                        self.visit_node(subschema, resolver.clone(), draft, item, child, 0);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -26,7 +26,8 @@ defmodule ExJsonschema.CacheBehaviorTest do
       {:ok, validator1} = ExJsonschema.compile(schema_with_id)
 
       # Verify it's cached by checking if we can get it directly
-      assert {:ok, _cached} = ExJsonschema.Cache.Test.get("http://example.com/person.json")
+      {:ok, key} = ExJsonschema.Cache.key(schema_with_id)
+      assert {:ok, _cached} = ExJsonschema.Cache.Test.get(key)
 
       # Second compilation with same schema - should reuse cached result
       {:ok, validator2} = ExJsonschema.compile(schema_with_id)
@@ -53,43 +54,64 @@ defmodule ExJsonschema.CacheBehaviorTest do
       {:ok, _validator2} = ExJsonschema.compile(schema2)
 
       # Both should be cached
-      assert {:ok, _} = ExJsonschema.Cache.Test.get("http://example.com/schema1.json")
-      assert {:ok, _} = ExJsonschema.Cache.Test.get("http://example.com/schema2.json")
+      {:ok, key1} = ExJsonschema.Cache.key(schema1)
+      {:ok, key2} = ExJsonschema.Cache.key(schema2)
+      assert key1 != key2
+      assert {:ok, _} = ExJsonschema.Cache.Test.get(key1)
+      assert {:ok, _} = ExJsonschema.Cache.Test.get(key2)
 
       # Recompile first schema - should reuse cache
       {:ok, validator1_cached} = ExJsonschema.compile(schema1)
       assert validator1 == validator1_cached
     end
 
-    test "schemas without IDs bypass cache" do
+    test "schemas without IDs are cached by content" do
       schema_no_id = ~s({"type": "object", "properties": {"name": {"type": "string"}}})
+      reformatted = ~s({"properties":{"name":{"type":"string"}},"type":"object"})
 
-      # Compile schema multiple times - should never use cache
       {:ok, validator1} = ExJsonschema.compile(schema_no_id)
-      {:ok, validator2} = ExJsonschema.compile(schema_no_id)
-      {:ok, validator3} = ExJsonschema.compile(schema_no_id)
+      {:ok, validator2} = ExJsonschema.compile(reformatted)
+
+      # Whitespace and key order do not change the cache key
+      assert validator1 == validator2
+    end
+
+    test "schemas sharing $schema do not collide" do
+      string_schema = ~s({"$schema": "http://json-schema.org/draft-07/schema#", "type": "string"})
+      number_schema = ~s({"$schema": "http://json-schema.org/draft-07/schema#", "type": "number"})
+
+      {:ok, string_validator} = ExJsonschema.compile(string_schema)
+      {:ok, number_validator} = ExJsonschema.compile(number_schema)
+
+      assert string_validator != number_validator
+      assert :ok = ExJsonschema.validate(string_validator, ~s("text"))
+      assert :ok = ExJsonschema.validate(number_validator, ~s(42))
+    end
+
+    test "schemas sharing $id but differing in content do not collide" do
+      v1 = ~s({"$id": "http://example.com/versioned.json", "type": "string"})
+      v2 = ~s({"$id": "http://example.com/versioned.json", "type": "integer"})
+
+      {:ok, validator1} = ExJsonschema.compile(v1)
+      {:ok, validator2} = ExJsonschema.compile(v2)
 
-      # Each should be a different reference since they're not cached
       assert validator1 != validator2
-      assert validator2 != validator3
+      assert {:error, _} = ExJsonschema.validate(validator2, ~s("text"))
     end
 
-    test "uses $schema as fallback ID when no $id present" do
-      schema_with_schema = ~s({
-        "$schema": "http://json-schema.org/draft-07/schema#",
-        "type": "string",
-        "minLength": 1
-      })
+    test "compile options are part of the cache key" do
+      schema = ~s({"type": "string", "format": "email"})
 
-      # First compilation
-      {:ok, validator1} = ExJsonschema.compile(schema_with_schema)
+      {:ok, lenient} = ExJsonschema.compile(schema)
+      {:ok, strict} = ExJsonschema.compile(schema, validate_formats: true)
+      {:ok, regex} = ExJsonschema.compile(schema, regex_engine: :regex)
 
-      # Should be cached using $schema as key
-      assert {:ok, _} = ExJsonschema.Cache.Test.get("http://json-schema.org/draft-07/schema#")
+      assert lenient != strict
+      assert lenient != regex
+      assert ExJsonschema.valid?(lenient, ~s("not an email"))
+      refute ExJsonschema.valid?(strict, ~s("not an email"))
 
-      # Second compilation - should hit cache using $schema as key
-      {:ok, validator2} = ExJsonschema.compile(schema_with_schema)
-      assert validator1 == validator2
+      assert {:ok, ^strict} = ExJsonschema.compile(schema, validate_formats: true)
     end
 
     test "cache operations work correctly" do
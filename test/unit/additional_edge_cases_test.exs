@@ -51,13 +51,13 @@ defmodule ExJsonschema.AdditionalEdgeCasesTest do
 
       result = ExJsonschema.compile(schema_with_draft, draft: :auto)
       assert {:ok, compiled} = result
-      assert is_reference(compiled)
+      assert match?(%ExJsonschema.Schema{}, compiled)
 
       # Test without $schema
       schema_no_draft = ~s({"type": "number"})
       result = ExJsonschema.compile(schema_no_draft, draft: :auto)
       assert {:ok, compiled} = result
-      assert is_reference(compiled)
+      assert match?(%ExJsonschema.Schema{}, compiled)
     end
 
     test "Options validation edge cases" do
@@ -88,7 +88,7 @@ defmodule ExJsonschema.AdditionalEdgeCasesTest do
       for opts <- option_sets do
         result = ExJsonschema.compile(schema, opts)
         assert {:ok, compiled} = result
-        assert is_reference(compiled)
+        assert match?(%ExJsonschema.Schema{}, compiled)
       end
     end
   end
@@ -0,0 +1,243 @@
+defmodule ExJsonschema.AvroTest do
+  use ExUnit.Case, async: true
+
+  alias ExJsonschema.{Avro, CompilationError}
+
+  doctest ExJsonschema.Avro
+  doctest ExJsonschema, only: [to_avro: 2, from_avro: 1]
+
+  @order %{
+    "title" => "order",
+    "type" => "object",
+    "required" => ["id", "items", "status"],
+    "properties" => %{
+      "id" => %{"type" => "string", "format" => "uuid"},
+      "placed" => %{"type" => "string", "format" => "date"},
+      "status" => %{"enum" => ["open", "closed"]},
+      "note" => %{"type" => ["string", "null"], "maxLength" => 10},
+      "items" => %{"type" => "array", "items" => %{"$ref" => "#/$defs/item"}},
+      "gift" => %{"$ref" => "#/$defs/item"},
+      "labels" => %{"type" => "object", "additionalProperties" => %{"type" => "string"}},
+      "first-name" => %{"type" => "string", "default" => "x"},
+      "count" => %{"type" => "integer", "minimum" => 0, "default" => 1}
+    },
+    "$defs" => %{
+      "item" => %{
+        "type" => "object",
+        "required" => ["sku"],
+        "properties" => %{"sku" => %{"type" => "string"}},
+        "additionalProperties" => false
+      }
+    }
+  }
+
+  describe "to_avro/2" do
+    test "converts objects into records" do
+      assert {:ok, avro, _issues} = Avro.to_avro(@order, namespace: "com.shop")
+
+      assert %{"type" => "record", "name" => "Order", "namespace" => "com.shop"} = avro
+      fields = Map.new(avro["fields"], &{&1["name"], &1})
+
+      assert fields["id"]["type"] == %{"type" => "string", "logicalType" => "uuid"}
+      assert fields["placed"]["type"] == ["null", %{"type" => "int", "logicalType" => "date"}]
+      assert %{"default" => nil} = fields["placed"]
+      assert fields["status"]["type"]["symbols"] == ["open", "closed"]
+      assert fields["note"]["type"] == ["null", "string"]
+      assert fields["labels"]["type"] == ["null", %{"type" => "map", "values" => "string"}]
+      assert fields["first_name"]["default"] == "x"
+      assert %{"type" => "long", "default" => 1} = fields["count"]
+    end
+
+    test "defines referenced records once and names them after that" do
+      assert {:ok, avro, _issues} = Avro.to_avro(@order, namespace: "com.shop")
+      fields = Map.new(avro["fields"], &{&1["name"], &1})
+
+      assert ["null", %{"type" => "record", "name" => "Item"} = item] = fields["gift"]["type"]
+      refute Map.has_key?(item, "namespace")
+      assert fields["items"]["type"] == %{"type" => "array", "items" => "Item"}
+    end
+
+    test "reports what Avro cannot express at its path" do
+      assert {:ok, _avro, issues} = Avro.to_avro(@order)
+
+      assert Enum.map(issues, &{&1.path, &1.keyword}) == [
+               {"/properties/count/minimum", "minimum"},
+               {"/properties/first-name", "properties"},
+               {"/properties/note/maxLength", "maxLength"}
+             ]
+
+      assert Enum.at(issues, 1).message ==
+               ~s("first-name" becomes the field "first_name"; ) <>
+                 "Avro names hold only letters, digits and underscores"
+    end
+
+    test "converts oneOf into unions, dropping branches of the same type" do
+      schema = ~s({"oneOf": [
+        {"type": "integer"},
+        {"type": "integer", "maximum": 3},
+        {"type": "null"},
+        {"type": "string", "format": "date-time"}
+      ]})
+
+      assert {:ok, ["long", "null"], issues} = Avro.to_avro(schema)
+
+      assert Enum.map(issues, &{&1.path, &1.keyword}) == [
+               {"/oneOf/1/maximum", "maximum"},
+               {"/oneOf/1", "oneOf"},
+               {"/oneOf/3/format", "format"},
+               {"/oneOf/3", "oneOf"}
+             ]
+    end
+
+    test "converts recursive records" do
+      tree = ~s({"type": "object",
+        "properties": {"children": {"type": "array", "items": {"$ref": "#"}}}})
+
+      assert {:ok, %{"name" => "Tree", "fields" => [field]}, []} =
+               Avro.to_avro(tree, name: "Tree")
+
+      assert field["type"] == ["null", %{"type" => "array", "items" => "Tree"}]
+    end
+
+    test "returns errors for schemas it cannot convert" do
+      list = ~s({"$ref": "#/$defs/list",
+        "$defs": {"list": {"type": "array", "items": {"$ref": "#/$defs/list"}}}})
+
+      assert {:error, %CompilationError{type: :conversion_error, details: details}} =
+               Avro.to_avro(list)
+
+      assert details =~ "recursive"
+
+      assert {:error, %CompilationError{type: :json_parse_error}} = Avro.to_avro("{")
+    end
+
+    test "rejects invalid options" do
+      assert_raise ArgumentError, ~r/Invalid name option/, fn ->
+        Avro.to_avro(~s({"type": "string"}), name: "my-record")
+      end
+
+      assert_raise ArgumentError, ~r/Invalid namespace option/, fn ->
+        Avro.to_avro(~s({"type": "string"}), namespace: "com..shop")
+      end
+    end
+  end
+
+  describe "from_avro/1" do
+    @avro %{
+      "type" => "record",
+      "name" => "Order",
+      "namespace" => "com.shop",
+      "fields" => [
+        %{"name" => "id", "type" => %{"type" => "string", "logicalType" => "uuid"}},
+        %{"name" => "count", "type" => "int", "default" => 1},
+        %{"name" => "note", "type" => ["null", "string"], "default" => nil},
+        %{
+          "name" => "placed",
+          "type" => %{"type" => "int", "logicalType" => "date"},
+          "default" => 0
+        },
+        %{
+          "name" => "status",
+          "type" => %{
+            "type" => "enum",
+            "name" => "Status",
+            "symbols" => ["OPEN"],
+            "default" => "OPEN"
+          }
+        },
+        %{
+          "name" => "items",
+          "type" => %{
+            "type" => "array",
+            "items" => %{
+              "type" => "record",
+              "name" => "Item",
+              "fields" => [%{"name" => "sku", "type" => "string"}]
+            }
+          }
+        },
+        %{"name" => "gift", "type" => ["null", "Item"]},
+        %{
+          "name" => "price",
+          "type" => %{
+            "type" => "bytes",
+            "logicalType" => "decimal",
+            "precision" => 9,
+            "scale" => 2
+          }
+        },
+        %{"name" => "hash", "type" => %{"type" => "fixed", "name" => "Hash", "size" => 16}},
+        %{"name" => "parent", "type" => ["null", "Order"], "order" => "descending"}
+      ]
+    }
+
+    test "converts records into closed objects" do
+      assert {:ok, schema, _issues} = Avro.from_avro(@avro)
+
+      assert schema["$schema"] == "https://json-schema.org/draft/2020-12/schema"
+      assert schema["title"] == "Order"
+      assert schema["additionalProperties"] == false
+
+      assert schema["required"] == ["id", "status", "items", "gift", "price", "hash", "parent"]
+
+      properties = schema["properties"]
+      assert properties["id"] == %{"type" => "string", "format" => "uuid"}
+      assert properties["note"] == %{"type" => ["string", "null"], "default" => nil}
+      assert properties["placed"] == %{"type" => "string", "format" => "date"}
+      assert properties["price"] == %{"type" => "number"}
+    end
+
+    test "defines named types under $defs by full name" do
+      assert {:ok, schema, _issues} = Avro.from_avro(@avro)
+
+      assert schema["properties"]["gift"] ==
+               %{"anyOf" => [%{"type" => "null"}, %{"$ref" => "#/$defs/com.shop.Item"}]}
+
+      assert schema["properties"]["parent"]["anyOf"] == [%{"type" => "null"}, %{"$ref" => "#"}]
+      assert schema["$defs"]["com.shop.Status"]["enum"] == ["OPEN"]
+      assert schema["$defs"]["com.shop.Hash"]["maxLength"] == 24
+      assert {:ok, _compiled} = ExJsonschema.compile(Jason.encode!(schema))
+    end
+
+    test "reports Avro attributes JSON Schema has no place for" do
+      assert {:ok, _schema, issues} = Avro.from_avro(@avro)
+
+      assert Enum.map(issues, &{&1.path, &1.keyword}) == [
+               {"/fields/3/default", "default"},
+               {"/fields/4/type/default", "default"},
+               {"/fields/7/type/logicalType", "logicalType"},
+               {"/fields/9/order", "order"}
+             ]
+
+      assert Enum.at(issues, 2).message ==
+               "decimal(9, 2) values become numbers, whose precision and scale are not kept"
+    end
+
+    test "round trips through to_avro/2" do
+      assert {:ok, schema, _issues} = Avro.from_avro(@avro)
+      assert {:ok, avro, _issues} = Avro.to_avro(schema, namespace: "com.shop")
+
+      assert avro["name"] == "Order"
+      fields = Map.new(avro["fields"], &{&1["name"], &1["type"]})
+      assert fields["count"] == "int"
+
+      assert fields["gift"] == [
+               "null",
+               %{
+                 "type" => "record",
+                 "name" => "Item",
+                 "fields" => [%{"name" => "sku", "type" => "string"}]
+               }
+             ]
+
+      assert fields["parent"] == ["null", "Order"]
+    end
+
+    test "returns errors for names used before they are defined" do
+      assert {:error, %CompilationError{type: :conversion_error, details: details}} =
+               Avro.from_avro(~s({"type": "array", "items": "Missing"}))
+
+      assert details == ~s(Type "Missing" at "/items" is not defined before it is used)
+    end
+  end
+end
@@ -0,0 +1,94 @@
+defmodule ExJsonschema.BestErrorTest do
+  use ExUnit.Case, async: true
+
+  alias ExJsonschema.{ErrorFormatter, ValidationError}
+
+  @schema ~s({
+    "type": "object",
+    "required": ["name"],
+    "properties": {
+      "name": {"type": "string"},
+      "id": {"oneOf": [{"type": "string", "minLength": 3}, {"type": "integer", "minimum": 1}]},
+      "contact": {
+        "anyOf": [
+          {"type": "object", "required": ["email"], "properties": {"email": {"type": "string"}}},
+          {"type": "object", "required": ["phone"]}
+        ]
+      },
+      "address": {
+        "type": "object",
+        "properties": {"zip": {"type": "string", "pattern": "^[0-9]{5}$"}}
+      }
+    }
+  })
+
+  setup_all do
+    {:ok, compiled: ExJsonschema.compile!(@schema)}
+  end
+
+  describe "best_error/2" do
+    test "returns nil for valid instances", %{compiled: compiled} do
+      assert ExJsonschema.best_error(compiled, ~s({"name": "Ada"})) == nil
+    end
+
+    test "prefers deeper errors", %{compiled: compiled} do
+      error = ExJsonschema.best_error(compiled, ~s({"address": {"zip": "abc"}}))
+
+      assert %ValidationError{instance_path: "/address/zip"} = error
+      assert error.schema_path =~ ~r{/pattern$}
+    end
+
+    test "picks the oneOf branch whose type matches", %{compiled: compiled} do
+      error = ExJsonschema.best_error(compiled, ~s({"name": "Ada", "id": "ab"}))
+
+      assert error.schema_path == "/properties/id/oneOf/0/minLength"
+      assert error.branch == %{index: 0, schema_pointer: "/properties/id/oneOf/0"}
+    end
+
+    test "picks the anyOf branch with the fewest errors", %{compiled: compiled} do
+      error = ExJsonschema.best_error(compiled, ~s({"name": "Ada", "contact": {"email": 5}}))
+
+      assert error.instance_path == "/contact/email"
+      assert error.branch.index == 0
+    end
+
+    test "raises on invalid JSON", %{compiled: compiled} do
+      assert_raise ArgumentError, ~r/Invalid JSON/, fn ->
+        ExJsonschema.best_error(compiled, "{")
+      end
+    end
+  end
+
+  describe ":ranked" do
+    @instance ~s({"id": true, "address": {"zip": "abc"}})
+
+    test "orders errors by relevance", %{compiled: compiled} do
+      for output <- [:detailed, :verbose] do
+        {:error, errors} =
+          ExJsonschema.validate(compiled, @instance, output: output, ranked: true)
+
+        assert Enum.map(errors, & &1.instance_path) == ["/address/zip", "/id", ""]
+      end
+    end
+
+    test "reorders without dropping errors", %{compiled: compiled} do
+      {:error, ranked} = ExJsonschema.validate(compiled, @instance, ranked: true)
+      {:error, unranked} = ExJsonschema.validate(compiled, @instance)
+
+      assert Enum.sort_by(unranked, & &1.schema_path) == Enum.sort_by(ranked, & &1.schema_path)
+    end
+
+    test "leads formatted output with the most relevant error", %{compiled: compiled} do
+      {:error, errors} = ExJsonschema.validate(compiled, @instance, ranked: true)
+
+      assert ErrorFormatter.format(errors, :llm, max_errors: 1) =~ "/address/zip"
+      assert ErrorFormatter.format(errors, :human, color: false) =~ ~r{Error 1:\n.*/address/zip}
+    end
+
+    test "must be a boolean", %{compiled: compiled} do
+      assert_raise ArgumentError, ~r/Invalid ranked option/, fn ->
+        ExJsonschema.validate(compiled, @instance, ranked: :yes)
+      end
+    end
+  end
+end
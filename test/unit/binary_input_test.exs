@@ -0,0 +1,145 @@
+defmodule ExJsonschema.BinaryInputTest do
+  use ExUnit.Case, async: true
+
+  alias ExJsonschema.CompilationError
+
+  @schema ~s({
+    "type": "object",
+    "required": ["name"],
+    "properties": {
+      "name": {"type": "string"},
+      "tags": {"type": "array", "items": {"type": "integer"}}
+    }
+  })
+
+  setup_all do
+    {:ok, compiled: ExJsonschema.compile!(@schema)}
+  end
+
+  test "validates MessagePack instances", %{compiled: compiled} do
+    # {"name": "web", "tags": [1, 2]}
+    instance = <<0x82, 0xA4, "name", 0xA3, "web", 0xA4, "tags", 0x92, 0x01, 0x02>>
+    assert :ok = ExJsonschema.validate(compiled, instance, input_format: :msgpack)
+    assert ExJsonschema.valid?(compiled, instance, input_format: :msgpack)
+
+    # {"name": 42, "tags": [-1.5]}
+    instance = <<0x82, 0xA4, "name", 0x2A, 0xA4, "tags", 0x91, 0xCB, -1.5::float-size(64)>>
+
+    assert {:error, errors} = ExJsonschema.validate(compiled, instance, input_format: :msgpack)
+    assert errors |> Enum.map(& &1.instance_path) |> Enum.sort() == ["/name", "/tags/0"]
+  end
+
+  test "validates CBOR instances", %{compiled: compiled} do
+    # {"name": "web", "tags": [1, -2]}
+    instance = <<0xA2, 0x64, "name", 0x63, "web", 0x64, "tags", 0x82, 0x01, 0x21>>
+    assert :ok = ExJsonschema.validate(compiled, instance, input_format: :cbor)
+
+    # {"tags": ["x"]}
+    instance = <<0xA1, 0x64, "tags", 0x81, 0x61, "x">>
+
+    assert {:error, errors} =
+             ExJsonschema.validate(compiled, instance, input_format: :cbor, output: :verbose)
+
+    assert errors |> Enum.map(& &1.keyword) |> Enum.sort() == ["required", "type"]
+  end
+
+  test "reads tagged CBOR values as the values they tag", %{compiled: compiled} do
+    # {"name": 0("2024-01-01T00:00:00Z")}
+    instance = <<0xA1, 0x64, "name", 0xC0, 0x74, "2024-01-01T00:00:00Z">>
+    assert :ok = ExJsonschema.validate(compiled, instance, input_format: :cbor)
+  end
+
+  test "rejects binary blobs", %{compiled: compiled} do
+    # {"name": h'0102'}
+    instance = <<0x81, 0xA4, "name", 0xC4, 0x02, 0x01, 0x02>>
+
+    assert {:error, %CompilationError{type: :parse_error} = error} =
+             ExJsonschema.validate(compiled, instance, input_format: :msgpack)
+
+    assert error.message == "Invalid MessagePack"
+    assert error.details == ~s(Binary data at "/name" cannot be represented in JSON)
+
+    instance = <<0xA1, 0x64, "name", 0x42, 0x01, 0x02>>
+
+    assert {:error, %CompilationError{message: "Invalid CBOR", details: details}} =
+             ExJsonschema.validate(compiled, instance, input_format: :cbor)
+
+    assert details == ~s(Binary data at "/name" cannot be represented in JSON)
+  end
+
+  test "rejects map keys that are not strings", %{compiled: compiled} do
+    # {1: "web"}
+    assert {:error, %CompilationError{details: details}} =
+             ExJsonschema.validate(compiled, <<0x81, 0x01, 0xA3, "web">>, input_format: :msgpack)
+
+    assert details == "Map key at the root is an integer; JSON object keys must be strings"
+
+    # {"tags": {true: 1}}
+    instance = <<0xA1, 0x64, "tags", 0xA1, 0xF5, 0x01>>
+
+    assert {:error, %CompilationError{details: details}} =
+             ExJsonschema.validate(compiled, instance, input_format: :cbor, output: :verbose)
+
+    assert details == ~s(Map key at "/tags" is a boolean; JSON object keys must be strings)
+  end
+
+  test "rejects values JSON cannot represent", %{compiled: compiled} do
+    # -2^63 - 1, below the 64-bit range
+    assert {:error, %CompilationError{details: details}} =
+             ExJsonschema.validate(compiled, <<0x3B, 0x80, 0::56>>, input_format: :cbor)
+
+    assert details == "Integer -9223372036854775809 at the root cannot be represented in JSON"
+
+    # NaN
+    assert {:error, %CompilationError{details: details}} =
+             ExJsonschema.validate(compiled, <<0xF9, 0x7E, 0x00>>, input_format: :cbor)
+
+    assert details =~ "at the root cannot be represented in JSON"
+
+    # {"name": ext 1}
+    instance = <<0x81, 0xA4, "name", 0xD4, 0x01, 0x00>>
+
+    assert {:error, %CompilationError{details: details}} =
+             ExJsonschema.validate(compiled, instance, input_format: :msgpack)
+
+    assert details == ~s(Extension type 1 at "/name" cannot be represented in JSON)
+  end
+
+  test "rejects truncated and trailing data", %{compiled: compiled} do
+    assert {:error, %CompilationError{type: :parse_error}} =
+             ExJsonschema.validate(compiled, <<0x82, 0xA4, "name">>, input_format: :msgpack)
+
+    assert {:error, %CompilationError{details: details}} =
+             ExJsonschema.validate(compiled, <<0xC0, 0xC0>>, input_format: :msgpack)
+
+    assert details == "1 unexpected bytes after the value"
+
+    refute ExJsonschema.valid?(compiled, <<0xA1, 0x64>>, input_format: :cbor)
+  end
+
+  test "does not accept corrupt payloads against permissive schemas" do
+    permissive = ExJsonschema.compile!("{}")
+
+    refute ExJsonschema.valid?(permissive, <<0x82, 0xA4, "name">>, input_format: :msgpack)
+    refute ExJsonschema.valid?(permissive, <<0xC0, 0xC0>>, input_format: :msgpack)
+    refute ExJsonschema.valid?(permissive, <<0xA1, 0x64>>, input_format: :cbor)
+    refute ExJsonschema.valid?(permissive, <<0xF6, 0xF6>>, input_format: :cbor)
+
+    assert {:error, :validation_failed} =
+             ExJsonschema.validate(permissive, <<0xC0, 0xC0>>,
+               input_format: :msgpack,
+               output: :basic
+             )
+
+    assert {:error, %CompilationError{type: :parse_error}} =
+             ExJsonschema.validate(permissive, <<0xC0, 0xC0>>,
+               input_format: :msgpack,
+               output: :verbose
+             )
+  end
+
+  test "does not compile schemas from binary formats" do
+    assert {:error, %CompilationError{type: :options_error}} =
+             ExJsonschema.compile(<<0x80>>, input_format: :msgpack)
+  end
+end
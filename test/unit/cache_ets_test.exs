@@ -0,0 +1,116 @@
+defmodule ExJsonschema.Cache.ETSTest do
+  # The cache owns a single named table
+  use ExUnit.Case, async: false
+
+  alias ExJsonschema.Cache.ETS
+
+  describe "without TTL or size limits" do
+    setup do
+      start_supervised!(ETS)
+      :ok
+    end
+
+    test "stores, fetches and deletes entries" do
+      assert {:error, :not_found} = ETS.get("key")
+      assert :ok = ETS.put("key", :value)
+      assert {:ok, :value} = ETS.get("key")
+
+      assert :ok = ETS.delete("key")
+      assert {:error, :not_found} = ETS.get("key")
+    end
+
+    test "clears all entries" do
+      ETS.put("a", 1)
+      ETS.put("b", 2)
+      assert ETS.size() == 2
+
+      assert :ok = ETS.clear()
+      assert ETS.size() == 0
+    end
+
+    test "serves compiled schemas to ExJsonschema.compile/2" do
+      original = Application.get_env(:ex_jsonschema, :cache)
+      Application.put_env(:ex_jsonschema, :cache, ETS)
+
+      on_exit(fn ->
+        case original do
+          nil -> Application.delete_env(:ex_jsonschema, :cache)
+          cache -> Application.put_env(:ex_jsonschema, :cache, cache)
+        end
+      end)
+
+      schema = ~s({"type": "string"})
+      {:ok, first} = ExJsonschema.compile(schema)
+      {:ok, second} = ExJsonschema.compile(schema)
+
+      assert first == second
+      assert ETS.size() == 1
+    end
+  end
+
+  describe "with a TTL" do
+    setup do
+      start_supervised!({ETS, ttl: 50, sweep_interval: 10})
+      :ok
+    end
+
+    test "expires entries on read" do
+      ETS.put("key", :value)
+      assert {:ok, :value} = ETS.get("key")
+
+      Process.sleep(60)
+      assert {:error, :not_found} = ETS.get("key")
+    end
+
+    test "sweeps expired entries in the background" do
+      ETS.put("key", :value)
+      Process.sleep(100)
+
+      assert ETS.size() == 0
+    end
+  end
+
+  describe "with max_entries" do
+    setup do
+      start_supervised!({ETS, max_entries: 2})
+      :ok
+    end
+
+    test "evicts the least recently used entry" do
+      ETS.put("a", 1)
+      Process.sleep(2)
+      ETS.put("b", 2)
+      Process.sleep(2)
+
+      # Touch "a" so that "b" becomes the least recently used
+      assert {:ok, 1} = ETS.get("a")
+      Process.sleep(2)
+      ETS.put("c", 3)
+
+      # Eviction runs in the cache process; a call waits for it to finish
+      :sys.get_state(ETS)
+
+      assert ETS.size() == 2
+      assert {:ok, 1} = ETS.get("a")
+      assert {:error, :not_found} = ETS.get("b")
+      assert {:ok, 3} = ETS.get("c")
+    end
+  end
+
+  describe "when not started" do
+    test "misses and ignores writes" do
+      assert {:error, :not_found} = ETS.get("key")
+      assert :ok = ETS.put("key", :value)
+      assert :ok = ETS.delete("key")
+      assert :ok = ETS.clear()
+      assert ETS.size() == 0
+    end
+  end
+
+  test "rejects invalid limits" do
+    Process.flag(:trap_exit, true)
+
+    assert {:error, {%ArgumentError{message: message}, _}} = ETS.start_link(ttl: -1)
+    assert message =~ "Invalid ttl"
+  end
+end
@@ -0,0 +1,53 @@
+defmodule ExJsonschema.Cache.PersistentTermTest do
+  # Entries are global to the node
+  use ExUnit.Case, async: false
+
+  alias ExJsonschema.Cache.PersistentTerm
+
+  setup do
+    on_exit(&PersistentTerm.clear/0)
+    :ok
+  end
+
+  test "stores, fetches and deletes entries" do
+    assert {:error, :not_found} = PersistentTerm.get("key")
+    assert :ok = PersistentTerm.put("key", :value)
+    assert {:ok, :value} = PersistentTerm.get("key")
+
+    assert :ok = PersistentTerm.delete("key")
+    assert {:error, :not_found} = PersistentTerm.get("key")
+  end
+
+  test "clears only its own entries" do
+    :persistent_term.put({__MODULE__, :unrelated}, true)
+    on_exit(fn -> :persistent_term.erase({__MODULE__, :unrelated}) end)
+
+    PersistentTerm.put("a", 1)
+    PersistentTerm.put("b", 2)
+    assert :ok = PersistentTerm.clear()
+
+    assert {:error, :not_found} = PersistentTerm.get("a")
+    assert {:error, :not_found} = PersistentTerm.get("b")
+    assert :persistent_term.get({__MODULE__, :unrelated})
+  end
+
+  test "serves compiled schemas to ExJsonschema.compile/2" do
+    original = Application.get_env(:ex_jsonschema, :cache)
+    Application.put_env(:ex_jsonschema, :cache, PersistentTerm)
+
+    on_exit(fn ->
+      case original do
+        nil -> Application.delete_env(:ex_jsonschema, :cache)
+        cache -> Application.put_env(:ex_jsonschema, :cache, cache)
+      end
+    end)
+
+    schema = ~s({"type": "integer"})
+    {:ok, first} = ExJsonschema.compile(schema)
+    {:ok, second} = ExJsonschema.compile(schema)
+
+    assert first == second
+    assert {:ok, key} = ExJsonschema.Cache.key(schema)
+    assert {:ok, ^first} = PersistentTerm.get(key)
+  end
+end
@@ -0,0 +1,139 @@
+defmodule ExJsonschema.ContentValidationTest do
+  use ExUnit.Case, async: true
+
+  alias ExJsonschema.Serializer
+
+  @schema ~s({
+    "type": "object",
+    "properties": {
+      "envelope": {"$ref": "#/$defs/Envelope"},
+      "note": {
+        "type": "string",
+        "contentEncoding": "quoted-printable",
+        "contentMediaType": "application/json",
+        "contentSchema": {"type": "array", "maxItems": 1}
+      }
+    },
+    "$defs": {
+      "Envelope": {
+        "type": "object",
+        "properties": {
+          "data": {
+            "type": "string",
+            "contentEncoding": "base64",
+            "contentMediaType": "application/json",
+            "contentSchema": {"$ref": "#/$defs/Event"}
+          },
+          "signature": {"type": "string", "contentEncoding": "base64url"}
+        }
+      },
+      "Event": {
+        "type": "object",
+        "required": ["id"],
+        "properties": {"id": {"type": "integer"}}
+      }
+    }
+  })
+
+  defp envelope(data, extra \\ %{}) do
+    Jason.encode!(%{"envelope" => Map.merge(%{"data" => Base.encode64(data)}, extra)})
+  end
+
+  setup_all do
+    {:ok, compiled: ExJsonschema.compile!(@schema, validate_content: true)}
+  end
+
+  test "ignores content by default" do
+    compiled = ExJsonschema.compile!(@schema)
+
+    assert :ok = ExJsonschema.validate(compiled, envelope(~s({"id": "x"})))
+  end
+
+  test "accepts valid content", %{compiled: compiled} do
+    assert :ok = ExJsonschema.validate(compiled, envelope(~s({"id": 1})))
+    assert ExJsonschema.valid?(compiled, envelope(~s({"id": 1})))
+  end
+
+  test "reports errors inside the decoded content", %{compiled: compiled} do
+    assert {:error, [error]} = ExJsonschema.validate(compiled, envelope(~s({"id": "x"})))
+
+    assert error.instance_path == "/envelope/data/id"
+    assert error.schema_path ==
+             "/properties/envelope/$ref/properties/data/contentSchema/$ref/properties/id/type"
+    refute ExJsonschema.valid?(compiled, envelope(~s({"id": "x"})))
+  end
+
+  test "reports strings that do not decode or parse", %{compiled: compiled} do
+    instance = Jason.encode!(%{"envelope" => %{"data" => "not base64!"}})
+    assert {:error, [error]} = ExJsonschema.validate(compiled, instance)
+    assert error.schema_path =~ ~r{/contentEncoding$}
+
+    assert {:error, [error]} = ExJsonschema.validate(compiled, envelope("not json"))
+    assert error.instance_path == "/envelope/data"
+    assert error.schema_path =~ ~r{/contentMediaType$}
+
+    instance = envelope(~s({"id": 1}), %{"signature" => "a+b/"})
+    assert {:error, [error]} = ExJsonschema.validate(compiled, instance)
+    assert error.instance_path == "/envelope/signature"
+  end
+
+  test "decodes quoted-printable", %{compiled: compiled} do
+    assert :ok = ExJsonschema.validate(compiled, ~s({"note": "[1=5D"}))
+
+    assert {:error, [error]} = ExJsonschema.validate(compiled, ~s({"note": "[1,=\\n2]"}))
+    assert error.instance_path == "/note"
+    assert error.schema_path == "/properties/note/contentSchema/maxItems"
+  end
+
+  test "reports content errors in verbose output", %{compiled: compiled} do
+    assert {:error, [error]} =
+             ExJsonschema.validate(compiled, envelope(~s({"id": "x"})), output: :verbose)
+
+    assert error.keyword == "type"
+    assert error.instance_value == "x"
+  end
+
+  test "checks content in the oneOf branch the value matches" do
+    compiled =
+      ExJsonschema.compile!(
+        ~s({
+          "type": "object",
+          "oneOf": [
+            {"required": ["kind"], "properties": {
+              "kind": {"const": "json"},
+              "body": {
+                "contentMediaType": "application/json",
+                "contentSchema": {"required": ["id"]}
+              }
+            }},
+            {"required": ["kind"], "properties": {
+              "kind": {"const": "text"}, "body": {"type": "string"}
+            }}
+          ]
+        }),
+        validate_content: true
+      )
+
+    assert {:error, [error]} = ExJsonschema.validate(compiled, ~s({"kind": "json", "body": "{}"}))
+    assert error.instance_path == "/body"
+    assert error.schema_path == "/oneOf/0/properties/body/contentSchema/required"
+
+    assert :ok = ExJsonschema.validate(compiled, ~s({"kind": "text", "body": "plain"}))
+  end
+
+  test "checks content under validate_at instance pointers", %{compiled: compiled} do
+    assert {:error, [error]} =
+             ExJsonschema.validate_at(compiled, envelope(~s({})), instance_pointer: "/envelope")
+
+    assert error.instance_path == "/envelope/data"
+  end
+
+  test "survives serialization", %{compiled: compiled} do
+    assert {:ok, restored} = Serializer.deserialize(Serializer.serialize(compiled))
+    assert {:error, [_]} = ExJsonschema.validate(restored, envelope(~s({"id": "x"})))
+  end
+
+  test "is part of the options fingerprint", %{compiled: compiled} do
+    assert ExJsonschema.info(compiled).options_fingerprint =~ "validate_content=true"
+  end
+end
@@ -8,7 +8,7 @@ defmodule ExJsonschema.CoreTest do
     test "compiles valid schema successfully" do
       schema = ~s({"type": "string"})
       assert {:ok, validator} = ExJsonschema.compile(schema)
-      assert is_reference(validator)
+      assert match?(%ExJsonschema.Schema{}, validator)
     end
 
     test "rejects invalid JSON with clear error" do
@@ -30,7 +30,7 @@ defmodule ExJsonschema.CoreTest do
     test "compiles schema with empty options" do
       schema = ~s({"type": "string"})
       assert {:ok, validator} = ExJsonschema.compile(schema, [])
-      assert is_reference(validator)
+      assert match?(%ExJsonschema.Schema{}, validator)
     end
   end
 
@@ -38,7 +38,7 @@ defmodule ExJsonschema.CoreTest do
     test "compiles valid schema successfully" do
       schema = ~s({"type": "string"})
       validator = ExJsonschema.compile!(schema)
-      assert is_reference(validator)
+      assert match?(%ExJsonschema.Schema{}, validator)
     end
 
     test "raises on invalid schema" do
@@ -0,0 +1,137 @@
+defmodule ExJsonschema.CSVTest do
+  use ExUnit.Case, async: true
+
+  alias ExJsonschema.{CompilationError, CSV, ValidationError}
+
+  doctest ExJsonschema, only: [validate_csv: 3]
+
+  @schema ~s({
+    "type": "object",
+    "required": ["sku", "quantity"],
+    "properties": {
+      "sku": {"type": "string", "pattern": "^[A-Z]+-[0-9]+$"},
+      "quantity": {"$ref": "#/$defs/quantity"},
+      "price": {"type": "number"},
+      "gift": {"type": "boolean"},
+      "note": {"type": "string", "maxLength": 10}
+    },
+    "$defs": {"quantity": {"type": "integer", "minimum": 1}}
+  })
+
+  setup_all do
+    {:ok, compiled: ExJsonschema.compile!(@schema)}
+  end
+
+  test "converts fields to the types of their properties", %{compiled: compiled} do
+    csv = ~s(sku,quantity,price,gift,note\nAB-1,2,9.99,true,\nCD-2,1,10,false,"a, ""b"""\n)
+
+    assert CSV.validate(compiled, csv) == :ok
+  end
+
+  test "finds column types through $id references and patternProperties" do
+    compiled =
+      ExJsonschema.compile!(~s({
+        "$id": "https://example.com/row",
+        "type": "object",
+        "properties": {"quantity": {"$ref": "types#/$defs/count"}},
+        "patternProperties": {"^is_": {"type": "boolean"}},
+        "additionalProperties": {"type": "string"},
+        "$defs": {
+          "types": {"$id": "types", "$defs": {"count": {"type": "integer", "minimum": 1}}}
+        }
+      }))
+
+    assert CSV.validate(compiled, "quantity,is_gift,note\n2,true,7\n") == :ok
+
+    assert {:error, [%{line: 2, column: "quantity"}]} =
+             CSV.validate(compiled, "quantity,is_gift\n0,false\n")
+  end
+
+  test "gives the line and column of each error", %{compiled: compiled} do
+    csv = """
+    sku,quantity,price
+    AB-1,2,9.99
+    cd-2,0,cheap
+    EF-3,three,1
+    """
+
+    assert {:error, errors} = CSV.validate(compiled, csv)
+
+    assert errors |> Enum.map(&{&1.line, &1.column, &1.error.keyword}) |> Enum.sort() == [
+             {3, "price", "type"},
+             {3, "quantity", "minimum"},
+             {3, "sku", "pattern"},
+             {4, "quantity", "type"}
+           ]
+
+    assert %ValidationError{instance_path: "/quantity", params: %{"limit" => 1}} =
+             Enum.find(errors, &(&1.error.keyword == "minimum")).error
+  end
+
+  test "reports empty required fields at their column", %{compiled: compiled} do
+    assert {:error, [%{line: 2, column: "quantity", error: error}]} =
+             CSV.validate(compiled, "sku,quantity\r\nAB-1,\r\n")
+
+    assert error.keyword == "required"
+  end
+
+  test "counts lines through quoted line breaks", %{compiled: compiled} do
+    csv = ~s(sku,quantity,note\nAB-1,1,"two\nlines"\nAB-2,1,"a note that is too long"\n)
+
+    assert {:error, [%{line: 4, column: "note"}]} = CSV.validate(compiled, csv)
+  end
+
+  test "reports rows with more fields than columns", %{compiled: compiled} do
+    assert {:error, [%{line: 2, column: nil, error: error}]} =
+             CSV.validate(compiled, "sku,quantity\nAB-1,1,extra\n")
+
+    assert error.keyword == "columns"
+    assert error.message == "Row has 3 fields but there are 2 columns"
+  end
+
+  test "reads streams whose chunks split records anywhere", %{compiled: compiled} do
+    csv = "sku,quantity,note\nAB-1,1,\"café\"\nAB-2,0,\"x\r\ny\"\r\nAB-3,1,ok"
+
+    for size <- [1, 2, 5, 7] do
+      chunks =
+        csv
+        |> :binary.bin_to_list()
+        |> Stream.chunk_every(size)
+        |> Stream.map(&:binary.list_to_bin/1)
+
+      assert {:error, [%{line: 3, column: "quantity"}]} = CSV.validate(compiled, chunks)
+    end
+  end
+
+  test "takes headers and a separator", %{compiled: compiled} do
+    csv = "AB-1;1\nAB-2;0\n"
+
+    assert {:error, [%{line: 2, column: "quantity"}]} =
+             CSV.validate(compiled, csv, headers: ["sku", "quantity"], separator: ";")
+  end
+
+  test "returns parse errors with their line", %{compiled: compiled} do
+    assert {:error, %CompilationError{type: :parse_error, details: details}} =
+             CSV.validate(compiled, "sku,quantity\nAB-1,\"1\n")
+
+    assert details == "Line 2: quoted field is never closed"
+
+    assert {:error, %CompilationError{details: "Line 1: Header \"sku\" appears twice"}} =
+             CSV.validate(compiled, "sku,sku\n")
+  end
+
+  test "accepts files with no rows", %{compiled: compiled} do
+    assert CSV.validate(compiled, "") == :ok
+    assert CSV.validate(compiled, "sku,quantity\n") == :ok
+  end
+
+  test "rejects invalid options", %{compiled: compiled} do
+    assert_raise ArgumentError, ~r/Invalid separator option/, fn ->
+      CSV.validate(compiled, "", separator: "::")
+    end
+
+    assert_raise ArgumentError, ~r/Invalid headers option/, fn ->
+      CSV.validate(compiled, "", headers: [:sku])
+    end
+  end
+end
@@ -0,0 +1,134 @@
+defmodule ExJsonschema.DereferencerTest do
+  use ExUnit.Case, async: true
+
+  alias ExJsonschema.{CompilationError, Dereferencer}
+
+  describe "dereference/2" do
+    test "inlines local $defs references" do
+      schema = ~s({
+        "type": "object",
+        "properties": {
+          "home": {"$ref": "#/$defs/address"},
+          "work": {"$ref": "#/$defs/address"}
+        },
+        "$defs": {
+          "address": {"type": "object", "properties": {"city": {"type": "string"}}}
+        }
+      })
+
+      assert {:ok, flat} = ExJsonschema.dereference(schema)
+
+      address = %{"type" => "object", "properties" => %{"city" => %{"type" => "string"}}}
+      assert flat["properties"]["home"] == address
+      assert flat["properties"]["work"] == address
+      refute Map.has_key?(flat, "$defs")
+    end
+
+    test "follows nested references" do
+      schema = ~s({
+        "$ref": "#/$defs/outer",
+        "$defs": {
+          "outer": {"type": "array", "items": {"$ref": "#/$defs/inner"}},
+          "inner": {"type": "integer"}
+        }
+      })
+
+      assert {:ok, %{"allOf" => [%{"type" => "array", "items" => %{"type" => "integer"}}]}} =
+               ExJsonschema.dereference(schema)
+    end
+
+    test "inlines properties named after data keywords, but not data" do
+      schema = ~s({
+        "properties": {
+          "default": {"$ref": "#/$defs/name"},
+          "enum": {"$ref": "#/$defs/name"}
+        },
+        "default": {"$ref": "#/$defs/name"},
+        "$defs": {"name": {"type": "string"}}
+      })
+
+      assert {:ok, flat} = ExJsonschema.dereference(schema)
+
+      assert flat["properties"] == %{
+               "default" => %{"type" => "string"},
+               "enum" => %{"type" => "string"}
+             }
+
+      assert flat["default"] == %{"$ref" => "#/$defs/name"}
+    end
+
+    test "inlines draft-07 definitions and ignores $ref siblings" do
+      schema = ~s({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "$ref": "#/definitions/name",
+        "definitions": {"name": {"type": "string", "minLength": 1}}
+      })
+
+      assert {:ok, %{"type" => "string", "minLength" => 1}} = ExJsonschema.dereference(schema)
+    end
+
+    test "accepts compiled schemas" do
+      compiled =
+        ExJsonschema.compile!(~s({"items": {"$ref": "#/$defs/n"}, "$defs": {"n": {"type": "number"}}}))
+
+      assert {:ok, %{"items" => %{"type" => "number"}}} = Dereferencer.dereference(compiled)
+    end
+
+    test "accepts decoded maps" do
+      schema = %{"not" => %{"$ref" => "#/$defs/s"}, "$defs" => %{"s" => %{"type" => "string"}}}
+
+      assert {:ok, %{"not" => %{"type" => "string"}}} = ExJsonschema.dereference(schema)
+    end
+
+    test "leaves data keywords untouched" do
+      schema = ~s({"const": {"$ref": "#/nowhere"}, "default": {"$ref": "#/nowhere"}})
+
+      assert {:ok, %{"const" => %{"$ref" => "#/nowhere"}}} = ExJsonschema.dereference(schema)
+    end
+  end
+
+  describe "cyclic references" do
+    @tree ~s({
+      "type": "object",
+      "properties": {
+        "children": {"type": "array", "items": {"$ref": "#/$defs/node"}}
+      },
+      "$defs": {
+        "node": {"type": "object", "properties": {"next": {"$ref": "#/$defs/node"}}}
+      }
+    })
+
+    test "are reported by default" do
+      assert {:error, %CompilationError{type: :dereference_error, message: message, details: details}} =
+               ExJsonschema.dereference(@tree)
+
+      assert message =~ "#/$defs/node"
+      assert details =~ "/properties/next"
+    end
+
+    test "are left as references with cycles: :keep" do
+      assert {:ok, flat} = ExJsonschema.dereference(@tree, cycles: :keep)
+
+      node = flat["properties"]["children"]["items"]
+      assert node["properties"]["next"] == %{"$ref" => "#/$defs/node"}
+      assert Map.has_key?(flat, "$defs")
+    end
+
+    test "detects references to the root" do
+      assert {:error, %CompilationError{type: :dereference_error}} =
+               ExJsonschema.dereference(~s({"items": {"$ref": "#"}}))
+    end
+
+    test "rejects unknown policies" do
+      assert_raise ArgumentError, ~r/Invalid cycles policy/, fn ->
+        ExJsonschema.dereference(@tree, cycles: :ignore)
+      end
+    end
+  end
+
+  describe "errors" do
+    test "returns compilation errors for invalid schemas" do
+      assert {:error, %CompilationError{}} = ExJsonschema.dereference(~s({"type": "invalid_type"}))
+    end
+  end
+end
@@ -0,0 +1,118 @@
+defmodule ExJsonschema.DirectionTest do
+  use ExUnit.Case, async: true
+
+  @schema ~s({
+    "type": "object",
+    "properties": {
+      "id": {"type": "integer", "readOnly": true},
+      "name": {"type": "string"},
+      "password": {"$ref": "#/$defs/Secret"}
+    },
+    "$defs": {
+      "Secret": {"type": "string", "writeOnly": true}
+    }
+  })
+
+  @full ~s({"id": 1, "name": "Ada", "password": "hunter2"})
+
+  setup_all do
+    {:ok, compiled: ExJsonschema.compile!(@schema)}
+  end
+
+  test "ignores readOnly and writeOnly without a direction", %{compiled: compiled} do
+    assert :ok = ExJsonschema.validate(compiled, @full)
+  end
+
+  test "rejects readOnly values in requests", %{compiled: compiled} do
+    assert {:error, [error]} = ExJsonschema.validate(compiled, @full, direction: :request)
+
+    assert error.instance_path == "/id"
+    assert error.schema_path == "/properties/id/readOnly"
+    assert error.message =~ "read-only"
+
+    assert :ok =
+             ExJsonschema.validate(compiled, ~s({"name": "Ada", "password": "x"}),
+               direction: :request
+             )
+  end
+
+  test "rejects writeOnly values in responses", %{compiled: compiled} do
+    assert {:error, [error]} = ExJsonschema.validate(compiled, @full, direction: :response)
+
+    assert error.instance_path == "/password"
+    assert error.schema_path == "/properties/password/$ref/writeOnly"
+    refute ExJsonschema.valid?(compiled, @full, direction: :response)
+    assert ExJsonschema.valid?(compiled, ~s({"id": 1}), direction: :response)
+  end
+
+  test "reports the keyword in verbose output", %{compiled: compiled} do
+    assert {:error, [error]} =
+             ExJsonschema.validate(compiled, @full, direction: :request, output: :verbose)
+
+    assert error.keyword == "readOnly"
+    assert error.instance_value == 1
+  end
+
+  test "combines with schema errors", %{compiled: compiled} do
+    instance = ~s({"id": 1, "name": 5})
+
+    assert {:error, errors} = ExJsonschema.validate(compiled, instance, direction: :request)
+    assert errors |> Enum.map(& &1.instance_path) |> Enum.sort() == ["/id", "/name"]
+
+    assert {:error, :validation_failed} =
+             ExJsonschema.validate(compiled, @full, direction: :request, output: :basic)
+  end
+
+  test "redacts writeOnly values it reports" do
+    compiled = ExJsonschema.compile!(@schema, redact: true)
+
+    assert {:error, [error]} =
+             ExJsonschema.validate(compiled, @full, direction: :response, output: :verbose)
+
+    assert error.instance_value == "[REDACTED]"
+  end
+
+  test "finds readOnly values in the oneOf branch the value matches" do
+    compiled =
+      ExJsonschema.compile!(~s({
+        "oneOf": [
+          {"type": "object", "required": ["sku"], "properties": {
+            "sku": {"type": "string"}, "id": {"readOnly": true}
+          }},
+          {"type": "object", "required": ["email"], "properties": {
+            "email": {"type": "string"}, "name": {"readOnly": true}
+          }}
+        ]
+      }))
+
+    assert {:error, [error]} =
+             ExJsonschema.validate(compiled, ~s({"sku": "a", "id": 1, "name": "b"}),
+               direction: :request
+             )
+
+    assert error.instance_path == "/id"
+    assert error.schema_path == "/oneOf/0/properties/id/readOnly"
+  end
+
+  test "finds readOnly values under patternProperties" do
+    compiled =
+      ExJsonschema.compile!(~s({
+        "type": "object",
+        "patternProperties": {"^x-": {"readOnly": true}},
+        "additionalProperties": {"writeOnly": true}
+      }))
+
+    assert {:error, [error]} =
+             ExJsonschema.validate(compiled, ~s({"x-id": 1}), direction: :request)
+
+    assert error.instance_path == "/x-id"
+    assert error.schema_path == "/patternProperties/^x-/readOnly"
+    assert :ok = ExJsonschema.validate(compiled, ~s({"x-id": 1}), direction: :response)
+  end
+
+  test "rejects unknown directions", %{compiled: compiled} do
+    assert_raise ArgumentError, ~r/Invalid direction option: :inbound/, fn ->
+      ExJsonschema.validate(compiled, @full, direction: :inbound)
+    end
+  end
+end
@@ -0,0 +1,140 @@
+defmodule ExJsonschema.DiscriminatorTest do
+  use ExUnit.Case, async: true
+
+  alias ExJsonschema.Serializer
+
+  @schema ~s({
+    "type": "object",
+    "properties": {
+      "payment": {
+        "oneOf": [
+          {"$ref": "#/components/schemas/Card"},
+          {"$ref": "#/components/schemas/Bank"}
+        ],
+        "discriminator": {
+          "propertyName": "type",
+          "mapping": {"card": "#/components/schemas/Card", "bank": "Bank"}
+        }
+      }
+    },
+    "components": {
+      "schemas": {
+        "Card": {
+          "type": "object",
+          "required": ["number"],
+          "properties": {
+            "type": {"type": "string"},
+            "number": {"type": "string", "minLength": 12}
+          }
+        },
+        "Bank": {
+          "type": "object",
+          "required": ["iban"],
+          "properties": {"type": {"type": "string"}, "iban": {"type": "string"}}
+        }
+      }
+    }
+  })
+
+  setup_all do
+    {:ok, compiled: ExJsonschema.compile!(@schema)}
+  end
+
+  test "accepts an instance of the tagged branch", %{compiled: compiled} do
+    assert :ok = ExJsonschema.validate(compiled, ~s({"payment": {"type": "bank", "iban": "DE1"}}))
+  end
+
+  test "reports the tagged branch's errors directly", %{compiled: compiled} do
+    assert {:error, [error]} =
+             ExJsonschema.validate(compiled, ~s({"payment": {"type": "card", "number": "1"}}))
+
+    assert error.instance_path == "/payment/number"
+    assert error.schema_path == "/properties/payment/oneOf/0/$ref/properties/number/minLength"
+    assert error.branch == nil
+    assert error.branches == []
+  end
+
+  test "reports a missing tag", %{compiled: compiled} do
+    assert {:error, [error]} = ExJsonschema.validate(compiled, ~s({"payment": {"iban": "x"}}))
+
+    assert error.instance_path == "/payment"
+    assert error.schema_path == "/properties/payment/discriminator"
+    assert error.message == ~s(Missing discriminator property "type")
+  end
+
+  test "reports an unknown tag", %{compiled: compiled} do
+    assert {:error, [error]} = ExJsonschema.validate(compiled, ~s({"payment": {"type": "wire"}}))
+
+    assert error.instance_path == "/payment/type"
+    assert error.message == ~s("wire" is not a known "type"; expected one of "bank", "card")
+  end
+
+  test "reports the tagged branch in verbose output", %{compiled: compiled} do
+    assert {:error, [error]} =
+             ExJsonschema.validate(compiled, ~s({"payment": {"type": "bank"}}), output: :verbose)
+
+    assert error.keyword == "required"
+    assert error.schema_path == "/properties/payment/oneOf/1/$ref/required"
+  end
+
+  test "survives serialization", %{compiled: compiled} do
+    assert {:ok, restored} = Serializer.deserialize(Serializer.serialize(compiled))
+
+    assert {:error, [error]} =
+             ExJsonschema.validate(restored, ~s({"payment": {"type": "card", "number": "1"}}))
+
+    assert error.instance_path == "/payment/number"
+    assert ExJsonschema.info(restored).content_hash == ExJsonschema.info(compiled).content_hash
+  end
+
+  test "dereferences and serializes the schema as written", %{compiled: compiled} do
+    assert {:ok, flat} = ExJsonschema.dereference(compiled)
+    refute Jason.encode!(flat) =~ "x-ex-jsonschema-discriminator"
+    refute Serializer.serialize(compiled) =~ "x-ex-jsonschema-discriminator"
+  end
+
+  describe "infer_discriminators" do
+    @shapes ~s({
+      "oneOf": [
+        {"properties": {"kind": {"const": "circle"}, "radius": {"type": "number"}}},
+        {"properties": {"kind": {"const": "square"}, "side": {"type": "number"}}}
+      ]
+    })
+
+    test "treats a const property as the tag" do
+      compiled = ExJsonschema.compile!(@shapes, infer_discriminators: true)
+
+      assert {:error, [error]} =
+               ExJsonschema.validate(compiled, ~s({"kind": "circle", "radius": "big"}))
+
+      assert error.schema_path == "/oneOf/0/properties/radius/type"
+
+      assert {:error, [error]} = ExJsonschema.validate(compiled, ~s({"kind": "hexagon"}))
+      assert error.schema_path == "/oneOf"
+      assert error.instance_path == "/kind"
+      assert ExJsonschema.info(compiled).options_fingerprint =~ "infer_discriminators=true"
+    end
+
+    test "finds tags in properties named after data keywords" do
+      compiled =
+        ExJsonschema.compile!(~s({"properties": {"default": #{@shapes}}}),
+          infer_discriminators: true
+        )
+
+      instance = ~s({"default": {"kind": "circle", "radius": "big"}})
+      assert {:error, [error]} = ExJsonschema.validate(compiled, instance)
+
+      assert error.schema_path == "/properties/default/oneOf/0/properties/radius/type"
+    end
+
+    test "is off by default" do
+      compiled = ExJsonschema.compile!(@shapes)
+
+      assert {:error, [error]} =
+               ExJsonschema.validate(compiled, ~s({"kind": "circle", "radius": "big"}))
+
+      assert error.schema_path == "/oneOf"
+      assert length(error.branches) == 2
+    end
+  end
+end
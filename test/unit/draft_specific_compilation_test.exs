@@ -11,7 +11,7 @@ defmodule ExJsonschema.DraftSpecificCompilationTest do
     test "compiles valid Draft 4 schema" do
       schema = ~s({"type": "string"})
       assert {:ok, compiled} = ExJsonschema.compile_draft4(schema)
-      assert is_reference(compiled)
+      assert match?(%ExJsonschema.Schema{}, compiled)
     end
 
     test "compiles Draft 4 schema with specific keywords" do
@@ -24,13 +24,13 @@ defmodule ExJsonschema.DraftSpecificCompilationTest do
         "required": ["name"]
       })
       assert {:ok, compiled} = ExJsonschema.compile_draft4(schema)
-      assert is_reference(compiled)
+      assert match?(%ExJsonschema.Schema{}, compiled)
     end
 
     test "compiles with additional options" do
       schema = ~s({"type": "string", "format": "email"})
       assert {:ok, compiled} = ExJsonschema.compile_draft4(schema, validate_formats: true)
-      assert is_reference(compiled)
+      assert match?(%ExJsonschema.Schema{}, compiled)
     end
 
     test "returns compilation error for invalid schema" do
@@ -48,19 +48,19 @@ defmodule ExJsonschema.DraftSpecificCompilationTest do
     test "compiles valid Draft 6 schema" do
       schema = ~s({"type": "string"})
       assert {:ok, compiled} = ExJsonschema.compile_draft6(schema)
-      assert is_reference(compiled)
+      assert match?(%ExJsonschema.Schema{}, compiled)
     end
 
     test "compiles Draft 6 schema with const keyword" do
       schema = ~s({"const": "hello"})
       assert {:ok, compiled} = ExJsonschema.compile_draft6(schema)
-      assert is_reference(compiled)
+      assert match?(%ExJsonschema.Schema{}, compiled)
     end
 
     test "compiles with additional options" do
       schema = ~s({"type": "array", "contains": {"type": "number"}})
       assert {:ok, compiled} = ExJsonschema.compile_draft6(schema, collect_annotations: false)
-      assert is_reference(compiled)
+      assert match?(%ExJsonschema.Schema{}, compiled)
     end
   end
 
@@ -68,7 +68,7 @@ defmodule ExJsonschema.DraftSpecificCompilationTest do
     test "compiles valid Draft 7 schema" do
       schema = ~s({"type": "string"})
       assert {:ok, compiled} = ExJsonschema.compile_draft7(schema)
-      assert is_reference(compiled)
+      assert match?(%ExJsonschema.Schema{}, compiled)
     end
 
     test "compiles Draft 7 schema with conditional keywords" do
@@ -90,7 +90,7 @@ defmodule ExJsonschema.DraftSpecificCompilationTest do
         }
       })
       assert {:ok, compiled} = ExJsonschema.compile_draft7(schema)
-      assert is_reference(compiled)
+      assert match?(%ExJsonschema.Schema{}, compiled)
     end
 
     test "handles readOnly and writeOnly keywords" do
@@ -102,7 +102,7 @@ defmodule ExJsonschema.DraftSpecificCompilationTest do
         }
       })
       assert {:ok, compiled} = ExJsonschema.compile_draft7(schema)
-      assert is_reference(compiled)
+      assert match?(%ExJsonschema.Schema{}, compiled)
     end
   end
 
@@ -110,7 +110,7 @@ defmodule ExJsonschema.DraftSpecificCompilationTest do
     test "compiles valid Draft 2019-09 schema" do
       schema = ~s({"type": "string"})
       assert {:ok, compiled} = ExJsonschema.compile_draft201909(schema)
-      assert is_reference(compiled)
+      assert match?(%ExJsonschema.Schema{}, compiled)
     end
 
     test "compiles schema with unevaluatedProperties" do
@@ -122,7 +122,7 @@ defmodule ExJsonschema.DraftSpecificCompilationTest do
         "unevaluatedProperties": false
       })
       assert {:ok, compiled} = ExJsonschema.compile_draft201909(schema)
-      assert is_reference(compiled)
+      assert match?(%ExJsonschema.Schema{}, compiled)
     end
 
     test "handles dependentSchemas keyword" do
@@ -135,7 +135,7 @@ defmodule ExJsonschema.DraftSpecificCompilationTest do
         }
       })
       assert {:ok, compiled} = ExJsonschema.compile_draft201909(schema)
-      assert is_reference(compiled)
+      assert match?(%ExJsonschema.Schema{}, compiled)
     end
   end
 
@@ -143,7 +143,7 @@ defmodule ExJsonschema.DraftSpecificCompilationTest do
     test "compiles valid Draft 2020-12 schema" do
       schema = ~s({"type": "string"})
       assert {:ok, compiled} = ExJsonschema.compile_draft202012(schema)
-      assert is_reference(compiled)
+      assert match?(%ExJsonschema.Schema{}, compiled)
     end
 
     test "compiles schema with prefixItems" do
@@ -156,7 +156,7 @@ defmodule ExJsonschema.DraftSpecificCompilationTest do
         "items": false
       })
       assert {:ok, compiled} = ExJsonschema.compile_draft202012(schema)
-      assert is_reference(compiled)
+      assert match?(%ExJsonschema.Schema{}, compiled)
     end
 
     test "handles dynamic references" do
@@ -171,7 +171,7 @@ defmodule ExJsonschema.DraftSpecificCompilationTest do
         }
       })
       assert {:ok, compiled} = ExJsonschema.compile_draft202012(schema)
-      assert is_reference(compiled)
+      assert match?(%ExJsonschema.Schema{}, compiled)
     end
   end
 
@@ -182,7 +182,7 @@ defmodule ExJsonschema.DraftSpecificCompilationTest do
         "type": "string"
       })
       assert {:ok, compiled} = ExJsonschema.compile_auto_draft(schema)
-      assert is_reference(compiled)
+      assert match?(%ExJsonschema.Schema{}, compiled)
     end
 
     test "automatically detects Draft 2020-12 from $schema" do
@@ -191,13 +191,13 @@ defmodule ExJsonschema.DraftSpecificCompilationTest do
         "type": "object"
       })
       assert {:ok, compiled} = ExJsonschema.compile_auto_draft(schema)
-      assert is_reference(compiled)
+      assert match?(%ExJsonschema.Schema{}, compiled)
     end
 
     test "defaults to latest draft when no $schema present" do
       schema = ~s({"type": "string"})
       assert {:ok, compiled} = ExJsonschema.compile_auto_draft(schema)
-      assert is_reference(compiled)
+      assert match?(%ExJsonschema.Schema{}, compiled)
     end
 
     test "works with additional options" do
@@ -207,7 +207,7 @@ defmodule ExJsonschema.DraftSpecificCompilationTest do
         "format": "email"
       })
       assert {:ok, compiled} = ExJsonschema.compile_auto_draft(schema, validate_formats: true)
-      assert is_reference(compiled)
+      assert match?(%ExJsonschema.Schema{}, compiled)
     end
   end
 
@@ -226,7 +226,7 @@ defmodule ExJsonschema.DraftSpecificCompilationTest do
 
       # All should be references
       validators = [draft4, draft6, draft7, draft201909, draft202012, auto, generic]
-      assert Enum.all?(validators, &is_reference/1)
+      assert Enum.all?(validators, &match?(%ExJsonschema.Schema{}, &1))
 
       # All should validate the same valid input
       valid_json = ~s("test")
@@ -0,0 +1,118 @@
+defmodule ExJsonschema.EctoTest do
+  use ExUnit.Case, async: true
+
+  alias Ecto.Changeset
+
+  @schema ~s({
+    "type": "object",
+    "required": ["items"],
+    "properties": {
+      "items": {
+        "type": "array",
+        "items": {
+          "type": "object",
+          "required": ["sku"],
+          "properties": {
+            "sku": {"type": "string"},
+            "quantity": {"type": "integer", "maximum": 10}
+          }
+        }
+      },
+      "labels": {"type": "object", "additionalProperties": {"type": "string"}}
+    }
+  })
+
+  setup_all do
+    {:ok, compiled: ExJsonschema.compile!(@schema)}
+  end
+
+  defp changeset(order) do
+    Changeset.cast({%{}, %{order: :map}}, %{"order" => order}, [:order])
+  end
+
+  test "keeps valid changesets valid", %{compiled: compiled} do
+    changeset =
+      changeset(%{"items" => [%{"sku" => "A-1", "quantity" => 2}]})
+      |> ExJsonschema.Ecto.validate_json(:order, compiled)
+
+    assert changeset.valid?
+    assert changeset.errors == []
+  end
+
+  test "puts errors on the field with their path, keyword and params", %{compiled: compiled} do
+    items = [%{"sku" => "A-1"}, %{"sku" => "B-2"}, %{"sku" => "C-3"}, %{"sku" => 4}]
+
+    changeset =
+      changeset(%{"items" => items})
+      |> ExJsonschema.Ecto.validate_json(:order, compiled)
+
+    refute changeset.valid?
+    assert [order: {message, metadata}] = changeset.errors
+    assert message =~ "is not of type"
+
+    assert metadata == [
+             validation: :json_schema,
+             path: ["items", 3, "sku"],
+             keyword: "type",
+             params: %{"expected" => "string"}
+           ]
+  end
+
+  test "tells array indices from object keys made of digits", %{compiled: compiled} do
+    changeset =
+      changeset(%{"items" => [%{"sku" => "A-1", "quantity" => 11}], "labels" => %{"7" => 7}})
+      |> ExJsonschema.Ecto.validate_json(:order, compiled)
+
+    paths = for {:order, {_message, metadata}} <- changeset.errors, do: metadata[:path]
+    assert Enum.sort(paths) == [["items", 0, "quantity"], ["labels", "7"]]
+
+    assert {_message, metadata} =
+             Enum.find_value(changeset.errors, fn {:order, {_, meta} = error} ->
+               meta[:keyword] == "maximum" && error
+             end)
+
+    assert metadata[:params] == %{"limit" => 10}
+  end
+
+  test "locates missing properties at their object", %{compiled: compiled} do
+    changeset =
+      changeset(%{"items" => [%{}]})
+      |> ExJsonschema.Ecto.validate_json(:order, compiled)
+
+    assert [order: {_message, metadata}] = changeset.errors
+    assert metadata[:path] == ["items", 0]
+    assert metadata[:keyword] == "required"
+    assert metadata[:params] == %{"property" => "sku"}
+  end
+
+  test "takes a custom message", %{compiled: compiled} do
+    changeset =
+      changeset(%{})
+      |> ExJsonschema.Ecto.validate_json(:order, compiled, message: &"fails #{&1.keyword}")
+
+    assert [order: {"fails required", metadata}] = changeset.errors
+    assert metadata[:path] == []
+
+    assert Changeset.traverse_errors(changeset, fn {message, _} -> message end) ==
+             %{order: ["fails required"]}
+  end
+
+  test "validates only changes", %{compiled: compiled} do
+    changeset =
+      {%{order: %{"items" => "not a list"}}, %{order: :map}}
+      |> Changeset.cast(%{}, [:order])
+      |> ExJsonschema.Ecto.validate_json(:order, compiled)
+
+    assert changeset.valid?
+    assert [order: {:json_schema, _compiled}] = changeset.validations
+  end
+
+  test "validates maps with atom keys", %{compiled: compiled} do
+    changeset =
+      {%{}, %{order: :map}}
+      |> Changeset.change(order: %{items: [%{sku: :a}]})
+      |> ExJsonschema.Ecto.validate_json(:order, compiled)
+
+    assert changeset.valid?
+  end
+end
@@ -7,7 +7,7 @@ defmodule ExJsonschema.EdgeCasesTest do
     test "returns compiled schema on success" do
       schema = ~s({"type": "string"})
       compiled = ExJsonschema.compile!(schema)
-      assert is_reference(compiled)
+      assert match?(%ExJsonschema.Schema{}, compiled)
     end
 
     test "raises ArgumentError on compilation failure" do
@@ -112,7 +112,7 @@ defmodule ExJsonschema.EdgeCasesTest do
       # Should succeed with any draft option
       result = ExJsonschema.compile(schema, draft: :draft4)
       assert {:ok, compiled} = result
-      assert is_reference(compiled)
+      assert match?(%ExJsonschema.Schema{}, compiled)
     end
 
     test "auto draft detection works correctly" do
@@ -123,7 +123,7 @@ defmodule ExJsonschema.EdgeCasesTest do
 
       result = ExJsonschema.compile(schema, draft: :auto)
       assert {:ok, compiled} = result
-      assert is_reference(compiled)
+      assert match?(%ExJsonschema.Schema{}, compiled)
     end
   end
 
@@ -0,0 +1,98 @@
+defmodule ExJsonschema.ErrorBranchesTest do
+  use ExUnit.Case, async: true
+
+  alias ExJsonschema.ValidationError
+
+  @schema ~s({
+    "type": "object",
+    "properties": {
+      "id": {"oneOf": [{"type": "string", "format": "uuid"}, {"type": "integer", "minimum": 1}]},
+      "contact": {
+        "anyOf": [
+          {"type": "object", "required": ["email"]},
+          {"$ref": "#/$defs/Phone"}
+        ]
+      },
+      "amount": {"allOf": [{"type": "number"}, {"minimum": 0}]}
+    },
+    "$defs": {"Phone": {"type": "object", "required": ["phone"]}}
+  })
+
+  setup_all do
+    {:ok, compiled: ExJsonschema.compile!(@schema, validate_formats: true)}
+  end
+
+  defp error_at(compiled, instance, path, output \\ :detailed) do
+    {:error, errors} = ExJsonschema.validate(compiled, instance, output: output)
+    Enum.find(errors, &(&1.instance_path == path))
+  end
+
+  describe "oneOf" do
+    test "lists the errors of every subschema", %{compiled: compiled} do
+      error = error_at(compiled, ~s({"id": 0}), "/id")
+
+      assert error.schema_path == "/properties/id/oneOf"
+      assert [[string_error], [minimum_error]] = error.branches
+
+      assert %ValidationError{schema_path: "/properties/id/oneOf/0/type"} = string_error
+      assert string_error.branch == %{index: 0, schema_pointer: "/properties/id/oneOf/0"}
+
+      assert minimum_error.schema_path == "/properties/id/oneOf/1/minimum"
+      assert minimum_error.branch == %{index: 1, schema_pointer: "/properties/id/oneOf/1"}
+      assert minimum_error.instance_path == "/id"
+    end
+
+    test "reports branches in verbose output", %{compiled: compiled} do
+      error = error_at(compiled, ~s({"id": 0}), "/id", :verbose)
+
+      assert [[string_error], [minimum_error]] = error.branches
+      assert string_error.keyword == "type"
+      assert minimum_error.instance_value == 0
+      assert minimum_error.branch.index == 1
+    end
+  end
+
+  describe "anyOf" do
+    test "follows references inside branches", %{compiled: compiled} do
+      error = error_at(compiled, ~s({"contact": {}}), "/contact")
+
+      assert [[email_error], [phone_error]] = error.branches
+      assert email_error.message =~ "email"
+      assert phone_error.message =~ "phone"
+      assert phone_error.branch.schema_pointer == "/properties/contact/anyOf/1"
+    end
+  end
+
+  test "other errors have no branches", %{compiled: compiled} do
+    {:error, errors} = ExJsonschema.validate(compiled, ~s({"amount": -1}))
+
+    # allOf failures are already separate errors
+    assert [%ValidationError{branches: nil, branch: nil}] = errors
+  end
+
+  test "keeps branches in validate_at", %{compiled: compiled} do
+    assert {:error, [error]} =
+             ExJsonschema.validate_at(compiled, ~s({"id": 0}), instance_pointer: "/id")
+
+    assert [[_], [minimum_error]] = error.branches
+    assert minimum_error.schema_path == "/properties/id/oneOf/1/minimum"
+  end
+
+  test "redacts sensitive values in branches" do
+    schema = ~s({
+      "properties": {
+        "secret": {"writeOnly": true, "anyOf": [{"type": "integer"}, {"maxLength": 3}]}
+      }
+    })
+
+    compiled = ExJsonschema.compile!(schema, redact: true)
+
+    for output <- [:detailed, :verbose] do
+      error = error_at(compiled, ~s({"secret": "hunter2"}), "/secret", output)
+
+      for error <- List.flatten(error.branches) do
+        refute error.message =~ "hunter2"
+      end
+    end
+  end
+end
@@ -0,0 +1,146 @@
+defmodule ExJsonschema.FormatPolicyTest do
+  use ExUnit.Case, async: true
+
+  alias ExJsonschema.{CompilationError, Options, Serializer}
+
+  @schema ~s({
+    "type": "object",
+    "properties": {
+      "email": {"type": "string", "format": "email"},
+      "homepage": {"type": "string", "format": "uri"}
+    }
+  })
+
+  @invalid ~s({"email": "not an email", "homepage": "not a uri"})
+
+  defp failing_properties(compiled, instance) do
+    case ExJsonschema.validate(compiled, instance) do
+      :ok -> []
+      {:error, errors} -> errors |> Enum.map(& &1.instance_path) |> Enum.sort()
+    end
+  end
+
+  test "checks every known format by default" do
+    compiled = ExJsonschema.compile!(@schema, validate_formats: true)
+
+    assert failing_properties(compiled, @invalid) == ["/email", "/homepage"]
+  end
+
+  test "denied formats are not checked" do
+    compiled = ExJsonschema.compile!(@schema, validate_formats: true, denied_formats: ["uri"])
+
+    assert failing_properties(compiled, @invalid) == ["/email"]
+  end
+
+  test "only allowed formats are checked" do
+    compiled =
+      ExJsonschema.compile!(@schema, validate_formats: true, allowed_formats: ["uri"])
+
+    assert failing_properties(compiled, @invalid) == ["/homepage"]
+  end
+
+  test "deny wins over allow" do
+    compiled =
+      ExJsonschema.compile!(@schema,
+        validate_formats: true,
+        allowed_formats: ["email", "uri"],
+        denied_formats: ["email"]
+      )
+
+    assert failing_properties(compiled, @invalid) == ["/homepage"]
+  end
+
+  describe "unknown formats" do
+    @misspelled ~s({"type": "string", "format": "date_time"})
+
+    test "are ignored by default" do
+      compiled = ExJsonschema.compile!(@misspelled, validate_formats: true)
+
+      assert :ok = ExJsonschema.validate(compiled, ~s("anything"))
+    end
+
+    test "fail compilation with unknown_formats: :error" do
+      assert {:error, %CompilationError{} = error} =
+               ExJsonschema.compile(@misspelled, unknown_formats: :error)
+
+      assert error.details =~ ~s(Unknown format "date_time")
+      assert error.details =~ "/format"
+    end
+
+    test "are found in nested subschemas but not in data keywords" do
+      nested = ~s({"items": {"format": "ip-address"}})
+      data = ~s({"const": {"format": "ip-address"}, "examples": [{"format": "x"}]})
+
+      assert {:error, %CompilationError{} = error} =
+               ExJsonschema.compile(nested, unknown_formats: :error)
+
+      assert error.details =~ "/items/format"
+      assert {:ok, _} = ExJsonschema.compile(data, unknown_formats: :error)
+
+      example = ~s({"example": {"format": "x"}})
+      assert {:ok, _} = ExJsonschema.compile(example, unknown_formats: :error)
+    end
+
+    test "are found in properties named after data keywords" do
+      schema = ~s({"properties": {"default": {"format": "ip-address"}}})
+
+      assert {:error, %CompilationError{} = error} =
+               ExJsonschema.compile(schema, unknown_formats: :error)
+
+      assert error.details =~ "/properties/default/format"
+    end
+
+    test "honour the legacy ignore_unknown_formats option" do
+      assert {:error, %CompilationError{}} =
+               ExJsonschema.compile(@misspelled, ignore_unknown_formats: false)
+
+      assert %Options{unknown_formats: :error} = Options.new(ignore_unknown_formats: false)
+    end
+  end
+
+  test "the format-assertion vocabulary turns on format checks" do
+    schema = ~s({
+      "$schema": "https://example.com/format-assertion",
+      "$defs": {
+        "meta": {
+          "$id": "https://example.com/format-assertion",
+          "$vocabulary": {
+            "https://json-schema.org/draft/2020-12/vocab/core": true,
+            "https://json-schema.org/draft/2020-12/vocab/format-assertion": true
+          }
+        }
+      },
+      "format": "email"
+    })
+
+    compiled = ExJsonschema.compile!(schema, draft: :draft202012)
+
+    assert {:error, [_]} = ExJsonschema.validate(compiled, ~s("not an email"))
+    assert :ok = ExJsonschema.validate(compiled, ~s("user@example.com"))
+  end
+
+  test "is part of the options fingerprint" do
+    compiled = ExJsonschema.compile!(@schema, validate_formats: true, denied_formats: ["uri"])
+
+    assert ExJsonschema.info(compiled).options_fingerprint =~ ~s(formats=*,["uri"],false)
+    refute ExJsonschema.info(ExJsonschema.compile!(@schema)).options_fingerprint =~ "formats="
+  end
+
+  test "survives serialization" do
+    compiled = ExJsonschema.compile!(@schema, validate_formats: true, denied_formats: ["uri"])
+
+    assert {:ok, restored} = Serializer.deserialize(Serializer.serialize(compiled))
+    assert failing_properties(restored, @invalid) == ["/email"]
+  end
+
+  test "rejects invalid policy options" do
+    assert {:error, "Invalid allowed_formats option: \"email\""} =
+             Options.validate(Options.new(allowed_formats: "email"))
+
+    assert {:error, "Invalid denied_formats option: [:uri]"} =
+             Options.validate(Options.new(denied_formats: [:uri]))
+
+    assert {:error, "Invalid unknown_formats option: :warn"} =
+             Options.validate(Options.new(unknown_formats: :warn))
+  end
+end
@@ -0,0 +1,150 @@
+defmodule ExJsonschema.InferrerTest do
+  use ExUnit.Case, async: true
+
+  alias ExJsonschema.{CompilationError, Inferrer}
+
+  @samples [
+    %{
+      "id" => "550e8400-e29b-41d4-a716-446655440000",
+      "status" => "active",
+      "email" => "ana@example.com",
+      "created_at" => "2024-01-15T10:30:00Z",
+      "amount" => 10,
+      "tags" => ["new"]
+    },
+    %{
+      "id" => "6ba7b810-9dad-11d1-80b4-00c04fd430c8",
+      "status" => "closed",
+      "email" => "bo@example.org",
+      "created_at" => "2024-02-01T08:00:00+01:00",
+      "amount" => 12.5,
+      "tags" => [],
+      "note" => nil
+    },
+    %{
+      "id" => "6ba7b811-9dad-11d1-80b4-00c04fd430c8",
+      "status" => "active",
+      "email" => "cy@example.net",
+      "created_at" => "2024-03-09T23:59:59Z",
+      "amount" => -2,
+      "tags" => ["vip", "new"],
+      "note" => "call back"
+    }
+  ]
+
+  describe "infer/2" do
+    test "produces a schema that accepts every sample" do
+      assert {:ok, schema} = ExJsonschema.infer(@samples)
+
+      compiled = ExJsonschema.compile!(Jason.encode!(schema))
+      for sample <- @samples, do: assert(:ok = ExJsonschema.validate(compiled, Jason.encode!(sample)))
+    end
+
+    test "requires fields present in every sample" do
+      assert {:ok, schema} = ExJsonschema.infer(@samples)
+
+      assert schema["type"] == "object"
+      assert schema["required"] == ["amount", "created_at", "email", "id", "status", "tags"]
+      assert schema["properties"]["note"] == %{"type" => ["null", "string"]}
+    end
+
+    test "detects formats shared by every value" do
+      assert {:ok, %{"properties" => properties}} = ExJsonschema.infer(@samples)
+
+      assert properties["id"] == %{"type" => "string", "format" => "uuid"}
+      assert properties["email"] == %{"type" => "string", "format" => "email"}
+      assert properties["created_at"] == %{"type" => "string", "format" => "date-time"}
+    end
+
+    test "drops a format when some values do not match it" do
+      samples = [%{"at" => "2024-01-15T10:30:00Z"}, %{"at" => "yesterday"}]
+
+      assert {:ok, %{"properties" => %{"at" => %{"type" => "string"}}}} =
+               ExJsonschema.infer(samples)
+    end
+
+    test "reports low-cardinality strings as enums" do
+      assert {:ok, %{"properties" => properties}} = ExJsonschema.infer(@samples)
+
+      assert properties["status"] == %{"type" => "string", "enum" => ["active", "closed"]}
+      assert properties["tags"]["items"] == %{"type" => "string", "enum" => ["new", "vip"]}
+    end
+
+    test "respects the enum threshold" do
+      assert {:ok, %{"properties" => properties}} = ExJsonschema.infer(@samples, enum_threshold: 1)
+      assert properties["status"] == %{"type" => "string"}
+
+      assert {:ok, %{"properties" => properties}} = ExJsonschema.infer(@samples, enum_threshold: 0)
+      refute Map.has_key?(properties["tags"]["items"], "enum")
+    end
+
+    test "records numeric ranges" do
+      assert {:ok, %{"properties" => %{"amount" => amount}}} = ExJsonschema.infer(@samples)
+      assert amount == %{"type" => "number", "minimum" => -2, "maximum" => 12.5}
+
+      assert {:ok, %{"items" => items}} = Inferrer.infer([[1, 2], [3]])
+      assert items == %{"type" => "integer", "minimum" => 1, "maximum" => 3}
+    end
+
+    test "merges nested objects" do
+      samples = [
+        %{"user" => %{"name" => "a", "age" => 30}},
+        %{"user" => %{"name" => "b"}}
+      ]
+
+      assert {:ok, %{"properties" => %{"user" => user}}} = ExJsonschema.infer(samples)
+      assert user["required"] == ["name"]
+      assert user["properties"]["age"] == %{"type" => "integer", "minimum" => 30, "maximum" => 30}
+    end
+
+    test "accepts JSON text with the json option" do
+      assert {:ok, %{"type" => ["boolean", "object"]}} =
+               Inferrer.infer([~s({"a": 1}), "true"], json: true)
+    end
+
+    test "treats strings as JSON strings" do
+      assert {:ok, %{"type" => "string"}} = Inferrer.infer(["true", "1],[2"])
+    end
+  end
+
+  describe "drafts" do
+    test "defaults to draft 2020-12" do
+      assert {:ok, %{"$schema" => "https://json-schema.org/draft/2020-12/schema"}} =
+               ExJsonschema.infer([%{}])
+    end
+
+    test "emits any supported draft" do
+      for draft <- ExJsonschema.DraftDetector.supported_drafts() do
+        assert {:ok, schema} = Inferrer.infer(@samples, draft: draft)
+        assert {:ok, ^draft} = ExJsonschema.DraftDetector.detect_draft(schema)
+        assert {:ok, _} = ExJsonschema.compile(Jason.encode!(schema))
+      end
+    end
+
+    test "rejects unsupported drafts" do
+      assert_raise ArgumentError, ~r/Invalid draft/, fn ->
+        Inferrer.infer(@samples, draft: :draft3)
+      end
+    end
+  end
+
+  describe "errors" do
+    test "requires at least one sample" do
+      assert_raise ArgumentError, ~r/At least one sample/, fn -> Inferrer.infer([]) end
+    end
+
+    test "returns parse errors for malformed JSON samples" do
+      assert {:error, %CompilationError{type: :json_parse_error, details: details}} =
+               Inferrer.infer(["1", ~s({"a": )], json: true)
+
+      assert details =~ "Sample 1"
+
+      assert {:error, %CompilationError{type: :json_parse_error}} =
+               Inferrer.infer([%{"a" => 1}], json: true)
+    end
+
+    test "rejects invalid json options" do
+      assert_raise ArgumentError, ~r/Invalid json/, fn -> Inferrer.infer([1], json: "yes") end
+    end
+  end
+end
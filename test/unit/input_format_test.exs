@@ -0,0 +1,167 @@
+defmodule ExJsonschema.InputFormatTest do
+  use ExUnit.Case, async: true
+
+  alias ExJsonschema.{CompilationError, DraftDetector}
+
+  @yaml_schema """
+  type: object
+  required: [name]
+  properties:
+    name:
+      type: string
+    port:
+      $ref: "#/$defs/Port"
+  $defs:
+    Port:
+      type: integer
+      maximum: 65535
+  """
+
+  @toml_schema """
+  type = "object"
+  required = ["name"]
+
+  [properties.name]
+  type = "string"
+  """
+
+  setup_all do
+    {:ok, compiled} = ExJsonschema.compile(@yaml_schema, input_format: :yaml)
+    {:ok, compiled: compiled}
+  end
+
+  test "compiles YAML schemas and validates YAML and TOML instances", %{compiled: compiled} do
+    assert :ok = ExJsonschema.validate(compiled, "name: web\nport: 80\n", input_format: :yaml)
+    assert :ok = ExJsonschema.validate(compiled, ~s(name = "web"\n), input_format: :toml)
+    assert :ok = ExJsonschema.validate(compiled, ~s({"name": "web"}))
+
+    assert ExJsonschema.valid?(compiled, "name: web", input_format: :yaml)
+    refute ExJsonschema.valid?(compiled, "name: 42", input_format: :yaml)
+  end
+
+  test "reads YAML scalars with the core schema", %{compiled: compiled} do
+    assert {:error, [error]} = ExJsonschema.validate(compiled, "name: true", input_format: :yaml)
+    assert error.instance_path == "/name"
+
+    assert :ok = ExJsonschema.validate(compiled, ~s(name: "true"), input_format: :yaml)
+
+    assert {:error, [error]} =
+             ExJsonschema.validate(compiled, ~s(name: web\nport: "80"), input_format: :yaml)
+
+    assert error.instance_path == "/port"
+  end
+
+  test "locates errors in the instance and the schema", %{compiled: compiled} do
+    instance = """
+    name = "web"
+    port = 70000
+    """
+
+    assert {:error, [error]} = ExJsonschema.validate(compiled, instance, input_format: :toml)
+    assert error.schema_path == "/properties/port/$ref/maximum"
+    assert error.instance_position == %{line: 2, column: 1}
+    assert error.schema_position == %{line: 11, column: 5}
+  end
+
+  test "locates missing properties at their object", %{compiled: compiled} do
+    assert {:error, [error]} =
+             ExJsonschema.validate(compiled, "port: 80", input_format: :yaml, output: :verbose)
+
+    assert error.keyword == "required"
+    assert error.instance_position == %{line: 1, column: 1}
+    assert error.schema_position == %{line: 2, column: 1}
+  end
+
+  test "has no positions for JSON" do
+    compiled = ExJsonschema.compile!(~s({"type": "string"}))
+    assert {:error, [error]} = ExJsonschema.validate(compiled, "42")
+    assert error.instance_position == nil
+    assert error.schema_position == nil
+  end
+
+  test "compiles TOML schemas" do
+    assert {:ok, compiled} = ExJsonschema.compile(@toml_schema, input_format: :toml)
+    assert {:error, [error]} = ExJsonschema.validate(compiled, ~s({"name": 1}))
+
+    assert error.schema_path == "/properties/name/type"
+    assert error.schema_position == %{line: 5, column: 1}
+    assert error.instance_position == nil
+  end
+
+  test "detects drafts in YAML schemas" do
+    schema = "$schema: http://json-schema.org/draft-04/schema#\ntype: string\n"
+
+    assert DraftDetector.detect_draft(schema, :yaml) == {:ok, :draft4}
+    assert {:ok, compiled} = ExJsonschema.compile(schema, input_format: :yaml)
+    assert ExJsonschema.Schema.info(compiled).draft == :draft4
+  end
+
+  test "reports where parsing stopped" do
+    assert {:error, %CompilationError{details: details}} =
+             ExJsonschema.compile("type: [string", input_format: :yaml)
+
+    assert details =~ ~r/Invalid YAML: line \d+, column \d+: /
+
+    assert {:error, %CompilationError{details: details}} =
+             ExJsonschema.compile("type = ", input_format: :toml, draft: :draft7)
+
+    assert details =~ "Invalid TOML: line 1, column "
+  end
+
+  test "does not accept malformed instances against permissive schemas" do
+    permissive = ExJsonschema.compile!("{}")
+
+    refute ExJsonschema.valid?(permissive, "name: [web", input_format: :yaml)
+    refute ExJsonschema.valid?(permissive, "name = ", input_format: :toml)
+    refute ExJsonschema.valid?(permissive, "{")
+
+    assert {:error, :validation_failed} =
+             ExJsonschema.validate(permissive, "name: [web", input_format: :yaml, output: :basic)
+
+    assert {:error, [:validation_error]} =
+             ExJsonschema.validate(permissive, "name: [web", input_format: :yaml)
+  end
+
+  test "expands aliases and stops documents that expand without bound" do
+    permissive = ExJsonschema.compile!("{}")
+
+    assert :ok = ExJsonschema.validate(permissive, "a: &x [1, 2]\nb: *x\n", input_format: :yaml)
+
+    levels =
+      for level <- 1..8 do
+        aliases = List.duplicate("*a#{level - 1}", 10) |> Enum.join(", ")
+        "l#{level}: &a#{level} [#{aliases}]\n"
+      end
+
+    yaml = Enum.join(["l0: &a0 [x, x, x, x, x, x, x, x, x, x]\n" | levels])
+
+    assert {:error, %CompilationError{type: :parse_error, details: details}} =
+             ExJsonschema.compile(yaml, input_format: :yaml)
+
+    assert details =~ "more than 1000000 values once aliases are expanded"
+    refute ExJsonschema.valid?(permissive, yaml, input_format: :yaml)
+  end
+
+  test "rejects numbers JSON cannot represent" do
+    assert {:error, %CompilationError{details: details}} =
+             ExJsonschema.compile("maximum: .inf", input_format: :yaml)
+
+    assert details =~ ".inf is not a number JSON can represent"
+  end
+
+  test "keeps YAML schemas apart from their JSON equivalents in the cache" do
+    {:ok, yaml_key} = ExJsonschema.Cache.key("type: string", input_format: :yaml)
+    {:ok, json_key} = ExJsonschema.Cache.key(~s({"type": "string"}))
+
+    assert yaml_key != json_key
+  end
+
+  test "rejects unknown input formats", %{compiled: compiled} do
+    assert_raise ArgumentError, ~r/Invalid input_format option/, fn ->
+      ExJsonschema.validate(compiled, "{}", input_format: :xml)
+    end
+
+    assert {:error, %CompilationError{type: :options_error}} =
+             ExJsonschema.compile("{}", input_format: :xml)
+  end
+end
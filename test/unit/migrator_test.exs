@@ -0,0 +1,169 @@
+defmodule ExJsonschema.MigratorTest do
+  use ExUnit.Case, async: true
+
+  alias ExJsonschema.{CompilationError, Migrator}
+
+  @draft4 "http://json-schema.org/draft-04/schema#"
+
+  describe "migrate/2 to draft 2020-12" do
+    test "rewrites definitions and references into $defs" do
+      schema = %{
+        "$schema" => @draft4,
+        "properties" => %{"home" => %{"$ref" => "#/definitions/address"}},
+        "definitions" => %{"address" => %{"type" => "object"}}
+      }
+
+      assert {:ok, migrated, []} = ExJsonschema.migrate(schema, to: :draft202012)
+      assert migrated["$schema"] == "https://json-schema.org/draft/2020-12/schema"
+      assert migrated["$defs"] == %{"address" => %{"type" => "object"}}
+      assert migrated["properties"]["home"] == %{"$ref" => "#/$defs/address"}
+      refute Map.has_key?(migrated, "definitions")
+    end
+
+    test "keeps properties that happen to be named like keywords" do
+      schema = %{
+        "$schema" => @draft4,
+        "properties" => %{"definitions" => %{"type" => "string"}, "id" => %{"type" => "integer"}}
+      }
+
+      assert {:ok, migrated, []} = ExJsonschema.migrate(schema)
+      assert Map.keys(migrated["properties"]) == ["definitions", "id"]
+    end
+
+    test "converts boolean exclusive bounds to numeric ones" do
+      schema = %{
+        "$schema" => @draft4,
+        "type" => "number",
+        "minimum" => 0,
+        "exclusiveMinimum" => true,
+        "maximum" => 10,
+        "exclusiveMaximum" => false
+      }
+
+      assert {:ok, migrated, []} = ExJsonschema.migrate(schema)
+      assert migrated["exclusiveMinimum"] == 0
+      refute Map.has_key?(migrated, "minimum")
+      assert migrated["maximum"] == 10
+      refute Map.has_key?(migrated, "exclusiveMaximum")
+    end
+
+    test "converts id to $id and $anchor" do
+      schema = %{"$schema" => @draft4, "id" => "http://example.com/root.json#"}
+      assert {:ok, %{"$id" => "http://example.com/root.json"}, []} = ExJsonschema.migrate(schema)
+
+      schema = %{
+        "$schema" => "http://json-schema.org/draft-07/schema#",
+        "$defs" => %{"a" => %{"$id" => "#item", "type" => "string"}}
+      }
+
+      assert {:ok, migrated, []} = ExJsonschema.migrate(schema)
+      assert migrated["$defs"]["a"] == %{"$anchor" => "item", "type" => "string"}
+    end
+
+    test "converts array-form items to prefixItems" do
+      schema = %{
+        "$schema" => @draft4,
+        "items" => [%{"type" => "string"}, %{"type" => "integer"}],
+        "additionalItems" => false
+      }
+
+      assert {:ok, migrated, []} = ExJsonschema.migrate(schema)
+      assert migrated["prefixItems"] == [%{"type" => "string"}, %{"type" => "integer"}]
+      assert migrated["items"] == false
+      refute Map.has_key?(migrated, "additionalItems")
+    end
+
+    test "splits dependencies" do
+      schema = %{
+        "$schema" => @draft4,
+        "dependencies" => %{
+          "credit_card" => ["billing_address"],
+          "shipping" => %{"required" => ["address"]}
+        }
+      }
+
+      assert {:ok, migrated, []} = ExJsonschema.migrate(schema)
+      assert migrated["dependentRequired"] == %{"credit_card" => ["billing_address"]}
+      assert migrated["dependentSchemas"] == %{"shipping" => %{"required" => ["address"]}}
+      refute Map.has_key?(migrated, "dependencies")
+    end
+
+    test "migrated schemas compile as 2020-12" do
+      schema = %{
+        "$schema" => @draft4,
+        "type" => "object",
+        "properties" => %{"age" => %{"$ref" => "#/definitions/age"}},
+        "definitions" => %{"age" => %{"minimum" => 0, "exclusiveMinimum" => true}}
+      }
+
+      assert {:ok, migrated, []} = ExJsonschema.migrate(schema)
+      compiled = ExJsonschema.compile!(Jason.encode!(migrated))
+
+      assert :ok = ExJsonschema.validate(compiled, ~s({"age": 1}))
+      assert {:error, _} = ExJsonschema.validate(compiled, ~s({"age": 0}))
+    end
+  end
+
+  describe "migrate/2 reporting" do
+    test "reports exclusive bounds without a limit" do
+      schema = %{"$schema" => @draft4, "exclusiveMinimum" => true}
+
+      assert {:ok, migrated, [issue]} = ExJsonschema.migrate(schema)
+      refute Map.has_key?(migrated, "exclusiveMinimum")
+      assert %{path: "/exclusiveMinimum", keyword: "exclusiveMinimum"} = issue
+    end
+
+    test "reports $ref siblings whose meaning changes" do
+      schema = %{"$schema" => @draft4, "$ref" => "#/definitions/a", "minLength" => 2}
+
+      assert {:ok, _migrated, [%{keyword: "$ref", path: "/$ref"}]} = ExJsonschema.migrate(schema)
+    end
+
+    test "reports recursive references" do
+      schema = %{
+        "$schema" => "https://json-schema.org/draft/2019-09/schema",
+        "$recursiveAnchor" => true,
+        "items" => %{"$recursiveRef" => "#"}
+      }
+
+      assert {:ok, _migrated, issues} = ExJsonschema.migrate(schema, to: :draft202012)
+      assert Enum.map(issues, & &1.keyword) |> Enum.sort() == ["$recursiveAnchor", "$recursiveRef"]
+    end
+
+    test "reports external references that were not rewritten" do
+      schema = %{"$schema" => @draft4, "$ref" => "other.json#/definitions/a"}
+
+      assert {:ok, migrated, [%{keyword: "$ref"}]} = ExJsonschema.migrate(schema)
+      assert migrated["$ref"] == "other.json#/definitions/a"
+    end
+  end
+
+  describe "migrate/2 targets" do
+    test "keeps array-form items when targeting 2019-09" do
+      schema = %{"$schema" => @draft4, "items" => [%{"type" => "string"}], "additionalItems" => false}
+
+      assert {:ok, migrated, []} = Migrator.migrate(schema, to: :draft201909)
+      assert migrated["$schema"] == "https://json-schema.org/draft/2019-09/schema"
+      assert migrated["items"] == [%{"type" => "string"}]
+      assert migrated["additionalItems"] == false
+    end
+
+    test "refuses to downgrade" do
+      schema = %{"$schema" => "https://json-schema.org/draft/2020-12/schema"}
+
+      assert {:error, %CompilationError{type: :migration_error}} =
+               Migrator.migrate(schema, to: :draft201909)
+    end
+
+    test "rejects unsupported targets" do
+      assert_raise ArgumentError, ~r/Invalid migration target/, fn ->
+        Migrator.migrate(%{}, to: :draft7)
+      end
+    end
+
+    test "returns parse errors for malformed JSON" do
+      assert {:error, %CompilationError{type: :json_parse_error}} =
+               Migrator.migrate(~s({"type": ))
+    end
+  end
+end
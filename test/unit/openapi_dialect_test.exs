@@ -0,0 +1,109 @@
+defmodule ExJsonschema.OpenApiDialectTest do
+  use ExUnit.Case, async: true
+
+  alias ExJsonschema.{DraftDetector, Options, Serializer}
+
+  describe "OpenAPI 3.0" do
+    @schema ~s({
+      "type": "object",
+      "required": ["id"],
+      "properties": {
+        "id": {"type": "integer", "format": "int64", "minimum": 0, "exclusiveMinimum": true},
+        "nickname": {"type": "string", "nullable": true, "example": "ace"},
+        "status": {"type": "string", "enum": ["active"], "nullable": true}
+      },
+      "discriminator": {"propertyName": "kind"}
+    })
+
+    setup_all do
+      {:ok, compiled: ExJsonschema.compile!(@schema, draft: :openapi30)}
+    end
+
+    test "nullable: true allows null", %{compiled: compiled} do
+      assert :ok = ExJsonschema.validate(compiled, ~s({"id": 1, "nickname": null}))
+      assert {:error, [error]} = ExJsonschema.validate(compiled, ~s({"id": 1, "nickname": 5}))
+      assert error.instance_path == "/nickname"
+    end
+
+    test "nullable does not widen enum", %{compiled: compiled} do
+      assert {:error, [error]} = ExJsonschema.validate(compiled, ~s({"id": 1, "status": null}))
+      assert error.schema_path == "/properties/status/enum"
+    end
+
+    test "nullable applies in properties named after data keywords and extensions" do
+      schema = ~s({"type": "object", "properties": {
+        "default": {"type": "string", "nullable": true},
+        "x-note": {"type": "string", "nullable": true}
+      }})
+
+      compiled = ExJsonschema.compile!(schema, draft: :openapi30)
+
+      assert :ok = ExJsonschema.validate(compiled, ~s({"default": null, "x-note": null}))
+    end
+
+    test "exclusiveMinimum is a boolean modifier", %{compiled: compiled} do
+      assert {:error, [error]} = ExJsonschema.validate(compiled, ~s({"id": 0}))
+      assert error.instance_path == "/id"
+    end
+
+    test "without the dialect, nullable is ignored" do
+      compiled = ExJsonschema.compile!(@schema, draft: :draft4)
+
+      assert {:error, _} = ExJsonschema.validate(compiled, ~s({"id": 1, "nickname": null}))
+    end
+
+    test "reports the dialect", %{compiled: compiled} do
+      assert ExJsonschema.info(compiled).draft == :openapi30
+      assert ExJsonschema.info(compiled).options_fingerprint =~ "draft=openapi30"
+    end
+
+    test "OpenAPI formats are known formats" do
+      assert {:ok, _} = ExJsonschema.compile(@schema, Options.openapi30(unknown_formats: :error))
+
+      assert {:error, _} =
+               ExJsonschema.compile(@schema, draft: :draft4, unknown_formats: :error)
+    end
+
+    test "survives serialization", %{compiled: compiled} do
+      assert {:ok, restored} = Serializer.deserialize(Serializer.serialize(compiled))
+      assert :ok = ExJsonschema.validate(restored, ~s({"id": 1, "nickname": null}))
+      assert ExJsonschema.info(restored).draft == :openapi30
+    end
+  end
+
+  describe "OpenAPI 3.1" do
+    @schema ~s({
+      "$schema": "https://spec.openapis.org/oas/3.1/dialect/base",
+      "type": "object",
+      "properties": {
+        "id": {"type": "integer", "exclusiveMinimum": 0},
+        "nickname": {"type": ["string", "null"]}
+      },
+      "discriminator": {"propertyName": "kind"},
+      "xml": {"name": "user"},
+      "externalDocs": {"url": "https://example.com/docs"},
+      "example": {"id": 1}
+    })
+
+    test "is detected from the base dialect $schema" do
+      assert {:ok, :openapi31} = DraftDetector.detect_draft(@schema)
+
+      compiled = ExJsonschema.compile!(@schema)
+
+      assert ExJsonschema.info(compiled).draft == :openapi31
+      assert :ok = ExJsonschema.validate(compiled, ~s({"id": 1, "nickname": null}))
+      assert {:error, [error]} = ExJsonschema.validate(compiled, ~s({"id": 0}))
+      assert error.instance_path == "/id"
+    end
+
+    test "compiles with an explicit draft" do
+      assert {:ok, _} = ExJsonschema.compile(@schema, Options.openapi31())
+      assert {:ok, _} = ExJsonschema.compile(~s({"type": "string"}), draft: :openapi31)
+    end
+  end
+
+  test "rejects unknown dialects" do
+    assert {:error, "Invalid draft version: :openapi20"} =
+             Options.validate(Options.new(draft: :openapi20))
+  end
+end
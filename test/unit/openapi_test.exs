@@ -0,0 +1,229 @@
+defmodule ExJsonschema.OpenAPITest do
+  use ExUnit.Case, async: true
+
+  alias ExJsonschema.{CompilationError, OpenAPI, ValidationError}
+
+  @document %{
+    "openapi" => "3.0.3",
+    "info" => %{"title" => "Users", "version" => "1.0.0"},
+    "paths" => %{
+      "/users/{id}" => %{
+        "parameters" => [
+          %{"name" => "id", "in" => "path", "schema" => %{"type" => "integer", "minimum" => 1}}
+        ],
+        "put" => %{
+          "operationId" => "updateUser",
+          "parameters" => [
+            %{"$ref" => "#/components/parameters/Fields"},
+            %{
+              "name" => "X-Request-Id",
+              "in" => "header",
+              "required" => true,
+              "schema" => %{"type" => "string"}
+            }
+          ],
+          "requestBody" => %{
+            "required" => true,
+            "content" => %{
+              "application/json" => %{"schema" => %{"$ref" => "#/components/schemas/User"}}
+            }
+          },
+          "responses" => %{
+            "200" => %{
+              "description" => "Updated",
+              "content" => %{
+                "application/json" => %{"schema" => %{"$ref" => "#/components/schemas/User"}}
+              }
+            },
+            "4XX" => %{"description" => "Rejected"}
+          }
+        }
+      },
+      "/users/me" => %{
+        "get" => %{"responses" => %{"204" => %{"description" => "No content"}}}
+      }
+    },
+    "components" => %{
+      "parameters" => %{
+        "Fields" => %{
+          "name" => "fields",
+          "in" => "query",
+          "schema" => %{"type" => "array", "items" => %{"type" => "string", "enum" => ["name"]}}
+        }
+      },
+      "schemas" => %{
+        "User" => %{
+          "type" => "object",
+          "required" => ["name"],
+          "properties" => %{
+            "id" => %{"type" => "integer", "readOnly" => true},
+            "name" => %{"type" => "string", "nullable" => true}
+          }
+        }
+      }
+    }
+  }
+
+  @headers %{"x-request-id" => "abc"}
+
+  setup_all do
+    {:ok, spec: OpenAPI.load!(@document)}
+  end
+
+  describe "load/1" do
+    test "lists the operations", %{spec: spec} do
+      operations = OpenAPI.operations(spec)
+
+      assert %{method: "PUT", path: "/users/{id}", operation_id: "updateUser"} in operations
+      assert length(operations) == 2
+    end
+
+    test "accepts JSON text" do
+      assert {:ok, %OpenAPI{}} = OpenAPI.load(Jason.encode!(@document))
+    end
+
+    test "rejects documents that are not OpenAPI 3.x" do
+      assert {:error, %CompilationError{details: details}} =
+               OpenAPI.load(%{"swagger" => "2.0"})
+
+      assert details =~ "openapi"
+      assert {:error, %CompilationError{type: :json_parse_error}} = OpenAPI.load("{")
+    end
+  end
+
+  describe "validate_request/4" do
+    test "accepts a valid request", %{spec: spec} do
+      assert :ok =
+               OpenAPI.validate_request(spec, "PUT", "/users/42", %{
+                 body: ~s({"name": null}),
+                 query: %{"fields" => "name"},
+                 headers: %{"X-Request-Id" => "abc"}
+               })
+    end
+
+    test "coerces path and query parameters", %{spec: spec} do
+      request = %{body: %{"name" => "Ada"}, headers: @headers}
+
+      assert {:error, [error]} = OpenAPI.validate_request(spec, :put, "/users/0", request)
+
+      assert error.instance_path == "/path/id"
+      assert error.schema_path == "/paths/~1users~1{id}/parameters/0/schema/minimum"
+
+      assert {:error, [error]} =
+               OpenAPI.validate_request(spec, "PUT", "/users/1", %{
+                 body: %{"name" => "Ada"},
+                 headers: @headers,
+                 query: %{"fields" => "name,email"}
+               })
+
+      assert error.instance_path == "/query/fields/1"
+      assert error.schema_path == "/components/parameters/Fields/schema/items/enum"
+    end
+
+    test "coerces parameters whose schemas refer elsewhere in the document" do
+      spec =
+        OpenAPI.load!(%{
+          "openapi" => "3.1.0",
+          "info" => %{"title" => "Items", "version" => "1.0.0"},
+          "paths" => %{
+            "/items" => %{
+              "get" => %{
+                "parameters" => [
+                  %{
+                    "name" => "limit",
+                    "in" => "query",
+                    "schema" => %{"$ref" => "#/x-limits/Page"}
+                  }
+                ],
+                "responses" => %{"204" => %{"description" => "No content"}}
+              }
+            }
+          },
+          "x-limits" => %{"Page" => %{"type" => "integer", "maximum" => 10}}
+        })
+
+      assert :ok = OpenAPI.validate_request(spec, "GET", "/items", %{query: %{"limit" => "5"}})
+
+      assert {:error, [error]} =
+               OpenAPI.validate_request(spec, "GET", "/items", %{query: %{"limit" => "50"}})
+
+      assert error.instance_path == "/query/limit"
+      assert error.schema_path =~ ~r{/maximum$}
+    end
+
+    test "takes path parameters for a path template", %{spec: spec} do
+      request = %{body: %{"name" => "Ada"}, headers: @headers, path: %{id: "abc"}}
+
+      assert {:error, [%ValidationError{instance_path: "/path/id"}]} =
+               OpenAPI.validate_request(spec, "PUT", "/users/{id}", request)
+    end
+
+    test "reports missing parameters and bodies", %{spec: spec} do
+      assert {:error, errors} = OpenAPI.validate_request(spec, "PUT", "/users/1")
+
+      assert Enum.map(errors, &{&1.instance_path, &1.schema_path}) == [
+               {"/headers/x-request-id", "/paths/~1users~1{id}/put/parameters/1/required"},
+               {"/body", "/paths/~1users~1{id}/put/requestBody/required"}
+             ]
+    end
+
+    test "validates the body against the referenced schema", %{spec: spec} do
+      assert {:error, [error]} =
+               OpenAPI.validate_request(spec, "PUT", "/users/1", %{
+                 body: ~s({"id": 1, "name": "Ada"}),
+                 headers: @headers
+               })
+
+      assert error.instance_path == "/body/id"
+      assert error.message =~ "read-only"
+
+      assert {:error, [error]} =
+               OpenAPI.validate_request(spec, "PUT", "/users/1", %{body: "{", headers: @headers})
+
+      assert error.instance_path == "/body"
+      assert error.message =~ "not valid JSON"
+    end
+
+    test "prefers literal paths over templates", %{spec: spec} do
+      assert :ok = OpenAPI.validate_request(spec, "GET", "/users/me")
+    end
+
+    test "reports unknown operations", %{spec: spec} do
+      assert {:error, :unknown_operation} = OpenAPI.validate_request(spec, "DELETE", "/users/1")
+      assert {:error, :unknown_operation} = OpenAPI.validate_request(spec, "GET", "/groups")
+    end
+  end
+
+  describe "validate_response/4" do
+    test "validates the body of the documented status", %{spec: spec} do
+      body = ~s({"id": 1, "name": "Ada"})
+
+      assert :ok = OpenAPI.validate_response(spec, "updateUser", 200, body)
+
+      assert {:error, [error]} =
+               OpenAPI.validate_response(spec, {"PUT", "/users/{id}"}, 200, %{"id" => 1})
+
+      assert error.instance_path == "/body"
+
+      assert error.schema_path ==
+               "/paths/~1users~1{id}/put/responses/200/content/application~1json/schema" <>
+                 "/$ref/required"
+    end
+
+    test "falls back to status ranges", %{spec: spec} do
+      assert :ok = OpenAPI.validate_response(spec, "updateUser", 404, ~s({"oops": true}))
+    end
+
+    test "reports undocumented statuses", %{spec: spec} do
+      assert {:error, [error]} = OpenAPI.validate_response(spec, "updateUser", 500, nil)
+
+      assert error.instance_path == "/status"
+      assert error.message =~ "500"
+    end
+
+    test "reports unknown operations", %{spec: spec} do
+      assert {:error, :unknown_operation} =
+               OpenAPI.validate_response(spec, "deleteUser", 200, nil)
+    end
+  end
+end
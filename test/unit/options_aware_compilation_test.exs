@@ -10,7 +10,7 @@ defmodule ExJsonschema.OptionsAwareCompilationTest do
       options = Options.new(draft: :draft7)
 
       assert {:ok, compiled} = ExJsonschema.compile(schema, options)
-      assert is_reference(compiled)
+      assert match?(%ExJsonschema.Schema{}, compiled)
     end
 
     test "compiles successfully when no $schema in document and draft specified in options" do
@@ -18,7 +18,7 @@ defmodule ExJsonschema.OptionsAwareCompilationTest do
       options = Options.new(draft: :draft6)
 
       assert {:ok, compiled} = ExJsonschema.compile(schema, options)
-      assert is_reference(compiled)
+      assert match?(%ExJsonschema.Schema{}, compiled)
     end
 
     test "compiles successfully with :auto draft detection" do
@@ -26,7 +26,7 @@ defmodule ExJsonschema.OptionsAwareCompilationTest do
       options = Options.new(draft: :auto)
 
       assert {:ok, compiled} = ExJsonschema.compile(schema, options)
-      assert is_reference(compiled)
+      assert match?(%ExJsonschema.Schema{}, compiled)
     end
 
     test "returns error when draft in options conflicts with schema $schema" do
@@ -41,7 +41,7 @@ defmodule ExJsonschema.OptionsAwareCompilationTest do
       schema = ~s({"$schema": "http://json-schema.org/draft-07/schema#", "type": "object"})
 
       assert {:ok, compiled} = ExJsonschema.compile(schema, draft: :draft7)
-      assert is_reference(compiled)
+      assert match?(%ExJsonschema.Schema{}, compiled)
     end
 
     test "auto-detection works in compile/2 with Options struct" do
@@ -52,7 +52,7 @@ defmodule ExJsonschema.OptionsAwareCompilationTest do
       options = Options.new(draft: :auto)
 
       assert {:ok, compiled} = ExJsonschema.compile(schema_with_draft, options)
-      assert is_reference(compiled)
+      assert match?(%ExJsonschema.Schema{}, compiled)
     end
 
     test "handles invalid schema JSON during options validation" do
@@ -0,0 +1,153 @@
+defmodule ExJsonschema.PatchTest do
+  use ExUnit.Case, async: true
+
+  alias ExJsonschema.{CompilationError, Patch, PatchError, ValidationError}
+
+  doctest ExJsonschema.Patch
+
+  @schema ~s({
+    "type": "object",
+    "required": ["name"],
+    "properties": {
+      "name": {"type": "string"},
+      "age": {"type": "integer", "minimum": 0},
+      "tags": {"type": "array", "items": {"type": "string"}},
+      "history": {"type": "array", "items": {"type": "object"}, "uniqueItems": true},
+      "address": {
+        "type": "object",
+        "required": ["zip"],
+        "properties": {"zip": {"type": "string", "pattern": "^[0-9]{5}$"}}
+      }
+    }
+  })
+
+  @document ~s({"name": "Ada", "age": 36, "tags": ["math"], "address": {"zip": "12345"}})
+
+  setup_all do
+    {:ok, compiled: ExJsonschema.compile!(@schema)}
+  end
+
+  describe "JSON Patch" do
+    test "returns the patched document when it is valid", %{compiled: compiled} do
+      patch = ~s([
+        {"op": "replace", "path": "/age", "value": 37},
+        {"op": "add", "path": "/tags/-", "value": "poetry"},
+        {"op": "remove", "path": "/address"}
+      ])
+
+      assert {:ok, patched} = Patch.patch(compiled, @document, patch)
+      assert patched == ~s({"age":37,"name":"Ada","tags":["math","poetry"]})
+    end
+
+    test "attributes errors to the operation responsible", %{compiled: compiled} do
+      patch = ~s([
+        {"op": "replace", "path": "/age", "value": -1},
+        {"op": "replace", "path": "/address/zip", "value": "abc"},
+        {"op": "remove", "path": "/name"}
+      ])
+
+      assert {:error, errors} = Patch.patch(compiled, @document, patch)
+
+      assert errors
+             |> Enum.map(fn {operation, %ValidationError{} = error} ->
+               {operation, error.instance_path}
+             end)
+             |> Enum.sort() == [{0, "/age"}, {1, "/address/zip"}, {2, ""}]
+    end
+
+    test "reports operations that cannot be applied", %{compiled: compiled} do
+      patch = ~s([
+        {"op": "replace", "path": "/age", "value": 37},
+        {"op": "test", "path": "/name", "value": "Grace"}
+      ])
+
+      assert {:error, %PatchError{operation: 1, message: message}} =
+               Patch.patch(compiled, @document, patch)
+
+      assert message =~ "/name"
+
+      for patch <- [
+            ~s([{"op": "remove", "path": "/missing"}]),
+            ~s([{"op": "add", "path": "/tags/5", "value": "x"}]),
+            ~s([{"op": "replace", "path": "/tags/+0", "value": "x"}]),
+            ~s([{"op": "replace", "path": "/tags/00", "value": "x"}]),
+            ~s([{"op": "move", "from": "/address", "path": "/address/old"}]),
+            ~s([{"op": "frobnicate", "path": "/age"}]),
+            ~s({"op": "add"})
+          ] do
+        assert {:error, %PatchError{operation: 0}} = Patch.patch(compiled, @document, patch)
+      end
+    end
+
+    test "compares numbers by value in test operations", %{compiled: compiled} do
+      patch = ~s([{"op": "test", "path": "/age", "value": 36.0}])
+      assert {:ok, _} = Patch.patch(compiled, @document, patch)
+    end
+  end
+
+  describe "JSON Merge Patch" do
+    test "merges, removes nulls and validates", %{compiled: compiled} do
+      assert {:ok, patched} =
+               Patch.patch(compiled, @document, ~s({"age": 37, "address": null}),
+                 format: :merge_patch
+               )
+
+      assert patched == ~s({"age":37,"name":"Ada","tags":["math"]})
+    end
+
+    test "attributes errors to patch members", %{compiled: compiled} do
+      patch = ~s({"address": {"zip": 12345}, "name": null})
+
+      assert {:error, errors} = Patch.patch(compiled, @document, patch, format: :merge_patch)
+
+      assert errors |> Enum.map(&elem(&1, 0)) |> Enum.sort() == ["/address/zip", "/name"]
+    end
+  end
+
+  describe "incremental validation" do
+    test "ignores errors outside the changed subtrees", %{compiled: compiled} do
+      # The stored document is already invalid at /age, which incremental mode assumes it is not
+      document = ~s({"name": "Ada", "age": -1, "address": {"zip": "12345"}})
+      patch = ~s([{"op": "replace", "path": "/address/zip", "value": "54321"}])
+
+      assert {:error, [{nil, _}]} = Patch.patch(compiled, document, patch)
+      assert {:ok, _} = Patch.patch(compiled, document, patch, incremental: true)
+    end
+
+    test "catches errors in the changed subtrees", %{compiled: compiled} do
+      patch = ~s([{"op": "remove", "path": "/address/zip"}])
+
+      assert {:error, [{0, error}]} = Patch.patch(compiled, @document, patch, incremental: true)
+      assert error.instance_path == "/address"
+    end
+
+    test "validates the whole document where subtrees cannot be isolated", %{
+      compiled: compiled
+    } do
+      # uniqueItems compares whole entries, so a change inside one can break it
+      document = ~s({"name": "Ada", "history": [{"note": "a"}, {"note": "b"}]})
+      patch = ~s([{"op": "replace", "path": "/history/1/note", "value": "a"}])
+
+      assert {:error, [{0, error}]} = Patch.patch(compiled, document, patch, incremental: true)
+      assert error.instance_path == "/history"
+    end
+  end
+
+  test "reports invalid JSON", %{compiled: compiled} do
+    assert {:error, %CompilationError{type: :json_parse_error}} =
+             Patch.patch(compiled, "{", "[]")
+
+    assert {:error, %CompilationError{type: :json_parse_error}} =
+             Patch.patch(compiled, @document, "[")
+  end
+
+  test "rejects invalid options", %{compiled: compiled} do
+    assert_raise ArgumentError, ~r/Invalid patch format/, fn ->
+      Patch.patch(compiled, @document, "[]", format: :xml_patch)
+    end
+
+    assert_raise ArgumentError, ~r/Invalid incremental option/, fn ->
+      Patch.patch(compiled, @document, "[]", incremental: "yes")
+    end
+  end
+end
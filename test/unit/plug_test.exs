@@ -0,0 +1,104 @@
+defmodule ExJsonschema.PlugTest do
+  use ExUnit.Case, async: true
+
+  import Plug.Test
+
+  alias ExJsonschema.Plug, as: SchemaPlug
+
+  @schema ~s({
+    "type": "object",
+    "required": ["sku"],
+    "properties": {
+      "sku": {"type": "string"},
+      "id": {"type": "integer", "readOnly": true}
+    }
+  })
+
+  setup_all do
+    {:ok, compiled: ExJsonschema.compile!(@schema)}
+  end
+
+  defp run(conn, opts), do: SchemaPlug.call(conn, SchemaPlug.init(opts))
+
+  test "passes valid bodies through", %{compiled: compiled} do
+    conn = conn(:post, "/orders", %{"sku" => "A-1"}) |> run(schema: compiled)
+
+    refute conn.halted
+    assert conn.state == :unset
+  end
+
+  test "rejects invalid bodies with Problem Details", %{compiled: compiled} do
+    conn = conn(:post, "/orders", %{"sku" => 4}) |> run(schema: compiled)
+
+    assert conn.halted
+    assert conn.status == 422
+    assert ["application/problem+json"] = Plug.Conn.get_resp_header(conn, "content-type")
+
+    assert %{
+             "type" => "about:blank",
+             "status" => 422,
+             "detail" => "The request body does not match its schema.",
+             "errors" => [error]
+           } = Jason.decode!(conn.resp_body)
+
+    assert error["pointer"] == "#/sku"
+    assert error["keyword"] == "type"
+    assert error["schema_path"] == "/properties/sku/type"
+  end
+
+  test "validates requests, rejecting readOnly values", %{compiled: compiled} do
+    conn = conn(:post, "/orders", %{"sku" => "A-1", "id" => 1}) |> run(schema: compiled)
+
+    assert %{"errors" => [%{"pointer" => "#/id", "keyword" => "readOnly"}]} =
+             Jason.decode!(conn.resp_body)
+
+    conn =
+      conn(:post, "/orders", %{"sku" => "A-1", "id" => 1})
+      |> run(schema: compiled, direction: :response)
+
+    refute conn.halted
+  end
+
+  test "picks the schema per request", %{compiled: compiled} do
+    pick = fn conn -> if conn.request_path == "/orders", do: compiled end
+
+    assert conn(:post, "/orders", %{}) |> run(schema: pick) |> Map.get(:halted)
+    refute conn(:post, "/other", %{}) |> run(schema: pick) |> Map.get(:halted)
+
+    assert conn(:post, "/orders", %{})
+           |> SchemaPlug.put_schema(compiled)
+           |> run([])
+           |> Map.get(:halted)
+
+    refute conn(:post, "/orders", %{}) |> run([]) |> Map.get(:halted)
+  end
+
+  test "validates JSON bodies that are not objects", %{compiled: compiled} do
+    conn = conn(:post, "/orders", %{"_json" => [1, 2]}) |> run(schema: compiled)
+
+    assert %{"errors" => [%{"pointer" => "#", "keyword" => "type"}]} =
+             Jason.decode!(conn.resp_body)
+  end
+
+  test "takes a status and an error handler", %{compiled: compiled} do
+    conn = conn(:post, "/orders", %{}) |> run(schema: compiled, status: 400)
+    assert %{"status" => 400} = Jason.decode!(conn.resp_body)
+
+    handler = fn conn, errors ->
+      Plug.Conn.send_resp(conn, 418, Enum.map_join(errors, ",", & &1.keyword))
+    end
+
+    conn = conn(:post, "/orders", %{}) |> run(schema: compiled, on_error: handler)
+    assert conn.halted
+    assert {418, _headers, "required"} = sent_resp(conn)
+  end
+
+  test "builds Problem Details for basic output and unreadable bodies" do
+    assert %{"status" => 422, "detail" => detail} = SchemaPlug.problem_details(:validation_failed)
+    assert detail == "The request body does not match its schema."
+    refute Map.has_key?(SchemaPlug.problem_details(:validation_failed), "errors")
+
+    error = %ExJsonschema.CompilationError{type: :parse_error, details: "A tuple at the root"}
+    assert SchemaPlug.problem_details(error, 400)["detail"] =~ "A tuple at the root"
+  end
+end
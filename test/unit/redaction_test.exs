@@ -0,0 +1,160 @@
+defmodule ExJsonschema.RedactionTest do
+  use ExUnit.Case, async: true
+
+  alias ExJsonschema.Options
+
+  doctest ExJsonschema.Options, only: [redaction: 1]
+
+  @schema ~s({
+    "type": "object",
+    "properties": {
+      "password": {"type": "string", "format": "password", "minLength": 12},
+      "token": {"$ref": "#/$defs/Token"},
+      "pin": {"type": "integer", "writeOnly": true, "maximum": 9999},
+      "profile": {
+        "type": "object",
+        "properties": {"ssn": {"type": "string", "x-sensitive": true}},
+        "additionalProperties": false
+      },
+      "users": {"type": "array", "items": {"type": "object"}},
+      "name": {"type": "string", "maxLength": 3}
+    },
+    "$defs": {"Token": {"type": "string", "pattern": "^tok_", "x-sensitive": true}}
+  })
+
+  @instance ~s({
+    "password": "hunter2",
+    "token": "secret-token",
+    "pin": 123456,
+    "profile": {"ssn": "111-22-3333", "extra": 1},
+    "users": ["a@example.com"],
+    "name": "Alexander"
+  })
+
+  defp errors(opts, output \\ :verbose) do
+    compiled = ExJsonschema.compile!(@schema, opts)
+    {:error, errors} = ExJsonschema.validate(compiled, @instance, output: output)
+    Map.new(errors, &{&1.instance_path, &1})
+  end
+
+  test "leaves errors alone by default" do
+    errors = errors([])
+
+    assert errors["/password"].message =~ "hunter2"
+    assert errors["/password"].instance_value == "hunter2"
+  end
+
+  test "masks values marked writeOnly, format password or x-sensitive" do
+    errors = errors(redact: true)
+
+    for path <- ["/password", "/token", "/pin"] do
+      error = errors[path]
+
+      assert error.instance_value == "[REDACTED]"
+      assert error.message =~ "[REDACTED]"
+      refute error.message =~ ~r/hunter2|secret-token|123456/
+      refute inspect(error.context) =~ ~r/hunter2|secret-token|123456/
+      refute inspect(error.suggestions) =~ ~r/hunter2|secret-token|123456/
+    end
+
+    # Unmarked values are reported as before
+    assert errors["/name"].message =~ "Alexander"
+  end
+
+  test "masks sensitive values inside reported values" do
+    # additionalProperties reports the whole /profile object
+    error = errors(redact: true)["/profile"]
+
+    assert error.instance_value == %{"ssn" => "[REDACTED]", "extra" => 1}
+    refute inspect(error) =~ "111-22-3333"
+  end
+
+  test "masks values marked in combinators and patternProperties" do
+    schema = ~s({
+      "type": "object",
+      "properties": {"secret": {"maxLength": 3}},
+      "oneOf": [{"properties": {"secret": {"writeOnly": true}}}],
+      "patternProperties": {"^key_": {"x-sensitive": true, "maxLength": 3}}
+    })
+
+    compiled = ExJsonschema.compile!(schema, redact: true)
+    instance = ~s({"secret": "s3cr3t-value", "key_api": "k3y-value"})
+
+    assert {:error, errors} = ExJsonschema.validate(compiled, instance, output: :verbose)
+    assert errors |> Enum.map(& &1.instance_path) |> Enum.sort() == ["/key_api", "/secret"]
+    refute inspect(errors) =~ ~r/s3cr3t-value|k3y-value/
+  end
+
+  test "masks a short sensitive number only where its error quotes it" do
+    schema = ~s({
+      "properties": {
+        "pin": {"type": "integer", "writeOnly": true, "maximum": 0},
+        "count": {"type": "integer", "maximum": 1}
+      }
+    })
+
+    compiled = ExJsonschema.compile!(schema, redact: true)
+
+    assert {:error, errors} = ExJsonschema.validate(compiled, ~s({"pin": 1, "count": 10}))
+    errors = Map.new(errors, &{&1.instance_path, &1})
+
+    assert errors["/pin"].message =~ ~r/^"\[REDACTED\]" is greater than the maximum of 0/
+    assert errors["/count"].message =~ ~r/^10 is greater than the maximum of 1/
+  end
+
+  test "masks values at configured pointers" do
+    compiled =
+      ExJsonschema.compile!(@schema, redact: [pointers: ["/users/*"], mask: "***"])
+
+    assert {:error, [error]} =
+             ExJsonschema.validate(compiled, ~s({"users": ["a@example.com"]}), output: :verbose)
+
+    assert error.instance_path == "/users/0"
+    assert error.instance_value == "***"
+    refute error.message =~ "a@example.com"
+  end
+
+  test "uses a custom keyword" do
+    schema = ~s({"properties": {"iban": {"x-pii": true, "maxLength": 4}}})
+    compiled = ExJsonschema.compile!(schema, redact: [keyword: "x-pii"])
+
+    assert {:error, [error]} = ExJsonschema.validate(compiled, ~s({"iban": "DE8937040044"}))
+    assert error.message =~ "[REDACTED]"
+    refute error.message =~ "DE89"
+  end
+
+  test "applies to detailed output and validate_at" do
+    detailed = errors([redact: true], :detailed)
+    refute detailed["/password"].message =~ "hunter2"
+
+    compiled = ExJsonschema.compile!(@schema, redact: true)
+
+    assert {:error, [error]} =
+             ExJsonschema.validate_at(compiled, @instance, instance_pointer: "/password")
+
+    refute error.message =~ "hunter2"
+  end
+
+  test "is part of the cache key" do
+    plain = ExJsonschema.compile!(@schema)
+    redacted = ExJsonschema.compile!(@schema, redact: true)
+
+    assert ExJsonschema.info(plain).options_fingerprint !=
+             ExJsonschema.info(redacted).options_fingerprint
+
+    joined = ExJsonschema.compile!(@schema, redact: [pointers: ["/a|/b"]])
+    split = ExJsonschema.compile!(@schema, redact: [pointers: ["/a", "/b"]])
+
+    assert ExJsonschema.info(joined).options_fingerprint !=
+             ExJsonschema.info(split).options_fingerprint
+  end
+
+  test "rejects invalid policies" do
+    for redact <- [:yes, [mask: 1], [pointers: "/a"], [unknown: true]] do
+      assert {:error, %ExJsonschema.CompilationError{type: :options_error}} =
+               ExJsonschema.compile(@schema, redact: redact)
+    end
+
+    assert {:ok, _} = Options.validate(Options.new(redact: [keyword: "x-secret"]))
+  end
+end
@@ -0,0 +1,77 @@
+defmodule ExJsonschema.SchemaTest do
+  use ExUnit.Case, async: true
+
+  alias ExJsonschema.Schema
+
+  doctest ExJsonschema.Schema
+
+  describe "info/1" do
+    test "reports the explicit draft and options" do
+      compiled =
+        ExJsonschema.compile!(~s({"type": "string"}), draft: :draft7, validate_formats: true)
+
+      info = ExJsonschema.info(compiled)
+
+      assert info.draft == :draft7
+      assert info.id == nil
+      assert info.options_fingerprint =~ "draft=Draft7"
+      assert info.options_fingerprint =~ "validate_formats=true"
+    end
+
+    test "reports the draft detected from $schema" do
+      schema = ~s({"$schema": "http://json-schema.org/draft-04/schema#", "id": "urn:legacy"})
+      info = ExJsonschema.info(ExJsonschema.compile!(schema))
+
+      assert info.draft == :draft4
+      assert info.id == "urn:legacy"
+    end
+
+    test "hashes content regardless of key order and whitespace" do
+      a = ExJsonschema.compile!(~s({"type": "object", "required": ["id"]}))
+      b = ExJsonschema.compile!(~s({ "required" : ["id"], "type" : "object" }))
+      c = ExJsonschema.compile!(~s({"type": "object"}))
+
+      assert ExJsonschema.info(a).content_hash == ExJsonschema.info(b).content_hash
+      assert ExJsonschema.info(a).content_hash != ExJsonschema.info(c).content_hash
+      assert ExJsonschema.info(a).content_hash =~ ~r/\A[0-9a-f]{64}\z/
+    end
+
+    test "records the compile time" do
+      before = DateTime.utc_now()
+      %{compiled_at: compiled_at} = ExJsonschema.info(ExJsonschema.compile!(~s({})))
+
+      assert DateTime.diff(compiled_at, before, :millisecond) >= -1
+      assert DateTime.diff(DateTime.utc_now(), compiled_at, :second) < 5
+    end
+
+    test "survives serialization" do
+      compiled = ExJsonschema.compile!(~s({"$id": "urn:shipped"}), draft: :draft6)
+      {:ok, restored} = compiled |> ExJsonschema.serialize() |> ExJsonschema.deserialize()
+
+      assert %{id: "urn:shipped", draft: :draft6} = ExJsonschema.info(restored)
+      assert ExJsonschema.info(restored).content_hash == ExJsonschema.info(compiled).content_hash
+    end
+  end
+
+  describe "Inspect" do
+    test "shows the draft, id and hash prefix" do
+      compiled = ExJsonschema.compile!(~s({"$id": "https://example.com/user.json"}))
+      hash = String.slice(ExJsonschema.info(compiled).content_hash, 0, 12)
+
+      assert inspect(compiled) ==
+               ~s(#ExJsonschema.Schema<draft202012 id: "https://example.com/user.json" hash: #{hash}>)
+    end
+
+    test "omits a missing id" do
+      compiled = ExJsonschema.compile!(~s({"type": "string"}), draft: :draft7)
+
+      assert inspect(compiled) =~ ~r/\A#ExJsonschema.Schema<draft7 hash: [0-9a-f]{12}>\z/
+    end
+
+    test "is returned by compile/2 and accepted by validation" do
+      assert {:ok, %Schema{} = compiled} = ExJsonschema.compile(~s({"type": "integer"}))
+      assert ExJsonschema.valid?(compiled, "1")
+      assert {:error, [_]} = ExJsonschema.validate(compiled, ~s("one"))
+    end
+  end
+end
@@ -0,0 +1,97 @@
+defmodule ExJsonschema.SerializerTest do
+  use ExUnit.Case, async: true
+
+  alias ExJsonschema.{CompilationError, Serializer}
+
+  describe "serialize/1 and deserialize/1" do
+    test "round-trip a compiled schema" do
+      compiled = ExJsonschema.compile!(~s({"type": "object", "required": ["id"]}))
+
+      assert {:ok, restored} = compiled |> ExJsonschema.serialize() |> ExJsonschema.deserialize()
+      assert match?(%ExJsonschema.Schema{}, restored)
+      assert :ok = ExJsonschema.validate(restored, ~s({"id": 1}))
+      assert {:error, [_]} = ExJsonschema.validate(restored, ~s({}))
+    end
+
+    test "survive external term format" do
+      compiled = ExJsonschema.compile!(~s({"type": "string"}))
+      shipped = compiled |> Serializer.serialize() |> :erlang.term_to_binary()
+
+      assert {:ok, restored} = shipped |> :erlang.binary_to_term() |> Serializer.deserialize()
+      assert ExJsonschema.valid?(restored, ~s("text"))
+    end
+
+    test "preserve an explicit draft" do
+      schema = ~s({"minimum": 0, "exclusiveMinimum": true})
+      compiled = ExJsonschema.compile!(schema, draft: :draft4)
+
+      assert {:ok, restored} = Serializer.deserialize(Serializer.serialize(compiled))
+      refute ExJsonschema.valid?(restored, "0")
+      assert ExJsonschema.valid?(restored, "1")
+    end
+
+    test "preserve format validation" do
+      schema = ~s({"type": "string", "format": "email"})
+
+      with_formats = ExJsonschema.compile!(schema, validate_formats: true)
+      assert {:ok, restored} = Serializer.deserialize(Serializer.serialize(with_formats))
+      refute ExJsonschema.valid?(restored, ~s("not an email"))
+
+      without_formats = ExJsonschema.compile!(schema)
+      assert {:ok, restored} = Serializer.deserialize(Serializer.serialize(without_formats))
+      assert ExJsonschema.valid?(restored, ~s("not an email"))
+    end
+
+    test "records the canonical schema and options" do
+      compiled = ExJsonschema.compile!(~s({"type": "string"}), draft: :draft7, regex_engine: :regex)
+
+      assert %{
+               "schema" => %{"type" => "string"},
+               "draft" => "draft7",
+               "regex_engine" => "regex",
+               "version" => 6
+             } = Jason.decode!(Serializer.serialize(compiled))
+    end
+
+    test "keeps the redaction policy" do
+      schema = ~s({"properties": {"pin": {"writeOnly": true, "maxLength": 4}}})
+      compiled = ExJsonschema.compile!(schema, redact: [mask: "***"])
+      assert {:ok, restored} = Serializer.deserialize(Serializer.serialize(compiled))
+
+      assert {:error, [error]} = ExJsonschema.validate(restored, ~s({"pin": "123456"}))
+      refute error.message =~ "123456"
+    end
+
+    test "reads binaries written before redaction" do
+      binary =
+        ~s({"format": "ex_jsonschema/compiled_schema", "version": 1, "schema": {"type": "string"}})
+
+      assert {:ok, restored} = Serializer.deserialize(binary)
+      assert ExJsonschema.valid?(restored, ~s("text"))
+    end
+  end
+
+  describe "deserialize/1 errors" do
+    test "rejects binaries that are not serialized schemas" do
+      for binary <- ["", "not a schema", ~s({"type": "string"}), <<0xFF, 0xFE>>] do
+        assert {:error, %CompilationError{type: :deserialization_error}} =
+                 Serializer.deserialize(binary)
+      end
+    end
+
+    test "rejects unsupported versions" do
+      binary =
+        ~s({"type": "string"})
+        |> ExJsonschema.compile!()
+        |> Serializer.serialize()
+        |> Jason.decode!()
+        |> Map.put("version", 999)
+        |> Jason.encode!()
+
+      assert {:error, %CompilationError{type: :deserialization_error, details: details}} =
+               Serializer.deserialize(binary)
+
+      assert details =~ "version 999"
+    end
+  end
+end
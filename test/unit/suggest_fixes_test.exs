@@ -0,0 +1,119 @@
+defmodule ExJsonschema.SuggestFixesTest do
+  use ExUnit.Case, async: true
+
+  alias ExJsonschema.CompilationError
+
+  @schema ~s({
+    "type": "object",
+    "required": ["name", "role", "id"],
+    "additionalProperties": false,
+    "properties": {
+      "id": {"type": "integer"},
+      "name": {"type": "string", "maxLength": 5},
+      "role": {"$ref": "#/$defs/Role"},
+      "age": {"type": "integer", "minimum": 0, "maximum": 150},
+      "color": {"enum": ["red", "green", "blue"]},
+      "size": {"enum": [1, 5, 10]},
+      "kind": {"const": "user"},
+      "profile": {"$ref": "#/$defs/Profile"}
+    },
+    "$defs": {
+      "Role": {"type": "string", "default": "viewer"},
+      "Profile": {
+        "type": "object",
+        "required": ["locale"],
+        "properties": {"locale": {"type": "string", "default": "en"}}
+      }
+    }
+  })
+
+  setup_all do
+    {:ok, compiled: ExJsonschema.compile!(@schema)}
+  end
+
+  defp fixes(compiled, instance) do
+    {:ok, operations} = ExJsonschema.suggest_fixes(compiled, Jason.encode!(instance))
+    operations
+  end
+
+  test "suggests nothing for valid instances", %{compiled: compiled} do
+    assert fixes(compiled, %{"id" => 1, "name" => "Ada", "role" => "admin"}) == []
+  end
+
+  test "clamps numbers and truncates strings", %{compiled: compiled} do
+    operations = fixes(compiled, %{"id" => 1, "role" => "x", "name" => "Alexander", "age" => -3})
+
+    assert %{"op" => "replace", "path" => "/name", "value" => "Alexa"} in operations
+    assert %{"op" => "replace", "path" => "/age", "value" => 0} in operations
+  end
+
+  test "picks the closest enum value and the const", %{compiled: compiled} do
+    operations =
+      fixes(compiled, %{
+        "id" => 1,
+        "name" => "Ada",
+        "role" => "x",
+        "color" => "GREN",
+        "size" => 7,
+        "kind" => "admin"
+      })
+
+    assert Enum.sort_by(operations, & &1["path"]) == [
+             %{"op" => "replace", "path" => "/color", "value" => "green"},
+             %{"op" => "replace", "path" => "/kind", "value" => "user"},
+             %{"op" => "replace", "path" => "/size", "value" => 5}
+           ]
+  end
+
+  test "adds missing required properties that have defaults", %{compiled: compiled} do
+    # "id" has no default, so it cannot be fixed
+    operations = fixes(compiled, %{"name" => "Ada", "profile" => %{}})
+
+    assert Enum.sort_by(operations, & &1["path"]) == [
+             %{"op" => "add", "path" => "/profile/locale", "value" => "en"},
+             %{"op" => "add", "path" => "/role", "value" => "viewer"}
+           ]
+  end
+
+  test "removes additional properties last", %{compiled: compiled} do
+    operations =
+      fixes(compiled, %{"id" => 1, "name" => "Ada", "role" => "x", "age" => 200, "junk" => 1})
+
+    assert List.last(operations) == %{"op" => "remove", "path" => "/junk"}
+  end
+
+  test "produces a patch that patch/4 applies", %{compiled: compiled} do
+    instance = %{"id" => 1, "name" => "Alexander", "age" => 200, "junk" => true}
+    operations = fixes(compiled, instance)
+
+    assert {:ok, patched} =
+             ExJsonschema.patch(compiled, Jason.encode!(instance), Jason.encode!(operations))
+
+    assert Jason.decode!(patched) == %{
+             "id" => 1,
+             "name" => "Alexa",
+             "age" => 150,
+             "role" => "viewer"
+           }
+  end
+
+  test "leaves values the redaction policy masks alone" do
+    schema = ~s({"properties": {
+      "password": {"type": "string", "format": "password", "maxLength": 5},
+      "pin": {"type": "integer", "writeOnly": true, "maximum": 9999},
+      "name": {"type": "string", "maxLength": 5}
+    }})
+
+    compiled = ExJsonschema.compile!(schema, redact: true)
+    instance = %{"password" => "hunter2!", "pin" => 123_456, "name" => "Alexander"}
+
+    assert fixes(compiled, instance) == [
+             %{"op" => "replace", "path" => "/name", "value" => "Alexa"}
+           ]
+  end
+
+  test "reports invalid JSON", %{compiled: compiled} do
+    assert {:error, %CompilationError{type: :json_parse_error}} =
+             ExJsonschema.suggest_fixes(compiled, "{")
+  end
+end
@@ -0,0 +1,135 @@
+defmodule ExJsonschema.TelemetryTest do
+  # Telemetry handlers are global, so events from concurrent tests would leak in
+  use ExUnit.Case, async: false
+
+  @events for event <- [:compile, :validate, :meta_validate],
+              phase <- [:start, :stop, :exception],
+              do: [:ex_jsonschema, event, phase]
+
+  setup do
+    test_pid = self()
+    handler_id = "telemetry-test-#{inspect(test_pid)}"
+
+    :telemetry.attach_many(
+      handler_id,
+      @events,
+      fn event, measurements, metadata, _config ->
+        send(test_pid, {:telemetry, event, measurements, metadata})
+      end,
+      nil
+    )
+
+    on_exit(fn -> :telemetry.detach(handler_id) end)
+    :ok
+  end
+
+  describe "compile" do
+    test "emits a span with NIF timings and schema metadata" do
+      schema = ~s({"$id": "https://example.com/user.json", "type": "object"})
+      {:ok, _compiled} = ExJsonschema.compile(schema, draft: :draft7)
+
+      assert_received {:telemetry, [:ex_jsonschema, :compile, :start], %{system_time: _},
+                       %{draft: :draft7}}
+
+      assert_received {:telemetry, [:ex_jsonschema, :compile, :stop], measurements, metadata}
+
+      assert %{duration: _, parse_time: _, build_time: _, encode_time: _} = measurements
+      assert metadata.schema_id == "https://example.com/user.json"
+      assert metadata.draft == :draft7
+      assert metadata.cache == :miss
+      assert metadata.schema_size == byte_size(schema)
+    end
+
+    test "resolves the draft detected from $schema" do
+      schema = ~s({"$schema": "http://json-schema.org/draft-04/schema#", "id": "urn:legacy"})
+      {:ok, _compiled} = ExJsonschema.compile(schema, draft: :auto)
+
+      assert_received {:telemetry, [:ex_jsonschema, :compile, :stop], _measurements,
+                       %{draft: :draft4, schema_id: "urn:legacy"}}
+    end
+
+    test "reports failed compilations" do
+      {:error, _} = ExJsonschema.compile(~s({"type": "invalid_type"}))
+
+      assert_received {:telemetry, [:ex_jsonschema, :compile, :stop], measurements,
+                       %{schema_id: nil}}
+
+      assert Map.has_key?(measurements, :build_time)
+    end
+  end
+
+  describe "validate" do
+    setup do
+      compiled = ExJsonschema.compile!(~s({"$id": "urn:age", "type": "integer", "minimum": 0}))
+      # Drop the compile events
+      flush()
+      {:ok, compiled: compiled}
+    end
+
+    test "reports error enrichment for failed validations", %{compiled: compiled} do
+      {:error, [_]} = ExJsonschema.validate(compiled, "-1")
+
+      assert_received {:telemetry, [:ex_jsonschema, :validate, :start], _,
+                       %{schema_id: "urn:age", output_format: :detailed}}
+
+      assert_received {:telemetry, [:ex_jsonschema, :validate, :stop], measurements, metadata}
+
+      assert %{
+               duration: _,
+               parse_time: _,
+               validation_time: _,
+               enrichment_time: _,
+               encode_time: _,
+               error_count: 1
+             } = measurements
+
+      assert metadata.draft == :draft202012
+      assert metadata.instance_size == 2
+    end
+
+    test "skips error enrichment for valid instances", %{compiled: compiled} do
+      :ok = ExJsonschema.validate(compiled, "5", output: :verbose)
+
+      assert_received {:telemetry, [:ex_jsonschema, :validate, :stop], measurements,
+                       %{output_format: :verbose}}
+
+      assert measurements.error_count == 0
+      refute Map.has_key?(measurements, :enrichment_time)
+    end
+
+    test "covers valid?/2 with the basic output format", %{compiled: compiled} do
+      refute ExJsonschema.valid?(compiled, ~s("text"))
+
+      assert_received {:telemetry, [:ex_jsonschema, :validate, :stop], measurements,
+                       %{output_format: :basic}}
+
+      assert %{parse_time: _, validation_time: _} = measurements
+      refute Map.has_key?(measurements, :error_count)
+    end
+  end
+
+  describe "meta_validate" do
+    test "emits a span for each meta-validation function" do
+      schema = ~s({"type": "invalid_type"})
+
+      refute ExJsonschema.MetaValidator.valid?(schema)
+      {:error, _} = ExJsonschema.MetaValidator.validate_simple(schema)
+      {:error, _} = ExJsonschema.MetaValidator.validate(schema)
+
+      for format <- [:basic, :simple, :detailed] do
+        assert_received {:telemetry, [:ex_jsonschema, :meta_validate, :stop], measurements,
+                         %{output_format: ^format}}
+
+        assert %{parse_time: _, validation_time: _, error_count: 1} = measurements
+      end
+    end
+  end
+
+  defp flush do
+    receive do
+      {:telemetry, _, _, _} -> flush()
+    after
+      0 -> :ok
+    end
+  end
+end
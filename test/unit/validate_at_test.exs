@@ -0,0 +1,168 @@
+defmodule ExJsonschema.ValidateAtTest do
+  use ExUnit.Case, async: true
+
+  alias ExJsonschema.ValidationError
+
+  @schema ~s({
+    "type": "object",
+    "required": ["name"],
+    "properties": {
+      "name": {"type": "string"},
+      "billing": {"$ref": "#/$defs/Address"},
+      "shipping": {"type": "array", "items": {"$ref": "#/$defs/Address"}},
+      "payment": {"allOf": [{"$ref": "#/$defs/Card"}]},
+      "notes": {}
+    },
+    "$defs": {
+      "Address": {
+        "$anchor": "address",
+        "type": "object",
+        "required": ["zip"],
+        "properties": {"zip": {"$ref": "#/$defs/Zip"}}
+      },
+      "Zip": {"type": "string", "pattern": "^[0-9]{5}$"},
+      "Card": {"type": "object", "properties": {"number": {"type": "string"}}}
+    }
+  })
+
+  setup_all do
+    {:ok, compiled: ExJsonschema.compile!(@schema)}
+  end
+
+  describe "schema_pointer" do
+    test "validates against a subschema", %{compiled: compiled} do
+      assert :ok =
+               ExJsonschema.validate_at(compiled, ~s({"zip": "12345"}),
+                 schema_pointer: "#/$defs/Address"
+               )
+
+      assert {:error, [%ValidationError{} = error]} =
+               ExJsonschema.validate_at(compiled, ~s({"zip": "abc"}),
+                 schema_pointer: "#/$defs/Address"
+               )
+
+      assert error.instance_path == "/zip"
+      assert error.schema_path =~ ~r{^/\$defs/Address/properties/zip/}
+    end
+
+    test "accepts bare pointers and anchors", %{compiled: compiled} do
+      for pointer <- ["/$defs/Address", "#address"] do
+        assert {:error, [_]} = ExJsonschema.validate_at(compiled, ~s({}), schema_pointer: pointer)
+      end
+    end
+
+    test "resolves references against the root", %{compiled: compiled} do
+      assert {:error, [_]} =
+               ExJsonschema.validate_at(compiled, ~s("1234"), schema_pointer: "#/$defs/Zip")
+
+      assert :ok =
+               ExJsonschema.validate_at(compiled, ~s({"zip": "12345"}),
+                 schema_pointer: "#/properties/billing"
+               )
+    end
+
+    test "validates against the root itself like validate/2", %{compiled: compiled} do
+      instance = ~s({"billing": {"zip": "1"}})
+
+      {:error, expected} = ExJsonschema.validate(compiled, instance)
+
+      for pointer <- ["#", ""] do
+        assert {:error, errors} =
+                 ExJsonschema.validate_at(compiled, instance, schema_pointer: pointer)
+
+        assert Enum.map(errors, &{&1.instance_path, &1.schema_path}) ==
+                 Enum.map(expected, &{&1.instance_path, &1.schema_path})
+      end
+    end
+
+    test "supports basic output", %{compiled: compiled} do
+      assert {:error, :validation_failed} =
+               ExJsonschema.validate_at(compiled, ~s({}),
+                 schema_pointer: "#/$defs/Address",
+                 output: :basic
+               )
+    end
+
+    test "raises on unresolvable pointers", %{compiled: compiled} do
+      assert_raise ArgumentError, ~r/Unable to resolve/, fn ->
+        ExJsonschema.validate_at(compiled, ~s({}), schema_pointer: "#/$defs/Missing")
+      end
+    end
+  end
+
+  describe "instance_pointer" do
+    test "validates only the subtree", %{compiled: compiled} do
+      # The root is missing "name", which is outside the subtree
+      document = ~s({"billing": {"zip": "12345"}, "shipping": [{"zip": "x"}]})
+
+      assert :ok = ExJsonschema.validate_at(compiled, document, instance_pointer: "/billing")
+
+      assert {:error, [error]} =
+               ExJsonschema.validate_at(compiled, document, instance_pointer: "/shipping/0")
+
+      assert error.instance_path == "/shipping/0/zip"
+    end
+
+    test "reports paths relative to the whole document", %{compiled: compiled} do
+      document = ~s({"name": "Ada", "billing": {"zip": 5}})
+
+      assert {:error, [error]} =
+               ExJsonschema.validate_at(compiled, document, instance_pointer: "/billing/zip")
+
+      assert error.instance_path == "/billing/zip"
+    end
+
+    test "accepts anything where the schema places no constraints", %{compiled: compiled} do
+      assert :ok =
+               ExJsonschema.validate_at(compiled, ~s({"notes": {"any": ["thing"]}}),
+                 instance_pointer: "/notes/any"
+               )
+
+      assert :ok =
+               ExJsonschema.validate_at(compiled, ~s({"extra": 1}), instance_pointer: "/extra")
+    end
+
+    test "keeps only errors under the pointer inside combinators", %{compiled: compiled} do
+      # allOf decides what applies to /payment/number, so the whole document is validated
+      assert :ok =
+               ExJsonschema.validate_at(compiled, ~s({"payment": {"number": "4111"}}),
+                 instance_pointer: "/payment/number"
+               )
+
+      assert {:error, [error]} =
+               ExJsonschema.validate_at(compiled, ~s({"payment": {"number": 4111}}),
+                 instance_pointer: "/payment/number"
+               )
+
+      assert error.instance_path == "/payment/number"
+    end
+
+    test "combines with schema_pointer", %{compiled: compiled} do
+      document = ~s({"legacy": {"address": {"zip": "abc"}}})
+
+      assert {:error, [error]} =
+               ExJsonschema.validate_at(compiled, document,
+                 schema_pointer: "#/$defs/Address",
+                 instance_pointer: "/legacy/address"
+               )
+
+      assert error.instance_path == "/legacy/address/zip"
+    end
+
+    test "raises when the instance has no value at the pointer", %{compiled: compiled} do
+      assert_raise ArgumentError, ~r{no value at '/billing'}, fn ->
+        ExJsonschema.validate_at(compiled, ~s({}), instance_pointer: "/billing")
+      end
+    end
+  end
+
+  test "rejects verbose output", %{compiled: compiled} do
+    assert_raise ArgumentError, ~r/Invalid output format/, fn ->
+      ExJsonschema.validate_at(compiled, ~s({}), output: :verbose)
+    end
+  end
+
+  test "reports invalid JSON like validate/3", %{compiled: compiled} do
+    assert {:error, [:validation_error]} = ExJsonschema.validate_at(compiled, "{", [])
+  end
+end
@@ -0,0 +1,79 @@
+defmodule ExJsonschema.ValidateTermTest do
+  use ExUnit.Case, async: true
+
+  alias ExJsonschema.CompilationError
+
+  @schema ~s({
+    "type": "object",
+    "required": ["name"],
+    "properties": {
+      "name": {"type": "string"},
+      "active": {"type": "boolean"},
+      "parent": {"type": "null"},
+      "scores": {"type": "array", "items": {"type": "number", "maximum": 100}}
+    }
+  })
+
+  setup_all do
+    {:ok, compiled: ExJsonschema.compile!(@schema)}
+  end
+
+  test "validates terms as Jason would encode them", %{compiled: compiled} do
+    term = %{"name" => "Ada", "active" => true, "parent" => nil, "scores" => [99, 12.5]}
+    assert :ok = ExJsonschema.validate_term(compiled, term)
+
+    assert :ok = ExJsonschema.validate_term(compiled, %{name: :ada, active: false})
+    assert :ok = ExJsonschema.validate_term(compiled, %{"name" => "Ada", 1 => "one"})
+  end
+
+  test "reports errors as validate/3 does", %{compiled: compiled} do
+    term = %{"name" => 1, "scores" => [50, 101]}
+
+    assert {:error, errors} = ExJsonschema.validate_term(compiled, term)
+    assert errors |> Enum.map(& &1.instance_path) |> Enum.sort() == ["/name", "/scores/1"]
+    assert {:error, errors} = ExJsonschema.validate(compiled, Jason.encode!(term))
+    assert length(errors) == 2
+
+    assert {:error, :validation_failed} =
+             ExJsonschema.validate_term(compiled, %{}, output: :basic)
+
+    assert {:error, [error]} = ExJsonschema.validate_term(compiled, [], output: :verbose)
+    assert error.keyword == "type"
+  end
+
+  test "rejects terms JSON cannot represent", %{compiled: compiled} do
+    assert {:error, %CompilationError{type: :parse_error, details: details}} =
+             ExJsonschema.validate_term(compiled, %{"name" => {:ok, "Ada"}})
+
+    assert details == ~s(A tuple at "/name" cannot be represented in JSON)
+
+    assert {:error, %CompilationError{details: details}} =
+             ExJsonschema.validate_term(compiled, %{"when" => ~D[2024-01-01]})
+
+    assert details == ~s(%Date{} struct at "/when" cannot be represented in JSON)
+
+    assert {:error, %CompilationError{details: details}} =
+             ExJsonschema.validate_term(compiled, %{{:a, 1} => 1})
+
+    assert details == "Map key at the root is a tuple; JSON object keys must be strings"
+
+    assert {:error, %CompilationError{details: details}} =
+             ExJsonschema.validate_term(compiled, %{"name" => <<0xFF>>})
+
+    assert details == ~s(Binary at "/name" is not valid UTF-8)
+
+    assert {:error, %CompilationError{details: details}} =
+             ExJsonschema.validate_term(compiled, %{:name => "a", "name" => "b"})
+
+    assert details =~ ~s(has the key "name" twice)
+  end
+
+  test "takes no input format", %{compiled: compiled} do
+    assert_raise ArgumentError, ~r/input_format/, fn ->
+      ExJsonschema.validate_term(compiled, %{}, input_format: :json)
+    end
+
+    assert {:error, %CompilationError{type: :options_error}} =
+             ExJsonschema.compile("{}", input_format: :term)
+  end
+end